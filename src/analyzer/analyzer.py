@@ -183,6 +183,9 @@ def get_extractor_args(db, input_file, args):
   if args.causality_dict_size != None:
     base_args += ['--causality-dictionary-size', str(args.causality_dict_size)]
 
+  if args.seed != None:
+    base_args += ['--seed', str(args.seed)]
+
   return base_args
 
 
@@ -0,0 +1,70 @@
+//! Time and memory taken to explore a slice forking its state 1023 times
+//!
+//! Run with `cargo bench --bench fork`, after building the test byte code with
+//! `make`. Forked states share their memory, trace and constraints until they
+//! write to them, so the allocations should stay far below 1024 full copies.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+/// The system allocator, counting the allocated bytes and the peak of the live
+/// bytes
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+    let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+    PEAK.fetch_max(live, Ordering::Relaxed);
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    System.dealloc(ptr, layout)
+  }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const RUNS: usize = 5;
+
+fn main() -> Result<(), String> {
+  let path = "tests/c_files/bench/fork_1000.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("bench_fork");
+    options.max_work = 2000;
+    options.max_explored_trace_per_slice = 2000;
+    options.max_trace_per_slice = 2000;
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).unwrap();
+    for run in 0..RUNS {
+      let (allocated, live) = (ALLOCATED.load(Ordering::Relaxed), LIVE.load(Ordering::Relaxed));
+      PEAK.store(live, Ordering::Relaxed);
+      let begin = Instant::now();
+      let (metadata, traces) = ctx.execute_slice_in_memory(slice.clone(), 0);
+      let elapsed = begin.elapsed();
+      println!(
+        "run {}: {} paths, {} traces in {:?}, {} KiB allocated, {} KiB peak",
+        run,
+        metadata.explored_trace_count,
+        traces.len(),
+        elapsed,
+        (ALLOCATED.load(Ordering::Relaxed) - allocated) / 1024,
+        (PEAK.load(Ordering::Relaxed) - live) / 1024,
+      );
+    }
+  })
+}
@@ -1,17 +1,36 @@
+use regex::Regex;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 
-use analyzer::{call_graph::*, feature_extraction::*, options::*, slicer::*, symbolic_execution::*, utils::*};
+use analyzer::{
+  call_graph::*, error::*, feature_extraction::*, options::*, slicer::*, symbolic_execution::*, utils::*,
+};
 
-#[derive(StructOpt, Debug, Clone)]
+#[derive(StructOpt, Debug, Clone, Serialize)]
 #[structopt(name = "analyzer")]
 pub struct Options {
-  #[structopt(index = 1, required = true, value_name = "INPUT")]
-  pub input: String,
+  #[structopt(index = 1, required_unless = "traces_root", value_name = "INPUT")]
+  pub input: Option<String>,
 
-  #[structopt(index = 2, required = true, value_name = "OUTPUT")]
-  pub output: String,
+  #[structopt(index = 2, required_unless = "traces_root", value_name = "OUTPUT")]
+  pub output: Option<String>,
+
+  /// Only extract features from the slices & traces under the output directory
+  /// of a previous run, without loading any byte code
+  #[structopt(
+    long,
+    takes_value = true,
+    value_name = "TRACES_ROOT",
+    conflicts_with_all = &["input", "output"]
+  )]
+  pub traces_root: Option<String>,
+
+  /// Additional byte code modules linked with the input, can be given multiple times
+  #[structopt(long, takes_value = true, value_name = "LINK")]
+  pub link: Vec<String>,
 
   #[structopt(long, takes_value = true, value_name = "SUBFOLDER")]
   pub subfolder: Option<String>,
@@ -23,6 +42,15 @@ pub struct Options {
   #[structopt(long)]
   pub print_options: bool,
 
+  /// Demangle C++ and Rust callee names in the traces, and match the target filters
+  /// against the demangled names as well. Call nodes keep the raw symbol aside
+  #[structopt(long)]
+  pub demangle: bool,
+
+  /// Print the debug diagnostics of all phases to stderr, unless `RUST_LOG` is set
+  #[structopt(short = "v", long)]
+  pub verbose: bool,
+
   /// Random seed
   #[structopt(long, takes_value = true, default_value = "12345", value_name = "SEED")]
   pub seed: u64,
@@ -39,13 +67,27 @@ pub struct Options {
   #[structopt(long)]
   pub print_call_graph: bool,
 
+  /// Add call graph edges from indirect calls to all the address-taken functions of the same type
+  #[structopt(long)]
+  pub resolve_indirect: bool,
+
+  /// Dump the call graph to this file, as DOT if it ends with `.dot` and as json otherwise
+  #[structopt(long, takes_value = true, value_name = "CALL_GRAPH_PATH")]
+  pub dump_call_graph: Option<String>,
+
   #[structopt(
     short = "d",
-    long,
+    long = "slice-depth",
     takes_value = true,
     default_value = "1",
     value_name = "SLICE_DEPTH"
   )]
+  #[serde(skip)]
+  slice_depth_arg: String,
+
+  /// `--slice-depth`, parsed by `Options::from_matches` so that a bad depth is
+  /// reported as an `AnalyzerError`
+  #[structopt(skip)]
   pub slice_depth: usize,
 
   /// Execute only slice
@@ -55,6 +97,10 @@ pub struct Options {
   #[structopt(long, takes_value = true, value_name = "EXECUTE_ONLY_SLICE_NAME")]
   pub execute_only_slice_function_name: Option<String>,
 
+  /// Only analyze the calls to the function of exactly this name, ignoring the target filters
+  #[structopt(long, takes_value = true, value_name = "TARGET")]
+  pub target: Option<String>,
+
   #[structopt(long, takes_value = true, value_name = "INCLUDE_TARGET")]
   pub target_inclusion_filter: Option<String>,
 
@@ -65,19 +111,73 @@ pub struct Options {
   #[structopt(long, takes_value = true, value_name = "ENTRY_LOCATION")]
   pub entry_filter: Option<String>,
 
+  /// Regex matching the source file that entry functions are defined in. Entries
+  /// without debug information are dropped when supplied
+  #[structopt(long = "entry-file", takes_value = true, value_name = "ENTRY_FILE")]
+  pub entry_file_filter: Option<String>,
+
+  /// Regex matching the caller functions whose calls to the targets are not sliced,
+  /// such as the wrappers of the targets
+  #[structopt(long, takes_value = true, value_name = "EXCLUDE_CALL_SITE")]
+  pub exclude_call_site: Option<String>,
+
   /// Use regex in the filters
   #[structopt(long)]
   pub use_regex_filter: bool,
 
+  /// Only keep call sites passing exactly this number of arguments to the target
+  #[structopt(long, takes_value = true, value_name = "TARGET_ARG_COUNT")]
+  pub target_arg_count: Option<usize>,
+
   /// Don't do slice reduction
   #[structopt(long)]
   pub no_reduce_slice: bool,
 
+  /// Drop slices covering the same functions from the same entry to the same target call site
+  #[structopt(long)]
+  pub dedup_slices: bool,
+
+  /// Drop slices containing more than this number of functions
+  #[structopt(long, takes_value = true, value_name = "MAX_SLICE_FUNCTIONS")]
+  pub max_slice_functions: Option<usize>,
+
+  /// Start every slice from this function instead of the entries found from the slice
+  /// depth. Slices whose caller it does not transitively call are dropped
+  #[structopt(long, takes_value = true, value_name = "FORCE_ENTRY")]
+  pub force_entry: Option<String>,
+
+  /// Only run the slicer and print the number of slices per target, without writing any file
+  #[structopt(long)]
+  pub dry_run: bool,
+
+  /// Only run the slicer and dump the slices, to be executed later on with `--execute-from-slices`
+  #[structopt(long)]
+  pub slice_only: bool,
+
+  /// Execute the slices dumped in the output folder by a previous `--slice-only` run on the
+  /// same byte code, instead of slicing again
+  #[structopt(long)]
+  pub execute_from_slices: bool,
+
+  /// Only compute the function types of the byte code and cache them to `target_types.json`,
+  /// where feature extraction picks them up instead of recomputing them from the byte code
+  #[structopt(long)]
+  pub dump_target_types: bool,
+
+  /// Only list the functions with their arity, whether they are defined and their number of call
+  /// sites, then exit
+  #[structopt(long)]
+  pub list_targets: bool,
+
+  /// Only list the functions whose name matches the regex under `--list-targets`
+  #[structopt(long, takes_value = true, value_name = "REGEX")]
+  pub list_targets_filter: Option<String>,
+
   /// Use batch execution. Especially useful when applying to large dataset
   #[structopt(long)]
   pub use_batch: bool,
 
-  /// The number of slices inside each batch
+  /// The number of call edges sliced and executed inside each batch
   #[structopt(long, takes_value = true, default_value = "50", value_name = "BATCH_SIZE")]
   pub batch_size: usize,
 
@@ -96,6 +196,19 @@ pub struct Options {
   #[structopt(long, takes_value = true, default_value = "50", value_name = "MAX_TRACE_PER_SLICE")]
   pub max_trace_per_slice: usize,
 
+  /// Also dump traces that miss the target or are unsatisfiable into `negative_traces`
+  #[structopt(long)]
+  pub collect_negative: bool,
+
+  /// The maximum number of negative traces of each kind per slice
+  #[structopt(
+    long,
+    takes_value = true,
+    default_value = "10",
+    value_name = "MAX_NEGATIVE_TRACE_PER_SLICE"
+  )]
+  pub max_negative_trace_per_slice: usize,
+
   #[structopt(
     long,
     takes_value = true,
@@ -107,18 +220,136 @@ pub struct Options {
   #[structopt(long, takes_value = true, default_value = "5000", value_name = "MAX_NODE_PER_TRACE")]
   pub max_node_per_trace: usize,
 
+  /// End a trace once its memory holds more than this many cells, guarding against states
+  /// growing without bound before reaching the maximum number of nodes
+  #[structopt(long, takes_value = true, value_name = "MAX_MEMORY_CELLS")]
+  pub max_memory_cells: Option<usize>,
+
   #[structopt(long)]
   pub no_trace_reduction: bool,
 
   #[structopt(long)]
   pub no_random_work: bool,
 
+  /// Randomly pick which direction of each forking branch is explored first, reproducibly given
+  /// the seed
+  #[structopt(long)]
+  pub sample_branches: bool,
+
   #[structopt(long)]
   pub print_block_trace: bool,
 
   #[structopt(long)]
   pub print_trace: bool,
 
+  /// Print the slowest N slices after symbolic execution
+  #[structopt(long, takes_value = true, value_name = "NUM_SLICES")]
+  pub timing_report: Option<usize>,
+
+  /// Dump a DOT graph next to each trace json
+  #[structopt(long)]
+  pub dump_dot: bool,
+
+  /// Dump the memory right before the target call along with each trace
+  #[structopt(long)]
+  pub dump_memory: bool,
+
+  /// Only dump the blocks visited by each trace and the target's block, see `BlockOnlyTrace`.
+  /// Traces are then neither reduced nor passed to feature extraction
+  #[structopt(long)]
+  pub block_trace_only: bool,
+
+  /// Dump the trace nodes backward, from the end of the trace back to the entry, see `Trace::reversed`
+  #[structopt(long)]
+  pub reverse_traces: bool,
+
+  /// Store traces as one json file per trace (`json`) or one jsonl file per slice (`jsonl`)
+  #[structopt(long, takes_value = true, default_value = "json", value_name = "TRACE_FORMAT")]
+  pub trace_format: TraceFormat,
+
+  /// Only execute the slices whose id modulo N is I, so that N processes sharing the output
+  /// folder split the slices between them
+  #[structopt(long, takes_value = true, value_name = "I/N")]
+  pub slice_shard: Option<SliceShard>,
+
+  /// Gzip the dumped json traces & features into `.json.gz` files, and the jsonl traces
+  /// into `.jsonl.gz` files
+  #[structopt(long)]
+  pub compress: bool,
+
+  /// Spread the json traces of a slice into subdirectories of at most this many traces each,
+  /// named after `trace_id / TRACE_SHARD_SIZE`, to keep huge slices' folders fast to list
+  #[structopt(long, takes_value = true, value_name = "TRACE_SHARD_SIZE")]
+  pub trace_shard_size: Option<usize>,
+
+  /// Comma separated kinds of trace nodes to dump, among call, compare, branch, return, store,
+  /// load, free, phi, gep, unary and binary. The target, branch and free nodes are always dumped
+  #[structopt(long, takes_value = true, value_name = "TRACE_KEEP_KINDS")]
+  pub trace_keep_kinds: Option<String>,
+
+  /// Stop adding path constraints past this many, leaving further branches unconstrained
+  #[structopt(long, takes_value = true, value_name = "MAX_CONSTRAINTS")]
+  pub max_constraints: Option<usize>,
+
+  /// Json file of concrete values for entry arguments (by index) & globals (by name)
+  #[structopt(long, takes_value = true, value_name = "SEED_FILE")]
+  pub seed_file: Option<String>,
+
+  /// Json map from target names to their own maximum number of traces per slice, overriding
+  /// `--max-trace-per-slice` for these targets
+  #[structopt(long, takes_value = true, value_name = "BUDGET_FILE")]
+  pub budget_file: Option<String>,
+
+  /// Merge the states of the two arms of small branches at their join, guarding diverging memory cells.
+  /// Only diamonds & triangles whose arms hold no call nor branch are merged, right at the branch;
+  /// other paths, and branches with an arm stopping early, e.g. past the memory limit, are still
+  /// forked. Slices are then explored without block trace prefiltering, which would keep the arms apart
+  #[structopt(long)]
+  pub merge_states: bool,
+
+  /// Dump the satisfiable paths reaching an `unreachable` instruction to the assertions folder
+  #[structopt(long)]
+  pub report_unreachable: bool,
+
+  /// Record the slices panicking during symbolic execution and go on with the others
+  #[structopt(long)]
+  pub keep_going: bool,
+
+  /// File of function name regexes, one per line, whose calls are never stepped into
+  #[structopt(long, takes_value = true, value_name = "OPAQUE_FUNCTIONS")]
+  pub opaque_functions: Option<String>,
+
+  /// File of callee name regexes, one per line, whose calls are skipped without any trace node.
+  /// Defaults to the llvm intrinsics, matched by `llvm\.`
+  #[structopt(long, takes_value = true, value_name = "EXCLUDE_INTRINSICS_LIST")]
+  pub exclude_intrinsics_list: Option<String>,
+
+  /// File of function name regexes, one per line, whose calls never return and end the trace,
+  /// on top of the known ones such as `exit` & `abort`
+  #[structopt(long, takes_value = true, value_name = "NORETURN_FUNCTIONS")]
+  pub noreturn_functions: Option<String>,
+
+  /// Skip the slices whose traces were completely dumped by a previous run, as told
+  /// by the `<SLICE_ID>.done` marker every run writes next to the slice folder
+  #[structopt(long)]
+  pub resume: bool,
+
+  /// Stop scheduling new slices once symbolic execution has run for this many seconds
+  #[structopt(long, takes_value = true, value_name = "GLOBAL_TIMEOUT_SEC")]
+  pub global_timeout_sec: Option<u64>,
+
+  /// Dump the blocks covered by symbolic execution in each function to this json file
+  #[structopt(long, takes_value = true, value_name = "COVERAGE_REPORT")]
+  pub coverage_report: Option<String>,
+
+  /// Bound the effort of z3 on each path with this resource limit, past which the path is unknown
+  #[structopt(long, takes_value = true, value_name = "SOLVER_RLIMIT")]
+  pub solver_rlimit: Option<u32>,
+
+  /// Keep the traces whose path satisfiability z3 cannot decide, instead of dropping them
+  #[structopt(long)]
+  pub keep_unknown: bool,
+
   #[structopt(long)]
   pub no_prefilter_block_trace: bool,
 
@@ -135,6 +366,39 @@ pub struct Options {
     value_name = "CAUSALITY_DICTIONARY_SIZE"
   )]
   pub causality_dictionary_size: usize,
+
+  /// Maximum number of calls reported between the previous target call and the target call
+  #[structopt(long, takes_value = true, default_value = "10", value_name = "INTER_CALL_WINDOW")]
+  pub inter_call_window: usize,
+
+  /// Abort feature extraction on unparseable traces instead of skipping them
+  #[structopt(long)]
+  pub strict_traces: bool,
+
+  /// Comma separated names of the only feature extractors to run, as keyed in the dumped features (e.g. ret,arg.0.pre)
+  #[structopt(long = "features", takes_value = true, value_name = "FEATURES")]
+  pub enabled_features: Option<String>,
+
+  /// Comma separated names of feature extractors not to run
+  #[structopt(long = "disable-features", takes_value = true, value_name = "DISABLED_FEATURES")]
+  pub disabled_features: Option<String>,
+
+  /// Randomly down-sample the traces of each target to at most this many before extracting features
+  #[structopt(long, takes_value = true, value_name = "MAX_TRACES_PER_TARGET")]
+  pub max_traces_per_target: Option<usize>,
+
+  /// Only extract the features of traces changed since their features were last extracted,
+  /// or of all the traces of a target whose extractor configuration changed
+  #[structopt(long)]
+  pub incremental: bool,
+
+  /// Load traces & slices dumped with a different json version
+  #[structopt(long)]
+  pub ignore_version: bool,
+
+  /// Dump the count, mean, std, min & max of each numeric feature of every target
+  #[structopt(long)]
+  pub feature_stats: bool,
 }
 
 impl GeneralOptions for Options {
@@ -145,15 +409,22 @@ impl GeneralOptions for Options {
   fn seed(&self) -> u64 {
     self.seed
   }
+
+  fn demangle(&self) -> bool {
+    self.demangle
+  }
 }
 
 impl IOOptions for Options {
   fn input_path(&self) -> PathBuf {
-    PathBuf::from(&self.input)
+    PathBuf::from(self.input.as_ref().expect("Missing input"))
   }
 
   fn output_path(&self) -> PathBuf {
-    PathBuf::from(&self.output)
+    match &self.traces_root {
+      Some(root) => PathBuf::from(root),
+      None => PathBuf::from(self.output.as_ref().expect("Missing output")),
+    }
   }
 
   fn default_package(&self) -> Option<&str> {
@@ -162,9 +433,35 @@ impl IOOptions for Options {
       None => None,
     }
   }
+
+  fn compress(&self) -> bool {
+    self.compress
+  }
+
+  fn trace_shard_size(&self) -> Option<usize> {
+    self.trace_shard_size
+  }
 }
 
 impl Options {
+  /// Parse the options from the command line matches, rejecting the values out
+  /// of their domain
+  fn from_matches(matches: &structopt::clap::ArgMatches) -> Result<Self, AnalyzerError> {
+    let mut options = Self::from_clap(matches);
+    options.slice_depth = parse_slice_depth(&options.slice_depth_arg)?;
+    if options.trace_shard_size == Some(0) {
+      return Err(AnalyzerError::OptionParse(
+        "--trace-shard-size must be positive".to_string(),
+      ));
+    }
+    if options.block_trace_only && options.traces_root.is_some() {
+      return Err(AnalyzerError::OptionParse(
+        "--traces-root extracts features, which cannot be done on --block-trace-only traces".to_string(),
+      ));
+    }
+    Ok(options)
+  }
+
   fn target_num_slices_map_path(&self) -> Option<PathBuf> {
     if let Some(filename) = &self.target_num_slices_map_file {
       Some(self.output_path().join(filename))
@@ -185,6 +482,10 @@ impl CallGraphOptions for Options {
   fn remove_llvm_funcs(&self) -> bool {
     !self.no_remove_llvm_funcs
   }
+
+  fn resolve_indirect(&self) -> bool {
+    self.resolve_indirect
+  }
 }
 
 impl SlicerOptions for Options {
@@ -200,6 +501,10 @@ impl SlicerOptions for Options {
     &self.entry_filter
   }
 
+  fn target(&self) -> &Option<String> {
+    &self.target
+  }
+
   fn target_inclusion_filter(&self) -> &Option<String> {
     &self.target_inclusion_filter
   }
@@ -215,6 +520,30 @@ impl SlicerOptions for Options {
   fn max_avg_num_blocks(&self) -> usize {
     self.max_avg_num_blocks
   }
+
+  fn target_arg_count(&self) -> Option<usize> {
+    self.target_arg_count
+  }
+
+  fn entry_file_filter(&self) -> &Option<String> {
+    &self.entry_file_filter
+  }
+
+  fn exclude_call_site(&self) -> &Option<String> {
+    &self.exclude_call_site
+  }
+
+  fn dedup_slices(&self) -> bool {
+    self.dedup_slices
+  }
+
+  fn max_slice_functions(&self) -> Option<usize> {
+    self.max_slice_functions
+  }
+
+  fn force_entry(&self) -> &Option<String> {
+    &self.force_entry
+  }
 }
 
 impl SymbolicExecutionOptions for Options {
@@ -230,10 +559,18 @@ impl SymbolicExecutionOptions for Options {
     self.no_random_work
   }
 
+  fn sample_branches(&self) -> bool {
+    self.sample_branches
+  }
+
   fn max_node_per_trace(&self) -> usize {
     self.max_node_per_trace
   }
 
+  fn max_memory_cells(&self) -> Option<usize> {
+    self.max_memory_cells
+  }
+
   fn max_explored_trace_per_slice(&self) -> usize {
     self.max_explored_trace_per_slice
   }
@@ -242,6 +579,14 @@ impl SymbolicExecutionOptions for Options {
     self.max_trace_per_slice
   }
 
+  fn collect_negative(&self) -> bool {
+    self.collect_negative
+  }
+
+  fn max_negative_trace_per_slice(&self) -> usize {
+    self.max_negative_trace_per_slice
+  }
+
   fn no_trace_reduction(&self) -> bool {
     self.no_trace_reduction
   }
@@ -257,116 +602,429 @@ impl SymbolicExecutionOptions for Options {
   fn print_trace(&self) -> bool {
     self.print_trace
   }
+
+  fn timing_report(&self) -> Option<usize> {
+    self.timing_report
+  }
+
+  fn dump_dot(&self) -> bool {
+    self.dump_dot
+  }
+
+  fn dump_memory(&self) -> bool {
+    self.dump_memory
+  }
+
+  fn block_trace_only(&self) -> bool {
+    self.block_trace_only
+  }
+
+  fn reverse_traces(&self) -> bool {
+    self.reverse_traces
+  }
+
+  fn trace_format(&self) -> TraceFormat {
+    self.trace_format
+  }
+
+  fn slice_shard(&self) -> Option<SliceShard> {
+    self.slice_shard
+  }
+
+  fn trace_keep_kinds(&self) -> &Option<String> {
+    &self.trace_keep_kinds
+  }
+
+  fn max_constraints(&self) -> Option<usize> {
+    self.max_constraints
+  }
+
+  fn seed_file(&self) -> &Option<String> {
+    &self.seed_file
+  }
+
+  fn merge_states(&self) -> bool {
+    self.merge_states
+  }
+
+  fn report_unreachable(&self) -> bool {
+    self.report_unreachable
+  }
+
+  fn keep_going(&self) -> bool {
+    self.keep_going
+  }
+
+  fn opaque_functions(&self) -> &Option<String> {
+    &self.opaque_functions
+  }
+
+  fn exclude_intrinsics_list(&self) -> &Option<String> {
+    &self.exclude_intrinsics_list
+  }
+
+  fn noreturn_functions(&self) -> &Option<String> {
+    &self.noreturn_functions
+  }
+
+  fn resume(&self) -> bool {
+    self.resume
+  }
+
+  fn global_timeout_sec(&self) -> Option<u64> {
+    self.global_timeout_sec
+  }
+
+  fn coverage_report(&self) -> &Option<String> {
+    &self.coverage_report
+  }
+
+  fn budget_file(&self) -> &Option<String> {
+    &self.budget_file
+  }
+
+  fn solver_rlimit(&self) -> Option<u32> {
+    self.solver_rlimit
+  }
+
+  fn keep_unknown(&self) -> bool {
+    self.keep_unknown
+  }
 }
 
 impl FeatureExtractorOptions for Options {
   fn causality_dictionary_size(&self) -> usize {
     self.causality_dictionary_size
   }
+
+  fn inter_call_window(&self) -> usize {
+    self.inter_call_window
+  }
+
+  fn strict_traces(&self) -> bool {
+    self.strict_traces
+  }
+
+  fn max_traces_per_target(&self) -> Option<usize> {
+    self.max_traces_per_target
+  }
+
+  fn enabled_features(&self) -> &Option<String> {
+    &self.enabled_features
+  }
+
+  fn disabled_features(&self) -> &Option<String> {
+    &self.disabled_features
+  }
+
+  fn incremental(&self) -> bool {
+    self.incremental
+  }
+
+  fn ignore_version(&self) -> bool {
+    self.ignore_version
+  }
+
+  fn feature_stats(&self) -> bool {
+    self.feature_stats
+  }
+}
+
+/// Execute the slices dumped by a previous `--slice-only` run
+fn execute_from_slices<'ctx>(
+  llmods: &[llir::Module<'ctx>],
+  call_graph: &CallGraph<'ctx>,
+  options: &Options,
+  logging_ctx: &mut LoggingContext,
+) -> Result<(TargetNumSlicesMap, MetaData), String> {
+  let target_slices_map = TargetSlicesMap::load(call_graph, options, options.ignore_version)?;
+  logging_ctx.log(&format!(
+    "Loaded {} slices from {:?}",
+    target_slices_map.num_elements(),
+    options.slice_dir()
+  ))?;
+  let target_num_slices_map = target_slices_map
+    .iter()
+    .map(|(target, slices)| (target.clone(), slices.len()))
+    .collect::<TargetNumSlicesMap>();
+
+  let mut sym_exec_ctx = SymbolicExecutionContext::new(llmods, call_graph, options)?;
+  sym_exec_ctx.deadline = options
+    .global_timeout_sec
+    .map(|sec| Instant::now() + Duration::from_secs(sec));
+  let metadata = sym_exec_ctx.execute_target_slices_map(
+    target_slices_map
+      .into_iter()
+      .map(|(target, slices)| (target, (0, slices)))
+      .collect(),
+  );
+  Ok((target_num_slices_map, metadata))
+}
+
+/// Slice all the call edges at once and only execute the slice with the given id
+fn execute_only_slice<'ctx>(
+  slice_id: usize,
+  target_edges_map: &TargetEdgesMap,
+  llmods: &[llir::Module<'ctx>],
+  call_graph: &CallGraph<'ctx>,
+  options: &Options,
+  logging_ctx: &mut LoggingContext,
+) -> Result<(), String> {
+  let func_name = if let Some(func_name) = &options.execute_only_slice_function_name {
+    func_name
+  } else {
+    return Err(format!("Must provide function name"));
+  };
+
+  // Generate and dump the slices
+  let (target_slices_map, num_dropped) =
+    TargetSlicesMap::from_target_edges_map_with_dropped(target_edges_map, call_graph, options)?;
+  if num_dropped > 0 {
+    logging_ctx.log_dropped_slices(num_dropped)?;
+  }
+  logging_ctx.log_generated_slices(target_slices_map.num_elements())?;
+  target_slices_map.dump(options);
+  TargetTypes::from_modules(llmods).dump(options.target_types_path())?;
+
+  // Only execute slice
+  logging_ctx.log(&format!(
+    "Executing the only slice for function {} and slice id {}",
+    func_name, slice_id
+  ))?;
+
+  if let Some(slices) = target_slices_map.get(func_name) {
+    if let Some(slice) = slices.get(slice_id) {
+      // Do symbolic execution on that single slice
+      let sym_exec_ctx = SymbolicExecutionContext::new(llmods, call_graph, options)?;
+      let metadata = sym_exec_ctx.execute_slice(slice.clone(), slice_id);
+
+      // Print the result
+      logging_ctx.log(&format!(
+        "Result executing slice {} {} {:?}",
+        func_name, slice_id, metadata
+      ))?;
+
+      Ok(())
+    } else {
+      Err(format!(
+        "Cannot find slice for function {} with slice id {}",
+        func_name, slice_id
+      ))
+    }
+  } else {
+    Err(format!("Cannot find slice for function {}", func_name))
+  }
+}
+
+/// Report the execution of all the slices, and dump the run summary & slice counts
+fn report_execution<'ctx>(
+  begin: Instant,
+  global_metadata: MetaData,
+  target_num_slices_map: &TargetNumSlicesMap,
+  call_graph: &CallGraph<'ctx>,
+  options: &Options,
+  logging_ctx: &mut LoggingContext,
+) -> Result<(), String> {
+  if let Some(n) = options.timing_report {
+    logging_ctx.log_timing_report(&global_metadata, n)?;
+  }
+  if let Some(path) = &options.coverage_report {
+    dump_json(&global_metadata.block_coverage.report(call_graph), PathBuf::from(path))?;
+  }
+  let run_summary = RunSummary {
+    metadata: global_metadata.clone(),
+    num_targets: target_num_slices_map.len(),
+    num_slices: target_num_slices_map.values().sum(),
+    wall_clock_ms: begin.elapsed().as_secs_f64() * 1000.0,
+    args: std::env::args().collect(),
+    options: serde_json::to_value(options).map_err(|err| format!("Cannot serialize options: {}", err))?,
+  };
+  dump_json(&run_summary.to_json(), options.run_summary_path())?;
+  logging_ctx.log_finished_execution(options.use_batch, global_metadata)?;
+
+  if let Some(filename) = options.target_num_slices_map_path() {
+    target_num_slices_map.dump(filename)?;
+  }
+  Ok(())
 }
 
 fn main() -> Result<(), String> {
-  let options = Options::from_args();
+  let begin = Instant::now();
+  let options = Options::from_matches(&Options::clap().get_matches())?;
   if options.print_options {
     println!("{:?}", options);
   }
 
+  // Diagnostics go to stderr, away from the traces & logs printed to stdout
+  let default_level = if options.verbose { "debug" } else { "warn" };
+  env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_level)).init();
+
   // Load a logging context
-  let mut logging_ctx = LoggingContext::new(&options)?;
+  let mut logging_ctx = if options.dry_run {
+    LoggingContext::stdout_only()
+  } else {
+    LoggingContext::new(&options)?
+  };
+
+  // Extract features from a previous run without touching the byte code
+  if options.traces_root.is_some() {
+    let target_types = TargetTypes::load(&options.target_types_path(), options.ignore_version)?;
+    let target_num_slices_map = discover_target_num_slices_map(&options)?;
+    logging_ctx.log_extracting_features()?;
+    let feat_ext_ctx = FeatureExtractionContext::from_target_types(target_types, target_num_slices_map, &options)?;
+    feat_ext_ctx.extract_features(&mut logging_ctx);
+    return logging_ctx.log_finished_extracting_features();
+  }
 
-  // Load the byte code module and generate analyzer context
+  // Load the byte code modules and generate analyzer context
   logging_ctx.log_loading_bc()?;
   let llctx = llir::Context::create();
-  let llmod = llctx
+  let mut llmods = vec![llctx
     .load_module(&options.input_path())
-    .map_err(|err| err.to_string())?;
+    .map_err(|err| err.to_string())?];
+  for path in &options.link {
+    llmods.push(llctx.load_module(&PathBuf::from(path)).map_err(|err| err.to_string())?);
+  }
+
+  // Only cache the function types for later feature extraction
+  if options.dump_target_types {
+    TargetTypes::from_modules(&llmods).dump(options.target_types_path())?;
+    return logging_ctx.log(&format!("Dumped target types to {:?}", options.target_types_path()));
+  }
 
   // Generate call graph
   logging_ctx.log_generating_call_graph()?;
-  let call_graph = CallGraph::from_module(&llmod, &options);
+  let call_graph = CallGraph::from_modules(&llmods, &options);
   if options.print_call_graph {
     call_graph.print();
   }
+  if let Some(path) = &options.dump_call_graph {
+    call_graph.dump(PathBuf::from(path))?;
+  }
+  if options.list_targets {
+    let filter = match &options.list_targets_filter {
+      Some(filter) => Some(Regex::new(filter).map_err(|_| format!("Cannot parse target list filter {}", filter))?),
+      None => None,
+    };
+    for listing in call_graph.list_functions(filter.as_ref()) {
+      println!("{}", listing.to_display_string());
+    }
+    return Ok(());
+  }
 
   // Finding call edges
   logging_ctx.log_finding_call_edges()?;
   let target_edges_map = TargetEdgesMap::from_call_graph(&call_graph, &options)?;
 
   // Check if we need to "redo" the symbolic execution
-  let target_num_slices_map = if !options.feature_only {
-    // Generate slices
+  let target_num_slices_map = if options.execute_from_slices {
+    let (target_num_slices_map, global_metadata) =
+      execute_from_slices(&llmods, &call_graph, &options, &mut logging_ctx)?;
+    report_execution(
+      begin,
+      global_metadata,
+      &target_num_slices_map,
+      &call_graph,
+      &options,
+      &mut logging_ctx,
+    )?;
+    target_num_slices_map
+  } else if !options.feature_only {
     logging_ctx.log_generated_call_edges(target_edges_map.num_elements())?;
-    let target_slices_map = TargetSlicesMap::from_target_edges_map(&target_edges_map, &call_graph, &options);
-    let target_num_slices_map = target_slices_map.keyed_num_elements();
+    if !options.dry_run {
+      if let Some(slice_id) = options.execute_only_slice_id {
+        return execute_only_slice(
+          slice_id,
+          &target_edges_map,
+          &llmods,
+          &call_graph,
+          &options,
+          &mut logging_ctx,
+        );
+      }
 
-    // Dump slices
-    logging_ctx.log_generated_slices(target_slices_map.num_elements())?;
-    target_slices_map.dump(&options);
+      // Dump the target names & types needed to extract features later on
+      target_edges_map.dump_target_names(&options);
+      TargetTypes::from_modules(&llmods).dump(options.target_types_path())?;
+    }
 
-    if let Some(slice_id) = &options.execute_only_slice_id {
-      let func_name = if let Some(func_name) = &options.execute_only_slice_function_name {
-        func_name
-      } else {
-        return Err(format!("Must provide function name"));
-      };
+    // Divide the call edges into batches, only keeping the slices of one batch at a time
+    logging_ctx.log_dividing_batches(options.use_batch)?;
+    let mut progress = TargetSlicingProgressMap::new();
+    let mut global_metadata = MetaData::new();
+    let deadline = options
+      .global_timeout_sec
+      .map(|sec| Instant::now() + Duration::from_secs(sec));
+    for (i, target_edges_batch) in target_edges_map.clone().batches(options.use_batch, options.batch_size) {
+      // Generate and dump the slices of the batch, numbered after the previous batches
+      let (target_slices_map, num_dropped) =
+        OffsetTargetSlicesMap::from_target_edges_batch(&target_edges_batch, &call_graph, &options, &mut progress)?;
+      if num_dropped > 0 {
+        logging_ctx.log_dropped_slices(num_dropped)?;
+      }
 
-      // Only execute slice
-      logging_ctx.log(&format!(
-        "Executing the only slice for function {} and slice id {}",
-        func_name, slice_id
-      ))?;
+      // In dry run mode we only count the slices
+      if options.dry_run {
+        continue;
+      }
+      logging_ctx.log_generated_slices(target_slices_map.num_elements())?;
+      target_slices_map.dump(&options);
+      if options.slice_only {
+        continue;
+      }
 
-      return if let Some(slices) = target_slices_map.get(func_name) {
-        if let Some(slice) = slices.get(*slice_id) {
-          // Do symbolic execution on that single slice
-          let sym_exec_ctx = SymbolicExecutionContext::new(&llmod, &call_graph, &options);
-          let metadata = sym_exec_ctx.execute_slice(slice.clone(), *slice_id);
-
-          // Print the result
-          logging_ctx.log(&format!(
-            "Result executing slice {} {} {:?}",
-            func_name, slice_id, metadata
-          ))?;
-
-          Ok(())
-        } else {
-          Err(format!(
-            "Cannot find slice for function {} with slice id {}",
-            func_name, slice_id
-          ))
-        }
-      } else {
-        Err(format!("Cannot find slice for function {}", func_name))
-      };
-    } else {
-      // Divide target slices into batches
-      logging_ctx.log_dividing_batches(options.use_batch)?;
-      let mut global_metadata = MetaData::new();
-      for (i, target_slices_map) in target_slices_map.batches(options.use_batch, options.batch_size) {
-        // Generate slices from the edges
-        logging_ctx.log_executing_batch(i, options.use_batch, target_slices_map.num_elements())?;
-        let sym_exec_ctx = SymbolicExecutionContext::new(&llmod, &call_graph, &options);
-        let metadata = sym_exec_ctx.execute_target_slices_map(target_slices_map);
-        global_metadata = global_metadata.combine(metadata.clone());
-        logging_ctx.log_finished_execution_batch(i, options.use_batch, metadata)?;
+      // Execute the slices, all batches sharing the same deadline
+      logging_ctx.log_executing_batch(i, options.use_batch, target_slices_map.num_elements())?;
+      let mut sym_exec_ctx = SymbolicExecutionContext::new(&llmods, &call_graph, &options)?;
+      sym_exec_ctx.deadline = deadline;
+      let metadata = sym_exec_ctx.execute_target_slices_map(target_slices_map);
+      global_metadata = global_metadata.combine(metadata.clone());
+      logging_ctx.log_finished_execution_batch(i, options.use_batch, metadata)?;
+
+      // Stop scheduling the remaining batches past the global timeout
+      if global_metadata.truncated {
+        logging_ctx.log("Global timeout exceeded, skipping the remaining slices")?;
+        break;
       }
-      logging_ctx.log_finished_execution(options.use_batch, global_metadata)?;
+    }
+    let target_num_slices_map = progress.target_num_slices_map();
 
+    // In dry run mode we only report the slice counts
+    if options.dry_run {
+      return logging_ctx.log_slice_counts(&target_num_slices_map);
+    }
+    if options.slice_only {
       if let Some(filename) = options.target_num_slices_map_path() {
         target_num_slices_map.dump(filename)?;
       }
-
-      target_num_slices_map
+      return logging_ctx.log(&format!("Dumped slices to {:?}", options.slice_dir()));
     }
+
+    report_execution(
+      begin,
+      global_metadata,
+      &target_num_slices_map,
+      &call_graph,
+      &options,
+      &mut logging_ctx,
+    )?;
+    target_num_slices_map
   } else {
     // If not, we directly load slices information from file
     load_target_num_slices_map(target_edges_map, &options)
   };
 
-  if !options.no_feature {
+  if !options.no_feature && options.block_trace_only {
+    logging_ctx.log_skipped_block_only_features()?;
+  } else if !options.no_feature {
     // Extract features
     logging_ctx.log_extracting_features()?;
-    let feat_ext_ctx = FeatureExtractionContext::new(&llmod, target_num_slices_map, &options)?;
+    let target_types = if options.target_types_path().exists() {
+      TargetTypes::load(&options.target_types_path(), options.ignore_version)?
+    } else {
+      TargetTypes::from_modules(&llmods)
+    };
+    let feat_ext_ctx = FeatureExtractionContext::from_target_types(target_types, target_num_slices_map, &options)?;
     feat_ext_ctx.extract_features(&mut logging_ctx);
     logging_ctx.log_finished_extracting_features()?;
   }
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+use analyzer::unchecked_return::*;
+use analyzer::utils::*;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "detect-unchecked-return")]
+pub struct Options {
+  /// Directory holding the features, usually the `features` folder of an output directory
+  #[structopt(index = 1, required = true, value_name = "FEATURES_DIR")]
+  features_dir: String,
+
+  /// Only flag the traces of targets whose return value is checked by at least this ratio of traces
+  #[structopt(long, takes_value = true, default_value = "0.8", value_name = "MIN_CHECK_RATE")]
+  min_check_rate: f32,
+
+  /// Also dump the report as json to the given path
+  #[structopt(long, takes_value = true, value_name = "REPORT")]
+  report: Option<String>,
+}
+
+fn main() -> Result<(), String> {
+  let options = Options::from_args();
+  let report = detect_unchecked_returns(&PathBuf::from(&options.features_dir), options.min_check_rate)?;
+  for (target, trace) in &report.unchecked {
+    println!("{}: return value of {} unchecked", trace, target);
+  }
+  println!(
+    "{} targets, {} unchecked traces",
+    report.check_counts.len(),
+    report.unchecked.len()
+  );
+  if let Some(path) = &options.report {
+    dump_json(&report.to_json(), PathBuf::from(path))?;
+  }
+  Ok(())
+}
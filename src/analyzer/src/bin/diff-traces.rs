@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+use analyzer::trace_diff::*;
+use analyzer::utils::*;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "diff-traces")]
+pub struct Options {
+  /// The traces of the first run, usually the `traces` folder of an output directory
+  #[structopt(index = 1, required = true, value_name = "OLD_TRACES_DIR")]
+  old_traces_dir: String,
+
+  /// The traces of the second run
+  #[structopt(index = 2, required = true, value_name = "NEW_TRACES_DIR")]
+  new_traces_dir: String,
+
+  /// Accept traces dumped with a different json version
+  #[structopt(long)]
+  ignore_version: bool,
+
+  /// Also dump the report as json to the given path
+  #[structopt(long, takes_value = true, value_name = "REPORT")]
+  report: Option<String>,
+}
+
+fn main() -> Result<(), String> {
+  let options = Options::from_args();
+  let report = diff_traces(
+    &PathBuf::from(&options.old_traces_dir),
+    &PathBuf::from(&options.new_traces_dir),
+    options.ignore_version,
+  )?;
+  for (kind, keys) in vec![("+", &report.added), ("-", &report.removed), ("~", &report.changed)] {
+    for (target, slice_id, trace_id) in keys {
+      println!("{} {} slice {} trace {}", kind, target, slice_id, trace_id);
+    }
+  }
+  println!(
+    "{} added, {} removed, {} changed, {} unchanged",
+    report.added.len(),
+    report.removed.len(),
+    report.changed.len(),
+    report.num_unchanged
+  );
+  if let Some(path) = &options.report {
+    dump_json(&report.to_json(), PathBuf::from(path))?;
+  }
+  Ok(())
+}
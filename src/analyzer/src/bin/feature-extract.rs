@@ -1,11 +1,14 @@
 use llir::{types::*, *};
+use log::{info, warn};
 use rayon::prelude::*;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use structopt::StructOpt;
 
+use analyzer::error::*;
 use analyzer::feature_extraction::*;
 use analyzer::options::*;
 use analyzer::utils::*;
@@ -19,8 +22,59 @@ pub struct Options {
   #[structopt(index = 2, required = true, value_name = "OUTPUT")]
   output: String,
 
+  /// Random seed, as given to the analyzer
+  #[structopt(long, takes_value = true, default_value = "12345", value_name = "SEED")]
+  seed: u64,
+
   #[structopt(long, default_value = "10")]
   causality_dictionary_size: usize,
+
+  /// Maximum number of calls reported between the previous target call and the target call
+  #[structopt(long, default_value = "10")]
+  inter_call_window: usize,
+
+  /// Abort on unparseable traces instead of skipping them
+  #[structopt(long)]
+  strict_traces: bool,
+
+  /// Comma separated names of the only feature extractors to run, as keyed in the dumped features (e.g. ret,arg.0.pre)
+  #[structopt(long = "features", takes_value = true, value_name = "FEATURES")]
+  enabled_features: Option<String>,
+
+  /// Comma separated names of feature extractors not to run
+  #[structopt(long = "disable-features", takes_value = true, value_name = "DISABLED_FEATURES")]
+  disabled_features: Option<String>,
+
+  /// Only extract the features of traces changed since their features were last extracted,
+  /// or of all the traces of a target whose extractor configuration changed
+  #[structopt(long)]
+  incremental: bool,
+
+  /// Load traces & slices dumped with a different json version
+  #[structopt(long)]
+  ignore_version: bool,
+
+  /// Gzip the dumped feature files
+  #[structopt(long)]
+  compress: bool,
+
+  /// The `--trace-shard-size` the traces were dumped with
+  #[structopt(long, takes_value = true, value_name = "TRACE_SHARD_SIZE")]
+  trace_shard_size: Option<usize>,
+}
+
+impl GeneralOptions for Options {
+  fn use_serial(&self) -> bool {
+    false
+  }
+
+  fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  fn demangle(&self) -> bool {
+    false
+  }
 }
 
 impl IOOptions for Options {
@@ -35,12 +89,68 @@ impl IOOptions for Options {
   fn default_package(&self) -> Option<&str> {
     None
   }
+
+  fn compress(&self) -> bool {
+    self.compress
+  }
+
+  fn trace_shard_size(&self) -> Option<usize> {
+    self.trace_shard_size
+  }
 }
 
 impl FeatureExtractorOptions for Options {
   fn causality_dictionary_size(&self) -> usize {
     self.causality_dictionary_size
   }
+
+  fn inter_call_window(&self) -> usize {
+    self.inter_call_window
+  }
+
+  fn strict_traces(&self) -> bool {
+    self.strict_traces
+  }
+
+  /// Traces of multiple packages are never down-sampled
+  fn max_traces_per_target(&self) -> Option<usize> {
+    None
+  }
+
+  fn enabled_features(&self) -> &Option<String> {
+    &self.enabled_features
+  }
+
+  fn disabled_features(&self) -> &Option<String> {
+    &self.disabled_features
+  }
+
+  fn incremental(&self) -> bool {
+    self.incremental
+  }
+
+  fn ignore_version(&self) -> bool {
+    self.ignore_version
+  }
+
+  /// Only computed by the analyzer's feature extraction
+  fn feature_stats(&self) -> bool {
+    false
+  }
+}
+
+impl Options {
+  /// Parse the options from the command line matches, rejecting the values out
+  /// of their domain
+  fn from_matches(matches: &structopt::clap::ArgMatches) -> Result<Self, AnalyzerError> {
+    let options = Self::from_clap(matches);
+    if options.trace_shard_size == Some(0) {
+      return Err(AnalyzerError::OptionParse(
+        "--trace-shard-size must be positive".to_string(),
+      ));
+    }
+    Ok(options)
+  }
 }
 
 /// Read input file
@@ -97,8 +207,8 @@ pub type TargetPackageNumSlicesMap = HashMap<String, Vec<(String, usize)>>;
 
 pub type Packages<'ctx> = HashMap<String, (Module<'ctx>, HashMap<String, FunctionType<'ctx>>)>;
 
-fn load_slice(path: PathBuf) -> Slice {
-  load_json_t(&path).expect("Cannot load slice file")
+fn load_slice(options: &Options, path: PathBuf) -> Slice {
+  load_versioned_json_t(&path, options.ignore_version).expect("Cannot load slice file")
 }
 
 fn load_slices(options: &Options, target: &str, package: &str, num_slices: usize) -> Vec<Slice> {
@@ -107,24 +217,50 @@ fn load_slices(options: &Options, target: &str, package: &str, num_slices: usize
     .into_par_iter()
     .map(|slice_id| {
       let path = options.slice_target_package_file_path(target, package, slice_id);
-      load_slice(path)
+      load_slice(options, path)
     })
     .collect::<Vec<_>>()
 }
 
-fn load_trace_file_paths(options: &Options, target: &str, package: &str, slice_id: usize) -> Vec<(usize, PathBuf)> {
-  fs::read_dir(options.trace_target_package_slice_dir(target, package, slice_id))
-    .expect("Cannot read traces folder")
-    .map(|path| {
-      let path = path.expect("Cannot read traces folder path").path();
-      let trace_id = path.file_stem().unwrap().to_str().unwrap().parse::<usize>().unwrap();
-      (trace_id, path)
+/// Load the traces of a slice along with their ids, from the slice's jsonl file
+/// or its per-trace json files. Traces dumped under `--reverse-traces` are turned
+/// forward, as the extractors expect. Unparseable traces abort under `--strict-traces`
+/// and are otherwise skipped, and reported and counted in `num_skipped` when given,
+/// so that traces loaded more than once are only reported once
+fn load_traces(
+  options: &Options,
+  target: &str,
+  package: &str,
+  slice_id: usize,
+  num_skipped: Option<&AtomicUsize>,
+) -> Vec<(usize, Trace)> {
+  let files = SliceTraceFiles::of_package_slice(options, target, package, slice_id);
+  let traces = match files.load(options.ignore_version) {
+    Ok(traces) => traces,
+    Err(err) if options.strict_traces => panic!("Cannot load {} slice {} traces: {}", target, slice_id, err),
+    Err(err) => {
+      if num_skipped.is_some() {
+        warn!(target: FEATURES_LOG, "Skipping {} slice {} traces: {}", target, slice_id, err);
+      }
+      vec![]
+    }
+  };
+  traces
+    .into_iter()
+    .filter_map(|(trace_id, trace)| match trace {
+      Ok(trace) => Some((trace_id, trace)),
+      Err(err) if options.strict_traces => {
+        panic!("Cannot load {} slice {} trace {}: {}", target, slice_id, trace_id, err)
+      }
+      Err(err) => {
+        if let Some(num_skipped) = num_skipped {
+          warn!(target: FEATURES_LOG, "Skipping {} slice {} trace {}: {}", target, slice_id, trace_id, err);
+          num_skipped.fetch_add(1, Ordering::SeqCst);
+        }
+        None
+      }
     })
-    .collect::<Vec<_>>()
-}
-
-pub fn load_trace(path: PathBuf) -> Trace {
-  load_json_t(&path).expect("Cannot load trace file")
+    .collect()
 }
 
 pub fn func_types<'ctx>(packages: &Packages<'ctx>, target: &str) -> Option<FunctionType<'ctx>> {
@@ -137,10 +273,12 @@ pub fn func_types<'ctx>(packages: &Packages<'ctx>, target: &str) -> Option<Funct
 }
 
 fn main() -> Result<(), String> {
-  let options = Options::from_args();
+  let options = Options::from_matches(&Options::clap().get_matches())?;
+  env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+  FeatureExtractorRegistry::builtin().enabled_names(&options)?;
   let input = Input::from_options(&options);
 
-  println!("Loading modules...");
+  info!(target: FEATURES_LOG, "Loading modules...");
 
   let llctx = Context::create();
   let mut packages = Packages::new();
@@ -154,30 +292,28 @@ fn main() -> Result<(), String> {
     return Err("No packages included".to_string());
   }
 
-  println!("Building target map...");
+  info!(target: FEATURES_LOG, "Building target map...");
 
   let mut target_map = TargetPackageNumSlicesMap::new();
   for input_function in input.functions {
     target_map.insert(input_function.name, input_function.occurrences);
   }
 
+  let num_skipped = AtomicUsize::new(0);
   target_map.into_par_iter().for_each(|(target, package_num_slices)| {
-    let func_type = func_types(&packages, &target).unwrap();
+    let target_type = TargetType::from_function_type(func_types(&packages, &target).unwrap());
 
-    let mut extractors = FeatureExtractors::extractors_for_target(&target, func_type, &options);
+    let mut extractors = FeatureExtractors::extractors_for_target(&target, &target_type, &options);
 
-    println!("Initializing feature extractors for {}...", target);
+    info!(target: FEATURES_LOG, "Initializing feature extractors for {}...", target);
 
     for (package, num_slices) in &package_num_slices {
       let slices = load_slices(&options, &target, &package, num_slices.clone());
       (0..num_slices.clone()).for_each(|slice_id| {
         let slice = &slices[slice_id];
-        let traces = load_trace_file_paths(&options, &target, &package, slice_id)
-          .into_par_iter()
-          .map(|(_, dir_entry)| load_trace(dir_entry))
-          .collect::<Vec<_>>();
+        let traces = load_traces(&options, &target, &package, slice_id, Some(&num_skipped));
         let num_traces = traces.len();
-        for trace in traces {
+        for (_, trace) in traces {
           extractors.initialize(slice_id, slice, num_traces, &trace);
         }
       });
@@ -185,7 +321,7 @@ fn main() -> Result<(), String> {
 
     extractors.finalize();
 
-    println!("Extracting features for {}...", target);
+    info!(target: FEATURES_LOG, "Extracting features for {}...", target);
 
     package_num_slices.into_par_iter().for_each(|(package, num_slices)| {
       let slices = load_slices(&options, &target, &package, num_slices);
@@ -194,18 +330,22 @@ fn main() -> Result<(), String> {
         fs::create_dir_all(options.feature_target_package_slice_dir(&target, &package, slice_id))
           .expect("Cannot create features target slice directory");
 
-        // Then load trace file directories
-        load_trace_file_paths(&options, &target, &package, slice_id)
+        // Then load the traces and extract their features, the unparseable ones being
+        // already reported during initialization
+        load_traces(&options, &target, &package, slice_id, None)
           .into_par_iter()
-          .for_each(|(trace_id, dir_entry)| {
-            let trace = load_trace(dir_entry);
+          .for_each(|(trace_id, trace)| {
             let features = extractors.extract_features(slice_id, &slice, &trace);
             let path = options.feature_target_package_slice_file_path(&target, &package, slice_id, trace_id);
-            dump_json(&features, path).expect("Cannot dump features json");
+            dump_versioned_json(&features, path).expect("Cannot dump features json");
           });
       })
     })
   });
 
+  let num_skipped = num_skipped.into_inner();
+  if num_skipped > 0 {
+    warn!(target: FEATURES_LOG, "Skipped {} unparseable traces", num_skipped);
+  }
   Ok(())
 }
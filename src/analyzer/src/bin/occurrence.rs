@@ -35,12 +35,24 @@ impl IOOptions for Options {
   fn default_package(&self) -> Option<&str> {
     None
   }
+
+  fn compress(&self) -> bool {
+    false
+  }
+
+  fn trace_shard_size(&self) -> Option<usize> {
+    None
+  }
 }
 
 impl CallGraphOptions for Options {
   fn remove_llvm_funcs(&self) -> bool {
     true
   }
+
+  fn resolve_indirect(&self) -> bool {
+    false
+  }
 }
 
 impl Options {
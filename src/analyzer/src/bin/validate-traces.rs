@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+use analyzer::trace_validation::*;
+use analyzer::utils::*;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "validate-traces")]
+pub struct Options {
+  /// Directory holding the traces, usually the `traces` folder of an output directory
+  #[structopt(index = 1, required = true, value_name = "TRACES_DIR")]
+  traces_dir: String,
+
+  /// Accept traces dumped with a different json version
+  #[structopt(long)]
+  ignore_version: bool,
+
+  /// Also dump the report as json to the given path
+  #[structopt(long, takes_value = true, value_name = "REPORT")]
+  report: Option<String>,
+}
+
+fn main() -> Result<(), String> {
+  let options = Options::from_args();
+  let report = validate_traces(&PathBuf::from(&options.traces_dir), options.ignore_version)?;
+  for (trace, reason) in &report.problems {
+    println!("{}: {}", trace, reason);
+  }
+  println!(
+    "{} traces, {} corrupt, {} invalid",
+    report.num_traces, report.num_corrupt, report.num_invalid
+  );
+  if let Some(path) = &options.report {
+    dump_json(&report.to_json(), PathBuf::from(path))?;
+  }
+  if report.is_ok() {
+    Ok(())
+  } else {
+    Err(format!("{} bad traces", report.num_corrupt + report.num_invalid))
+  }
+}
@@ -2,34 +2,49 @@ use llir::{values::*, *};
 use petgraph::{
   graph::{DiGraph, EdgeIndex, Graph, NodeIndex},
   visit::EdgeRef,
+  Direction,
 };
-use std::collections::HashMap;
+use regex::Regex;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
 
 use crate::utils::*;
 
 pub trait CallGraphOptions {
   fn remove_llvm_funcs(&self) -> bool;
+
+  fn resolve_indirect(&self) -> bool;
 }
 
 pub struct CallEdge<'ctx> {
   pub caller: Function<'ctx>,
   pub callee: Function<'ctx>,
-  pub instr: CallInstruction<'ctx>,
+  pub instr: CallSite<'ctx>,
+
+  /// The name of the alias the callee was called through, if any
+  pub alias: Option<String>,
 }
 
 impl<'ctx> std::fmt::Display for CallEdge<'ctx> {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let via = match &self.alias {
+      Some(alias) => format!(" (via {})", alias),
+      None => String::new(),
+    };
     f.write_fmt(format_args!(
-      "{} -> {}: {}",
+      "{} -> {}{}: {}",
       self.caller.simp_name(),
       self.callee.simp_name(),
+      via,
       self.instr.debug_loc_string(),
     ))
   }
 }
 
 /// CallGraph is defined by function vertices + instruction edges connecting caller & callee
-pub type CallGraphRaw<'ctx> = DiGraph<Function<'ctx>, CallInstruction<'ctx>>;
+pub type CallGraphRaw<'ctx> = DiGraph<Function<'ctx>, CallSite<'ctx>>;
 
 pub trait CallGraphTrait<'ctx> {
   type Edge;
@@ -60,7 +75,13 @@ impl<'ctx> CallGraphTrait<'ctx> for CallGraphRaw<'ctx> {
       let instr = self[edge_id];
       let caller = self[caller_id];
       let callee = self[callee_id];
-      CallEdge { caller, callee, instr }
+      let alias = instr.callee_alias().map(|alias| alias.name());
+      CallEdge {
+        caller,
+        callee,
+        instr,
+        alias,
+      }
     })
   }
 
@@ -76,6 +97,36 @@ impl<'ctx> CallGraphTrait<'ctx> for CallGraphRaw<'ctx> {
 
 pub type FunctionIdMap<'ctx> = HashMap<Function<'ctx>, NodeIndex>;
 
+/// A function of the call graph as listed by `--list-targets`, to help writing
+/// target filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionListing {
+  pub name: String,
+  pub num_arguments: usize,
+  pub is_var_arg: bool,
+  pub has_return_type: bool,
+  pub is_definition: bool,
+  pub num_call_sites: usize,
+}
+
+impl FunctionListing {
+  pub fn to_display_string(&self) -> String {
+    format!(
+      "{}({}{}){} {}, {} call sites",
+      self.name,
+      self.num_arguments,
+      if self.is_var_arg { ", ..." } else { "" },
+      if self.has_return_type { " -> value" } else { "" },
+      if self.is_definition {
+        "definition"
+      } else {
+        "declaration"
+      },
+      self.num_call_sites
+    )
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct GraphPath<N, E>
 where
@@ -179,11 +230,14 @@ impl<N, E> GraphTrait for Graph<N, E> {
   }
 }
 
-pub type CallGraphPath<'ctx> = GraphPath<Function<'ctx>, CallInstruction<'ctx>>;
+pub type CallGraphPath<'ctx> = GraphPath<Function<'ctx>, CallSite<'ctx>>;
 
 pub struct CallGraph<'ctx> {
   pub graph: CallGraphRaw<'ctx>,
   pub function_id_map: FunctionIdMap<'ctx>,
+
+  /// Declarations resolved to the function standing for them across modules
+  pub resolved: HashMap<Function<'ctx>, Function<'ctx>>,
 }
 
 impl<'ctx> CallGraph<'ctx> {
@@ -194,35 +248,105 @@ impl<'ctx> CallGraph<'ctx> {
     paths.into_iter().map(|path| path.into_elements(&self.graph)).collect()
   }
 
+  /// The function a callee stands for in the call graph. A declaration resolves
+  /// to the definition of the same name in another module, if any, and a weak
+  /// definition to a strong one
+  pub fn resolve(&self, func: Function<'ctx>) -> Function<'ctx> {
+    self.resolved.get(&func).cloned().unwrap_or(func)
+  }
+
   pub fn from_module(module: &Module<'ctx>, options: &impl CallGraphOptions) -> Self {
+    Self::from_modules(std::slice::from_ref(module), options)
+  }
+
+  /// Generate a single call graph over all the modules. Functions with the same
+  /// name are merged into one node, preferring a definition over declarations
+  /// and a strong definition over weak ones. Calls through a global alias are
+  /// edges to the aliasee
+  pub fn from_modules(modules: &[Module<'ctx>], options: &impl CallGraphOptions) -> Self {
+    let mut representatives: HashMap<String, Function<'ctx>> = HashMap::new();
+    for func in modules.iter().flat_map(|module| module.iter_functions()) {
+      let repr = representatives.entry(func.name()).or_insert(func);
+      let is_stronger = if repr.is_declaration_only() {
+        !func.is_declaration_only()
+      } else {
+        repr.is_weak() && !func.is_declaration_only() && !func.is_weak()
+      };
+      if is_stronger {
+        *repr = func;
+      }
+    }
+    let resolved: HashMap<Function<'ctx>, Function<'ctx>> = modules
+      .iter()
+      .flat_map(|module| module.iter_functions())
+      .filter_map(|func| {
+        let repr = representatives[&func.name()];
+        if repr != func && (func.is_declaration_only() || func.is_weak()) {
+          Some((func, repr))
+        } else {
+          None
+        }
+      })
+      .collect();
+    let resolve = |func: Function<'ctx>| resolved.get(&func).cloned().unwrap_or(func);
+
+    // Candidate targets of indirect calls, the definitions whose address is taken
+    let indirect_targets: Vec<Function<'ctx>> = if options.resolve_indirect() {
+      let address_taken = address_taken_functions(modules, resolve);
+      modules
+        .iter()
+        .flat_map(|module| module.iter_functions())
+        .filter(|func| !func.is_declaration_only() && address_taken.contains(func))
+        .collect()
+    } else {
+      vec![]
+    };
+
     let mut value_id_map: HashMap<Function<'ctx>, NodeIndex> = HashMap::new();
 
     // Generate Call Graph by iterating through all blocks & instructions for each function
     let mut cg = Graph::new();
-    for caller in module.iter_functions() {
+    // Resolved declarations and weak definitions are skipped as the function
+    // standing for them is visited on its own
+    let callers = modules.iter().flat_map(|module| module.iter_functions());
+    for caller in callers.filter(|func| !resolved.contains_key(func)) {
       let caller_id = value_id_map
         .entry(caller)
         .or_insert_with(|| cg.add_node(caller))
         .clone();
       for b in caller.iter_blocks() {
         for i in b.iter_instructions() {
-          match i {
-            Instruction::Call(call_instr) => {
-              if !options.remove_llvm_funcs() || !call_instr.is_intrinsic_call() {
-                match call_instr.callee_function() {
+          match CallSite::from_instruction(i) {
+            Some(call_site) => {
+              if !options.remove_llvm_funcs() || !call_site.is_intrinsic_call() {
+                match call_site.callee_function() {
                   Some(callee) => {
+                    let callee = resolve(callee);
                     let callee_id = value_id_map
                       .entry(callee)
                       .or_insert_with(|| cg.add_node(callee))
                       .clone();
-                    cg.add_edge(caller_id, callee_id, call_instr);
+                    cg.add_edge(caller_id, callee_id, call_site);
+                  }
+                  None => {
+                    // Over-approximate the targets of an indirect call by the
+                    // functions of the same type
+                    if !call_site.is_inline_asm_call() {
+                      let func_type = call_site.callee_function_type();
+                      for callee in indirect_targets.iter().filter(|f| f.get_function_type() == func_type) {
+                        let callee_id = value_id_map
+                          .entry(*callee)
+                          .or_insert_with(|| cg.add_node(*callee))
+                          .clone();
+                        cg.add_edge(caller_id, callee_id, call_site);
+                      }
+                    }
                   }
-                  None => {}
                 }
               } else {
               }
             }
-            _ => {}
+            None => {}
           }
         }
       }
@@ -232,10 +356,160 @@ impl<'ctx> CallGraph<'ctx> {
     Self {
       graph: cg,
       function_id_map: value_id_map,
+      resolved,
     }
   }
 
   pub fn print(&self) {
     self.graph.print()
   }
+
+  /// The functions whose name matches the filter, if any, sorted by name, with
+  /// their number of incoming call edges
+  pub fn list_functions(&self, filter: Option<&Regex>) -> Vec<FunctionListing> {
+    let mut listings = self
+      .graph
+      .node_indices()
+      .map(|node_id| (self.graph[node_id], node_id))
+      .filter(|(func, _)| filter.map_or(true, |filter| filter.is_match(&func.simp_name())))
+      .map(|(func, node_id)| {
+        let func_type = func.get_function_type();
+        FunctionListing {
+          name: func.simp_name(),
+          num_arguments: func_type.num_argument_types(),
+          is_var_arg: func_type.is_var_arg(),
+          has_return_type: func_type.has_return_type(),
+          is_definition: !func.is_declaration_only(),
+          num_call_sites: self.graph.edges_directed(node_id, Direction::Incoming).count(),
+        }
+      })
+      .collect::<Vec<_>>();
+    listings.sort_by(|a, b| a.name.cmp(&b.name));
+    listings
+  }
+
+  /// The number of call sites from caller to callee for each pair of functions
+  /// calling one another, sorted by caller and callee names
+  pub fn edge_counts(&self) -> Vec<(String, String, usize)> {
+    let mut counts = HashMap::new();
+    for edge in self.graph.edge_references() {
+      let caller = self.graph[edge.source()].simp_name();
+      let callee = self.graph[edge.target()].simp_name();
+      *counts.entry((caller, callee)).or_insert(0) += 1;
+    }
+    let mut counts = counts
+      .into_iter()
+      .map(|((caller, callee), count)| (caller, callee, count))
+      .collect::<Vec<_>>();
+    counts.sort();
+    counts
+  }
+
+  fn node_names(&self) -> Vec<String> {
+    let mut names = self
+      .graph
+      .node_weights()
+      .map(|func| func.simp_name())
+      .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    let edges = self
+      .edge_counts()
+      .into_iter()
+      .map(|(caller, callee, count)| json!({ "caller": caller, "callee": callee, "count": count }))
+      .collect::<Vec<_>>();
+    json!({
+      "nodes": self.node_names(),
+      "edges": edges,
+    })
+  }
+
+  /// Render the call graph as a DOT graph, with edges labeled by their number
+  /// of call sites
+  pub fn to_dot(&self) -> String {
+    let nodes = self
+      .node_names()
+      .into_iter()
+      .map(|name| format!("  \"{}\";", name))
+      .collect::<Vec<_>>();
+    let edges = self
+      .edge_counts()
+      .into_iter()
+      .map(|(caller, callee, count)| format!("  \"{}\" -> \"{}\" [label=\"{}\"];", caller, callee, count))
+      .collect::<Vec<_>>();
+    format!(
+      "digraph call_graph {{\n{}\n{}\n}}\n",
+      nodes.join("\n"),
+      edges.join("\n")
+    )
+  }
+
+  /// Dump the call graph as DOT if the path ends with `.dot`, and as json otherwise
+  pub fn dump(&self, path: PathBuf) -> Result<(), String> {
+    if path.extension().map_or(false, |ext| ext == "dot") {
+      fs::write(path, self.to_dot()).map_err(|_| "Cannot dump call graph".to_string())
+    } else {
+      dump_json(&self.to_json(), path)
+    }
+  }
+}
+
+/// The functions whose address is taken, i.e. used as a value rather than called:
+/// stored, passed as an argument, returned, merged by a phi, or referred to by
+/// the initializer of a global variable (e.g. a table of handlers)
+fn address_taken_functions<'ctx>(
+  modules: &[Module<'ctx>],
+  resolve: impl Fn(Function<'ctx>) -> Function<'ctx>,
+) -> HashSet<Function<'ctx>> {
+  let mut functions = HashSet::new();
+  for module in modules {
+    for global in module.iter_global_variables() {
+      if let Some(init) = global.initializer() {
+        functions_in_constant(init, &mut functions);
+      }
+    }
+    for instr in module
+      .iter_functions()
+      .flat_map(|func| func.iter_blocks())
+      .flat_map(|block| block.iter_instructions())
+    {
+      let operands = match instr {
+        Instruction::Store(store) => vec![store.value()],
+        Instruction::Return(ret) => ret.op().into_iter().collect(),
+        Instruction::Phi(phi) => phi.incomings().into_iter().map(|incoming| incoming.value).collect(),
+        _ => match CallSite::from_instruction(instr) {
+          Some(call_site) => call_site.arguments(),
+          None => vec![],
+        },
+      };
+      for operand in operands {
+        if let Operand::Constant(constant) = operand {
+          functions_in_constant(constant, &mut functions);
+        }
+      }
+    }
+  }
+  functions.into_iter().map(resolve).collect()
+}
+
+fn functions_in_constant<'ctx>(constant: Constant<'ctx>, functions: &mut HashSet<Function<'ctx>>) {
+  let elements = match constant {
+    Constant::Function(func) => {
+      functions.insert(func);
+      vec![]
+    }
+    Constant::Struct(s) => s.elements(),
+    Constant::Array(a) => a.elements(),
+    Constant::Vector(v) => v.elements(),
+    // Function pointers cast to another type, e.g. `(void *)handler`
+    Constant::ConstExpr(ConstExpr::Unary(u)) => vec![u.op0()],
+    _ => vec![],
+  };
+  for element in elements {
+    functions_in_constant(element, functions);
+  }
 }
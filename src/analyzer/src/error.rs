@@ -0,0 +1,38 @@
+//! Errors of the analysis pipeline, telling apart bad option values, bad regexes
+//! and unreadable files so that embedders can match on them
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalyzerError {
+  /// An option value out of its domain, e.g. a negative slice depth
+  OptionParse(String),
+
+  /// A filter or a line of a function list that is not a valid regex
+  Regex(String),
+
+  /// A file that cannot be read or written
+  Io(String),
+
+  /// A json file not matching the layout it is loaded as
+  Serde(String),
+}
+
+impl fmt::Display for AnalyzerError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::OptionParse(msg) | Self::Regex(msg) | Self::Io(msg) | Self::Serde(msg) => {
+        write!(f, "{}", msg)
+      }
+    }
+  }
+}
+
+impl std::error::Error for AnalyzerError {}
+
+/// Most of the pipeline still reports errors as strings
+impl From<AnalyzerError> for String {
+  fn from(err: AnalyzerError) -> Self {
+    err.to_string()
+  }
+}
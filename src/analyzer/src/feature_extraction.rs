@@ -1,9 +1,13 @@
 use llir::{types::*, Module};
+use log::warn;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use rayon::prelude::*;
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 use crate::feature_extractors::*;
 use crate::options::*;
@@ -28,10 +32,65 @@ pub struct Instr {
   pub res: Option<Value>,
 }
 
+#[derive(Deserialize)]
+pub struct Constraint {
+  pub cond: Comparison,
+  pub branch: bool,
+}
+
+#[derive(Deserialize)]
+pub struct MemoryCell {
+  pub loc: Value,
+  pub val: Value,
+}
+
 #[derive(Deserialize)]
 pub struct Trace {
+  /// The trace id, only stored in jsonl lines
+  #[serde(default)]
+  pub id: Option<usize>,
   pub target: usize,
   pub instrs: Vec<Instr>,
+  /// The path constraints, missing from traces dumped by older versions
+  #[serde(default)]
+  pub cons: Vec<Constraint>,
+  /// The memory right before the target call, only dumped under `--dump-memory`
+  #[serde(default)]
+  pub pre_call_memory: Option<Vec<MemoryCell>>,
+  /// Whether constraints were dropped past `--max-constraints`, leaving `cons` incomplete
+  #[serde(default)]
+  pub constraint_truncated: bool,
+  /// Whether the trace was dumped under `--reverse-traces`, from its end back to the
+  /// entry. Loaded traces are turned forward again, see `Trace::into_forward`
+  #[serde(default)]
+  pub reversed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TraceBlock {
+  pub func: String,
+  /// The index of the block in its function
+  pub block: usize,
+}
+
+/// A trace dumped under `--block-trace-only`, with the blocks it went through
+/// in place of its nodes
+#[derive(Deserialize)]
+pub struct BlockOnlyTrace {
+  /// The trace id, only stored in jsonl lines
+  #[serde(default)]
+  pub id: Option<usize>,
+  /// The position of the target's block in `blocks`
+  pub target: usize,
+  pub blocks: Vec<TraceBlock>,
+}
+
+/// Only the id of a jsonl trace line, to list the traces of a slice without
+/// building them
+#[derive(Deserialize)]
+struct TraceId {
+  #[serde(default)]
+  id: Option<usize>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -47,6 +106,23 @@ impl TraceIterDirection {
 }
 
 impl Trace {
+  /// The trace in its forward orientation, from the entry to the target, whichever
+  /// orientation it was dumped in
+  pub fn into_forward(self) -> Self {
+    if self.reversed {
+      let mut instrs = self.instrs;
+      instrs.reverse();
+      Self {
+        target: instrs.len() - 1 - self.target,
+        instrs,
+        reversed: false,
+        ..self
+      }
+    } else {
+      self
+    }
+  }
+
   pub fn target_result(&self) -> &Option<Value> {
     &self.target_instr().res
   }
@@ -88,14 +164,30 @@ impl Trace {
   }
 }
 
-pub trait FeatureExtractorOptions: IOOptions + Send + Sync {
+pub trait FeatureExtractorOptions: GeneralOptions + IOOptions + Send + Sync {
   fn causality_dictionary_size(&self) -> usize;
+
+  fn inter_call_window(&self) -> usize;
+
+  fn strict_traces(&self) -> bool;
+
+  fn max_traces_per_target(&self) -> Option<usize>;
+
+  fn enabled_features(&self) -> &Option<String>;
+
+  fn disabled_features(&self) -> &Option<String>;
+
+  fn incremental(&self) -> bool;
+
+  fn ignore_version(&self) -> bool;
+
+  fn feature_stats(&self) -> bool;
 }
 
 pub trait FeatureExtractor: Send + Sync {
   fn name(&self) -> String;
 
-  fn filter<'ctx>(&self, target: &String, target_type: FunctionType<'ctx>) -> bool;
+  fn filter(&self, target: &String, target_type: &TargetType) -> bool;
 
   fn init(&mut self, slice_id: usize, slice: &Slice, num_traces: usize, trace: &Trace);
 
@@ -104,40 +196,135 @@ pub trait FeatureExtractor: Send + Sync {
   fn extract(&self, slice_id: usize, slice: &Slice, trace: &Trace) -> serde_json::Value;
 }
 
+/// Builds the extractor of a registry entry
+pub type FeatureExtractorConstructor =
+  Box<dyn Fn(&dyn FeatureExtractorOptions) -> Box<dyn FeatureExtractor> + Send + Sync>;
+
+/// Feature extractors keyed by their `FeatureExtractor::name`, which is both the key
+/// of their features in the dumped json and the name selecting them in `--features`
+/// and `--disable-features`
+pub struct FeatureExtractorRegistry {
+  entries: Vec<(String, FeatureExtractorConstructor)>,
+}
+
+impl FeatureExtractorRegistry {
+  pub fn new() -> Self {
+    Self { entries: vec![] }
+  }
+
+  pub fn builtin() -> Self {
+    let mut registry = Self::new();
+    registry.register("ret", |_| Box::new(ReturnValueFeatureExtractor::new()));
+    registry.register("ret.check", |_| Box::new(ReturnValueCheckFeatureExtractor::new()));
+    registry.register("ret.range", |_| Box::new(ReturnValueRangeFeatureExtractor::new()));
+    for i in 0..7 {
+      registry.register(&format!("arg.{}.pre", i), move |_| {
+        Box::new(ArgumentPreconditionFeatureExtractor::new(i))
+      });
+    }
+    for i in 0..7 {
+      registry.register(&format!("arg.{}.post", i), move |_| {
+        Box::new(ArgumentPostconditionFeatureExtractor::new(i))
+      });
+    }
+    registry.register("before", |options| {
+      Box::new(CausalityFeatureExtractor::pre(options.causality_dictionary_size()))
+    });
+    registry.register("after", |options| {
+      Box::new(CausalityFeatureExtractor::post(options.causality_dictionary_size()))
+    });
+    registry.register("constant_args", |_| Box::new(ConstantArgumentFeatureExtractor::new()));
+    registry.register("arg_concreteness", |_| {
+      Box::new(ArgumentConcretenessFeatureExtractor::new())
+    });
+    registry.register("control_flow", |_| Box::new(ControlFlowFeaturesExtractor::new()));
+    registry.register("use_after_free", |_| Box::new(UseAfterFreeFeatureExtractor::new()));
+    registry.register("inter_call", |options| {
+      Box::new(InterCallSequenceFeatureExtractor::new(options.inter_call_window()))
+    });
+    registry.register("var_arg", |_| Box::new(VarArgFeatureExtractor::new()));
+    registry
+  }
+
+  /// Register an extractor under the `name` it reports
+  pub fn register<F>(&mut self, name: &str, constructor: F)
+  where
+    F: Fn(&dyn FeatureExtractorOptions) -> Box<dyn FeatureExtractor> + Send + Sync + 'static,
+  {
+    self.entries.push((name.to_string(), Box::new(constructor)));
+  }
+
+  pub fn names(&self) -> Vec<&str> {
+    self.entries.iter().map(|(name, _)| name.as_str()).collect()
+  }
+
+  fn parse_names(&self, list: &Option<String>) -> Result<Option<Vec<String>>, String> {
+    match list {
+      Some(list) => {
+        let names = list.split(',').map(|name| name.trim().to_string()).collect::<Vec<_>>();
+        match names.iter().find(|name| !self.names().contains(&name.as_str())) {
+          Some(name) => Err(format!(
+            "Unknown feature {}, valid features are: {}",
+            name,
+            self.names().join(", ")
+          )),
+          None => Ok(Some(names)),
+        }
+      }
+      None => Ok(None),
+    }
+  }
+
+  /// The names of the extractors enabled by the options, all of them by default
+  pub fn enabled_names(&self, options: &impl FeatureExtractorOptions) -> Result<Vec<String>, String> {
+    let enabled = self.parse_names(options.enabled_features())?;
+    let disabled = self.parse_names(options.disabled_features())?.unwrap_or(vec![]);
+    Ok(
+      self
+        .names()
+        .into_iter()
+        .filter(|name| {
+          enabled
+            .as_ref()
+            .map_or(true, |enabled| enabled.iter().any(|e| e.as_str() == *name))
+        })
+        .filter(|name| !disabled.iter().any(|d| d.as_str() == *name))
+        .map(String::from)
+        .collect(),
+    )
+  }
+
+  /// Build the extractors enabled by the options
+  pub fn build(&self, options: &impl FeatureExtractorOptions) -> Result<Vec<Box<dyn FeatureExtractor>>, String> {
+    let enabled = self.enabled_names(options)?;
+    Ok(
+      self
+        .entries
+        .iter()
+        .filter(|(name, _)| enabled.contains(name))
+        .map(|(_, constructor)| constructor(options))
+        .collect(),
+    )
+  }
+}
+
 pub struct FeatureExtractors {
   extractors: Vec<Box<dyn FeatureExtractor>>,
 }
 
 impl FeatureExtractors {
+  /// All the extractors enabled by the options
   pub fn all(options: &impl FeatureExtractorOptions) -> Self {
     Self {
-      extractors: vec![
-        Box::new(ReturnValueFeatureExtractor::new()),
-        Box::new(ReturnValueCheckFeatureExtractor::new()),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(0)),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(1)),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(2)),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(3)),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(4)),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(5)),
-        Box::new(ArgumentPreconditionFeatureExtractor::new(6)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(0)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(1)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(2)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(3)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(4)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(5)),
-        Box::new(ArgumentPostconditionFeatureExtractor::new(6)),
-        Box::new(CausalityFeatureExtractor::pre(options.causality_dictionary_size())),
-        Box::new(CausalityFeatureExtractor::post(options.causality_dictionary_size())),
-        Box::new(ControlFlowFeaturesExtractor::new()),
-      ],
-    }
-  }
-
-  pub fn extractors_for_target<'ctx>(
+      extractors: FeatureExtractorRegistry::builtin()
+        .build(options)
+        .expect("Invalid feature selection"),
+    }
+  }
+
+  pub fn extractors_for_target(
     target: &String,
-    target_type: FunctionType<'ctx>,
+    target_type: &TargetType,
     options: &impl FeatureExtractorOptions,
   ) -> Self {
     Self {
@@ -149,6 +336,10 @@ impl FeatureExtractors {
     }
   }
 
+  pub fn names(&self) -> Vec<String> {
+    self.extractors.iter().map(|extractor| extractor.name()).collect()
+  }
+
   pub fn initialize(&mut self, slice_id: usize, slice: &Slice, num_traces: usize, trace: &Trace) {
     for extractor in &mut self.extractors {
       extractor.init(slice_id, slice, num_traces, trace);
@@ -170,31 +361,336 @@ impl FeatureExtractors {
   }
 }
 
-pub struct FeatureExtractionContext<'a, 'ctx, O>
+/// Count, mean, standard deviation, min & max of a numeric feature, updated one
+/// value at a time with Welford's algorithm
+#[derive(Debug, Clone, Default)]
+pub struct FeatureStat {
+  pub count: usize,
+  pub mean: f64,
+  pub min: f64,
+  pub max: f64,
+  m2: f64,
+}
+
+impl FeatureStat {
+  pub fn add(&mut self, value: f64) {
+    if self.count == 0 {
+      self.min = value;
+      self.max = value;
+    } else {
+      self.min = self.min.min(value);
+      self.max = self.max.max(value);
+    }
+    self.count += 1;
+    let delta = value - self.mean;
+    self.mean += delta / self.count as f64;
+    self.m2 += delta * (value - self.mean);
+  }
+
+  /// The population standard deviation
+  pub fn std(&self) -> f64 {
+    if self.count == 0 {
+      0.0
+    } else {
+      (self.m2 / self.count as f64).sqrt()
+    }
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    serde_json::json!({
+      "count": self.count,
+      "mean": self.mean,
+      "std": self.std(),
+      "min": self.min,
+      "max": self.max,
+    })
+  }
+}
+
+/// Statistics of the numeric features of a target, keyed by their path in the
+/// feature json joined with `.`. Booleans count as 0 and 1
+#[derive(Debug, Clone, Default)]
+pub struct FeatureStats {
+  pub stats: BTreeMap<String, FeatureStat>,
+}
+
+impl FeatureStats {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn add_features(&mut self, features: &serde_json::Value) {
+    self.add_value(String::new(), features);
+  }
+
+  fn add_value(&mut self, path: String, value: &serde_json::Value) {
+    match value {
+      serde_json::Value::Number(n) => {
+        if let Some(n) = n.as_f64() {
+          self.stats.entry(path).or_default().add(n);
+        }
+      }
+      serde_json::Value::Bool(b) => self.stats.entry(path).or_default().add(if *b { 1.0 } else { 0.0 }),
+      serde_json::Value::Object(map) => {
+        for (key, value) in map {
+          let path = if path.is_empty() {
+            key.clone()
+          } else {
+            format!("{}.{}", path, key)
+          };
+          self.add_value(path, value);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    let stats = self
+      .stats
+      .iter()
+      .map(|(name, stat)| (name.clone(), stat.to_json()))
+      .collect::<serde_json::Map<_, _>>();
+    serde_json::json!({ "stats": stats })
+  }
+}
+
+/// The configuration of the feature extractors of a target, dumped along with its
+/// features so that `--incremental` only reuses features extracted the same way
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureConfig {
+  pub features: Vec<String>,
+  pub causality_dictionary_size: usize,
+  pub inter_call_window: usize,
+  pub max_traces_per_target: Option<usize>,
+  pub seed: u64,
+}
+
+impl FeatureConfig {
+  pub fn from_options(options: &impl FeatureExtractorOptions) -> Result<Self, String> {
+    Ok(Self {
+      features: FeatureExtractorRegistry::builtin().enabled_names(options)?,
+      causality_dictionary_size: options.causality_dictionary_size(),
+      inter_call_window: options.inter_call_window(),
+      max_traces_per_target: options.max_traces_per_target(),
+      seed: options.seed(),
+    })
+  }
+}
+
+/// The parts of a target's function type the feature extractors look at, so
+/// that features can be extracted without loading the byte code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetType {
+  pub has_return_type: bool,
+  pub returns_int: bool,
+  pub num_arguments: usize,
+  pub is_var_arg: bool,
+}
+
+impl TargetType {
+  pub fn from_function_type<'ctx>(func_type: FunctionType<'ctx>) -> Self {
+    Self {
+      has_return_type: func_type.has_return_type(),
+      returns_int: match func_type.return_type() {
+        Type::Int(_) => true,
+        _ => false,
+      },
+      num_arguments: func_type.num_argument_types(),
+      is_var_arg: func_type.is_var_arg(),
+    }
+  }
+}
+
+pub type TargetTypes = HashMap<String, TargetType>;
+
+pub trait TargetTypesTrait: Sized {
+  fn from_modules<'ctx>(modules: &[Module<'ctx>]) -> Self;
+
+  fn dump(&self, path: PathBuf) -> Result<(), String>;
+
+  fn load(path: &PathBuf, ignore_version: bool) -> Result<Self, String>;
+}
+
+impl TargetTypesTrait for TargetTypes {
+  fn from_modules<'ctx>(modules: &[Module<'ctx>]) -> Self {
+    modules
+      .function_types()
+      .into_iter()
+      .map(|(name, func_type)| (name, TargetType::from_function_type(func_type)))
+      .collect()
+  }
+
+  fn dump(&self, path: PathBuf) -> Result<(), String> {
+    let json = serde_json::json!({ "types": self });
+    dump_versioned_json(&json, path)
+  }
+
+  fn load(path: &PathBuf, ignore_version: bool) -> Result<Self, String> {
+    #[derive(Deserialize)]
+    struct TargetTypesFile {
+      types: TargetTypes,
+    }
+    load_versioned_json_t::<TargetTypesFile>(path, ignore_version).map(|file| file.types)
+  }
+}
+
+/// Find the targets and their number of slices by walking the traces directory
+/// of a previous run, mapping the sanitized directory names back to the
+/// original target names when `target_names.json` is present
+pub fn discover_target_num_slices_map(options: &impl IOOptions) -> Result<HashMap<String, usize>, String> {
+  let target_names: HashMap<String, String> = if options.target_names_path().exists() {
+    load_json_t(&options.target_names_path())?
+  } else {
+    HashMap::new()
+  };
+  let entries = fs::read_dir(options.trace_dir()).map_err(|err| format!("Cannot read traces directory: {}", err))?;
+  let mut map = HashMap::new();
+  for entry in entries {
+    let entry = entry.map_err(|err| err.to_string())?;
+    if !entry.path().is_dir() {
+      continue;
+    }
+    let dir_name = entry.file_name().to_string_lossy().to_string();
+    let target = target_names.get(&dir_name).cloned().unwrap_or(dir_name);
+
+    // Slices are stored either as `<slice_id>/` directories or `<slice_id>.jsonl(.gz)` files
+    let slice_ids = match fs::read_dir(options.trace_target_dir(&target)) {
+      Ok(paths) => paths
+        .filter_map(|path| {
+          let path = path.ok()?.path();
+          if path.is_dir() {
+            path.file_name()?.to_str()?.parse::<usize>().ok()
+          } else {
+            jsonl_file_id(&path)
+          }
+        })
+        .collect::<Vec<_>>(),
+      Err(_) => vec![],
+    };
+    if let Some(max_slice_id) = slice_ids.into_iter().max() {
+      map.insert(target, max_slice_id + 1);
+    }
+  }
+  Ok(map)
+}
+
+/// Where the traces of a slice were dumped: the lines of the slice's jsonl file,
+/// or one json file per trace under the slice's traces folder
+pub enum SliceTraceFiles {
+  Jsonl(PathBuf),
+  Json(PathBuf),
+}
+
+impl SliceTraceFiles {
+  pub fn of_slice(options: &impl IOOptions, target: &str, slice_id: usize) -> Self {
+    match options.existing_trace_target_slice_jsonl_path(target, slice_id) {
+      Some(jsonl_path) => Self::Jsonl(jsonl_path),
+      None => Self::Json(options.trace_target_slice_dir(target, slice_id)),
+    }
+  }
+
+  /// The trace files of a slice of a package, as laid out for `feature-extract`
+  pub fn of_package_slice(options: &impl IOOptions, target: &str, package: &str, slice_id: usize) -> Self {
+    match options.existing_trace_target_package_slice_jsonl_path(target, package, slice_id) {
+      Some(jsonl_path) => Self::Jsonl(jsonl_path),
+      None => Self::Json(options.trace_target_package_slice_dir(target, package, slice_id)),
+    }
+  }
+
+  /// The per-trace json files along with their ids, none for a jsonl file
+  pub fn json_file_paths(&self) -> Vec<(usize, PathBuf)> {
+    match self {
+      Self::Jsonl(_) => vec![],
+      Self::Json(dir) => json_files_in_dir(dir).unwrap_or_default(),
+    }
+  }
+
+  /// Load all the traces along with their ids. Trace ids are the ones the traces
+  /// were dumped with, taken from `line_trace_id` for the lines of a jsonl file,
+  /// so that feature files line up with their traces. Fails when the jsonl file
+  /// cannot be read at all
+  pub fn load_t<T, F>(&self, ignore_version: bool, line_trace_id: F) -> Result<Vec<(usize, Result<T, String>)>, String>
+  where
+    T: DeserializeOwned + Send,
+    F: Fn(&T) -> Option<usize>,
+  {
+    match self {
+      Self::Jsonl(jsonl_path) => load_versioned_jsonl_t(jsonl_path, ignore_version)
+        .map(|traces| {
+          traces
+            .into_iter()
+            .enumerate()
+            .map(|(line, trace): (usize, Result<T, String>)| {
+              let trace_id = trace.as_ref().ok().and_then(&line_trace_id).unwrap_or(line);
+              (trace_id, trace)
+            })
+            .collect()
+        })
+        .map_err(|err| format!("Cannot load {:?}: {}", jsonl_path, err)),
+      Self::Json(_) => Ok(
+        self
+          .json_file_paths()
+          .into_par_iter()
+          .map(|(trace_id, path)| (trace_id, load_versioned_json_t(&path, ignore_version)))
+          .collect(),
+      ),
+    }
+  }
+
+  /// Load all the traces like `load_t`, turned forward
+  pub fn load(&self, ignore_version: bool) -> Result<Vec<(usize, Result<Trace, String>)>, String> {
+    let traces = self.load_t(ignore_version, |trace: &Trace| trace.id)?;
+    Ok(
+      traces
+        .into_iter()
+        .map(|(trace_id, trace)| (trace_id, trace.map(Trace::into_forward)))
+        .collect(),
+    )
+  }
+}
+
+pub struct FeatureExtractionContext<'a, O>
 where
   O: FeatureExtractorOptions + IOOptions,
 {
-  pub modules: &'a Module<'ctx>,
   pub options: &'a O,
+  pub config: FeatureConfig,
   pub target_num_slices_map: HashMap<String, usize>,
-  pub func_types: HashMap<String, FunctionType<'ctx>>,
+  pub target_types: TargetTypes,
 }
 
-impl<'a, 'ctx, O> FeatureExtractionContext<'a, 'ctx, O>
+impl<'a, O> FeatureExtractionContext<'a, O>
 where
   O: FeatureExtractorOptions + IOOptions,
 {
-  pub fn new(
-    module: &'a Module<'ctx>,
+  pub fn new<'ctx>(
+    modules: &[Module<'ctx>],
+    target_num_slices_map: HashMap<String, usize>,
+    options: &'a O,
+  ) -> Result<Self, String> {
+    Self::from_target_types(TargetTypes::from_modules(modules), target_num_slices_map, options)
+  }
+
+  /// Create a context from cached target types, without any byte code module
+  pub fn from_target_types(
+    target_types: TargetTypes,
     target_num_slices_map: HashMap<String, usize>,
     options: &'a O,
   ) -> Result<Self, String> {
-    let func_types = module.function_types();
+    // Check the feature selection before extracting anything
+    let config = FeatureConfig::from_options(options)?;
+    if let Some(target) = target_num_slices_map
+      .keys()
+      .find(|target| !target_types.contains_key(*target))
+    {
+      return Err(format!("Missing the function type of target {}", target));
+    }
     Ok(Self {
-      modules: module,
       options,
+      config,
       target_num_slices_map,
-      func_types,
+      target_types,
     })
   }
 
@@ -204,101 +700,347 @@ where
       .into_par_iter()
       .map(|slice_id| {
         let path = self.options.slice_target_file_path(target.as_str(), slice_id);
-        load_json_t(&path).expect("Cannot load slice files")
+        load_versioned_json_t(&path, self.options.ignore_version()).expect("Cannot load slice files")
       })
       .collect::<Vec<_>>()
   }
 
   pub fn load_trace_file_paths(&self, target: &String, slice_id: usize) -> Vec<(usize, PathBuf)> {
-    match fs::read_dir(self.options.trace_target_slice_dir(target.as_str(), slice_id)) {
-      Ok(paths) => paths.map(|path| {
-        let path = path.expect("Cannot read traces folder path").path();
-        let trace_id = path.file_stem().unwrap().to_str().unwrap().parse::<usize>().unwrap();
-        (trace_id, path)
-      })
-      .collect::<Vec<_>>(),
-      _ => vec![]
-    }
+    SliceTraceFiles::Json(self.options.trace_target_slice_dir(target.as_str(), slice_id)).json_file_paths()
   }
 
   pub fn load_trace(&self, path: &PathBuf) -> Result<Trace, String> {
-    load_json_t(path)
+    load_versioned_json_t(path, self.options.ignore_version()).map(Trace::into_forward)
+  }
+
+  /// Load all the traces of a slice along with their ids, reading the slice's
+  /// jsonl file if there is one and the per-trace json files otherwise. Trace
+  /// ids are the ones the traces were dumped with, so that feature files line
+  /// up with their traces
+  pub fn load_traces(&self, target: &String, slice_id: usize) -> Vec<(usize, Result<Trace, String>)> {
+    self
+      .load_traces_t(target, slice_id, |trace: &Trace| trace.id)
+      .into_iter()
+      .map(|(trace_id, trace)| (trace_id, trace.map(Trace::into_forward)))
+      .collect()
+  }
+
+  /// Load all the traces of a slice dumped under `--block-trace-only`, in the
+  /// same way as `load_traces`
+  pub fn load_block_traces(&self, target: &String, slice_id: usize) -> Vec<(usize, Result<BlockOnlyTrace, String>)> {
+    self.load_traces_t(target, slice_id, |trace: &BlockOnlyTrace| trace.id)
+  }
+
+  fn load_traces_t<T, F>(&self, target: &String, slice_id: usize, line_trace_id: F) -> Vec<(usize, Result<T, String>)>
+  where
+    T: DeserializeOwned + Send,
+    F: Fn(&T) -> Option<usize>,
+  {
+    let files = SliceTraceFiles::of_slice(self.options, target.as_str(), slice_id);
+    match files.load_t(self.options.ignore_version(), line_trace_id) {
+      Ok(traces) => traces,
+      // Like unparseable traces, an unreadable jsonl file aborts the extraction under
+      // `--strict-traces`, and is otherwise reported and skipped
+      Err(err) => {
+        if self.options.strict_traces() {
+          panic!("Cannot load {} slice {} traces: {}", target, slice_id, err);
+        }
+        warn!(target: FEATURES_LOG, "Skipping {} slice {} traces: {}", target, slice_id, err);
+        vec![]
+      }
+    }
+  }
+
+  /// The ids of the traces of a slice, only reading the ids of the lines of
+  /// jsonl files
+  pub fn trace_ids(&self, target: &String, slice_id: usize) -> Vec<usize> {
+    if self
+      .options
+      .existing_trace_target_slice_jsonl_path(target.as_str(), slice_id)
+      .is_some()
+    {
+      self
+        .load_traces_t(target, slice_id, |trace: &TraceId| trace.id)
+        .into_iter()
+        .map(|(trace_id, _)| trace_id)
+        .collect()
+    } else {
+      self
+        .load_trace_file_paths(target, slice_id)
+        .into_iter()
+        .map(|(trace_id, _)| trace_id)
+        .collect()
+    }
+  }
+
+  /// Randomly (but deterministically given the seed) pick at most `--max-traces-per-target`
+  /// traces of a target, as `(slice_id, trace_id)` pairs, along with the number of dropped
+  /// traces. `None` when all traces are kept. `trace_ids` are the ids of the traces of
+  /// each slice of the target
+  pub fn sample_traces(&self, trace_ids: &[Vec<usize>]) -> Option<(HashSet<(usize, usize)>, usize)> {
+    let max_traces = self.options.max_traces_per_target()?;
+    let mut ids = trace_ids
+      .iter()
+      .enumerate()
+      .flat_map(|(slice_id, trace_ids)| trace_ids.iter().map(move |trace_id| (slice_id, *trace_id)))
+      .collect::<Vec<_>>();
+    if ids.len() <= max_traces {
+      return None;
+    }
+    let num_dropped = ids.len() - max_traces;
+    ids.sort();
+    ids.shuffle(&mut StdRng::seed_from_u64(self.options.seed()));
+    ids.truncate(max_traces);
+    Some((ids.into_iter().collect(), num_dropped))
+  }
+
+  /// The file a trace was loaded from, which is the jsonl file of the slice if
+  /// there is one
+  fn trace_source_path(&self, target: &String, slice_id: usize, trace_id: usize) -> PathBuf {
+    match self
+      .options
+      .existing_trace_target_slice_jsonl_path(target.as_str(), slice_id)
+    {
+      Some(jsonl_path) => jsonl_path,
+      None => self
+        .options
+        .trace_target_slice_file_path(target.as_str(), slice_id, trace_id),
+    }
+  }
+
+  /// Whether the feature file of a trace was written after the trace was last
+  /// modified
+  fn trace_features_up_to_date(&self, target: &String, slice_id: usize, trace_id: usize) -> bool {
+    let modified = |path: PathBuf| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let trace_path = self.trace_source_path(target, slice_id, trace_id);
+    let feature_path = self
+      .options
+      .feature_target_slice_file_path(target.as_str(), slice_id, trace_id);
+    match (modified(trace_path), modified(feature_path)) {
+      (Some(trace_time), Some(feature_time)) => feature_time >= trace_time,
+      _ => false,
+    }
+  }
+
+  /// Under `--incremental`, the kept traces of a target whose features need to be
+  /// extracted again, as `(slice_id, trace_id)` pairs: the ones without features
+  /// written after they were last modified. `None` when all of them do, that is
+  /// without `--incremental` or when the features were extracted with another
+  /// configuration
+  fn stale_traces<F>(&self, target: &String, trace_ids: &[Vec<usize>], is_sampled: F) -> Option<HashSet<(usize, usize)>>
+  where
+    F: Fn(usize, usize) -> bool,
+  {
+    if !self.options.incremental() {
+      return None;
+    }
+    let config_path = self.options.feature_target_config_path(target.as_str());
+    match load_versioned_json_t::<FeatureConfig>(&config_path, self.options.ignore_version()) {
+      Ok(config) if config == self.config => {}
+      _ => return None,
+    }
+    let mut stale = HashSet::new();
+    for (slice_id, trace_ids) in trace_ids.iter().enumerate() {
+      for &trace_id in trace_ids {
+        if is_sampled(slice_id, trace_id) && !self.trace_features_up_to_date(target, slice_id, trace_id) {
+          stale.insert((slice_id, trace_id));
+        }
+      }
+    }
+    Some(stale)
   }
 
-  pub fn extract_features(&self, _: &mut LoggingContext) {
+  /// Remove the feature files of a slice whose traces no longer exist
+  fn remove_stale_features(&self, target: &String, slice_id: usize, trace_ids: &HashSet<usize>) {
+    if let Ok(paths) = fs::read_dir(self.options.feature_target_slice_dir(target.as_str(), slice_id)) {
+      for path in paths {
+        let path = path.expect("Cannot read features folder path").path();
+        if let Some(trace_id) = json_file_id(&path) {
+          if !trace_ids.contains(&trace_id) {
+            fs::remove_file(path).expect("Cannot remove stale features json");
+          }
+        }
+      }
+    }
+  }
+
+  /// Unparseable traces (e.g. truncated by a killed run) abort the extraction
+  /// under `--strict-traces`, and are otherwise reported and skipped
+  fn check_trace(
+    &self,
+    target: &String,
+    slice_id: usize,
+    trace_id: usize,
+    trace: Result<Trace, String>,
+  ) -> Option<Trace> {
+    match trace {
+      Ok(trace) => Some(trace),
+      Err(err) => {
+        if self.options.strict_traces() {
+          panic!("Cannot load {} slice {} trace {}: {}", target, slice_id, trace_id, err);
+        }
+        warn!(target: FEATURES_LOG, "Skipping {} slice {} trace {}: {}", target, slice_id, trace_id, err);
+        None
+      }
+    }
+  }
+
+  /// Extract the features of all traces, returning the number of skipped traces
+  pub fn extract_features(&self, logging_ctx: &mut LoggingContext) -> usize {
     fs::create_dir_all(self.options.feature_dir()).expect("Cannot create features directory");
+    let num_skipped = AtomicUsize::new(0);
+    let num_dropped = Mutex::new(HashMap::new());
 
     self.target_num_slices_map.par_iter().for_each(|(target, &num_slices)| {
       // Initialize extractors
-      let func_type = self.func_types[target];
-      let mut extractors = FeatureExtractors::extractors_for_target(&target, func_type, self.options);
+      let target_type = &self.target_types[target];
+      let mut extractors = FeatureExtractors::extractors_for_target(&target, target_type, self.options);
 
       // logging_ctx.log(&format!("[{}]", extractors.extractors.iter().map(|e| e.name()).collect::<Vec<_>>().join(", "))).unwrap();
 
       // Load slices
       let slices = self.load_slices(&target, num_slices);
 
+      // List the traces of each slice once, without loading them
+      let trace_ids = (0..num_slices)
+        .map(|slice_id| self.trace_ids(&target, slice_id))
+        .collect::<Vec<_>>();
+
+      // Down-sample the traces of the target if it has too many
+      let sampled = self.sample_traces(&trace_ids);
+      if let Some((_, dropped)) = &sampled {
+        num_dropped.lock().unwrap().insert(target.clone(), *dropped);
+      }
+      let is_sampled = |slice_id: usize, trace_id: usize| match &sampled {
+        Some((sampled, _)) => sampled.contains(&(slice_id, trace_id)),
+        None => true,
+      };
+
       // logging_ctx.log("Loaded all slices").unwrap();
 
+      // Under `--incremental`, only the traces changed since their features were last
+      // extracted are extracted again, unless the configuration changed. The extractors
+      // are still initialized with all the traces, as they build target-wide state
+      // such as the causality dictionaries
+      let stale = self.stale_traces(target, &trace_ids, &is_sampled);
+      let is_stale = |slice_id: usize, trace_id: usize| match &stale {
+        Some(stale) => stale.contains(&(slice_id, trace_id)),
+        None => true,
+      };
+      let up_to_date = stale.as_ref().map_or(false, |stale| stale.is_empty());
+
       // Initialize while loading traces
-      (0..num_slices).for_each(|slice_id| {
+      (0..num_slices).filter(|_| !up_to_date).for_each(|slice_id| {
         let slice = &slices[slice_id];
         let traces = self
-          .load_trace_file_paths(&target, slice_id)
+          .load_traces(&target, slice_id)
           .into_iter()
-          .map(|(trace_id, dir_entry)| {
+          .filter(|(trace_id, _)| is_sampled(slice_id, *trace_id))
+          .filter_map(|(trace_id, trace)| {
             use std::io::Write;
             print!("Loading slice {} trace {}\r", slice_id, trace_id);
             std::io::stdout().flush().unwrap();
 
-            let trace = self.load_trace(&dir_entry);
+            let trace = self.check_trace(target, slice_id, trace_id, trace);
+            if trace.is_none() {
+              num_skipped.fetch_add(1, Ordering::SeqCst);
+            }
             trace
           })
           .collect::<Vec<_>>();
         let num_traces = traces.len();
 
         for trace in traces {
-          match trace {
-            Ok(trace) => extractors.initialize(slice_id, slice, num_traces, &trace),
-            _ => {}
-          }
+          extractors.initialize(slice_id, slice, num_traces, &trace);
         }
       });
 
       // logging_ctx.log("Initialized extractors").unwrap();
 
       // Finalize feature extractor initialization
-      extractors.finalize();
+      if !up_to_date {
+        extractors.finalize();
+      }
 
       // logging_ctx.log("Finalized extractors").unwrap();
 
-      // Extract features
+      // Extract features, accumulating their statistics under `--feature-stats`
+      let stats = if self.options.feature_stats() {
+        Some(Mutex::new(FeatureStats::new()))
+      } else {
+        None
+      };
       slices.par_iter().enumerate().for_each(|(slice_id, slice)| {
         // First create directory
         fs::create_dir_all(self.options.feature_target_slice_dir(target.as_str(), slice_id))
           .expect("Cannot create features target slice directory");
 
-        // Then load trace file directories
+        // Drop the features of deleted traces under `--incremental`
+        if self.options.incremental() {
+          let trace_ids = trace_ids[slice_id].iter().cloned().collect::<HashSet<_>>();
+          self.remove_stale_features(target, slice_id, &trace_ids);
+        }
+
+        // Up to date features are only loaded back to account for them in the statistics
+        if let Some(stats) = &stats {
+          for &trace_id in &trace_ids[slice_id] {
+            if !is_sampled(slice_id, trace_id) || is_stale(slice_id, trace_id) {
+              continue;
+            }
+            let path = self
+              .options
+              .feature_target_slice_file_path(target.as_str(), slice_id, trace_id);
+            if let Ok(mut features) = load_json(&path) {
+              features.as_object_mut().map(|features| features.remove("version"));
+              stats.lock().unwrap().add_features(&features);
+            }
+          }
+        }
+        if up_to_date {
+          return;
+        }
+
+        // Then load the traces and extract the stale ones
         self
-          .load_trace_file_paths(&target, slice_id)
+          .load_traces(&target, slice_id)
           .into_par_iter()
-          .for_each(|(trace_id, dir_entry)| {
-            // Load trace json
-            let trace = self.load_trace(&dir_entry);
-
-            match trace {
-              Ok(trace) => {
-                // Extract and dump features
-                let features = extractors.extract_features(slice_id, slice, &trace);
-                let path = self
-                  .options
-                  .feature_target_slice_file_path(target.as_str(), slice_id, trace_id);
-                dump_json(&features, path).expect("Cannot dump features json");
+          .filter(|(trace_id, _)| is_sampled(slice_id, *trace_id) && is_stale(slice_id, *trace_id))
+          .for_each(|(trace_id, trace)| {
+            // Unparseable traces are already reported during initialization
+            if let Ok(trace) = trace {
+              // Extract and dump features
+              let path = self
+                .options
+                .feature_target_slice_file_path(target.as_str(), slice_id, trace_id);
+              let features = extractors.extract_features(slice_id, slice, &trace);
+              dump_versioned_json(&features, path).expect("Cannot dump features json");
+              if let Some(stats) = &stats {
+                stats.lock().unwrap().add_features(&features);
               }
-              _ => {}
             }
           })
       });
+
+      // Dump the configuration the features were extracted with
+      if !up_to_date {
+        fs::create_dir_all(self.options.feature_config_dir()).expect("Cannot create feature config directory");
+        let path = self.options.feature_target_config_path(target.as_str());
+        let config = serde_json::to_value(&self.config).expect("Cannot serialize feature config");
+        dump_versioned_json(&config, path).expect("Cannot dump feature config json");
+      }
+
+      // Dump the statistics of the target
+      if let Some(stats) = stats {
+        fs::create_dir_all(self.options.feature_stats_dir()).expect("Cannot create feature stats directory");
+        let path = self.options.feature_target_stats_path(target.as_str());
+        dump_versioned_json(&stats.into_inner().unwrap().to_json(), path).expect("Cannot dump feature stats json");
+      }
     });
+
+    let num_dropped = num_dropped.into_inner().unwrap();
+    logging_ctx.log_dropped_traces(&num_dropped).unwrap();
+    let num_skipped = num_skipped.into_inner();
+    logging_ctx.log_skipped_traces(num_skipped).unwrap();
+    num_skipped
   }
 }
@@ -0,0 +1,49 @@
+use serde_json::json;
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+
+/// How much is known about each argument of the target call on the trace:
+/// `concrete` constants, memory `location`s, the result of a call (`call-result`),
+/// or any other `symbolic` value. Traces with only symbolic arguments carry little
+/// information about the target
+pub struct ArgumentConcretenessFeatureExtractor;
+
+impl ArgumentConcretenessFeatureExtractor {
+  pub fn new() -> Self {
+    Self
+  }
+
+  pub fn classify(value: &Value) -> &'static str {
+    match value {
+      Value::Int(_) | Value::Float(_) | Value::Null | Value::Func(_) => "concrete",
+      Value::Alloc(_) | Value::AllocOf(_) | Value::Glob(_) | Value::GEP { .. } | Value::Field { .. } => "location",
+      Value::Call { .. } => "call-result",
+      _ => "symbolic",
+    }
+  }
+}
+
+impl FeatureExtractor for ArgumentConcretenessFeatureExtractor {
+  fn name(&self) -> String {
+    "arg_concreteness".to_string()
+  }
+
+  fn filter(&self, _: &String, _: &TargetType) -> bool {
+    true
+  }
+
+  fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
+
+  fn finalize(&mut self) {}
+
+  fn extract(&self, _: usize, _: &Slice, trace: &Trace) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    if let Semantics::Call { args, .. } = &trace.target_instr().sem {
+      for (i, arg) in args.iter().enumerate() {
+        map.insert(format!("arg{}", i), json!(Self::classify(arg)));
+      }
+    }
+    serde_json::Value::Object(map)
+  }
+}
@@ -1,4 +1,3 @@
-use llir::types::*;
 use serde_json::json;
 use std::collections::HashSet;
 
@@ -20,8 +19,8 @@ impl FeatureExtractor for ArgumentPostconditionFeatureExtractor {
     format!("arg.{}.post", self.index)
   }
 
-  fn filter<'ctx>(&self, _: &String, target_type: FunctionType<'ctx>) -> bool {
-    self.index < target_type.num_argument_types()
+  fn filter(&self, _: &String, target_type: &TargetType) -> bool {
+    self.index < target_type.num_arguments
   }
 
   fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
@@ -37,6 +36,11 @@ impl FeatureExtractor for ArgumentPostconditionFeatureExtractor {
     let mut returned = false;
     let mut indir_returned = false;
 
+    // Out-parameter, i.e. pointer argument written through after the call
+    let mut out_written = false;
+    let mut out_written_const = false;
+    let mut out_written_sym = false;
+
     // Helper structures
     let mut child_ptrs: HashSet<Value> = HashSet::new();
     let mut tracked_values: HashSet<Value> = HashSet::new();
@@ -65,7 +69,7 @@ impl FeatureExtractor for ArgumentPostconditionFeatureExtractor {
                 used_in_check = true;
               }
             }
-            Semantics::Ret { op } => {
+            Semantics::Ret { op, .. } => {
               if i == trace.instrs.len() - 1 {
                 if let Some(op) = op {
                   if arg == **op {
@@ -77,24 +81,28 @@ impl FeatureExtractor for ArgumentPostconditionFeatureExtractor {
               }
             }
             Semantics::Store { loc, val } => {
-              if **loc == arg {
+              // Written either straight through the argument or through a pointer
+              // into it, e.g. `out->field = ...`
+              if **loc == arg || child_ptrs.contains(&**loc) {
                 used = true;
                 derefed = true;
+                out_written = true;
+                match &**val {
+                  Value::Int(_) | Value::Float(_) | Value::Null => out_written_const = true,
+                  _ => out_written_sym = true,
+                }
               } else if **val == arg {
                 let loc = *loc.clone();
                 tracked_values.insert(Value::AllocOf(Box::new(loc.clone())));
                 match &loc {
-                  Value::Arg(_) | Value::Sym(_) | Value::Glob(_) | Value::Alloc(_) => {
+                  Value::Arg(_) | Value::Sym(_) | Value::LocSym(_) | Value::Glob(_) | Value::Alloc(_) => {
                     tracked_values.insert(loc);
                   }
-                  Value::GEP { loc, .. } => {
+                  Value::GEP { loc, .. } | Value::Field { loc, .. } => {
                     tracked_values.insert(*loc.clone());
                   }
                   _ => {}
                 }
-              } else if child_ptrs.contains(&**loc) {
-                used = true;
-                derefed = true;
               }
             }
             Semantics::Load { loc } => {
@@ -130,6 +138,9 @@ impl FeatureExtractor for ArgumentPostconditionFeatureExtractor {
       "derefed": derefed,
       "returned": returned,
       "indir_returned": indir_returned,
+      "out_written": out_written,
+      "out_written_const": out_written_const,
+      "out_written_sym": out_written_sym,
     })
   }
 }
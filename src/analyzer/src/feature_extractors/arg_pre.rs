@@ -1,4 +1,3 @@
-use llir::types::*;
 use serde_json::json;
 
 use crate::feature_extraction::*;
@@ -19,8 +18,8 @@ impl FeatureExtractor for ArgumentPreconditionFeatureExtractor {
     format!("arg.{}.pre", self.index)
   }
 
-  fn filter<'ctx>(&self, _: &String, target_type: FunctionType<'ctx>) -> bool {
-    self.index < target_type.num_argument_types()
+  fn filter(&self, _: &String, target_type: &TargetType) -> bool {
+    self.index < target_type.num_arguments
   }
 
   fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
@@ -130,10 +129,10 @@ fn arg_type(arg: &Value, is_global: &mut bool, is_arg: &mut bool, is_constant: &
       Value::Arg(_) => {
         *is_arg = true;
       }
-      Value::ConstSym(_) | Value::Null | Value::Int(_) | Value::Func(_) | Value::Asm => {
+      Value::ConstSym(_) | Value::Null | Value::Int(_) | Value::Float(_) | Value::Func(_) | Value::Asm => {
         *is_constant = true;
       }
-      Value::GEP { loc, .. } => {
+      Value::GEP { loc, .. } | Value::Field { loc, .. } => {
         arg_type(&*loc, is_global, is_arg, is_constant, is_alloca, depth - 1);
       }
       Value::Alloc(_) => {
@@ -1,4 +1,3 @@
-use llir::types::*;
 use serde::Serialize;
 use std::collections::{BinaryHeap, HashMap};
 
@@ -42,7 +41,7 @@ impl FeatureExtractor for CausalityFeatureExtractor {
     }
   }
 
-  fn filter<'ctx>(&self, _: &String, _: FunctionType<'ctx>) -> bool {
+  fn filter(&self, _: &String, _: &TargetType) -> bool {
     true
   }
 
@@ -0,0 +1,42 @@
+use serde_json::json;
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+
+/// The concrete integer arguments of the target call, e.g. the flags given to
+/// `open`. Symbolic arguments are reported as null
+pub struct ConstantArgumentFeatureExtractor;
+
+impl ConstantArgumentFeatureExtractor {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+impl FeatureExtractor for ConstantArgumentFeatureExtractor {
+  fn name(&self) -> String {
+    "constant_args".to_string()
+  }
+
+  fn filter(&self, _: &String, _: &TargetType) -> bool {
+    true
+  }
+
+  fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
+
+  fn finalize(&mut self) {}
+
+  fn extract(&self, _: usize, _: &Slice, trace: &Trace) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    if let Semantics::Call { args, .. } = &trace.target_instr().sem {
+      for (i, arg) in args.iter().enumerate() {
+        let value = match &**arg {
+          Value::Int(value) => json!(value),
+          _ => serde_json::Value::Null,
+        };
+        map.insert(format!("arg{}", i), value);
+      }
+    }
+    serde_json::Value::Object(map)
+  }
+}
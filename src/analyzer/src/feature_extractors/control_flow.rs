@@ -1,4 +1,3 @@
-use llir::types::*;
 use serde_json::json;
 
 use crate::feature_extraction::*;
@@ -17,7 +16,7 @@ impl FeatureExtractor for ControlFlowFeaturesExtractor {
     "control_flow".to_string()
   }
 
-  fn filter<'ctx>(&self, _: &String, _: FunctionType<'ctx>) -> bool {
+  fn filter(&self, _: &String, _: &TargetType) -> bool {
     true
   }
 
@@ -0,0 +1,57 @@
+use serde_json::json;
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+
+/// The functions called between the previous call to the target, or the entry
+/// of the trace if there is none, and the target call, in calling order. Only
+/// the `window` calls closest to the target are kept
+pub struct InterCallSequenceFeatureExtractor {
+  pub window: usize,
+}
+
+impl InterCallSequenceFeatureExtractor {
+  pub fn new(window: usize) -> Self {
+    Self { window }
+  }
+}
+
+impl FeatureExtractor for InterCallSequenceFeatureExtractor {
+  fn name(&self) -> String {
+    "inter_call".to_string()
+  }
+
+  fn filter(&self, _: &String, _: &TargetType) -> bool {
+    true
+  }
+
+  fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
+
+  fn finalize(&mut self) {}
+
+  fn extract(&self, _: usize, _: &Slice, trace: &Trace) -> serde_json::Value {
+    let target_func = match &trace.target_instr().sem {
+      Semantics::Call { func, .. } => Some(func),
+      _ => None,
+    };
+    let mut calls = vec![];
+    let mut since_entry = true;
+    for (_, instr) in trace.iter_instrs_from_target(TraceIterDirection::Backward) {
+      if let Semantics::Call { func, .. } = &instr.sem {
+        if Some(func) == target_func {
+          since_entry = false;
+          break;
+        }
+        if let Value::Func(name) = &**func {
+          calls.push(name.clone());
+        }
+      }
+    }
+    calls.truncate(self.window);
+    calls.reverse();
+    json!({
+      "calls": calls,
+      "since_entry": since_entry,
+    })
+  }
+}
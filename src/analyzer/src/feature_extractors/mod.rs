@@ -1,12 +1,24 @@
+mod arg_concreteness;
+pub use arg_concreteness::*;
 mod arg_pre;
 pub use arg_pre::*;
 mod arg_post;
 pub use arg_post::*;
 mod causality;
 pub use causality::*;
+mod constant_args;
+pub use constant_args::*;
 mod control_flow;
 pub use control_flow::*;
+mod inter_call;
+pub use inter_call::*;
 mod retval;
 pub use retval::*;
 mod retval_check;
 pub use retval_check::*;
+mod retval_range;
+pub use retval_range::*;
+mod use_after_free;
+pub use use_after_free::*;
+mod var_arg;
+pub use var_arg::*;
@@ -1,4 +1,3 @@
-use llir::types::*;
 use serde_json::json;
 use std::collections::HashSet;
 
@@ -18,8 +17,8 @@ impl FeatureExtractor for ReturnValueFeatureExtractor {
     "ret".to_string()
   }
 
-  fn filter<'ctx>(&self, _: &String, target_type: FunctionType<'ctx>) -> bool {
-    target_type.has_return_type()
+  fn filter(&self, _: &String, target_type: &TargetType) -> bool {
+    target_type.has_return_type
   }
 
   fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
@@ -61,10 +60,15 @@ impl FeatureExtractor for ReturnValueFeatureExtractor {
             stored = true;
             let loc = *loc.clone();
             match &loc {
-              Value::Arg(_) | Value::Sym(_) | Value::Glob(_) | Value::Alloc(_) | Value::AllocOf(_) => {
+              Value::Arg(_)
+              | Value::Sym(_)
+              | Value::LocSym(_)
+              | Value::Glob(_)
+              | Value::Alloc(_)
+              | Value::AllocOf(_) => {
                 tracked_values.insert(loc);
               }
-              Value::GEP { loc, .. } => {
+              Value::GEP { loc, .. } | Value::Field { loc, .. } => {
                 tracked_values.insert(*loc.clone());
               }
               _ => {}
@@ -79,7 +83,7 @@ impl FeatureExtractor for ReturnValueFeatureExtractor {
             child_ptrs.insert(instr.res.clone().unwrap());
           }
         }
-        Semantics::Ret { op } => {
+        Semantics::Ret { op, .. } => {
           // We only care about the last return statement
           if i == trace.instrs.len() - 1 {
             if let Some(op) = op {
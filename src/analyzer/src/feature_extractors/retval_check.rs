@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 
-use llir::types::*;
 use serde_json::json;
 
 use crate::feature_extraction::*;
@@ -26,8 +25,8 @@ impl FeatureExtractor for ReturnValueCheckFeatureExtractor {
 
   /// Return value check feature should only present when the return type
   /// is a pointer type
-  fn filter<'ctx>(&self, _: &String, target_type: FunctionType<'ctx>) -> bool {
-    target_type.has_return_type()
+  fn filter(&self, _: &String, target_type: &TargetType) -> bool {
+    target_type.has_return_type
   }
 
   fn init(&mut self, slice_id: usize, _: &Slice, _: usize, trace: &Trace) {
@@ -132,7 +131,7 @@ fn num_of_value(v: &Value) -> Option<i64> {
 
 fn icmp_pred_op0_op1(v: &Value) -> Option<(Predicate, Value, Value)> {
   match v {
-    Value::ICmp { pred, op0, op1 } => Some((pred.clone(), *op0.clone(), *op1.clone())),
+    Value::ICmp { pred, op0, op1, .. } => Some((pred.clone(), *op0.clone(), *op1.clone())),
     _ => None,
   }
 }
@@ -0,0 +1,54 @@
+use serde_json::json;
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+
+pub struct ReturnValueRangeFeatureExtractor;
+
+impl ReturnValueRangeFeatureExtractor {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+impl FeatureExtractor for ReturnValueRangeFeatureExtractor {
+  fn name(&self) -> String {
+    "ret.range".to_string()
+  }
+
+  /// Only integer return values have a range
+  fn filter(&self, _: &String, target_type: &TargetType) -> bool {
+    target_type.returns_int
+  }
+
+  fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
+
+  fn finalize(&mut self) {}
+
+  fn extract(&self, _: usize, _: &Slice, trace: &Trace) -> serde_json::Value {
+    let (min, max) = match trace.target_result() {
+      Some(result) => {
+        // The result is ranged at the width it is compared at along the path
+        let width = trace.cons.iter().find_map(|constraint| match &constraint.cond {
+          Comparison::ICmp { op0, op1, width, .. } if **op0 == *result || **op1 == *result => Some(*width),
+          _ => None,
+        });
+        let comparisons = trace
+          .cons
+          .iter()
+          .map(|constraint| (&constraint.cond, constraint.branch))
+          .collect::<Vec<_>>();
+        match width {
+          Some(width) => result.range_under(width, &comparisons),
+          None => (None, None),
+        }
+      }
+      None => (None, None),
+    };
+    json!({
+      "min": min,
+      "max": max,
+      "bounded": min.is_some() && max.is_some(),
+    })
+  }
+}
@@ -0,0 +1,80 @@
+use serde_json::json;
+use std::collections::HashSet;
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+
+pub struct UseAfterFreeFeatureExtractor;
+
+impl UseAfterFreeFeatureExtractor {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+impl FeatureExtractor for UseAfterFreeFeatureExtractor {
+  fn name(&self) -> String {
+    "use_after_free".to_string()
+  }
+
+  fn filter(&self, _: &String, _: &TargetType) -> bool {
+    true
+  }
+
+  fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
+
+  fn finalize(&mut self) {}
+
+  fn extract(&self, _: usize, _: &Slice, trace: &Trace) -> serde_json::Value {
+    let mut use_after_free = false;
+    let mut double_free = false;
+
+    // Freed locations, and pointers derived from them through GEP
+    let mut freed: HashSet<Value> = HashSet::new();
+    let mut derived: HashSet<Value> = HashSet::new();
+
+    for (i, instr) in trace.instrs.iter().enumerate() {
+      match &instr.sem {
+        Semantics::Free { loc } => {
+          if !freed.insert(*loc.clone()) {
+            double_free = true;
+          }
+        }
+        Semantics::Load { loc } | Semantics::Store { loc, .. } => {
+          if freed.contains(&**loc) || derived.contains(&**loc) {
+            use_after_free = true;
+          }
+        }
+        Semantics::Call { args, .. } => {
+          // The call to free itself is directly followed by its free node
+          let is_free_call = match trace.instrs.get(i + 1) {
+            Some(next) => match &next.sem {
+              Semantics::Free { .. } => true,
+              _ => false,
+            },
+            None => false,
+          };
+          let uses_freed = args
+            .iter()
+            .any(|arg| freed.contains(&**arg) || derived.contains(&**arg));
+          if !is_free_call && uses_freed {
+            use_after_free = true;
+          }
+        }
+        Semantics::GEP { loc, .. } => {
+          if freed.contains(&**loc) || derived.contains(&**loc) {
+            if let Some(res) = &instr.res {
+              derived.insert(res.clone());
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+
+    json!({
+      "use_after_free": use_after_free,
+      "double_free": double_free,
+    })
+  }
+}
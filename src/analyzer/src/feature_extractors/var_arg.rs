@@ -0,0 +1,37 @@
+use serde_json::json;
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+
+pub struct VarArgFeatureExtractor;
+
+impl VarArgFeatureExtractor {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+impl FeatureExtractor for VarArgFeatureExtractor {
+  fn name(&self) -> String {
+    "var_arg".to_string()
+  }
+
+  fn filter(&self, _: &String, target_type: &TargetType) -> bool {
+    target_type.is_var_arg
+  }
+
+  fn init(&mut self, _: usize, _: &Slice, _: usize, _: &Trace) {}
+
+  fn finalize(&mut self) {}
+
+  fn extract(&self, _: usize, _: &Slice, trace: &Trace) -> serde_json::Value {
+    let num_var_args = match &trace.target_instr().sem {
+      sem @ Semantics::Call { .. } => sem.call_var_args().len(),
+      _ => 0,
+    };
+    json!({
+      "has_var_args": num_var_args > 0,
+      "num_var_args": num_var_args,
+    })
+  }
+}
@@ -1,10 +1,14 @@
 pub extern crate llir;
 
 pub mod call_graph;
+pub mod error;
 pub mod feature_extraction;
 pub mod feature_extractors;
 pub mod options;
 pub mod semantics;
 pub mod slicer;
 pub mod symbolic_execution;
+pub mod trace_diff;
+pub mod trace_validation;
+pub mod unchecked_return;
 pub mod utils;
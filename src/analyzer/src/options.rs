@@ -1,9 +1,82 @@
+use serde::Serialize;
 use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::error::*;
+use crate::utils::*;
+
+/// How the traces of a slice are stored: one `.json` file per trace, or all
+/// traces appended into a single `<slice_id>.jsonl(.gz)` file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceFormat {
+  Json,
+  Jsonl,
+}
+
+impl FromStr for TraceFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "json" => Ok(Self::Json),
+      "jsonl" => Ok(Self::Jsonl),
+      _ => Err(format!("Unknown trace format {}", s)),
+    }
+  }
+}
+
+/// The share of the slices executed by one process under `--slice-shard I/N`: the
+/// slices whose id modulo `count` is `index`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct SliceShard {
+  pub index: usize,
+  pub count: usize,
+}
+
+impl SliceShard {
+  pub fn contains(&self, slice_id: usize) -> bool {
+    slice_id % self.count == self.index
+  }
+}
+
+impl FromStr for SliceShard {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || format!("Invalid slice shard {}, expected I/N with I < N", s);
+    let (index, count) = match s.split('/').collect::<Vec<_>>()[..] {
+      [index, count] => (
+        index.parse::<usize>().map_err(|_| invalid())?,
+        count.parse::<usize>().map_err(|_| invalid())?,
+      ),
+      _ => return Err(invalid()),
+    };
+    if index < count {
+      Ok(Self { index, count })
+    } else {
+      Err(invalid())
+    }
+  }
+}
+
+/// Parse the value of `--slice-depth`
+pub fn parse_slice_depth(depth: &str) -> Result<usize, AnalyzerError> {
+  depth.parse().map_err(|_| {
+    AnalyzerError::OptionParse(format!(
+      "Invalid slice depth {}, expected a non-negative integer",
+      depth
+    ))
+  })
+}
 
 pub trait GeneralOptions {
   fn use_serial(&self) -> bool;
 
   fn seed(&self) -> u64;
+
+  /// Demangle C++ and Rust callee names in traces and slicer filters
+  fn demangle(&self) -> bool;
 }
 
 pub trait IOOptions {
@@ -13,6 +86,21 @@ pub trait IOOptions {
 
   fn default_package(&self) -> Option<&str>;
 
+  fn compress(&self) -> bool;
+
+  /// Under `--trace-shard-size`, the number of trace files per subdirectory of a slice's traces folder
+  fn trace_shard_size(&self) -> Option<usize>;
+
+  /// The name of the file of a trace or its features, gzip compressed under `--compress`
+  fn json_file_name(&self, id: usize) -> String {
+    let extension = if self.compress() {
+      JSON_GZ_EXTENSION
+    } else {
+      JSON_EXTENSION
+    };
+    format!("{}.{}", id, extension)
+  }
+
   fn with_package(&self, path: PathBuf) -> PathBuf {
     match self.default_package() {
       Some(package) => path.join(package),
@@ -20,12 +108,29 @@ pub trait IOOptions {
     }
   }
 
+  /// Target names are sanitized before being used in paths; this file maps the
+  /// sanitized names back to the original ones
+  fn target_names_path(&self) -> PathBuf {
+    self.output_path().join("target_names.json")
+  }
+
+  /// Cached function types of the targets, enough to extract features without
+  /// the byte code
+  fn target_types_path(&self) -> PathBuf {
+    self.output_path().join("target_types.json")
+  }
+
+  /// Totals of a full run, along with the arguments it was started with
+  fn run_summary_path(&self) -> PathBuf {
+    self.output_path().join("run-summary.json")
+  }
+
   fn slice_dir(&self) -> PathBuf {
     self.output_path().join("slices")
   }
 
   fn slice_target_dir(&self, target: &str) -> PathBuf {
-    self.with_package(self.slice_dir().join(target))
+    self.with_package(self.slice_dir().join(sanitize_file_name(target)))
   }
 
   fn slice_target_file_path(&self, target: &str, slice_id: usize) -> PathBuf {
@@ -33,7 +138,7 @@ pub trait IOOptions {
   }
 
   fn slice_target_package_dir(&self, target: &str, package: &str) -> PathBuf {
-    self.slice_dir().join(target).join(package)
+    self.slice_dir().join(sanitize_file_name(target)).join(package)
   }
 
   fn slice_target_package_file_path(&self, target: &str, package: &str, slice_id: usize) -> PathBuf {
@@ -47,7 +152,7 @@ pub trait IOOptions {
   }
 
   fn trace_target_dir(&self, target: &str) -> PathBuf {
-    self.with_package(self.trace_dir().join(target))
+    self.with_package(self.trace_dir().join(sanitize_file_name(target)))
   }
 
   fn trace_target_slice_dir(&self, target: &str, slice_id: usize) -> PathBuf {
@@ -56,12 +161,80 @@ pub trait IOOptions {
 
   fn trace_target_slice_file_path(&self, target: &str, slice_id: usize, trace_id: usize) -> PathBuf {
     self
-      .trace_target_slice_dir(target, slice_id)
-      .join(format!("{}.json", trace_id))
+      .trace_shard_dir(self.trace_target_slice_dir(target, slice_id), trace_id)
+      .join(self.json_file_name(trace_id))
+  }
+
+  /// The subdirectory `<trace_id / shard_size>` of a slice's traces folder holding
+  /// the trace, or the folder itself when traces are not sharded
+  fn trace_shard_dir(&self, slice_dir: PathBuf, trace_id: usize) -> PathBuf {
+    match self.trace_shard_size() {
+      Some(shard_size) => slice_dir.join((trace_id / shard_size).to_string()),
+      None => slice_dir,
+    }
+  }
+
+  /// The jsonl file of the traces of a slice, gzip compressed under `--compress`
+  fn trace_target_slice_jsonl_path(&self, target: &str, slice_id: usize) -> PathBuf {
+    let extension = if self.compress() {
+      JSONL_GZ_EXTENSION
+    } else {
+      JSONL_EXTENSION
+    };
+    self
+      .trace_target_dir(target)
+      .join(format!("{}.{}", slice_id, extension))
+  }
+
+  /// The jsonl file the traces of a slice were dumped in, compressed or not, if
+  /// any; the newer one when there are both
+  fn existing_trace_target_slice_jsonl_path(&self, target: &str, slice_id: usize) -> Option<PathBuf> {
+    existing_jsonl_path(self.trace_target_dir(target), slice_id)
+  }
+
+  /// The statistics of a slice, in the traces folder of the slice
+  fn trace_target_slice_meta_path(&self, target: &str, slice_id: usize) -> PathBuf {
+    self.trace_target_slice_dir(target, slice_id).join("meta.json")
+  }
+
+  /// The statistics of a slice whose traces are in a jsonl file, next to that file
+  /// as there is no folder for the slice
+  fn trace_target_slice_jsonl_meta_path(&self, target: &str, slice_id: usize) -> PathBuf {
+    self
+      .trace_target_dir(target)
+      .join(format!("{}.{}", slice_id, SLICE_META_EXTENSION))
+  }
+
+  /// The statistics aggregated over all the slices of a target
+  fn trace_target_meta_path(&self, target: &str) -> PathBuf {
+    self.trace_target_dir(target).join("meta.json")
+  }
+
+  /// Written once all the traces of a slice are dumped, with or without `--resume`,
+  /// so that resuming any run does not mistake a partially written slice for a
+  /// finished one
+  fn trace_target_slice_done_path(&self, target: &str, slice_id: usize) -> PathBuf {
+    self.trace_target_dir(target).join(format!("{}.done", slice_id))
+  }
+
+  fn trace_target_package_dir(&self, target: &str, package: &str) -> PathBuf {
+    self.trace_dir().join(sanitize_file_name(target)).join(package)
   }
 
   fn trace_target_package_slice_dir(&self, target: &str, package: &str, slice_id: usize) -> PathBuf {
-    self.trace_dir().join(target).join(package).join(slice_id.to_string())
+    self
+      .trace_target_package_dir(target, package)
+      .join(slice_id.to_string())
+  }
+
+  /// Like `existing_trace_target_slice_jsonl_path`, for the traces of a package
+  fn existing_trace_target_package_slice_jsonl_path(
+    &self,
+    target: &str,
+    package: &str,
+    slice_id: usize,
+  ) -> Option<PathBuf> {
+    existing_jsonl_path(self.trace_target_package_dir(target, package), slice_id)
   }
 
   fn trace_target_package_slice_file_path(
@@ -72,8 +245,29 @@ pub trait IOOptions {
     trace_id: usize,
   ) -> PathBuf {
     self
-      .trace_target_package_slice_dir(target, package, slice_id)
-      .join(format!("{}.json", trace_id))
+      .trace_shard_dir(self.trace_target_package_slice_dir(target, package, slice_id), trace_id)
+      .join(self.json_file_name(trace_id))
+  }
+
+  fn negative_trace_dir(&self) -> PathBuf {
+    self.output_path().join("negative_traces")
+  }
+
+  fn negative_trace_target_slice_dir(&self, kind: &str, target: &str, slice_id: usize) -> PathBuf {
+    self
+      .with_package(self.negative_trace_dir().join(kind).join(sanitize_file_name(target)))
+      .join(slice_id.to_string())
+  }
+
+  /// Satisfiable paths reaching an `unreachable` instruction, under `--report-unreachable`
+  fn assertion_dir(&self) -> PathBuf {
+    self.output_path().join("assertions")
+  }
+
+  fn assertion_target_slice_dir(&self, target: &str, slice_id: usize) -> PathBuf {
+    self
+      .with_package(self.assertion_dir().join(sanitize_file_name(target)))
+      .join(slice_id.to_string())
   }
 
   fn feature_dir(&self) -> PathBuf {
@@ -81,7 +275,7 @@ pub trait IOOptions {
   }
 
   fn feature_target_dir(&self, target: &str) -> PathBuf {
-    self.with_package(self.feature_dir().join(target))
+    self.with_package(self.feature_dir().join(sanitize_file_name(target)))
   }
 
   fn feature_target_slice_dir(&self, target: &str, slice_id: usize) -> PathBuf {
@@ -91,11 +285,37 @@ pub trait IOOptions {
   fn feature_target_slice_file_path(&self, target: &str, slice_id: usize, trace_id: usize) -> PathBuf {
     self
       .feature_target_slice_dir(target, slice_id)
-      .join(format!("{}.json", trace_id))
+      .join(self.json_file_name(trace_id))
+  }
+
+  /// Statistics of the numeric features of each target, for normalizing them
+  fn feature_stats_dir(&self) -> PathBuf {
+    self.output_path().join("feature_stats")
+  }
+
+  fn feature_target_stats_path(&self, target: &str) -> PathBuf {
+    self
+      .feature_stats_dir()
+      .join(format!("{}.json", sanitize_file_name(target)))
+  }
+
+  /// The configuration of the feature extractors each target was extracted with
+  fn feature_config_dir(&self) -> PathBuf {
+    self.output_path().join("feature_config")
+  }
+
+  fn feature_target_config_path(&self, target: &str) -> PathBuf {
+    self
+      .feature_config_dir()
+      .join(format!("{}.json", sanitize_file_name(target)))
   }
 
   fn feature_target_package_slice_dir(&self, target: &str, package: &str, slice_id: usize) -> PathBuf {
-    self.feature_dir().join(target).join(package).join(slice_id.to_string())
+    self
+      .feature_dir()
+      .join(sanitize_file_name(target))
+      .join(package)
+      .join(slice_id.to_string())
   }
 
   fn feature_target_package_slice_file_path(
@@ -107,6 +327,15 @@ pub trait IOOptions {
   ) -> PathBuf {
     self
       .feature_target_package_slice_dir(target, package, slice_id)
-      .join(format!("{}.json", trace_id))
+      .join(self.json_file_name(trace_id))
   }
 }
+
+/// The `<slice_id>.jsonl(.gz)` file of a slice in `dir`, the newer one when there are both
+fn existing_jsonl_path(dir: PathBuf, slice_id: usize) -> Option<PathBuf> {
+  [JSONL_EXTENSION, JSONL_GZ_EXTENSION]
+    .iter()
+    .map(|extension| dir.join(format!("{}.{}", slice_id, extension)))
+    .filter(|path| path.exists())
+    .reduce(newer_file)
+}
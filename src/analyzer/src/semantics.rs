@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
+
+use crate::utils::{truncate_z3_bv, z3_binary, z3_icmp};
 // use serde_json::Value as Json;
 
 pub type UnaOp = llir::values::UnaryOpcode;
@@ -68,6 +71,101 @@ pub enum PredicateDef {
   ULT,
 }
 
+pub type FPredicate = llir::values::FCmpPredicate;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(remote = "llir::values::FCmpPredicate")]
+pub enum FPredicateDef {
+  OEQ,
+  OGE,
+  OGT,
+  OLE,
+  OLT,
+  ONE,
+  ORD,
+  UEQ,
+  UGE,
+  UGT,
+  ULE,
+  ULT,
+  UNE,
+  UNO,
+}
+
+/// The predicate holding once the operands of the comparison are swapped
+pub fn swapped_predicate(pred: Predicate) -> Predicate {
+  match pred {
+    Predicate::SGE => Predicate::SLE,
+    Predicate::UGE => Predicate::ULE,
+    Predicate::SGT => Predicate::SLT,
+    Predicate::UGT => Predicate::ULT,
+    Predicate::SLE => Predicate::SGE,
+    Predicate::ULE => Predicate::UGE,
+    Predicate::SLT => Predicate::SGT,
+    Predicate::ULT => Predicate::UGT,
+    Predicate::EQ | Predicate::NE => pred,
+  }
+}
+
+/// The float predicate holding once the operands of the comparison are swapped
+pub fn swapped_fpredicate(pred: FPredicate) -> FPredicate {
+  match pred {
+    FPredicate::OGE => FPredicate::OLE,
+    FPredicate::OGT => FPredicate::OLT,
+    FPredicate::OLE => FPredicate::OGE,
+    FPredicate::OLT => FPredicate::OGT,
+    FPredicate::UGE => FPredicate::ULE,
+    FPredicate::UGT => FPredicate::ULT,
+    FPredicate::ULE => FPredicate::UGE,
+    FPredicate::ULT => FPredicate::UGT,
+    _ => pred,
+  }
+}
+
+/// A floating point constant, compared and hashed by its bits so that it can
+/// live inside `Value`
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct Float(pub f64);
+
+impl PartialEq for Float {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.to_bits() == other.0.to_bits()
+  }
+}
+
+impl Eq for Float {}
+
+impl Hash for Float {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.0.to_bits().hash(state)
+  }
+}
+
+/// The kinds of semantics, as named by `Semantics::kind`
+pub const SEMANTICS_KINDS: [&str; 11] = [
+  "call", "compare", "branch", "return", "store", "load", "free", "phi", "gep", "unary", "binary",
+];
+
+/// The struct field accessed by a GEP, resolved from its constant indices
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StructField {
+  pub struct_name: Option<String>, // `None` for literal structs
+  pub index: usize,
+
+  /// The name of the field in the source, when debug information is present
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+}
+
+impl StructField {
+  pub fn to_display_string(&self) -> String {
+    match &self.struct_name {
+      Some(name) => format!("{}.{}", name, self.index),
+      None => format!("{{}}.{}", self.index),
+    }
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Branch {
   Then,
@@ -90,6 +188,43 @@ impl Branch {
   }
 }
 
+/// The lowercase name of an opcode or predicate, e.g. `add` or `eq`
+fn op_name<T: std::fmt::Debug>(op: &T) -> String {
+  format!("{:?}", op).to_lowercase()
+}
+
+/// The exact rational value of a finite float as a z3 real
+fn float_into_z3_real<'ctx>(f: f64, z3_ctx: &'ctx z3::Context) -> Option<z3::ast::Real<'ctx>> {
+  use z3::ast::*;
+  if !f.is_finite() {
+    return None;
+  }
+
+  // Scale the float by powers of two until it is an integer fitting in i64
+  let mut num = f;
+  let mut den = 1i64;
+  while num.fract() != 0.0 && den < (1 << 52) {
+    num *= 2.0;
+    den *= 2;
+  }
+  if num.fract() != 0.0 || num.abs() >= i64::MAX as f64 {
+    return None;
+  }
+  let num = Real::from_int(&Int::from_i64(z3_ctx, num as i64));
+  let den = Real::from_int(&Int::from_i64(z3_ctx, den));
+  Some(num.div(&den))
+}
+
+/// Where the value returned by the entry function of a trace comes from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReturnSource {
+  Constant,
+  Argument,
+  CallResult,
+  Symbolic,
+}
+
 macro_rules! decl_value_with_wrapper {
   ($wrapper:ident) => {
     #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -101,49 +236,217 @@ macro_rules! decl_value_with_wrapper {
       Func(String),    // Function Name
       Alloc(usize),    // Local alloca ID
       AllocOf($wrapper<Value>),
+      LocSym($wrapper<Value>), // Symbol of the initial content of a location, named after it
+      Undef($wrapper<Value>),  // Content of a stack slot read before anything was stored to it
       FuncPtr,
       Asm,
+      Exception, // Exception caught by a landing pad
       Int(i64),
+      Float(Float),
       Null,
       GEP {
         loc: $wrapper<Value>,
         indices: Vec<$wrapper<Value>>,
       },
+      /// A field of a named struct, from a GEP such as `p->y` whose pointee
+      /// type and field index are known
+      Field {
+        loc: $wrapper<Value>,
+        struct_name: String,
+        index: usize,
+      },
       Bin {
         #[serde(with = "BinaryOpcodeDef")]
         op: BinOp,
         op0: $wrapper<Value>,
         op1: $wrapper<Value>,
+        width: u32, // Bit width of the result
       },
       ICmp {
         #[serde(with = "PredicateDef")]
         pred: Predicate,
         op0: $wrapper<Value>,
         op1: $wrapper<Value>,
+        width: u32, // Bit width of the operands
+      },
+      FCmp {
+        #[serde(with = "FPredicateDef")]
+        pred: FPredicate,
+        op0: $wrapper<Value>,
+        op1: $wrapper<Value>,
       },
       Call {
         id: usize,
         func: $wrapper<Value>,
         args: Vec<$wrapper<Value>>,
       },
+      /// `then_val` when `cond` holds and `else_val` otherwise, from merging the
+      /// states of the two arms of a branch
+      Select {
+        cond: $wrapper<Value>,
+        then_val: $wrapper<Value>,
+        else_val: $wrapper<Value>,
+      },
+      /// The lanes of a vector, from vector loads, GEPs, binary operations and shuffles
+      Vector(Vec<$wrapper<Value>>),
+      /// The fields of an aggregate, such as the `{old, success}` pair of a cmpxchg
+      Aggregate(Vec<$wrapper<Value>>),
       Unknown,
     }
 
+    /// Comma separated display strings of the given values
+    fn values_display_string(values: &[$wrapper<Value>]) -> String {
+      values
+        .iter()
+        .map(|v| v.to_display_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    impl Value {
+      /// A compact and deterministic textual form of the value, e.g. `add(sym0, 1)`
+      pub fn to_display_string(&self) -> String {
+        match self {
+          Value::Arg(i) => format!("arg{}", i),
+          Value::ConstSym(i) => format!("csym{}", i),
+          Value::Sym(i) => format!("sym{}", i),
+          Value::LocSym(loc) => format!("sym@{}", loc.to_display_string()),
+          Value::Undef(loc) => format!("undef@{}", loc.to_display_string()),
+          Value::Glob(name) => format!("@{}", name),
+          Value::Func(name) => name.clone(),
+          Value::Alloc(i) => format!("alloca{}", i),
+          Value::AllocOf(v) => format!("alloca_of({})", v.to_display_string()),
+          Value::FuncPtr => "func_ptr".to_string(),
+          Value::Asm => "asm".to_string(),
+          Value::Exception => "exception".to_string(),
+          Value::Int(i) => format!("{}", i),
+          Value::Float(Float(f)) => format!("{:?}", f),
+          Value::Null => "null".to_string(),
+          Value::GEP { loc, indices } => format!("{}[{}]", loc.to_display_string(), values_display_string(indices)),
+          Value::Field {
+            loc,
+            struct_name,
+            index,
+          } => format!("{}->{}.{}", loc.to_display_string(), struct_name, index),
+          Value::Bin { op, op0, op1, .. } => format!(
+            "{}({}, {})",
+            op_name(op),
+            op0.to_display_string(),
+            op1.to_display_string()
+          ),
+          Value::ICmp { pred, op0, op1, .. } => format!(
+            "icmp_{}({}, {})",
+            op_name(pred),
+            op0.to_display_string(),
+            op1.to_display_string()
+          ),
+          Value::FCmp { pred, op0, op1 } => format!(
+            "fcmp_{}({}, {})",
+            op_name(pred),
+            op0.to_display_string(),
+            op1.to_display_string()
+          ),
+          Value::Call { id, func, args } => format!(
+            "call{}:{}({})",
+            id,
+            func.to_display_string(),
+            values_display_string(args)
+          ),
+          Value::Select {
+            cond,
+            then_val,
+            else_val,
+          } => format!(
+            "select({}, {}, {})",
+            cond.to_display_string(),
+            then_val.to_display_string(),
+            else_val.to_display_string()
+          ),
+          Value::Vector(lanes) => format!("<{}>", values_display_string(lanes)),
+          Value::Aggregate(fields) => format!("{{{}}}", values_display_string(fields)),
+          Value::Unknown => "unknown".to_string(),
+        }
+      }
+    }
+
     impl Value {
       pub fn as_comparison(&self) -> Option<Comparison> {
         match self {
-          Value::ICmp { pred, op0, op1 } => Some(Comparison {
-            pred: *pred,
-            op0: op0.clone(),
-            op1: op1.clone(),
-          }),
+          Value::ICmp { pred, op0, op1, width } => Some(
+            Comparison::ICmp {
+              pred: *pred,
+              op0: op0.clone(),
+              op1: op1.clone(),
+              width: *width,
+            }
+            .canonicalize(),
+          ),
+          Value::FCmp { pred, op0, op1 } => Some(
+            Comparison::FCmp {
+              pred: *pred,
+              op0: op0.clone(),
+              op1: op1.clone(),
+            }
+            .canonicalize(),
+          ),
           _ => None,
         }
       }
 
+      pub fn return_source(&self) -> ReturnSource {
+        match self {
+          Value::Int(_) | Value::Float(_) | Value::Null => ReturnSource::Constant,
+          Value::Arg(_) => ReturnSource::Argument,
+          Value::Call { .. } => ReturnSource::CallResult,
+          _ => ReturnSource::Symbolic,
+        }
+      }
+
+      pub fn is_constant(&self) -> bool {
+        match self {
+          Value::Int(_) | Value::Float(_) | Value::Null => true,
+          _ => false,
+        }
+      }
+
+      /// Whether the location denotes the same abstract cell in every trace, i.e.
+      /// it is built from arguments, globals and constants without any counter id
+      pub fn is_nameable_location(&self) -> bool {
+        match self {
+          Value::Arg(_) | Value::Glob(_) | Value::LocSym(_) | Value::Int(_) | Value::Null => true,
+          Value::GEP { loc, indices } => loc.is_nameable_location() && indices.iter().all(|i| i.is_nameable_location()),
+          Value::Field { loc, .. } => loc.is_nameable_location(),
+          Value::Bin { op0, op1, .. } => op0.is_nameable_location() && op1.is_nameable_location(),
+          _ => false,
+        }
+      }
+
+      /// Whether `value` is the value itself or one of its subterms
+      pub fn depends_on(&self, value: &Value) -> bool {
+        if self == value {
+          return true;
+        }
+        match self {
+          Value::AllocOf(v) | Value::LocSym(v) | Value::Undef(v) => v.depends_on(value),
+          Value::GEP { loc, indices } => loc.depends_on(value) || indices.iter().any(|i| i.depends_on(value)),
+          Value::Field { loc, .. } => loc.depends_on(value),
+          Value::Bin { op0, op1, .. } | Value::ICmp { op0, op1, .. } | Value::FCmp { op0, op1, .. } => {
+            op0.depends_on(value) || op1.depends_on(value)
+          }
+          Value::Call { func, args, .. } => func.depends_on(value) || args.iter().any(|a| a.depends_on(value)),
+          Value::Select {
+            cond,
+            then_val,
+            else_val,
+          } => cond.depends_on(value) || then_val.depends_on(value) || else_val.depends_on(value),
+          Value::Vector(lanes) | Value::Aggregate(lanes) => lanes.iter().any(|l| l.depends_on(value)),
+          _ => false,
+        }
+      }
+
       pub fn contains(&self, value: &Value) -> bool {
         match value {
-          Value::GEP { loc, .. } => {
+          Value::GEP { loc, .. } | Value::Field { loc, .. } => {
             if &**loc == self {
               true
             } else {
@@ -154,32 +457,137 @@ macro_rules! decl_value_with_wrapper {
         }
       }
 
+      /// The signed range of the low `width` bits of the value, subject to the
+      /// comparisons evaluating to their paired branch. A bound is `None` when
+      /// it is the extreme value of the width, i.e. when nothing constrains it
+      pub fn range_under(&self, width: u32, comparisons: &[(&Comparison, bool)]) -> (Option<i64>, Option<i64>) {
+        use z3::ast::Ast;
+        let z3_ctx = z3::Context::new(&z3::Config::default());
+        let mut symbol_map = HashMap::new();
+        let mut symbol_id = 0;
+        let formulas = comparisons
+          .iter()
+          .filter_map(|(cond, branch)| cond.into_z3_ast(*branch, &mut symbol_map, &mut symbol_id, &z3_ctx))
+          .collect::<Vec<_>>();
+        let value = match self
+          .into_z3_ast(&mut symbol_map, &mut symbol_id, &z3_ctx)
+          .and_then(|value| truncate_z3_bv(value, width))
+        {
+          Some(value) => value.sign_ext(64 - width),
+          None => return (None, None),
+        };
+
+        // Bitvectors are optimized as unsigned, flipping the sign bit maps the signed order onto it
+        let objective = value.bvxor(&z3::ast::BV::from_u64(&z3_ctx, 1 << 63, 64));
+        let bound = |maximize: bool| {
+          let optimize = z3::Optimize::new(&z3_ctx);
+          for formula in &formulas {
+            optimize.assert(formula);
+          }
+          if maximize {
+            optimize.maximize(&objective);
+          } else {
+            optimize.minimize(&objective);
+          }
+          match optimize.check(&[]) {
+            z3::SatResult::Sat => optimize
+              .get_model()
+              .and_then(|model| model.eval(&value))
+              .and_then(|value| value.as_i64()),
+            _ => None,
+          }
+        };
+        let min = bound(false).filter(|min| *min != i64::MIN >> (64 - width));
+        let max = bound(true).filter(|max| *max != i64::MAX >> (64 - width));
+        (min, max)
+      }
+
+      /// Model the value as a 64-bit bitvector holding its sign-extended value,
+      /// which is how integers are represented in `Value::Int`. Operations are
+      /// carried out on the low bits of their own width, so that they wrap around
+      /// like in LLVM, before being sign-extended back
       pub fn into_z3_ast<'ctx>(
         &self,
         symbol_map: &mut HashMap<Value, z3::Symbol>,
         symbol_id: &mut u32,
         z3_ctx: &'ctx z3::Context,
-      ) -> Option<z3::ast::Int<'ctx>> {
+      ) -> Option<z3::ast::BV<'ctx>> {
+        use z3::*;
+        match self {
+          Value::Int(i) => Some(ast::BV::from_i64(z3_ctx, *i, 64)),
+          Value::Null => Some(ast::BV::from_i64(z3_ctx, 0, 64)),
+          Value::Bin { op, op0, op1, width } => {
+            match (
+              op0
+                .into_z3_ast(symbol_map, symbol_id, z3_ctx)
+                .and_then(|op0| truncate_z3_bv(op0, *width)),
+              op1
+                .into_z3_ast(symbol_map, symbol_id, z3_ctx)
+                .and_then(|op1| truncate_z3_bv(op1, *width)),
+            ) {
+              (Some(op0), Some(op1)) => z3_binary(*op, &op0, &op1).map(|res| res.sign_ext(64 - width)),
+              _ => None,
+            }
+          }
+          Value::Select {
+            cond,
+            then_val,
+            else_val,
+          } => {
+            let cond = cond.select_guard_into_z3(symbol_map, symbol_id, z3_ctx)?;
+            let then_val = then_val.into_z3_ast(symbol_map, symbol_id, z3_ctx)?;
+            let else_val = else_val.into_z3_ast(symbol_map, symbol_id, z3_ctx)?;
+            Some(cond.ite(&then_val, &else_val))
+          }
+          Value::Unknown => None,
+          _ => {
+            let symbol = symbol_map.entry(self.clone()).or_insert_with(|| {
+              let result = *symbol_id;
+              *symbol_id += 1;
+              Symbol::Int(result)
+            });
+            Some(ast::BV::new_const(z3_ctx, symbol.clone(), 64))
+          }
+        }
+      }
+
+      /// Model the value as a real number, which is how floating point values
+      /// take part in path constraints
+      pub fn into_z3_real<'ctx>(
+        &self,
+        symbol_map: &mut HashMap<Value, z3::Symbol>,
+        symbol_id: &mut u32,
+        z3_ctx: &'ctx z3::Context,
+      ) -> Option<z3::ast::Real<'ctx>> {
         use z3::*;
         match self {
-          Value::Int(i) => Some(ast::Int::from_i64(z3_ctx, *i)),
-          Value::Null => Some(ast::Int::from_i64(z3_ctx, 0)),
-          Value::Bin { op, op0, op1 } => {
+          Value::Float(Float(f)) => float_into_z3_real(*f, z3_ctx),
+          Value::Int(i) => Some(ast::Real::from_int(&ast::Int::from_i64(z3_ctx, *i))),
+          Value::Bin { op, op0, op1, .. } => {
             match (
-              op0.into_z3_ast(symbol_map, symbol_id, z3_ctx),
-              op1.into_z3_ast(symbol_map, symbol_id, z3_ctx),
+              op0.into_z3_real(symbol_map, symbol_id, z3_ctx),
+              op1.into_z3_real(symbol_map, symbol_id, z3_ctx),
             ) {
               (Some(op0), Some(op1)) => match op {
-                BinOp::Add => Some(ast::Int::add(z3_ctx, &[&op0, &op1])),
-                BinOp::Sub => Some(ast::Int::sub(z3_ctx, &[&op0, &op1])),
-                BinOp::Mul => Some(ast::Int::mul(z3_ctx, &[&op0, &op1])),
-                BinOp::UDiv | BinOp::SDiv => Some(op0.div(&op1)),
-                BinOp::URem | BinOp::SRem => Some(op0.rem(&op1)),
+                BinOp::FAdd => Some(ast::Real::add(z3_ctx, &[&op0, &op1])),
+                BinOp::FSub => Some(ast::Real::sub(z3_ctx, &[&op0, &op1])),
+                BinOp::FMul => Some(ast::Real::mul(z3_ctx, &[&op0, &op1])),
+                BinOp::FDiv => Some(op0.div(&op1)),
                 _ => None,
               },
               _ => None,
             }
           }
+          Value::Select {
+            cond,
+            then_val,
+            else_val,
+          } => {
+            let cond = cond.select_guard_into_z3(symbol_map, symbol_id, z3_ctx)?;
+            let then_val = then_val.into_z3_real(symbol_map, symbol_id, z3_ctx)?;
+            let else_val = else_val.into_z3_real(symbol_map, symbol_id, z3_ctx)?;
+            Some(cond.ite(&then_val, &else_val))
+          }
           Value::Unknown => None,
           _ => {
             let symbol = symbol_map.entry(self.clone()).or_insert_with(|| {
@@ -187,41 +595,115 @@ macro_rules! decl_value_with_wrapper {
               *symbol_id += 1;
               Symbol::Int(result)
             });
-            Some(ast::Int::new_const(z3_ctx, symbol.clone()))
+            Some(ast::Real::new_const(z3_ctx, symbol.clone()))
+          }
+        }
+      }
+
+      /// The formula of a select guard: the comparison itself, or whether the
+      /// value is non-zero when it is not a comparison
+      fn select_guard_into_z3<'ctx>(
+        &self,
+        symbol_map: &mut HashMap<Value, z3::Symbol>,
+        symbol_id: &mut u32,
+        z3_ctx: &'ctx z3::Context,
+      ) -> Option<z3::ast::Bool<'ctx>> {
+        use z3::ast::Ast;
+        match self.as_comparison() {
+          Some(comparison) => comparison.into_z3_ast(true, symbol_map, symbol_id, z3_ctx),
+          None => {
+            let zero = z3::ast::BV::from_i64(z3_ctx, 0, 64);
+            Some(self.into_z3_ast(symbol_map, symbol_id, z3_ctx)?._eq(&zero).not())
           }
         }
       }
     }
 
-    #[derive(Debug, Clone)]
-    pub struct Comparison {
-      pred: Predicate,
-      op0: $wrapper<Value>,
-      op1: $wrapper<Value>,
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub enum Comparison {
+      ICmp {
+        #[serde(with = "PredicateDef")]
+        pred: Predicate,
+        op0: $wrapper<Value>,
+        op1: $wrapper<Value>,
+        width: u32,
+      },
+      FCmp {
+        #[serde(with = "FPredicateDef")]
+        pred: FPredicate,
+        op0: $wrapper<Value>,
+        op1: $wrapper<Value>,
+      },
     }
 
     impl Comparison {
+      /// Order the operands consistently, flipping the predicate accordingly, so that
+      /// equivalent comparisons such as `x < 5` and `5 > x` are equal. Constants go
+      /// to the right, and other operands are ordered by their textual form
+      pub fn canonicalize(self) -> Self {
+        let needs_swap = |op0: &Value, op1: &Value| match (op0.is_constant(), op1.is_constant()) {
+          (true, false) => true,
+          (false, true) => false,
+          _ => op0.to_display_string() > op1.to_display_string(),
+        };
+        match self {
+          Comparison::ICmp { pred, op0, op1, width } if needs_swap(&op0, &op1) => Comparison::ICmp {
+            pred: swapped_predicate(pred),
+            op0: op1,
+            op1: op0,
+            width,
+          },
+          Comparison::FCmp { pred, op0, op1 } if needs_swap(&op0, &op1) => Comparison::FCmp {
+            pred: swapped_fpredicate(pred),
+            op0: op1,
+            op1: op0,
+          },
+          _ => self,
+        }
+      }
+
+      /// The z3 formula of the comparison evaluating to `branch`. Float comparisons
+      /// are modeled over reals, which cannot be NaN; so an ordered predicate is only
+      /// asserted when it holds and an unordered one only when it does not, and
+      /// `None` is returned otherwise as the formula may be satisfied by NaN
       pub fn into_z3_ast<'ctx>(
         &self,
+        branch: bool,
         symbol_map: &mut HashMap<Value, z3::Symbol>,
         symbol_id: &mut u32,
         z3_ctx: &'ctx z3::Context,
       ) -> Option<z3::ast::Bool<'ctx>> {
         use z3::ast::Ast;
-        let Comparison { pred, op0, op1 } = self;
-        let z3_op0 = op0.into_z3_ast(symbol_map, symbol_id, z3_ctx);
-        let z3_op1 = op1.into_z3_ast(symbol_map, symbol_id, z3_ctx);
-        match (z3_op0, z3_op1) {
-          (Some(op0), Some(op1)) => match pred {
-            Predicate::EQ => Some(op0._eq(&op1)),
-            Predicate::NE => Some(op0._eq(&op1).not()),
-            Predicate::SGE | Predicate::UGE => Some(op0.ge(&op1)),
-            Predicate::SGT | Predicate::UGT => Some(op0.gt(&op1)),
-            Predicate::SLE | Predicate::ULE => Some(op0.le(&op1)),
-            Predicate::SLT | Predicate::ULT => Some(op0.lt(&op1)),
-          },
-          _ => None,
-        }
+        let formula = match self {
+          Comparison::ICmp { pred, op0, op1, width } => {
+            let z3_op0 = truncate_z3_bv(op0.into_z3_ast(symbol_map, symbol_id, z3_ctx)?, *width)?;
+            let z3_op1 = truncate_z3_bv(op1.into_z3_ast(symbol_map, symbol_id, z3_ctx)?, *width)?;
+            z3_icmp(*pred, &z3_op0, &z3_op1)
+          }
+          Comparison::FCmp { pred, op0, op1 } => {
+            let ordered = match pred {
+              FPredicate::OEQ | FPredicate::OGE | FPredicate::OGT => true,
+              FPredicate::OLE | FPredicate::OLT | FPredicate::ONE => true,
+              FPredicate::ORD | FPredicate::UNO => return None,
+              _ => false,
+            };
+            if branch != ordered {
+              return None;
+            }
+            let z3_op0 = op0.into_z3_real(symbol_map, symbol_id, z3_ctx)?;
+            let z3_op1 = op1.into_z3_real(symbol_map, symbol_id, z3_ctx)?;
+            match pred {
+              FPredicate::OEQ | FPredicate::UEQ => z3_op0._eq(&z3_op1),
+              FPredicate::ONE | FPredicate::UNE => z3_op0._eq(&z3_op1).not(),
+              FPredicate::OGE | FPredicate::UGE => z3_op0.ge(&z3_op1),
+              FPredicate::OGT | FPredicate::UGT => z3_op0.gt(&z3_op1),
+              FPredicate::OLE | FPredicate::ULE => z3_op0.le(&z3_op1),
+              FPredicate::OLT | FPredicate::ULT => z3_op0.lt(&z3_op1),
+              FPredicate::ORD | FPredicate::UNO => return None,
+            }
+          }
+        };
+        Some(if branch { formula } else { formula.not() })
       }
     }
 
@@ -230,6 +712,14 @@ macro_rules! decl_value_with_wrapper {
       Call {
         func: $wrapper<Value>,
         args: Vec<$wrapper<Value>>,
+        /// The number of parameters declared by the callee type
+        #[serde(default)]
+        num_params: usize,
+        #[serde(default)]
+        is_var_arg: bool,
+        /// The raw symbol of the callee, when `func` holds its demangled name
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mangled: Option<String>,
       },
       ICmp {
         #[serde(with = "PredicateDef")]
@@ -237,6 +727,12 @@ macro_rules! decl_value_with_wrapper {
         op0: $wrapper<Value>,
         op1: $wrapper<Value>,
       },
+      FCmp {
+        #[serde(with = "FPredicateDef")]
+        pred: FPredicate,
+        op0: $wrapper<Value>,
+        op1: $wrapper<Value>,
+      },
       CondBr {
         cond: $wrapper<Value>,
         br: Branch,
@@ -250,6 +746,9 @@ macro_rules! decl_value_with_wrapper {
       },
       Ret {
         op: Option<$wrapper<Value>>,
+        /// Only set on the returns of the entry function
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<ReturnSource>,
       },
       Store {
         loc: $wrapper<Value>,
@@ -258,9 +757,24 @@ macro_rules! decl_value_with_wrapper {
       Load {
         loc: $wrapper<Value>,
       },
+      Free {
+        loc: $wrapper<Value>,
+      },
+      Phi {
+        incoming_block: usize, // Index of the incoming block in the function
+        value: $wrapper<Value>,
+      },
       GEP {
         loc: $wrapper<Value>,
         indices: Vec<$wrapper<Value>>,
+        /// The innermost struct field accessed, when its index is constant
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        field: Option<StructField>,
+        /// Whether the constant array indices are within the array sizes of the
+        /// pointee type; `None` when there are none or when one is symbolic or
+        /// indexes a flexible array member
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        in_bounds: Option<bool>,
       },
       Una {
         #[serde(with = "UnaryOpcodeDef")]
@@ -273,9 +787,201 @@ macro_rules! decl_value_with_wrapper {
         op0: $wrapper<Value>,
         op1: $wrapper<Value>,
       },
+      /// Both arms of a branch on `cond` taken at once under `--merge-states`, with
+      /// the memory cells the arms stored to as `(loc, val)`, each value guarded
+      /// by `cond` through a `Value::Select` where the arms disagree
+      Merge {
+        cond: $wrapper<Value>,
+        cells: Vec<($wrapper<Value>, $wrapper<Value>)>,
+      },
+    }
+
+    impl Semantics {
+      /// A compact and deterministic textual form of the semantics, e.g. `call foo(arg0, 7)`
+      pub fn to_display_string(&self) -> String {
+        match self {
+          Semantics::Call { func, args, .. } => {
+            format!("call {}({})", func.to_display_string(), values_display_string(args))
+          }
+          Semantics::ICmp { pred, op0, op1 } => format!(
+            "icmp {} {}, {}",
+            op_name(pred),
+            op0.to_display_string(),
+            op1.to_display_string()
+          ),
+          Semantics::FCmp { pred, op0, op1 } => format!(
+            "fcmp {} {}, {}",
+            op_name(pred),
+            op0.to_display_string(),
+            op1.to_display_string()
+          ),
+          Semantics::CondBr { cond, br, beg_loop } => format!(
+            "br {} {}{}",
+            cond.to_display_string(),
+            if br.is_then() { "then" } else { "else" },
+            if *beg_loop { " loop" } else { "" }
+          ),
+          Semantics::UncondBr { end_loop } => format!("br{}", if *end_loop { " end_loop" } else { "" }),
+          Semantics::Switch { cond } => format!("switch {}", cond.to_display_string()),
+          Semantics::Ret { op, .. } => match op {
+            Some(op) => format!("ret {}", op.to_display_string()),
+            None => "ret".to_string(),
+          },
+          Semantics::Store { loc, val } => format!("store {} -> {}", val.to_display_string(), loc.to_display_string()),
+          Semantics::Load { loc } => format!("load {}", loc.to_display_string()),
+          Semantics::Free { loc } => format!("free {}", loc.to_display_string()),
+          Semantics::Phi { incoming_block, value } => {
+            format!("phi {} from block{}", value.to_display_string(), incoming_block)
+          }
+          Semantics::GEP {
+            loc, indices, field, ..
+          } => match field {
+            Some(field) => format!(
+              "gep {}[{}] ({})",
+              loc.to_display_string(),
+              values_display_string(indices),
+              field.to_display_string()
+            ),
+            None => format!("gep {}[{}]", loc.to_display_string(), values_display_string(indices)),
+          },
+          Semantics::Una { op, op0 } => format!("{} {}", op_name(op), op0.to_display_string()),
+          Semantics::Bin { op, op0, op1 } => format!(
+            "{} {}, {}",
+            op_name(op),
+            op0.to_display_string(),
+            op1.to_display_string()
+          ),
+          Semantics::Merge { cond, cells } => format!(
+            "merge {} [{}]",
+            cond.to_display_string(),
+            cells
+              .iter()
+              .map(|(loc, val)| format!("{} -> {}", val.to_display_string(), loc.to_display_string()))
+              .collect::<Vec<_>>()
+              .join(", ")
+          ),
+        }
+      }
     }
 
     impl Semantics {
+      /// The kind of the semantics, one of `SEMANTICS_KINDS`
+      pub fn kind(&self) -> &'static str {
+        match self {
+          Semantics::Call { .. } => "call",
+          Semantics::ICmp { .. } | Semantics::FCmp { .. } => "compare",
+          Semantics::CondBr { .. }
+          | Semantics::UncondBr { .. }
+          | Semantics::Switch { .. }
+          | Semantics::Merge { .. } => "branch",
+          Semantics::Ret { .. } => "return",
+          Semantics::Store { .. } => "store",
+          Semantics::Load { .. } => "load",
+          Semantics::Free { .. } => "free",
+          Semantics::Phi { .. } => "phi",
+          Semantics::GEP { .. } => "gep",
+          Semantics::Una { .. } => "unary",
+          Semantics::Bin { .. } => "binary",
+        }
+      }
+
+      /// All the values used by this semantics
+      pub fn operands(&self) -> Vec<&$wrapper<Value>> {
+        match self {
+          Semantics::Call { func, args, .. } => vec![vec![func], args.iter().collect()].concat(),
+          Semantics::ICmp { op0, op1, .. } => vec![op0, op1],
+          Semantics::FCmp { op0, op1, .. } => vec![op0, op1],
+          Semantics::CondBr { cond, .. } => vec![cond],
+          Semantics::UncondBr { .. } => vec![],
+          Semantics::Switch { cond } => vec![cond],
+          Semantics::Ret { op, .. } => op.iter().collect(),
+          Semantics::Store { loc, val } => vec![loc, val],
+          Semantics::Load { loc } => vec![loc],
+          Semantics::Free { loc } => vec![loc],
+          Semantics::Phi { value, .. } => vec![value],
+          Semantics::GEP { loc, indices, .. } => vec![vec![loc], indices.iter().collect()].concat(),
+          Semantics::Una { op0, .. } => vec![op0],
+          Semantics::Bin { op0, op1, .. } => vec![op0, op1],
+          Semantics::Merge { cond, cells } => {
+            vec![vec![cond], cells.iter().flat_map(|(loc, val)| vec![loc, val]).collect()].concat()
+          }
+        }
+      }
+
+      /// Rebuild the semantics with every value mapped through `f`
+      pub fn map_values<F>(&self, mut f: F) -> Semantics
+      where
+        F: FnMut(&$wrapper<Value>) -> $wrapper<Value>,
+      {
+        match self {
+          Semantics::Call {
+            func,
+            args,
+            num_params,
+            is_var_arg,
+            mangled,
+          } => Semantics::Call {
+            func: f(func),
+            args: args.iter().map(|a| f(a)).collect(),
+            num_params: *num_params,
+            is_var_arg: *is_var_arg,
+            mangled: mangled.clone(),
+          },
+          Semantics::ICmp { pred, op0, op1 } => Semantics::ICmp {
+            pred: *pred,
+            op0: f(op0),
+            op1: f(op1),
+          },
+          Semantics::FCmp { pred, op0, op1 } => Semantics::FCmp {
+            pred: *pred,
+            op0: f(op0),
+            op1: f(op1),
+          },
+          Semantics::CondBr { cond, br, beg_loop } => Semantics::CondBr {
+            cond: f(cond),
+            br: br.clone(),
+            beg_loop: *beg_loop,
+          },
+          Semantics::UncondBr { end_loop } => Semantics::UncondBr { end_loop: *end_loop },
+          Semantics::Switch { cond } => Semantics::Switch { cond: f(cond) },
+          Semantics::Ret { op, source } => Semantics::Ret {
+            op: op.as_ref().map(f),
+            source: *source,
+          },
+          Semantics::Store { loc, val } => Semantics::Store {
+            loc: f(loc),
+            val: f(val),
+          },
+          Semantics::Load { loc } => Semantics::Load { loc: f(loc) },
+          Semantics::Free { loc } => Semantics::Free { loc: f(loc) },
+          Semantics::Phi { incoming_block, value } => Semantics::Phi {
+            incoming_block: *incoming_block,
+            value: f(value),
+          },
+          Semantics::GEP {
+            loc,
+            indices,
+            field,
+            in_bounds,
+          } => Semantics::GEP {
+            loc: f(loc),
+            indices: indices.iter().map(|i| f(i)).collect(),
+            field: field.clone(),
+            in_bounds: *in_bounds,
+          },
+          Semantics::Una { op, op0 } => Semantics::Una { op: *op, op0: f(op0) },
+          Semantics::Bin { op, op0, op1 } => Semantics::Bin {
+            op: *op,
+            op0: f(op0),
+            op1: f(op1),
+          },
+          Semantics::Merge { cond, cells } => Semantics::Merge {
+            cond: f(cond),
+            cells: cells.iter().map(|(loc, val)| (f(loc), f(val))).collect(),
+          },
+        }
+      }
+
       pub fn call_args(&self) -> Vec<&Value> {
         match self {
           Semantics::Call { args, .. } => args.iter().map(|v| &**v).collect(),
@@ -295,6 +1001,21 @@ macro_rules! decl_value_with_wrapper {
           _ => panic!("Target is not a call"),
         }
       }
+
+      /// The arguments passed in the variadic tail of a call, past the fixed
+      /// parameters of the callee. Empty for callees of fixed arity
+      pub fn call_var_args(&self) -> Vec<&Value> {
+        match self {
+          Semantics::Call {
+            args,
+            num_params,
+            is_var_arg: true,
+            ..
+          } => args.iter().skip(*num_params).map(|v| &**v).collect(),
+          Semantics::Call { .. } => vec![],
+          _ => panic!("Target is not a call"),
+        }
+      }
     }
   };
 }
@@ -1,4 +1,5 @@
 use llir::values::*;
+use log::{debug, warn};
 use petgraph::{graph::*, visit::*, Direction};
 use rayon::prelude::*;
 use regex::Regex;
@@ -9,6 +10,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use crate::call_graph::*;
+use crate::error::*;
 use crate::options::*;
 use crate::utils::*;
 
@@ -19,6 +21,8 @@ pub trait SlicerOptions: GeneralOptions + Send + Sync {
 
   fn entry_filter(&self) -> &Option<String>;
 
+  fn target(&self) -> &Option<String>;
+
   fn target_inclusion_filter(&self) -> &Option<String>;
 
   fn target_exclusion_filter(&self) -> &Option<String>;
@@ -26,6 +30,18 @@ pub trait SlicerOptions: GeneralOptions + Send + Sync {
   fn use_regex_filter(&self) -> bool;
 
   fn max_avg_num_blocks(&self) -> usize;
+
+  fn target_arg_count(&self) -> Option<usize>;
+
+  fn entry_file_filter(&self) -> &Option<String>;
+
+  fn exclude_call_site(&self) -> &Option<String>;
+
+  fn dedup_slices(&self) -> bool;
+
+  fn max_slice_functions(&self) -> Option<usize>;
+
+  fn force_entry(&self) -> &Option<String>;
 }
 
 #[derive(Clone)]
@@ -33,7 +49,7 @@ pub struct Slice<'ctx> {
   pub entry: Function<'ctx>,
   pub caller: Function<'ctx>,
   pub callee: Function<'ctx>,
-  pub instr: CallInstruction<'ctx>,
+  pub instr: CallSite<'ctx>,
   pub functions: HashSet<Function<'ctx>>,
 }
 
@@ -48,10 +64,82 @@ impl<'ctx> Slice<'ctx> {
       "caller": self.caller.simp_name(),
       "callee": self.callee.simp_name(),
       "instr": self.instr.debug_loc_string(),
+      "instr_index": self.instr.index_in_function(),
       "functions": self.functions.iter().map(|f| f.simp_name()).collect::<Vec<_>>(),
     })
   }
 
+  /// Rebuild a slice dumped by `to_json` from the call graph of the same byte code.
+  /// The call site is the call from the caller to the callee at the dumped index
+  /// among the caller's instructions, and it has to be the only such call
+  pub fn from_json(json: &serde_json::Value, call_graph: &CallGraph<'ctx>) -> Result<Self, String> {
+    let name = |key: &str| {
+      json[key]
+        .as_str()
+        .ok_or_else(|| format!("Slice json misses its {}", key))
+    };
+    let function = |name: &str| {
+      call_graph
+        .function_id_map
+        .keys()
+        .find(|f| f.simp_name() == name)
+        .cloned()
+        .ok_or_else(|| format!("Cannot find function {} of the slice", name))
+    };
+    let (caller_name, callee_name) = (name("caller")?, name("callee")?);
+    let instr_index = json["instr_index"]
+      .as_u64()
+      .ok_or_else(|| "Slice json misses its instr_index".to_string())? as usize;
+    let call_sites = call_graph
+      .graph
+      .edge_indices()
+      .filter_map(|edge_id| {
+        let (caller_id, callee_id) = call_graph.graph.edge_endpoints(edge_id)?;
+        let (caller, callee, instr) = (
+          call_graph.graph[caller_id],
+          call_graph.graph[callee_id],
+          call_graph.graph[edge_id],
+        );
+        if caller.simp_name() == caller_name
+          && callee.simp_name() == callee_name
+          && instr.index_in_function() == instr_index
+        {
+          Some((caller, callee, instr))
+        } else {
+          None
+        }
+      })
+      .collect::<Vec<_>>();
+    let (caller, callee, instr) = match call_sites[..] {
+      [call_site] => call_site,
+      [] => {
+        return Err(format!(
+          "Cannot find the call to {} in {} at instruction {}",
+          callee_name, caller_name, instr_index
+        ))
+      }
+      _ => {
+        return Err(format!(
+          "Ambiguous call to {} in {} at instruction {}",
+          callee_name, caller_name, instr_index
+        ))
+      }
+    };
+    let functions = json["functions"]
+      .as_array()
+      .ok_or_else(|| "Slice json misses its functions".to_string())?
+      .iter()
+      .map(|f| function(f.as_str().unwrap_or_default()))
+      .collect::<Result<_, _>>()?;
+    Ok(Self {
+      entry: function(name("entry")?)?,
+      caller,
+      callee,
+      instr,
+      functions,
+    })
+  }
+
   pub fn target_function_name(&self) -> String {
     self.callee.simp_name()
   }
@@ -59,6 +147,19 @@ impl<'ctx> Slice<'ctx> {
   pub fn size(&self) -> usize {
     self.functions.len()
   }
+
+  /// Slices with the same key cover the same functions from the same entry
+  /// towards the same target call site
+  pub fn dedup_key(&self) -> (String, usize, String, Vec<String>) {
+    let mut functions = self.functions.iter().map(|f| f.simp_name()).collect::<Vec<_>>();
+    functions.sort();
+    (
+      self.caller.simp_name(),
+      self.instr.index_in_function(),
+      self.entry.simp_name(),
+      functions,
+    )
+  }
 }
 
 enum TargetFilter {
@@ -68,11 +169,12 @@ enum TargetFilter {
 }
 
 impl TargetFilter {
-  pub fn new(filter_str: Option<String>, use_regex: bool, default: bool) -> Result<Self, String> {
+  pub fn new(filter_str: Option<String>, use_regex: bool, default: bool) -> Result<Self, AnalyzerError> {
     match filter_str {
       Some(s) => {
         if use_regex {
-          let regex = Regex::new(s.as_str()).map_err(|_| "Cannot parse target filter".to_string())?;
+          let regex =
+            Regex::new(s.as_str()).map_err(|_| AnalyzerError::Regex("Cannot parse target filter".to_string()))?;
           Ok(Self::Regex(regex))
         } else {
           Ok(Self::Str(s.clone()))
@@ -96,15 +198,45 @@ impl TargetFilter {
   }
 }
 
+/// The regexes restricting the call sites and entries of the slices, compiled
+/// once before slicing the call edges
+pub struct SliceFilters {
+  exclude_call_site: Option<Regex>,
+  entry: Option<Regex>,
+  entry_file: Option<Regex>,
+}
+
+impl SliceFilters {
+  pub fn new(options: &impl SlicerOptions) -> Result<Self, AnalyzerError> {
+    Ok(Self {
+      exclude_call_site: compile_filter(options.exclude_call_site(), "call site exclusion")?,
+      entry: compile_filter(options.entry_filter(), "entry filter")?,
+      entry_file: compile_filter(options.entry_file_filter(), "entry file filter")?,
+    })
+  }
+}
+
+fn compile_filter(filter: &Option<String>, name: &str) -> Result<Option<Regex>, AnalyzerError> {
+  filter
+    .as_ref()
+    .map(|filter| Regex::new(filter).map_err(|_| AnalyzerError::Regex(format!("Cannot parse {} regex", name))))
+    .transpose()
+}
+
 /// Map from function name to Edges (`Vec<Edge>`)
 pub type TargetEdgesMap = HashMap<String, Vec<EdgeIndex>>;
 
 pub trait TargetEdgesMapTrait: Sized {
-  fn from_call_graph<'ctx>(call_graph: &CallGraph<'ctx>, options: &impl SlicerOptions) -> Result<Self, String>;
+  fn from_call_graph<'ctx>(call_graph: &CallGraph<'ctx>, options: &impl SlicerOptions) -> Result<Self, AnalyzerError>;
+
+  /// Dump the names of all the targets, before their slices get dumped batch by batch
+  fn dump_target_names<O>(&self, options: &O)
+  where
+    O: IOOptions;
 }
 
 impl TargetEdgesMapTrait for TargetEdgesMap {
-  fn from_call_graph<'ctx>(call_graph: &CallGraph<'ctx>, options: &impl SlicerOptions) -> Result<Self, String> {
+  fn from_call_graph<'ctx>(call_graph: &CallGraph<'ctx>, options: &impl SlicerOptions) -> Result<Self, AnalyzerError> {
     let inclusion_filter = TargetFilter::new(
       options.target_inclusion_filter().clone(),
       options.use_regex_filter(),
@@ -118,12 +250,25 @@ impl TargetEdgesMapTrait for TargetEdgesMap {
     let mut target_edges_map = TargetEdgesMap::new();
     for callee_id in call_graph.graph.node_indices() {
       let func = call_graph.graph[callee_id];
+
+      // Under `--demangle` the filters also match the demangled name
       let func_name = func.simp_name();
-      let include_from_inclusion = inclusion_filter.matches(func_name.as_str());
-      let include = if !include_from_inclusion {
+      let demangled = if options.demangle() { demangle(&func_name) } else { None };
+      let names = std::iter::once(&func_name).chain(demangled.iter()).collect::<Vec<_>>();
+
+      // A single target short-circuits the filters
+      if let Some(target) = options.target() {
+        if &func.name() != target && demangled.as_ref() != Some(target) {
+          continue;
+        }
+      }
+
+      let include = if options.target().is_some() {
+        true
+      } else if !names.iter().any(|name| inclusion_filter.matches(name.as_str())) {
         false
       } else {
-        !exclusion_filter.matches(func_name.as_str())
+        !names.iter().any(|name| exclusion_filter.matches(name.as_str()))
       };
       if include {
         for edge in call_graph.graph.edges_directed(callee_id, Direction::Incoming) {
@@ -136,6 +281,35 @@ impl TargetEdgesMapTrait for TargetEdgesMap {
     }
     Ok(target_edges_map)
   }
+
+  fn dump_target_names<O>(&self, options: &O)
+  where
+    O: IOOptions,
+  {
+    dump_target_names(self.keys(), options);
+  }
+}
+
+fn dump_target_names<'a, O>(targets: impl Iterator<Item = &'a String>, options: &O)
+where
+  O: IOOptions,
+{
+  fs::create_dir_all(options.output_path()).expect("Cannot create output folder");
+  let target_names = targets
+    .map(|target| (sanitize_file_name(target), target.clone()))
+    .collect::<HashMap<_, _>>();
+  dump_json(&json!(target_names), options.target_names_path()).expect("Cannot dump target names");
+}
+
+fn dump_target_slices<'ctx, O>(target: &str, slice_id_offset: usize, slices: &[Slice<'ctx>], options: &O)
+where
+  O: IOOptions,
+{
+  fs::create_dir_all(options.slice_target_dir(target)).expect("Cannot create slice folder");
+  slices.par_iter().enumerate().for_each(|(i, slice)| {
+    let path = options.slice_target_file_path(target, slice_id_offset + i);
+    dump_versioned_json(&slice.to_json(), path).expect("Cannot dump slice json");
+  });
 }
 
 /// Map frmo function name to Slices
@@ -146,11 +320,23 @@ pub trait TargetSlicesMapTrait<'ctx>: Sized {
     target_edges_map: &TargetEdgesMap,
     call_graph: &CallGraph<'ctx>,
     options: &impl SlicerOptions,
-  ) -> Self;
+  ) -> Result<Self, AnalyzerError>;
+
+  /// Generate the slices, also returning the number of slices dropped for having
+  /// more functions than allowed
+  fn from_target_edges_map_with_dropped(
+    target_edges_map: &TargetEdgesMap,
+    call_graph: &CallGraph<'ctx>,
+    options: &impl SlicerOptions,
+  ) -> Result<(Self, usize), AnalyzerError>;
 
   fn dump<O>(&self, options: &O)
   where
     O: SlicerOptions + IOOptions;
+
+  /// Load the slices dumped by a previous run, e.g. under `--slice-only`, for the
+  /// targets listed in `target_names.json`
+  fn load(call_graph: &CallGraph<'ctx>, options: &impl IOOptions, ignore_version: bool) -> Result<Self, String>;
 }
 
 impl<'ctx> TargetSlicesMapTrait<'ctx> for TargetSlicesMap<'ctx> {
@@ -158,25 +344,134 @@ impl<'ctx> TargetSlicesMapTrait<'ctx> for TargetSlicesMap<'ctx> {
     target_edges_map: &TargetEdgesMap,
     call_graph: &CallGraph<'ctx>,
     options: &impl SlicerOptions,
-  ) -> Self {
+  ) -> Result<Self, AnalyzerError> {
+    Ok(Self::from_target_edges_map_with_dropped(target_edges_map, call_graph, options)?.0)
+  }
+
+  fn from_target_edges_map_with_dropped(
+    target_edges_map: &TargetEdgesMap,
+    call_graph: &CallGraph<'ctx>,
+    options: &impl SlicerOptions,
+  ) -> Result<(Self, usize), AnalyzerError> {
+    let filters = SliceFilters::new(options)?;
     let mut result = HashMap::new();
+    let mut num_dropped = 0;
     for (target, edges) in target_edges_map {
-      let slices = call_graph.slices_of_call_edges(&edges[..], options);
+      let (slices, dropped) =
+        call_graph.slices_of_call_edges_with_visited(&edges[..], options, &filters, &mut HashSet::new());
       result.insert(target.clone(), slices);
+      num_dropped += dropped;
     }
-    result
+    Ok((result, num_dropped))
   }
 
   fn dump<O>(&self, options: &O)
   where
     O: SlicerOptions + IOOptions,
   {
+    dump_target_names(self.keys(), options);
     for (target, slices) in self {
-      fs::create_dir_all(options.slice_target_dir(target.as_str())).expect("Cannot create slice folder");
-      slices.par_iter().enumerate().for_each(|(i, slice)| {
-        let path = options.slice_target_file_path(target.as_str(), i);
-        dump_json(&slice.to_json(), path).expect("Cannot dump slice json");
-      });
+      dump_target_slices(target, 0, slices, options);
+    }
+  }
+
+  fn load(call_graph: &CallGraph<'ctx>, options: &impl IOOptions, ignore_version: bool) -> Result<Self, String> {
+    let target_names: HashMap<String, String> = load_json_t(&options.target_names_path())?;
+    let mut result = HashMap::new();
+    for target in target_names.values() {
+      let mut files = json_files_in_dir(&options.slice_target_dir(target))?;
+      files.sort();
+      let slices = files
+        .into_iter()
+        .enumerate()
+        .map(|(i, (slice_id, path))| {
+          if i != slice_id {
+            return Err(format!("Missing slice {} of target {}", i, target));
+          }
+          Slice::from_json(&load_versioned_json_t(&path, ignore_version)?, call_graph)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      result.insert(target.clone(), slices);
+    }
+    Ok(result)
+  }
+}
+
+/// Keys identifying the slices already generated for a target, see `Slice::dedup_key`
+pub type SliceDedupKeys = HashSet<(String, usize, String, Vec<String>)>;
+
+/// What is carried from one batch of call edges to the next for a target: the
+/// number of slices generated so far, which is the id of its next slice, and
+/// their keys so that deduplication also applies across batches
+#[derive(Default)]
+pub struct TargetSlicingProgress {
+  pub num_slices: usize,
+  pub visited: SliceDedupKeys,
+}
+
+pub type TargetSlicingProgressMap = HashMap<String, TargetSlicingProgress>;
+
+pub trait TargetSlicingProgressMapTrait {
+  fn target_num_slices_map(&self) -> TargetNumSlicesMap;
+}
+
+impl TargetSlicingProgressMapTrait for TargetSlicingProgressMap {
+  fn target_num_slices_map(&self) -> TargetNumSlicesMap {
+    self
+      .iter()
+      .map(|(target, progress)| (target.clone(), progress.num_slices))
+      .collect()
+  }
+}
+
+/// The slices of a batch of call edges, each target along with the id of its first slice
+pub type OffsetTargetSlicesMap<'ctx> = OffsetBatchableMap<String, Slice<'ctx>>;
+
+pub trait OffsetTargetSlicesMapTrait<'ctx>: Sized {
+  /// Slice a batch of call edges. The slices of each target are numbered after
+  /// the ones generated by the previous batches, so that slicing the edges batch
+  /// by batch gives the same slice ids as slicing them all at once. Also returns
+  /// the number of slices dropped for having more functions than allowed
+  fn from_target_edges_batch(
+    target_edges_batch: &OffsetBatchableMap<String, EdgeIndex>,
+    call_graph: &CallGraph<'ctx>,
+    options: &impl SlicerOptions,
+    progress: &mut TargetSlicingProgressMap,
+  ) -> Result<(Self, usize), AnalyzerError>;
+
+  fn dump<O>(&self, options: &O)
+  where
+    O: SlicerOptions + IOOptions;
+}
+
+impl<'ctx> OffsetTargetSlicesMapTrait<'ctx> for OffsetTargetSlicesMap<'ctx> {
+  fn from_target_edges_batch(
+    target_edges_batch: &OffsetBatchableMap<String, EdgeIndex>,
+    call_graph: &CallGraph<'ctx>,
+    options: &impl SlicerOptions,
+    progress: &mut TargetSlicingProgressMap,
+  ) -> Result<(Self, usize), AnalyzerError> {
+    let filters = SliceFilters::new(options)?;
+    let mut result = HashMap::new();
+    let mut num_dropped = 0;
+    for (target, (_, edges)) in target_edges_batch {
+      let target_progress = progress.entry(target.clone()).or_default();
+      let (slices, dropped) =
+        call_graph.slices_of_call_edges_with_visited(&edges[..], options, &filters, &mut target_progress.visited);
+      let slice_id_offset = target_progress.num_slices;
+      target_progress.num_slices += slices.len();
+      result.insert(target.clone(), (slice_id_offset, slices));
+      num_dropped += dropped;
+    }
+    Ok((result, num_dropped))
+  }
+
+  fn dump<O>(&self, options: &O)
+  where
+    O: SlicerOptions + IOOptions,
+  {
+    for (target, (offset, slices)) in self {
+      dump_target_slices(target, *offset, slices, options);
     }
   }
 }
@@ -209,13 +504,34 @@ impl TargetNumSlicesMapTrait for TargetNumSlicesMap {
 pub trait Slicer<'ctx> {
   fn reduce_slice(&self, target_id: NodeIndex, functions: HashSet<NodeIndex>, depth: usize) -> HashSet<NodeIndex>;
 
-  fn find_entries(&self, edge_id: EdgeIndex, options: &impl SlicerOptions) -> Vec<NodeIndex>;
+  fn find_entries(&self, edge_id: EdgeIndex, options: &impl SlicerOptions, filters: &SliceFilters) -> Vec<NodeIndex>;
 
   fn slice_of_entry(&self, entry_id: NodeIndex, edge_id: EdgeIndex, options: &impl SlicerOptions) -> Slice<'ctx>;
 
-  fn slices_of_call_edge(&self, edge_id: EdgeIndex, options: &impl SlicerOptions) -> Vec<Slice<'ctx>>;
+  fn slices_of_call_edge(
+    &self,
+    edge_id: EdgeIndex,
+    options: &impl SlicerOptions,
+    filters: &SliceFilters,
+  ) -> Vec<Slice<'ctx>>;
+
+  /// The slices of all the call edges, and the number of slices dropped for
+  /// having more functions than `max_slice_functions`
+  fn slices_of_call_edges(
+    &self,
+    edges: &[EdgeIndex],
+    options: &impl SlicerOptions,
+  ) -> Result<(Vec<Slice<'ctx>>, usize), AnalyzerError>;
 
-  fn slices_of_call_edges(&self, edges: &[EdgeIndex], options: &impl SlicerOptions) -> Vec<Slice<'ctx>>;
+  /// Same as `slices_of_call_edges` with the filters already compiled, also
+  /// deduplicating against the slices in `visited` and adding the kept ones to it
+  fn slices_of_call_edges_with_visited(
+    &self,
+    edges: &[EdgeIndex],
+    options: &impl SlicerOptions,
+    filters: &SliceFilters,
+    visited: &mut SliceDedupKeys,
+  ) -> (Vec<Slice<'ctx>>, usize);
 }
 
 impl<'ctx> Slicer<'ctx> for CallGraph<'ctx> {
@@ -244,15 +560,7 @@ impl<'ctx> Slicer<'ctx> for CallGraph<'ctx> {
       .collect()
   }
 
-  fn find_entries(&self, edge_id: EdgeIndex, options: &impl SlicerOptions) -> Vec<NodeIndex> {
-    let entry_location_filter = match options.entry_filter() {
-      Some(filter) => Some(
-        Regex::new(filter.as_str())
-          .map_err(|_| String::from("Cannot parse entry filter regex"))
-          .unwrap(),
-      ),
-      None => None,
-    };
+  fn find_entries(&self, edge_id: EdgeIndex, options: &impl SlicerOptions, filters: &SliceFilters) -> Vec<NodeIndex> {
     let mut result = HashSet::new();
     match self.graph.edge_endpoints(edge_id) {
       Some((func_id, _)) => {
@@ -278,7 +586,7 @@ impl<'ctx> Slicer<'ctx> for CallGraph<'ctx> {
     }
     result
       .into_iter()
-      .filter(|func_id| match &entry_location_filter {
+      .filter(|func_id| match &filters.entry {
         Some(regex) => {
           let func = self.graph.node_weight(*func_id).unwrap();
           match func.filename() {
@@ -342,12 +650,77 @@ impl<'ctx> Slicer<'ctx> for CallGraph<'ctx> {
     }
   }
 
-  fn slices_of_call_edge(&self, edge_id: EdgeIndex, options: &impl SlicerOptions) -> Vec<Slice<'ctx>> {
-    let entry_ids = self.find_entries(edge_id, options);
-    entry_ids
+  fn slices_of_call_edge(
+    &self,
+    edge_id: EdgeIndex,
+    options: &impl SlicerOptions,
+    filters: &SliceFilters,
+  ) -> Vec<Slice<'ctx>> {
+    // Only keep the call sites passing the expected number of arguments
+    if let Some(arg_count) = options.target_arg_count() {
+      if self.graph[edge_id].num_arguments() != arg_count {
+        return vec![];
+      }
+    }
+
+    // Drop the call sites inside the excluded callers, e.g. the target's own wrappers
+    if let Some(regex) = &filters.exclude_call_site {
+      let (caller_id, _) = self.graph.edge_endpoints(edge_id).unwrap();
+      if regex.is_match(self.graph[caller_id].simp_name().as_str()) {
+        return vec![];
+      }
+    }
+
+    // Pin the entry to the forced one, as long as it transitively calls the caller
+    // without going through the target. The functions on the way to the caller are
+    // kept whatever the slice depth
+    let entries = match options.force_entry() {
+      Some(name) => {
+        let (caller_id, callee_id) = self.graph.edge_endpoints(edge_id).unwrap();
+        let entry_id = self
+          .graph
+          .node_indices()
+          .find(|id| self.graph[*id].simp_name() == *name);
+        let without_callee = NodeFiltered::from_fn(&self.graph, |id| id != callee_id);
+        let path = entry_id.and_then(|entry_id| {
+          petgraph::algo::astar(&without_callee, entry_id, |id| id == caller_id, |_| 1, |_| 0).map(|(_, path)| path)
+        });
+        match (entry_id, path) {
+          (Some(entry_id), Some(path)) => vec![(entry_id, path)],
+          _ => {
+            warn!(
+              target: SLICER_LOG,
+              "Forced entry {} does not reach the call to {} in {}, dropping the slice",
+              name,
+              self.graph[callee_id].simp_name(),
+              self.graph[caller_id].simp_name()
+            );
+            return vec![];
+          }
+        }
+      }
+      None => self
+        .find_entries(edge_id, options, filters)
+        .into_iter()
+        .map(|entry_id| (entry_id, vec![]))
+        .collect(),
+    };
+
+    // Only keep the entries defined in the matched source files
+    entries
       .into_iter()
-      .filter_map(|entry_id| {
-        let slice = self.slice_of_entry(entry_id, edge_id, options);
+      .filter(|(entry_id, _)| match &filters.entry_file {
+        Some(regex) => match self.graph[*entry_id].filename() {
+          Some(name) => regex.is_match(name.as_str()),
+          None => false,
+        },
+        None => true,
+      })
+      .filter_map(|(entry_id, path)| {
+        let mut slice = self.slice_of_entry(entry_id, edge_id, options);
+        slice
+          .functions
+          .extend(path.into_iter().map(|func_id| self.graph[func_id]));
         if needs_include_slice(&slice, options) {
           Some(slice)
         } else {
@@ -357,13 +730,49 @@ impl<'ctx> Slicer<'ctx> for CallGraph<'ctx> {
       .collect()
   }
 
-  fn slices_of_call_edges(&self, edges: &[EdgeIndex], options: &impl SlicerOptions) -> Vec<Slice<'ctx>> {
-    let f = |edge_id: &EdgeIndex| -> Vec<Slice<'ctx>> { self.slices_of_call_edge(edge_id.clone(), options) };
-    if options.use_serial() {
+  fn slices_of_call_edges(
+    &self,
+    edges: &[EdgeIndex],
+    options: &impl SlicerOptions,
+  ) -> Result<(Vec<Slice<'ctx>>, usize), AnalyzerError> {
+    let filters = SliceFilters::new(options)?;
+    Ok(self.slices_of_call_edges_with_visited(edges, options, &filters, &mut HashSet::new()))
+  }
+
+  fn slices_of_call_edges_with_visited(
+    &self,
+    edges: &[EdgeIndex],
+    options: &impl SlicerOptions,
+    filters: &SliceFilters,
+    visited: &mut SliceDedupKeys,
+  ) -> (Vec<Slice<'ctx>>, usize) {
+    let f = |edge_id: &EdgeIndex| -> Vec<Slice<'ctx>> { self.slices_of_call_edge(edge_id.clone(), options, filters) };
+    let slices: Vec<Slice<'ctx>> = if options.use_serial() {
       edges.iter().map(f).flatten().collect()
     } else {
       edges.par_iter().map(f).flatten().collect()
+    };
+
+    // Slices with too many functions are hopeless for symbolic execution
+    let num_slices = slices.len();
+    let slices: Vec<Slice<'ctx>> = match options.max_slice_functions() {
+      Some(max) => slices.into_iter().filter(|slice| slice.size() <= max).collect(),
+      None => slices,
+    };
+    let num_dropped = num_slices - slices.len();
+    if num_dropped > 0 {
+      debug!(target: SLICER_LOG, "Dropped {} of {} slices exceeding the function limit", num_dropped, num_slices);
     }
+
+    let slices = if options.dedup_slices() {
+      slices
+        .into_iter()
+        .filter(|slice| visited.insert(slice.dedup_key()))
+        .collect()
+    } else {
+      slices
+    };
+    (slices, num_dropped)
   }
 }
 
@@ -19,7 +19,7 @@ use crate::utils;
 pub struct CompositeFunctionBlockTraces<'ctx> {
   function: Function<'ctx>,
   block_traces: Vec<Vec<Block<'ctx>>>,
-  call_instr: CallInstruction<'ctx>,
+  call_instr: utils::CallSite<'ctx>,
 }
 
 /// A block trace is a list of FunctionBlockTrace. When finally
@@ -30,7 +30,7 @@ pub type CompositeBlockTrace<'ctx> = Vec<CompositeFunctionBlockTraces<'ctx>>;
 pub struct FunctionBlockTrace<'ctx> {
   pub function: Function<'ctx>,
   pub block_trace: Vec<Block<'ctx>>,
-  pub call_instr: CallInstruction<'ctx>,
+  pub call_instr: utils::CallSite<'ctx>,
 }
 
 /// Block trace is an array of function block trace
@@ -99,7 +99,7 @@ impl<'ctx> BlockTraceIterator<'ctx> {
     }
   }
 
-  pub fn visit_call(&mut self, instr: CallInstruction<'ctx>) -> bool {
+  pub fn visit_call(&mut self, instr: utils::CallSite<'ctx>) -> bool {
     if self.function_id < self.block_trace.len() {
       if self.block_trace[self.function_id].call_instr == instr {
         self.function_id += 1;
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::semantics::rced::*;
+
+/// Renames symbol, alloca and call ids in a trace to a trace-local normal form,
+/// numbering them in the order they are first seen. Two traces which only
+/// differ in the order their symbols are allocated are renamed identically.
+///
+/// Renamed values are cached by their `Rc` so a value shared by several trace
+/// nodes is still shared after renaming.
+pub struct SymbolRenaming {
+  symbol_ids: HashMap<usize, usize>,
+  alloca_ids: HashMap<usize, usize>,
+  call_ids: HashMap<usize, usize>,
+  cache: HashMap<*const Value, Rc<Value>>,
+}
+
+impl SymbolRenaming {
+  pub fn new() -> Self {
+    Self {
+      symbol_ids: HashMap::new(),
+      alloca_ids: HashMap::new(),
+      call_ids: HashMap::new(),
+      cache: HashMap::new(),
+    }
+  }
+
+  pub fn rename_semantics(&mut self, semantics: &Semantics) -> Semantics {
+    semantics.map_values(|v| self.rename_value(v))
+  }
+
+  pub fn rename_comparison(&mut self, comparison: &Comparison) -> Comparison {
+    match comparison {
+      Comparison::ICmp { pred, op0, op1, width } => Comparison::ICmp {
+        pred: *pred,
+        op0: self.rename_value(op0),
+        op1: self.rename_value(op1),
+        width: *width,
+      },
+      Comparison::FCmp { pred, op0, op1 } => Comparison::FCmp {
+        pred: *pred,
+        op0: self.rename_value(op0),
+        op1: self.rename_value(op1),
+      },
+    }
+  }
+
+  pub fn rename_value(&mut self, value: &Rc<Value>) -> Rc<Value> {
+    if let Some(renamed) = self.cache.get(&Rc::as_ptr(value)) {
+      return renamed.clone();
+    }
+    let renamed = Rc::new(match &**value {
+      Value::Sym(id) => Value::Sym(next_id(&mut self.symbol_ids, *id)),
+      Value::ConstSym(id) => Value::ConstSym(next_id(&mut self.symbol_ids, *id)),
+      Value::Alloc(id) => Value::Alloc(next_id(&mut self.alloca_ids, *id)),
+      Value::AllocOf(v) => Value::AllocOf(self.rename_value(v)),
+      Value::LocSym(loc) => Value::LocSym(self.rename_value(loc)),
+      Value::Undef(loc) => Value::Undef(self.rename_value(loc)),
+      Value::GEP { loc, indices } => Value::GEP {
+        loc: self.rename_value(loc),
+        indices: indices.iter().map(|i| self.rename_value(i)).collect(),
+      },
+      Value::Field {
+        loc,
+        struct_name,
+        index,
+      } => Value::Field {
+        loc: self.rename_value(loc),
+        struct_name: struct_name.clone(),
+        index: *index,
+      },
+      Value::Bin { op, op0, op1, width } => Value::Bin {
+        op: *op,
+        op0: self.rename_value(op0),
+        op1: self.rename_value(op1),
+        width: *width,
+      },
+      Value::ICmp { pred, op0, op1, width } => Value::ICmp {
+        pred: *pred,
+        op0: self.rename_value(op0),
+        op1: self.rename_value(op1),
+        width: *width,
+      },
+      Value::FCmp { pred, op0, op1 } => Value::FCmp {
+        pred: *pred,
+        op0: self.rename_value(op0),
+        op1: self.rename_value(op1),
+      },
+      Value::Call { id, func, args } => Value::Call {
+        id: next_id(&mut self.call_ids, *id),
+        func: self.rename_value(func),
+        args: args.iter().map(|a| self.rename_value(a)).collect(),
+      },
+      Value::Select {
+        cond,
+        then_val,
+        else_val,
+      } => Value::Select {
+        cond: self.rename_value(cond),
+        then_val: self.rename_value(then_val),
+        else_val: self.rename_value(else_val),
+      },
+      Value::Vector(lanes) => Value::Vector(lanes.iter().map(|l| self.rename_value(l)).collect()),
+      Value::Aggregate(fields) => Value::Aggregate(fields.iter().map(|f| self.rename_value(f)).collect()),
+      other => other.clone(),
+    });
+    self.cache.insert(Rc::as_ptr(value), renamed.clone());
+    renamed
+  }
+}
+
+fn next_id(ids: &mut HashMap<usize, usize>, id: usize) -> usize {
+  let next = ids.len();
+  *ids.entry(id).or_insert(next)
+}
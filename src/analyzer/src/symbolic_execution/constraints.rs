@@ -1,38 +1,105 @@
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 
-use crate::semantics::rced::*;
+use super::fold::*;
+use crate::semantics::{rced::*, *};
+use crate::utils::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct Constraint {
   pub cond: Comparison,
   pub branch: bool,
 }
 
+impl Constraint {
+  /// Whether the constraint holds, when both operands of its comparison are constants
+  pub fn concrete_value(&self) -> Option<bool> {
+    let holds = match &self.cond {
+      Comparison::ICmp { pred, op0, op1, width } => fold_icmp(*pred, int_constant(op0)?, int_constant(op1)?, *width),
+      Comparison::FCmp { pred, op0, op1 } => match (&**op0, &**op1) {
+        (Value::Float(Float(f0)), Value::Float(Float(f1))) => fold_fcmp(*pred, *f0, *f1),
+        _ => return None,
+      },
+    };
+    Some(holds == self.branch)
+  }
+}
+
+/// The integer a constant stands for in a comparison, with null pointers as 0
+fn int_constant(value: &Value) -> Option<i64> {
+  match value {
+    Value::Int(i) => Some(*i),
+    Value::Null => Some(0),
+    _ => None,
+  }
+}
+
 pub type Constraints = Vec<Constraint>;
 
+/// The verdict of z3 on a path; `Unknown` when it gives up, e.g. past its resource limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Satisfiability {
+  Sat,
+  Unsat,
+  Unknown,
+}
+
 pub trait ConstraintsTrait {
-  fn sat(&self) -> bool;
+  /// The constraints left to solve, without the ones holding concretely nor the
+  /// duplicates; `None` when a constraint concretely does not hold
+  fn simplify(&self) -> Option<Constraints>;
+
+  /// Solve the constraints, bounding the effort of z3 with `rlimit` when given
+  fn satisfiability(&self, rlimit: Option<u32>) -> Satisfiability;
+
+  /// Whether the constraints may hold, i.e. are not proven unsatisfiable
+  fn sat(&self) -> bool {
+    self.satisfiability(None) != Satisfiability::Unsat
+  }
 }
 
 impl ConstraintsTrait for Constraints {
-  fn sat(&self) -> bool {
+  fn simplify(&self) -> Option<Constraints> {
+    let mut seen = HashSet::new();
+    let mut simplified = vec![];
+    for constraint in self {
+      match constraint.concrete_value() {
+        Some(true) => {}
+        Some(false) => return None,
+        None => {
+          if seen.insert(constraint) {
+            simplified.push(constraint.clone());
+          }
+        }
+      }
+    }
+    Some(simplified)
+  }
+
+  fn satisfiability(&self, rlimit: Option<u32>) -> Satisfiability {
     use z3::*;
-    let z3_ctx = Context::new(&z3::Config::default());
+    let constraints = match self.simplify() {
+      Some(constraints) => constraints,
+      None => return Satisfiability::Unsat,
+    };
+    let mut z3_cfg = z3::Config::default();
+    if let Some(rlimit) = rlimit {
+      z3_cfg.set_param_value("rlimit", &rlimit.to_string());
+    }
+    let z3_ctx = Context::new(&z3_cfg);
     let solver = Solver::new(&z3_ctx);
     let mut symbol_map = HashMap::new();
     let mut symbol_id = 0;
-    for Constraint { cond, branch } in self.iter() {
-      match cond.into_z3_ast(&mut symbol_map, &mut symbol_id, &z3_ctx) {
-        Some(cond) => {
-          let formula = if *branch { cond } else { cond.not() };
-          solver.assert(&formula);
-        }
+    for Constraint { cond, branch } in constraints.iter() {
+      match cond.into_z3_ast(*branch, &mut symbol_map, &mut symbol_id, &z3_ctx) {
+        Some(formula) => solver.assert(&formula),
         _ => (),
       }
     }
     match solver.check() {
-      SatResult::Sat | SatResult::Unknown => true,
-      _ => false,
+      SatResult::Sat => Satisfiability::Sat,
+      SatResult::Unsat => Satisfiability::Unsat,
+      SatResult::Unknown => Satisfiability::Unknown,
     }
   }
 }
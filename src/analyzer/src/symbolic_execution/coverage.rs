@@ -0,0 +1,90 @@
+use llir::values::*;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+use crate::call_graph::*;
+use crate::slicer::*;
+use crate::utils::*;
+
+/// Indices of the visited blocks of each function, keyed on function name
+pub type BlockCoverage = HashMap<String, HashSet<usize>>;
+
+pub trait BlockCoverageTrait<'ctx>: Sized {
+  /// The coverage of the functions in a slice given the blocks visited while
+  /// executing it. Functions never entered are kept with no visited block
+  fn from_slice(slice: &Slice<'ctx>, visited_blocks: &HashSet<Block<'ctx>>) -> Self;
+
+  fn combine(self, other: Self) -> Self;
+
+  /// The coverage report, giving visited / total blocks of every function and
+  /// whether it is fully covered, partially covered or never entered
+  fn report(&self, call_graph: &CallGraph<'ctx>) -> serde_json::Value;
+}
+
+impl<'ctx> BlockCoverageTrait<'ctx> for BlockCoverage {
+  fn from_slice(slice: &Slice<'ctx>, visited_blocks: &HashSet<Block<'ctx>>) -> Self {
+    slice
+      .functions
+      .iter()
+      .map(|func| {
+        let visited = func
+          .iter_blocks()
+          .enumerate()
+          .filter(|(_, block)| visited_blocks.contains(block))
+          .map(|(i, _)| i)
+          .collect();
+        (func.simp_name(), visited)
+      })
+      .collect()
+  }
+
+  fn combine(mut self, other: Self) -> Self {
+    for (func_name, visited) in other {
+      self.entry(func_name).or_insert_with(HashSet::new).extend(visited);
+    }
+    self
+  }
+
+  fn report(&self, call_graph: &CallGraph<'ctx>) -> serde_json::Value {
+    let functions = call_graph
+      .graph
+      .node_weights()
+      .map(|func| (func.simp_name(), *func))
+      .collect::<HashMap<_, _>>();
+    let mut func_names = self.keys().collect::<Vec<_>>();
+    func_names.sort();
+
+    let (mut num_full, mut num_partial, mut num_never) = (0, 0, 0);
+    let mut report = serde_json::Map::new();
+    for func_name in func_names {
+      let visited = &self[func_name];
+      let total = functions.get(func_name).map_or(0, |func| func.iter_blocks().count());
+      let status = if visited.is_empty() {
+        num_never += 1;
+        "never_entered"
+      } else if visited.len() == total {
+        num_full += 1;
+        "full"
+      } else {
+        num_partial += 1;
+        "partial"
+      };
+      let uncovered = (0..total).filter(|i| !visited.contains(i)).collect::<Vec<_>>();
+      report.insert(
+        func_name.clone(),
+        json!({
+          "visited": visited.len(),
+          "total": total,
+          "status": status,
+          "uncovered_blocks": uncovered,
+        }),
+      );
+    }
+    json!({
+      "functions": report,
+      "fully_covered": num_full,
+      "partially_covered": num_partial,
+      "never_entered": num_never,
+    })
+  }
+}
@@ -1,5 +1,6 @@
 use llir::values::*;
 use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashSet;
 
 use crate::slicer::*;
 use crate::symbolic_execution::*;
@@ -12,6 +13,13 @@ pub struct Environment<'ctx> {
   pub call_id: usize,
   pub max_work: usize,
   pub rng: StdRng,
+  /// Writes the files of the slice, and its jsonl file under `--trace-format jsonl`
+  pub trace_writer: Option<JsonWriter>,
+  /// When set, proper traces are kept here instead of being dumped
+  pub collected_traces: Option<Vec<TraceWithTarget<'ctx>>>,
+  pub visited_blocks: HashSet<Block<'ctx>>,
+  /// Number of branches whose arms were merged into a single state
+  pub merged_state_count: usize,
 }
 
 impl<'ctx> Environment<'ctx> {
@@ -23,6 +31,10 @@ impl<'ctx> Environment<'ctx> {
       call_id: 0,
       max_work: max_work,
       rng: StdRng::seed_from_u64(seed),
+      trace_writer: None,
+      collected_traces: None,
+      visited_blocks: HashSet::new(),
+      merged_state_count: 0,
     }
   }
 
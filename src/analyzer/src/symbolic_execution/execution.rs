@@ -1,11 +1,21 @@
 use indicatif::*;
-use llir::{values::*, Module};
+use llir::{types::Type, values::*, Module};
+use log::{debug, trace, warn};
+use rand::Rng;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use regex::Regex;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use crate::call_graph::*;
+use crate::error::*;
+use crate::options::*;
 use crate::semantics::{rced::*, *};
 use crate::slicer::*;
 use crate::utils::*;
@@ -16,27 +26,123 @@ pub struct SymbolicExecutionContext<'a, 'ctx, O>
 where
   O: SymbolicExecutionOptions,
 {
-  pub module: &'a Module<'ctx>,
+  pub modules: &'a [Module<'ctx>],
   pub call_graph: &'a CallGraph<'ctx>,
   pub options: &'a O,
+  pub opaque_functions: Vec<Regex>,
+  pub excluded_callees: Vec<Regex>,
+  pub noreturn_functions: Vec<Regex>,
+  pub summaries: Summaries,
+  pub keep_kinds: Option<HashSet<String>>,
+  pub seed: SeedValues,
+
+  /// Maximum number of traces per slice of the targets given a budget of their own
+  pub trace_budgets: HashMap<String, usize>,
+
+  /// Instant after which no more work is done, shared by all the slices
+  pub deadline: Option<Instant>,
+  pub timed_out: AtomicBool,
+
+  /// Source files read to name the struct fields accessed by GEPs
+  pub sources: SourceFiles,
 }
 
 impl<'a, 'ctx, O> SymbolicExecutionContext<'a, 'ctx, O>
 where
   O: SymbolicExecutionOptions,
 {
-  pub fn new(module: &'a Module<'ctx>, call_graph: &'a CallGraph<'ctx>, options: &'a O) -> Self {
-    Self {
-      module,
+  pub fn new(
+    modules: &'a [Module<'ctx>],
+    call_graph: &'a CallGraph<'ctx>,
+    options: &'a O,
+  ) -> Result<Self, AnalyzerError> {
+    let opaque_functions = match options.opaque_functions() {
+      Some(path) => load_function_regexes(path)?,
+      None => vec![],
+    };
+    let excluded_callees = match options.exclude_intrinsics_list() {
+      Some(path) => load_function_regexes(path)?,
+      None => vec![Regex::new(r"llvm\.").unwrap()],
+    };
+    let mut noreturn_functions = NORETURN_FUNCTIONS
+      .iter()
+      .map(|name| Regex::new(&format!("^{}$", name)).unwrap())
+      .collect::<Vec<_>>();
+    if let Some(path) = options.noreturn_functions() {
+      noreturn_functions.extend(load_function_regexes(path)?);
+    }
+    let keep_kinds = match options.trace_keep_kinds() {
+      Some(list) => Some(parse_semantics_kinds(list).map_err(AnalyzerError::OptionParse)?),
+      None => None,
+    };
+    let seed = match options.seed_file() {
+      Some(path) => SeedValues::load(path).map_err(AnalyzerError::Serde)?,
+      None => SeedValues::new(),
+    };
+    let trace_budgets = match options.budget_file() {
+      Some(path) => load_json_t(&PathBuf::from(path))
+        .map_err(|err| AnalyzerError::Serde(format!("Cannot load budget file {}: {}", path, err)))?,
+      None => HashMap::new(),
+    };
+    let deadline = options
+      .global_timeout_sec()
+      .map(|sec| Instant::now() + Duration::from_secs(sec));
+    Ok(Self {
+      modules,
       call_graph,
       options,
+      opaque_functions,
+      excluded_callees,
+      noreturn_functions,
+      summaries: Summaries::libc(),
+      keep_kinds,
+      seed,
+      trace_budgets,
+      deadline,
+      timed_out: AtomicBool::new(false),
+      sources: SourceFiles::new(),
+    })
+  }
+
+  /// Whether the global timeout is exceeded. Once a thread sees it, all the
+  /// others stop without checking the clock again
+  pub fn timed_out(&self) -> bool {
+    if self.timed_out.load(Ordering::Relaxed) {
+      true
+    } else if self.deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+      self.timed_out.store(true, Ordering::Relaxed);
+      true
+    } else {
+      false
     }
   }
 
+  /// Opaque functions are never stepped into, as if they were external
+  pub fn is_opaque_function(&self, func: Function<'ctx>) -> bool {
+    let name = func.simp_name();
+    self.opaque_functions.iter().any(|regex| regex.is_match(name.as_str()))
+  }
+
+  /// Calls to excluded callees, e.g. intrinsics & instrumentation hooks, are skipped
+  /// without leaving any node in the trace
+  pub fn is_excluded_callee(&self, func: Function<'ctx>) -> bool {
+    let name = func.simp_name();
+    self.excluded_callees.iter().any(|regex| regex.is_match(name.as_str()))
+  }
+
+  /// Functions that never return, e.g. `exit` & `abort`, end the trace once called
+  pub fn is_noreturn_function(&self, func: Function<'ctx>) -> bool {
+    let name = func.simp_name();
+    self
+      .noreturn_functions
+      .iter()
+      .any(|regex| regex.is_match(name.as_str()))
+  }
+
   pub fn execute_function(
     &self,
     instr_node_id: usize,
-    instr: CallInstruction<'ctx>,
+    instr: CallSite<'ctx>,
     func: Function<'ctx>,
     args: Vec<Rc<Value>>,
     state: &mut State<'ctx>,
@@ -53,7 +159,10 @@ where
         state.stack.push(stack_frame);
         self.execute_block(block, state, env)
       }
-      None => panic!("The executed function is empty"),
+      None => {
+        state.finish_state = FinishState::Error(format!("Function {} is empty", func.simp_name()));
+        None
+      }
     }
   }
 
@@ -61,8 +170,9 @@ where
     &self,
     block: Block<'ctx>,
     state: &mut State<'ctx>,
-    _: &mut Environment<'ctx>,
+    env: &mut Environment<'ctx>,
   ) -> Option<Instruction<'ctx>> {
+    env.visited_blocks.insert(block);
     match state.prev_block {
       Some(prev_block) => {
         state.block_trace_iter.visit_block(prev_block, block);
@@ -90,15 +200,26 @@ where
             Branch(br) => self.transfer_br_instr(br, state, env),
             Switch(swi) => self.transfer_switch_instr(swi, state, env),
             Call(call) => self.transfer_call_instr(call, state, env),
+            Invoke(invoke) => self.transfer_invoke_instr(invoke, state, env),
             Alloca(alloca) => self.transfer_alloca_instr(alloca, state, env),
             Store(st) => self.transfer_store_instr(st, state, env),
             ICmp(icmp) => self.transfer_icmp_instr(icmp, state, env),
+            FCmp(fcmp) => self.transfer_fcmp_instr(fcmp, state, env),
             Load(ld) => self.transfer_load_instr(ld, state, env),
             Phi(phi) => self.transfer_phi_instr(phi, state, env),
             GetElementPtr(gep) => self.transfer_gep_instr(gep, state, env),
             Unreachable(unr) => self.transfer_unreachable_instr(unr, state, env),
+            LandingPad(lp) => self.transfer_landing_pad_instr(lp, state, env),
+            Resume(res) => self.transfer_resume_instr(res, state, env),
             Binary(bin) => self.transfer_binary_instr(bin, state, env),
             Unary(una) => self.transfer_unary_instr(una, state, env),
+            Freeze(frz) => self.transfer_freeze_instr(frz, state, env),
+            AtomicRMW(rmw) => self.transfer_atomic_rmw_instr(rmw, state, env),
+            AtomicCmpXchg(cmpxchg) => self.transfer_cmpxchg_instr(cmpxchg, state, env),
+            ExtractValue(ev) => self.transfer_extract_value_instr(ev, state, env),
+            ExtractElement(ee) => self.transfer_extract_element_instr(ee, state, env),
+            InsertElement(ie) => self.transfer_insert_element_instr(ie, state, env),
+            ShuffleVector(sv) => self.transfer_shuffle_vector_instr(sv, state, env),
             _ => self.transfer_instr(instr, state, env),
           }
         }
@@ -110,10 +231,9 @@ where
   pub fn eval_constant_value(&self, state: &mut State<'ctx>, constant: Constant<'ctx>) -> Rc<Value> {
     match constant {
       Constant::Int(i) => Rc::new(Value::Int(i.sext_value())),
+      Constant::Float(f) => Rc::new(Value::Float(Float(f.double_value()))),
       Constant::Null(_) => Rc::new(Value::Null),
-      Constant::Float(_) | Constant::Struct(_) | Constant::Array(_) | Constant::Vector(_) => {
-        Rc::new(Value::ConstSym(state.new_symbol_id()))
-      }
+      Constant::Struct(_) | Constant::Array(_) | Constant::Vector(_) => Rc::new(Value::ConstSym(state.new_symbol_id())),
       Constant::Global(glob) => Rc::new(Value::Glob(glob.name())),
       Constant::Function(func) => Rc::new(Value::Func(func.simp_name())),
       Constant::ConstExpr(ce) => match ce {
@@ -121,7 +241,8 @@ where
           let op = b.opcode();
           let op0 = self.eval_constant_value(state, b.op0());
           let op1 = self.eval_constant_value(state, b.op1());
-          Rc::new(Value::Bin { op, op0, op1 })
+          let width = int_width(b.get_type()).unwrap_or(64);
+          Rc::new(Value::Bin { op, op0, op1, width })
         }
         ConstExpr::Unary(u) => self.eval_constant_value(state, u.op0()),
         ConstExpr::GetElementPtr(g) => {
@@ -130,8 +251,8 @@ where
             .indices()
             .into_iter()
             .map(|i| self.eval_constant_value(state, i))
-            .collect();
-          Rc::new(Value::GEP { loc, indices })
+            .collect::<Vec<_>>();
+          Rc::new(gep_location(loc, g.location().get_type(), indices))
         }
         _ => Rc::new(Value::Unknown),
       },
@@ -170,6 +291,20 @@ where
     }
   }
 
+  /// Whether the memory outgrew `--max-memory-cells`, in which case the trace ends
+  fn exceeds_memory_limit(&self, state: &mut State<'ctx>) -> bool {
+    match self.options.max_memory_cells() {
+      Some(max_cells) if state.memory.len() > max_cells => {
+        state.finish_state = FinishState::MemoryLimit;
+        true
+      }
+      _ => false,
+    }
+  }
+
+  /// The value held at the location. A pointer stored in a cell is returned as
+  /// the very location it points to, so loading through it again reaches the
+  /// pointed cell, e.g. for `p = *pp; v = *p`
   pub fn load_from_memory(&self, state: &mut State<'ctx>, location: Rc<Value>) -> Rc<Value> {
     match &*location {
       Value::Unknown => Rc::new(Value::Unknown),
@@ -177,8 +312,7 @@ where
       _ => match state.memory.get(&location) {
         Some(value) => value.clone(),
         None => {
-          let symbol_id = state.new_symbol_id();
-          let value = Rc::new(Value::Sym(symbol_id));
+          let value = state.initial_value(&location);
           state.memory.insert(location, value.clone());
           value
         }
@@ -190,28 +324,50 @@ where
     &self,
     instr: ReturnInstruction<'ctx>,
     state: &mut State<'ctx>,
-    _: &mut Environment<'ctx>,
+    env: &mut Environment<'ctx>,
   ) -> Option<Instruction<'ctx>> {
     // First evaluate the return operand. There might not be one
     let val = instr.op().map(|val| self.eval_operand_value(state, val));
+
+    // Returns of the entry function record where their value comes from
+    let source = if state.stack.len() == 1 {
+      val.as_ref().map(|val| val.return_source())
+    } else {
+      None
+    };
     state.trace.push(TraceNode {
       instr: instr.as_instruction(),
-      semantics: Semantics::Ret { op: val.clone() },
+      semantics: Semantics::Ret {
+        op: val.clone(),
+        source,
+      },
       result: None,
     });
 
     // Then we peek the stack frame
-    let stack_frame = state.stack.pop().unwrap(); // There has to be a stack on the top
+    let stack_frame = match state.stack.pop() {
+      Some(stack_frame) => stack_frame,
+      None => {
+        state.finish_state = FinishState::Error("Returning with an empty stack".to_string());
+        return None;
+      }
+    };
     match stack_frame.instr {
       Some((node_id, call_site)) => {
-        let call_site_frame = state.stack.top_mut(); // If call site exists then there must be a stack top
+        let call_site_frame = match state.stack.last_mut() {
+          Some(frame) => frame,
+          None => {
+            state.finish_state = FinishState::Error("Returning to a call site without stack frame".to_string());
+            return None;
+          }
+        };
         if let Some(op0) = val {
           if stack_frame.function.get_function_type().has_return_type() {
             state.trace[node_id].result = Some(op0.clone());
             call_site_frame.memory.insert(call_site.as_instruction(), op0);
           }
         }
-        call_site.next_instruction()
+        self.return_to_call_site(call_site, state, env)
       }
 
       // If no call site then we are in the entry function. We will end the execution
@@ -255,17 +411,25 @@ where
     let comparison = cond.as_comparison();
     let is_loop_blk = curr_blk.is_loop_entry_block();
 
-    match state.block_trace_iter.cond_branch(instr) {
+    // A concrete condition leaves only one feasible branch, which we take without forking
+    let guided = match &*cond {
+      Value::Int(i) if *i != 0 => Some((Branch::Then, instr.then_block())),
+      Value::Int(_) => Some((Branch::Else, instr.else_block())),
+      _ => state.block_trace_iter.cond_branch(instr),
+    };
+
+    match guided {
       Some((br, block)) => {
         let br_dir = BranchDirection {
           from: curr_blk,
           to: block,
+          case: None,
         };
         let visited = state.visited_branch.contains(&br_dir);
         if !visited {
           if let Some(comparison) = comparison {
             if !is_loop_blk {
-              state.add_constraint(comparison, br.is_then());
+              state.add_constraint(comparison, br.is_then(), self.options.max_constraints());
             }
           }
           state.visited_branch.insert(br_dir);
@@ -289,78 +453,49 @@ where
         let then_br = BranchDirection {
           from: curr_blk,
           to: instr.then_block(),
+          case: None,
         };
         let else_br = BranchDirection {
           from: curr_blk,
           to: instr.else_block(),
+          case: None,
         };
         let visited_then = state.visited_branch.contains(&then_br);
         let visited_else = state.visited_branch.contains(&else_br);
-        if !visited_then {
-          // Check if we need to add a work for else branch
-          if !visited_else && env.can_add_work() {
-            // First add else branch into work
-            let mut else_state = state.clone();
-
-            // Add constraint
-            if let Some(comparison) = comparison.clone() {
-              if !is_loop_blk {
-                else_state.add_constraint(comparison, false);
-              }
-            }
-
-            // Update state
-            else_state.visited_branch.insert(else_br);
-            else_state.trace.push(TraceNode {
-              instr: instr.as_instruction(),
-              result: None,
-              semantics: Semantics::CondBr {
-                cond: cond.clone(),
-                br: Branch::Else,
-                beg_loop: false,
-              },
-            });
-
-            // Generate work
-            let else_work = Work::new(instr.else_block(), else_state);
-            env.add_work(else_work);
-          }
 
-          // Then execute the then branch
-          if let Some(comparison) = comparison {
-            if !is_loop_blk {
-              state.add_constraint(comparison, true);
+        // Join the arms of a small diamond into a single state instead of forking
+        if self.options.merge_states() && !is_loop_blk && !visited_then && !visited_else {
+          if let Some(join) = mergeable_join(instr) {
+            if self.merge_branch(instr, cond.clone(), join, state, env) {
+              state.visited_branch.insert(then_br);
+              state.visited_branch.insert(else_br);
+              return self.execute_block(join, state, env);
             }
           }
-          state.visited_branch.insert(then_br);
-          state.trace.push(TraceNode {
-            instr: instr.as_instruction(),
-            result: None,
-            semantics: Semantics::CondBr {
-              cond,
-              br: Branch::Then,
-              beg_loop: is_loop_blk,
-            },
-          });
-          self.execute_block(instr.then_block(), state, env)
+        }
+
+        if !visited_then && !visited_else && env.can_add_work() {
+          // Fork, deferring the else branch as a work and executing the then branch. Under
+          // `--sample-branches` the deferred branch is picked at random instead
+          let (taken, deferred) = if self.options.sample_branches() && env.rng.gen::<bool>() {
+            (Branch::Else, Branch::Then)
+          } else {
+            (Branch::Then, Branch::Else)
+          };
+          let mut deferred_state = state.clone();
+          let deferred_block =
+            self.follow_cond_branch(instr, cond.clone(), comparison.clone(), deferred, &mut deferred_state);
+          env.add_work(Work::new(deferred_block, deferred_state));
+          let block = self.follow_cond_branch(instr, cond, comparison, taken, state);
+          self.execute_block(block, state, env)
+        } else if !visited_then {
+          // Execute the then branch
+          let block = self.follow_cond_branch(instr, cond, comparison, Branch::Then, state);
+          self.execute_block(block, state, env)
         } else if !visited_else {
           // Execute the else branch
-          if let Some(comparison) = comparison {
-            if !is_loop_blk {
-              state.add_constraint(comparison.clone(), false);
-            }
-          }
-          state.visited_branch.insert(else_br);
-          state.trace.push(TraceNode {
-            instr: instr.as_instruction(),
-            semantics: Semantics::CondBr {
-              cond,
-              br: Branch::Else,
-              beg_loop: false,
-            },
-            result: None,
-          });
-          self.execute_block(instr.else_block(), state, env)
+          let block = self.follow_cond_branch(instr, cond, comparison, Branch::Else, state);
+          self.execute_block(block, state, env)
         } else {
           // If both then and else are visited, stop the execution with BranchExplored
           state.finish_state = FinishState::BranchExplored;
@@ -370,6 +505,106 @@ where
     }
   }
 
+  /// Take a direction of an unguided conditional branch, constraining the state by
+  /// the condition and pushing the branch node. Returns the block branched to
+  fn follow_cond_branch(
+    &self,
+    instr: ConditionalBranchInstruction<'ctx>,
+    cond: Rc<Value>,
+    comparison: Option<Comparison>,
+    br: Branch,
+    state: &mut State<'ctx>,
+  ) -> Block<'ctx> {
+    let curr_blk = instr.parent_block();
+    let is_loop_blk = curr_blk.is_loop_entry_block();
+    let is_then = br.is_then();
+    let block = if is_then {
+      instr.then_block()
+    } else {
+      instr.else_block()
+    };
+    if let Some(comparison) = comparison {
+      if !is_loop_blk {
+        state.add_constraint(comparison, is_then, self.options.max_constraints());
+      }
+    }
+    state.visited_branch.insert(BranchDirection {
+      from: curr_blk,
+      to: block,
+      case: None,
+    });
+    state.trace.push(TraceNode {
+      instr: instr.as_instruction(),
+      result: None,
+      semantics: Semantics::CondBr {
+        cond,
+        br,
+        beg_loop: is_loop_blk && is_then,
+      },
+    });
+    block
+  }
+
+  /// Execute both arms of the conditional branch from the state and merge the two
+  /// resulting states back into it, telling whether both arms reached the join.
+  /// The trace keeps the nodes before the branch followed by a single `Merge` node
+  /// of the branch, holding the cells stored to by the arms guarded by `cond`. Path
+  /// constraints are left as they are, as the merged state covers both arms. When
+  /// an arm stops before the join, e.g. past the memory limit, the state is left
+  /// untouched for the branch to be forked instead
+  fn merge_branch(
+    &self,
+    instr: ConditionalBranchInstruction<'ctx>,
+    cond: Rc<Value>,
+    join: Block<'ctx>,
+    state: &mut State<'ctx>,
+    env: &mut Environment<'ctx>,
+  ) -> bool {
+    let mut then_state = state.clone();
+    if !self.execute_arm(instr.then_block(), join, &mut then_state, env) {
+      return false;
+    }
+    let mut else_state = state.clone();
+    else_state.sync_symbol_id(&then_state);
+    if !self.execute_arm(instr.else_block(), join, &mut else_state, env) {
+      return false;
+    }
+
+    let cells = state.merge_arms(&cond, &then_state, &else_state);
+    trace!(target: EXECUTOR_LOG, "Merged the arms of branch on {}", cond.to_display_string());
+    state.trace.push(TraceNode {
+      instr: instr.as_instruction(),
+      semantics: Semantics::Merge { cond, cells },
+      result: None,
+    });
+    env.merged_state_count += 1;
+    true
+  }
+
+  /// Execute an arm of a mergeable branch up to its jump to the join block,
+  /// telling whether it got there. An arm stopping early is left with its
+  /// `finish_state` set
+  fn execute_arm(
+    &self,
+    arm: Block<'ctx>,
+    join: Block<'ctx>,
+    state: &mut State<'ctx>,
+    env: &mut Environment<'ctx>,
+  ) -> bool {
+    if arm == join {
+      return true;
+    }
+    env.visited_blocks.insert(arm);
+    let mut instr = arm.first_instruction();
+    while let Some(curr) = instr {
+      if let Instruction::Branch(_) = curr {
+        return true;
+      }
+      instr = self.execute_instr(Some(curr), state, env);
+    }
+    false
+  }
+
   pub fn transfer_br_instr(
     &self,
     instr: BranchInstruction<'ctx>,
@@ -394,6 +629,7 @@ where
     let default_br = BranchDirection {
       from: curr_blk,
       to: instr.default_destination(),
+      case: None,
     };
     let branches = instr
       .cases()
@@ -401,6 +637,7 @@ where
       .map(|case| BranchDirection {
         from: curr_blk,
         to: case.destination,
+        case: Some(case.case.sext_value()),
       })
       .collect::<Vec<_>>();
     let node = TraceNode {
@@ -436,84 +673,182 @@ where
     state: &mut State<'ctx>,
     env: &mut Environment<'ctx>,
   ) -> Option<Instruction<'ctx>> {
-    // If is intrinsic call, skip the instruction
-    if instr.is_dummy_intrinsic_call() {
+    // If is intrinsic call or excluded callee other than the target, skip the instruction
+    let is_excluded = match instr.callee_function() {
+      Some(func) => func != env.slice.callee && self.is_excluded_callee(func),
+      None => false,
+    };
+    if instr.is_dummy_intrinsic_call() || is_excluded {
       instr.next_instruction()
     } else {
-      // Visit call for block trace guidance
-      state.block_trace_iter.visit_call(instr);
-
-      // Check if stepping in the function, and get the function Value and also
-      // maybe function reference
-      let (step_in, func_value, func) = match instr.callee_function() {
-        Some(func) => {
-          let step_in = !state.stack.has_function(func)
-            && func != env.slice.callee
-            && !func.is_declaration_only()
-            && env.slice.functions.contains(&func);
-          (step_in, Rc::new(Value::Func(func.simp_name())), Some(func))
-        }
-        None => {
-          if instr.is_inline_asm_call() {
-            (false, Rc::new(Value::Asm), None)
-          } else {
-            (false, Rc::new(Value::FuncPtr), None)
+      self.transfer_call_site(CallSite::Call(instr), state, env)
+    }
+  }
+
+  /// An invoke is a call that may unwind. The unwinding path to the landing pad
+  /// is forked as a separate work, and the returning path continues to the
+  /// normal destination
+  pub fn transfer_invoke_instr(
+    &self,
+    instr: InvokeInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    env: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    self.transfer_call_site(CallSite::Invoke(instr), state, env)
+  }
+
+  /// Continue the execution after the call at the call site returned
+  pub fn return_to_call_site(
+    &self,
+    call_site: CallSite<'ctx>,
+    state: &mut State<'ctx>,
+    env: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    match call_site {
+      CallSite::Call(call) => call.next_instruction(),
+      CallSite::Invoke(invoke) => {
+        state.prev_block = Some(invoke.parent_block());
+        self.execute_block(invoke.normal_destination(), state, env)
+      }
+    }
+  }
+
+  pub fn transfer_call_site(
+    &self,
+    instr: CallSite<'ctx>,
+    state: &mut State<'ctx>,
+    env: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    // Visit call for block trace guidance
+    state.block_trace_iter.visit_call(instr);
+
+    // Check if stepping in the function, and get the function Value and also
+    // maybe function reference
+    let mut mangled = None;
+    let (step_in, func_value, func) = match instr.callee_function() {
+      Some(func) => {
+        // The callee may be declared here but defined in another module
+        let func = self.call_graph.resolve(func);
+        let step_in = !state.stack.has_function(func)
+          && func != env.slice.callee
+          && !func.is_declaration_only()
+          && env.slice.functions.contains(&func)
+          && !self.is_opaque_function(func);
+        let mut name = func.simp_name();
+        if self.options.demangle() {
+          if let Some(demangled) = demangle(&name) {
+            mangled = Some(std::mem::replace(&mut name, demangled));
           }
         }
-      };
+        (step_in, Rc::new(Value::Func(name)), Some(func))
+      }
+      None => {
+        if instr.is_inline_asm_call() {
+          (false, Rc::new(Value::Asm), None)
+        } else {
+          (false, Rc::new(Value::FuncPtr), None)
+        }
+      }
+    };
 
-      // Evaluate the arguments
-      let args = instr
-        .arguments()
-        .into_iter()
-        .map(|v| self.eval_operand_value(state, v))
-        .collect::<Vec<_>>();
+    // Evaluate the arguments
+    let args = instr
+      .arguments()
+      .into_iter()
+      .map(|v| self.eval_operand_value(state, v))
+      .collect::<Vec<_>>();
 
-      // Cache the node id for this call
-      let node_id = state.trace.len();
+    // Cache the node id for this call
+    let node_id = state.trace.len();
+
+    // Generate a semantics and push to the trace
+    let func_type = instr.callee_function_type();
+    let semantics = Semantics::Call {
+      func: func_value.clone(),
+      args: args.clone(),
+      num_params: func_type.num_argument_types(),
+      is_var_arg: func_type.is_var_arg(),
+      mangled,
+    };
+    let node = TraceNode {
+      instr: instr.as_instruction(),
+      semantics,
+      result: None,
+    };
+    state.trace.push(node);
 
-      // Generate a semantics and push to the trace
-      let semantics = Semantics::Call {
-        func: func_value.clone(),
-        args: args.clone(),
-      };
-      let node = TraceNode {
-        instr: instr.as_instruction(),
-        semantics,
-        result: None,
-      };
-      state.trace.push(node);
+    // Update the target_node in state if the target is now visited
+    if instr == env.slice.instr && state.target_node.is_none() {
+      state.target_node = Some(node_id);
 
-      // Update the target_node in state if the target is now visited
-      if instr == env.slice.instr && state.target_node.is_none() {
-        state.target_node = Some(node_id);
+      // Memory is copy-on-write, so the snapshot is only copied once written to
+      if self.options.dump_memory() {
+        state.pre_call_memory = Some(state.memory.clone());
       }
+    }
 
-      // Check if we need to get into the function
-      if step_in {
-        // If so, execute the function with all the information
-        self.execute_function(node_id, instr, func.unwrap(), args, state, env)
-      } else {
-        // We only add call result if the callee function has return type
-        if instr.callee_function_type().has_return_type() {
-          // We create a function call result with a call_id associated
-          let call_id = env.new_call_id();
-          let result = Rc::new(Value::Call {
-            id: call_id,
-            func: func_value.clone(),
-            args: args.clone(),
-          });
+    // Fork the unwinding path of an invoke, where the call has no result
+    if let CallSite::Invoke(invoke) = instr {
+      if env.can_add_work() {
+        let mut unwind_state = state.clone();
+        unwind_state.prev_block = Some(invoke.parent_block());
+        env.add_work(Work::new(invoke.unwind_destination(), unwind_state));
+      }
+    }
 
-          // Update the result stored in the trace
-          state.trace[node_id].result = Some(result.clone());
+    // Check if we need to get into the function
+    if step_in {
+      // If so, execute the function with all the information
+      self.execute_function(node_id, instr, func.unwrap(), args, state, env)
+    } else {
+      // The callee may initialize the stack slots it is given
+      for arg in &args {
+        state.escape_alloca(arg);
+      }
 
-          // Insert a result to the stack frame memory
-          state.stack.top_mut().memory.insert(instr.as_instruction(), result);
+      // We only add call result if the callee function has return type
+      let result = if func_type.has_return_type() {
+        // We create a function call result with a call_id associated
+        let call_id = env.new_call_id();
+        Some(Rc::new(Value::Call {
+          id: call_id,
+          func: func_value.clone(),
+          args: args.clone(),
+        }))
+      } else {
+        None
+      };
+
+      // Apply the summary of the callee if there is one. The target keeps its
+      // generic call result so that features can still refer to it
+      let result = match func.and_then(|func| self.summaries.get(func.simp_name().as_str())) {
+        Some(summary) => {
+          let summarized = summary(instr.as_instruction(), result.clone(), &args, state);
+          if instr == env.slice.instr {
+            result
+          } else {
+            summarized
+          }
         }
+        None => result,
+      };
+
+      if let Some(result) = result {
+        // Update the result stored in the trace
+        state.trace[node_id].result = Some(result.clone());
 
-        // Execute the next instruction directly
-        instr.next_instruction()
+        // Insert a result to the stack frame memory
+        state.stack.top_mut().memory.insert(instr.as_instruction(), result);
       }
+
+      // Nothing follows a call that never returns
+      if func.map_or(false, |func| self.is_noreturn_function(func)) {
+        state.finish_state = FinishState::ProperlyReturned;
+        return None;
+      }
+
+      // Execute the next instruction directly
+      self.return_to_call_site(instr, state, env)
     }
   }
 
@@ -535,32 +870,64 @@ where
   ) -> Option<Instruction<'ctx>> {
     let loc = self.eval_operand_value(state, instr.location());
     let val = self.eval_operand_value(state, instr.value());
+    let val_type = instr.value().get_type();
+    self.store_value(instr.as_instruction(), instr.location(), loc, val, val_type, state);
+    if self.exceeds_memory_limit(state) {
+      return None;
+    }
+    instr.next_instruction()
+  }
 
-    // First insert into memory
-    state.memory.insert(loc.clone(), val.clone());
-
-    // Then update the AllocOf
-    match (*loc).clone() {
-      Value::AllocOf(_) => match instr.location() {
-        Operand::Instruction(loc_instr) => {
-          state
-            .stack
-            .top_mut()
-            .memory
-            .insert(loc_instr, Rc::new(Value::AllocOf(val.clone())));
+  /// Store `val` of type `val_type` to `loc`, the value of the `location` operand,
+  /// and trace it as a store. Shared by stores and the store half of atomics
+  fn store_value(
+    &self,
+    instr: Instruction<'ctx>,
+    location: Operand<'ctx>,
+    loc: Rc<Value>,
+    val: Rc<Value>,
+    val_type: Type<'ctx>,
+    state: &mut State<'ctx>,
+  ) {
+    // A stack slot whose address is stored may be written through that copy
+    state.escape_alloca(&val);
+
+    match vector_num_lanes(val_type) {
+      // Vectors are stored lane by lane, each in its own cell
+      Some(num_lanes) => {
+        state.memory.remove(&loc);
+        for lane in 0..num_lanes {
+          let lane_loc = vector_lane_location(loc.clone(), lane);
+          state.memory.insert(lane_loc, vector_lane(&val, lane));
         }
-        _ => {}
-      },
-      _ => {}
-    };
+      }
+      None => {
+        // First insert into memory
+        state.memory.insert(loc.clone(), val.clone());
+
+        // Then update the AllocOf
+        match (*loc).clone() {
+          Value::AllocOf(_) => match location {
+            Operand::Instruction(loc_instr) => {
+              state
+                .stack
+                .top_mut()
+                .memory
+                .insert(loc_instr, Rc::new(Value::AllocOf(val.clone())));
+            }
+            _ => {}
+          },
+          _ => {}
+        };
+      }
+    }
 
     let node = TraceNode {
-      instr: instr.as_instruction(),
+      instr,
       semantics: Semantics::Store { loc, val },
       result: None,
     };
     state.trace.push(node);
-    instr.next_instruction()
   }
 
   pub fn transfer_load_instr(
@@ -570,7 +937,14 @@ where
     _: &mut Environment<'ctx>,
   ) -> Option<Instruction<'ctx>> {
     let loc = self.eval_operand_value(state, instr.location());
-    let res = self.load_from_memory(state, loc.clone());
+    let res = match vector_num_lanes(instr.get_type()) {
+      Some(num_lanes) => Rc::new(Value::Vector(
+        (0..num_lanes)
+          .map(|lane| self.load_from_memory(state, vector_lane_location(loc.clone(), lane)))
+          .collect(),
+      )),
+      None => self.load_from_memory(state, loc.clone()),
+    };
     let node = TraceNode {
       instr: instr.as_instruction(),
       semantics: Semantics::Load { loc },
@@ -578,6 +952,195 @@ where
     };
     state.trace.push(node);
     state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    if self.exceeds_memory_limit(state) {
+      return None;
+    }
+    instr.next_instruction()
+  }
+
+  /// The load half of an atomic instruction, traced as a load of the location
+  fn load_atomic(&self, instr: Instruction<'ctx>, loc: Rc<Value>, state: &mut State<'ctx>) -> Rc<Value> {
+    let old = self.load_from_memory(state, loc.clone());
+    state.trace.push(TraceNode {
+      instr,
+      semantics: Semantics::Load { loc },
+      result: Some(old.clone()),
+    });
+    old
+  }
+
+  /// An atomicrmw loads the location, stores back the operation of the loaded
+  /// value and the operand, and results in the loaded value
+  pub fn transfer_atomic_rmw_instr(
+    &self,
+    instr: AtomicRMWInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let loc = self.eval_operand_value(state, instr.location());
+    let val = self.eval_operand_value(state, instr.value());
+    let old = self.load_atomic(instr.as_instruction(), loc.clone(), state);
+    let width = int_width(instr.get_type());
+    let new = match instr.operation() {
+      AtomicRMWBinOp::Xchg => val,
+      AtomicRMWBinOp::Add => binary_value(BinOp::Add, old.clone(), val, width),
+      AtomicRMWBinOp::Sub => binary_value(BinOp::Sub, old.clone(), val, width),
+      AtomicRMWBinOp::And => binary_value(BinOp::And, old.clone(), val, width),
+      AtomicRMWBinOp::Or => binary_value(BinOp::Or, old.clone(), val, width),
+      AtomicRMWBinOp::Xor => binary_value(BinOp::Xor, old.clone(), val, width),
+      AtomicRMWBinOp::FAdd => binary_value(BinOp::FAdd, old.clone(), val, None),
+      AtomicRMWBinOp::FSub => binary_value(BinOp::FSub, old.clone(), val, None),
+      // Nand, min and max have no binary opcode to express them
+      _ => Rc::new(Value::Sym(state.new_symbol_id())),
+    };
+    let val_type = instr.value().get_type();
+    self.store_value(instr.as_instruction(), instr.location(), loc, new, val_type, state);
+    state.stack.top_mut().memory.insert(instr.as_instruction(), old);
+    if self.exceeds_memory_limit(state) {
+      return None;
+    }
+    instr.next_instruction()
+  }
+
+  /// A cmpxchg loads the location and stores the new value when the loaded value
+  /// equals the compared one, or the loaded value back otherwise. It results in
+  /// the `{old, success}` aggregate, the success flag being the comparison
+  pub fn transfer_cmpxchg_instr(
+    &self,
+    instr: AtomicCmpXchgInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let loc = self.eval_operand_value(state, instr.location());
+    let cmp = self.eval_operand_value(state, instr.compare());
+    let new = self.eval_operand_value(state, instr.new_value());
+    let old = self.load_atomic(instr.as_instruction(), loc.clone(), state);
+    let width = int_width(instr.compare().get_type());
+    let success = match (&*old, &*cmp, width) {
+      (Value::Int(i0), Value::Int(i1), Some(width)) => {
+        Rc::new(Value::Int(fold_icmp(Predicate::EQ, *i0, *i1, width) as i64))
+      }
+      _ => Rc::new(Value::ICmp {
+        pred: Predicate::EQ,
+        op0: old.clone(),
+        op1: cmp,
+        width: width.unwrap_or(64),
+      }),
+    };
+    let stored = match &*success {
+      Value::Int(0) => old.clone(),
+      Value::Int(_) => new,
+      _ => Rc::new(Value::Select {
+        cond: success.clone(),
+        then_val: new,
+        else_val: old.clone(),
+      }),
+    };
+    let val_type = instr.new_value().get_type();
+    self.store_value(instr.as_instruction(), instr.location(), loc, stored, val_type, state);
+    let res = Rc::new(Value::Aggregate(vec![old, success]));
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    if self.exceeds_memory_limit(state) {
+      return None;
+    }
+    instr.next_instruction()
+  }
+
+  /// An extractvalue picks a field of an aggregate tracked field by field, such as
+  /// the result of a cmpxchg, and is a fresh symbol for any other aggregate
+  pub fn transfer_extract_value_instr(
+    &self,
+    instr: ExtractValueInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let aggregate = self.eval_operand_value(state, instr.aggregate());
+    let field = instr
+      .indices()
+      .into_iter()
+      .try_fold(aggregate, |value, index| match &*value {
+        Value::Aggregate(fields) => fields.get(index as usize).cloned(),
+        _ => None,
+      });
+    let res = match field {
+      Some(field) => field,
+      None => Rc::new(Value::Sym(state.new_symbol_id())),
+    };
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    instr.next_instruction()
+  }
+
+  /// An extractelement picks a lane of a vector tracked lane by lane, and is a
+  /// fresh symbol when the index is symbolic or out of the vector
+  pub fn transfer_extract_element_instr(
+    &self,
+    instr: ExtractElementInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let vector = self.eval_operand_value(state, instr.vector());
+    let index = self.eval_operand_value(state, instr.index());
+    let lane = match (&*vector, &*index) {
+      (Value::Vector(lanes), Value::Int(i)) if *i >= 0 => lanes.get(*i as usize).cloned(),
+      _ => None,
+    };
+    let res = match lane {
+      Some(lane) => lane,
+      None => Rc::new(Value::Sym(state.new_symbol_id())),
+    };
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    instr.next_instruction()
+  }
+
+  /// An insertelement replaces one lane of a vector. With a symbolic index any
+  /// lane may have been replaced, so every lane becomes a fresh symbol
+  pub fn transfer_insert_element_instr(
+    &self,
+    instr: InsertElementInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let vector = self.eval_operand_value(state, instr.vector());
+    let element = self.eval_operand_value(state, instr.element());
+    let index = self.eval_operand_value(state, instr.index());
+    let num_lanes = vector_num_lanes(instr.get_type()).unwrap_or(0);
+    let res = Rc::new(Value::Vector(
+      (0..num_lanes)
+        .map(|lane| match &*index {
+          Value::Int(i) if *i == lane as i64 => element.clone(),
+          Value::Int(_) => vector_lane(&vector, lane),
+          _ => Rc::new(Value::Sym(state.new_symbol_id())),
+        })
+        .collect(),
+    ));
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    instr.next_instruction()
+  }
+
+  /// A shufflevector picks each lane of its result from the lanes of its two
+  /// operands laid end to end, as told by the mask. Undefined mask lanes are
+  /// fresh symbols
+  pub fn transfer_shuffle_vector_instr(
+    &self,
+    instr: ShuffleVectorInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let v0 = self.eval_operand_value(state, instr.op0());
+    let v1 = self.eval_operand_value(state, instr.op1());
+    let num_lanes = vector_num_lanes(instr.op0().get_type()).unwrap_or(0);
+    let res = Rc::new(Value::Vector(
+      instr
+        .mask()
+        .into_iter()
+        .map(|lane| match lane {
+          lane if lane < 0 => Rc::new(Value::Sym(state.new_symbol_id())),
+          lane if (lane as usize) < num_lanes => vector_lane(&v0, lane as usize),
+          lane => vector_lane(&v1, lane as usize - num_lanes),
+        })
+        .collect(),
+    ));
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
     instr.next_instruction()
   }
 
@@ -590,15 +1153,44 @@ where
     let pred = instr.predicate(); // ICMP must have a predicate
     let op0 = self.eval_operand_value(state, instr.op0());
     let op1 = self.eval_operand_value(state, instr.op1());
-    let res = Rc::new(Value::ICmp {
+    let width = int_width(instr.op0().get_type());
+    let res = match (&*op0, &*op1, width) {
+      (Value::Int(i0), Value::Int(i1), Some(width)) => Rc::new(Value::Int(fold_icmp(pred, *i0, *i1, width) as i64)),
+      _ => Rc::new(Value::ICmp {
+        pred,
+        op0: op0.clone(),
+        op1: op1.clone(),
+        width: width.unwrap_or(64),
+      }),
+    };
+    let semantics = Semantics::ICmp { pred, op0, op1 };
+    let node = TraceNode {
+      instr: instr.as_instruction(),
+      semantics,
+      result: Some(res.clone()),
+    };
+    state.trace.push(node);
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    instr.next_instruction()
+  }
+
+  pub fn transfer_fcmp_instr(
+    &self,
+    instr: FCmpInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let pred = instr.predicate();
+    let op0 = self.eval_operand_value(state, instr.op0());
+    let op1 = self.eval_operand_value(state, instr.op1());
+    let res = Rc::new(Value::FCmp {
       pred,
       op0: op0.clone(),
       op1: op1.clone(),
     });
-    let semantics = Semantics::ICmp { pred, op0, op1 };
     let node = TraceNode {
       instr: instr.as_instruction(),
-      semantics,
+      semantics: Semantics::FCmp { pred, op0, op1 },
       result: Some(res.clone()),
     };
     state.trace.push(node);
@@ -612,14 +1204,55 @@ where
     state: &mut State<'ctx>,
     _: &mut Environment<'ctx>,
   ) -> Option<Instruction<'ctx>> {
-    let prev_blk = state.prev_block.unwrap();
-    let incoming_val = instr
-      .incomings()
-      .iter()
-      .find(|incoming| incoming.block == prev_blk)
-      .unwrap()
-      .value;
-    let res = self.eval_operand_value(state, incoming_val);
+    let incoming = state.prev_block.and_then(|prev_blk| {
+      instr
+        .incomings()
+        .into_iter()
+        .find(|incoming| incoming.block == prev_blk)
+    });
+    let res = match incoming {
+      Some(incoming) => {
+        let value = self.eval_operand_value(state, incoming.value);
+
+        // A value built from the one the phi held in the previous loop iteration,
+        // as in `sum = phi [0, entry], [sum + x, body]`, would grow with every
+        // iteration. Cut the cycle with a fresh symbol carried by the loop instead
+        let is_cycle = match state.stack.top().memory.get(&instr.as_instruction()) {
+          Some(prev) => !prev.is_constant() && value != *prev && value.depends_on(prev),
+          None => false,
+        };
+        let value = if is_cycle {
+          Rc::new(Value::Sym(state.new_symbol_id()))
+        } else {
+          value
+        };
+
+        // Record which predecessor the value came from
+        let incoming_block = incoming
+          .block
+          .parent_function()
+          .iter_blocks()
+          .position(|block| block == incoming.block)
+          .unwrap_or(0);
+        state.trace.push(TraceNode {
+          instr: instr.as_instruction(),
+          semantics: Semantics::Phi {
+            incoming_block,
+            value: value.clone(),
+          },
+          result: Some(value.clone()),
+        });
+        value
+      }
+      None => {
+        // Malformed IR where we did not come from any incoming block
+        state.finish_state = FinishState::Error(format!(
+          "Phi {} has no incoming value from the previous block",
+          instr.debug_loc_string()
+        ));
+        return None;
+      }
+    };
     state.stack.top_mut().memory.insert(instr.as_instruction(), res);
     instr.next_instruction()
   }
@@ -636,15 +1269,52 @@ where
       .iter()
       .map(|index| self.eval_operand_value(state, *index))
       .collect::<Vec<_>>();
-    let res = Rc::new(Value::GEP {
-      loc: loc.clone(),
-      indices: indices.clone(),
+    let field = gep_location_field(instr.location().get_type(), &indices).map(|field| StructField {
+      name: self.sources.member_name_at(&instr.debug_loc_string()),
+      ..field
     });
+    let in_bounds = gep_in_bounds(
+      instr.location().get_type(),
+      &indices,
+      is_trailing_member(instr.location()),
+    );
+    let res = match vector_num_lanes(instr.get_type()) {
+      // A GEP over vectors of pointers or indices computes one location per lane,
+      // with the scalar operands shared by all lanes
+      Some(num_lanes) => {
+        let lane_of =
+          |operand: Operand<'ctx>, value: &Rc<Value>, lane: usize| match vector_num_lanes(operand.get_type()) {
+            Some(_) => vector_lane(value, lane),
+            None => value.clone(),
+          };
+        let lane_loc_type = match instr.location().get_type() {
+          Type::Vector(v) => v.element_type(),
+          ty => ty,
+        };
+        Rc::new(Value::Vector(
+          (0..num_lanes)
+            .map(|lane| {
+              let lane_loc = lane_of(instr.location(), &loc, lane);
+              let lane_indices = instr
+                .indices()
+                .iter()
+                .zip(&indices)
+                .map(|(index, value)| lane_of(*index, value, lane))
+                .collect();
+              Rc::new(gep_location(lane_loc, lane_loc_type, lane_indices))
+            })
+            .collect(),
+        ))
+      }
+      None => Rc::new(gep_location(loc.clone(), instr.location().get_type(), indices.clone())),
+    };
     let node = TraceNode {
       instr: instr.as_instruction(),
       semantics: Semantics::GEP {
         loc: loc.clone(),
         indices,
+        field,
+        in_bounds,
       },
       result: Some(res.clone()),
     };
@@ -662,11 +1332,18 @@ where
     let op = instr.binary_opcode();
     let v0 = self.eval_operand_value(state, instr.op0());
     let v1 = self.eval_operand_value(state, instr.op1());
-    let res = Rc::new(Value::Bin {
-      op,
-      op0: v0.clone(),
-      op1: v1.clone(),
-    });
+    let res = match instr.get_type() {
+      // Vector operations are carried out lane by lane
+      Type::Vector(v) => Rc::new(Value::Vector(
+        (0..v.num_elements())
+          .map(|lane| {
+            let (l0, l1) = (vector_lane(&v0, lane), vector_lane(&v1, lane));
+            binary_value(op, l0, l1, int_width(v.element_type()))
+          })
+          .collect(),
+      )),
+      ty => binary_value(op, v0.clone(), v1.clone(), int_width(ty)),
+    };
     let node = TraceNode {
       instr: instr.as_instruction(),
       semantics: Semantics::Bin { op, op0: v0, op1: v1 },
@@ -685,13 +1362,39 @@ where
   ) -> Option<Instruction<'ctx>> {
     let op = instr.unary_opcode();
     let op0 = self.eval_operand_value(state, instr.op0());
+    let widths = (int_width(instr.op0().get_type()), int_width(instr.get_type()));
+    let folded = match (&*op0, widths) {
+      (Value::Int(i), (Some(from), Some(to))) => fold_unary(op, *i, from, to),
+      _ => None,
+    };
+    let res = match folded {
+      Some(i) => Rc::new(Value::Int(i)),
+      None => op0.clone(),
+    };
     let node = TraceNode {
       instr: instr.as_instruction(),
-      semantics: Semantics::Una { op, op0: op0.clone() },
-      result: Some(op0.clone()),
+      semantics: Semantics::Una { op, op0 },
+      result: Some(res.clone()),
     };
     state.trace.push(node);
-    state.stack.top_mut().memory.insert(instr.as_instruction(), op0);
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    instr.next_instruction()
+  }
+
+  /// A freeze passes its operand through, except that an undefined operand is
+  /// pinned down to an arbitrary but fixed value, i.e. a fresh symbol
+  pub fn transfer_freeze_instr(
+    &self,
+    instr: FreezeInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    let op0 = self.eval_operand_value(state, instr.op0());
+    let res = match &*op0 {
+      Value::Undef(_) | Value::Unknown => Rc::new(Value::Sym(state.new_symbol_id())),
+      _ => op0,
+    };
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
     instr.next_instruction()
   }
 
@@ -705,6 +1408,30 @@ where
     None
   }
 
+  pub fn transfer_landing_pad_instr(
+    &self,
+    instr: LandingPadInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    // The landing pad gives the exception being unwound
+    let res = Rc::new(Value::Exception);
+    state.stack.top_mut().memory.insert(instr.as_instruction(), res);
+    instr.next_instruction()
+  }
+
+  pub fn transfer_resume_instr(
+    &self,
+    _: ResumeInstruction<'ctx>,
+    state: &mut State<'ctx>,
+    _: &mut Environment<'ctx>,
+  ) -> Option<Instruction<'ctx>> {
+    // Resuming propagates the exception out of the function, which we do not
+    // follow further
+    state.finish_state = FinishState::Unwound;
+    None
+  }
+
   pub fn transfer_instr(
     &self,
     instr: Instruction<'ctx>,
@@ -714,9 +1441,75 @@ where
     instr.next_instruction()
   }
 
-  pub fn continue_execution(&self, metadata: &MetaData) -> bool {
-    metadata.explored_trace_count < self.options.max_explored_trace_per_slice()
-      && metadata.proper_trace_count < self.options.max_trace_per_slice()
+  /// The maximum number of traces per slice of a target, from `--budget-file` if
+  /// the target is in there and `--max-trace-per-slice` otherwise
+  pub fn max_trace_per_slice(&self, target: &str) -> usize {
+    match self.trace_budgets.get(target) {
+      Some(budget) => *budget,
+      None => self.options.max_trace_per_slice(),
+    }
+  }
+
+  pub fn continue_execution(&self, target: &str, metadata: &MetaData) -> bool {
+    !self.timed_out()
+      && metadata.explored_trace_count < self.options.max_explored_trace_per_slice()
+      && (metadata.proper_trace_count < self.max_trace_per_slice(target)
+        || (self.options.collect_negative() && !self.negative_traces_full(metadata)))
+  }
+
+  fn negative_traces_full(&self, metadata: &MetaData) -> bool {
+    let max = self.options.max_negative_trace_per_slice();
+    metadata.negative_no_target_trace_count >= max && metadata.negative_path_unsat_trace_count >= max
+  }
+
+  fn dump_negative_trace(
+    &self,
+    kind: &str,
+    json: serde_json::Value,
+    slice_id: usize,
+    trace_id: usize,
+    env: &Environment<'ctx>,
+  ) {
+    if env.collected_traces.is_some() {
+      return;
+    }
+    let target = env.slice.target_function_name();
+    let dir = self
+      .options
+      .negative_trace_target_slice_dir(kind, target.as_str(), slice_id);
+    fs::create_dir_all(&dir).expect("Cannot create negative trace folder");
+    self.dump_json_file(&json, dir.join(self.options.json_file_name(trace_id)), env);
+  }
+
+  /// Dump a json file through the writer thread of the slice, if there is one
+  fn dump_json_file(&self, json: &serde_json::Value, path: PathBuf, env: &Environment<'ctx>) {
+    match &env.trace_writer {
+      Some(writer) => writer.dump_versioned_json(json, path),
+      None => dump_versioned_json(json, path),
+    }
+    .expect("Cannot dump json")
+  }
+
+  /// Report a path reaching an `unreachable` instruction, which the compiler assumed
+  /// impossible, when its constraints are satisfiable
+  fn report_unreachable(&self, state: &State<'ctx>, slice_id: usize, metadata: &mut MetaData, env: &Environment<'ctx>) {
+    if !state.constraints.sat() {
+      return;
+    }
+    let trace_id = metadata.reported_unreachable_count;
+    metadata.reported_unreachable_count += 1;
+    if env.collected_traces.is_some() {
+      return;
+    }
+    let json = json!({
+      "instrs": trace_instrs_json(&state.trace),
+      "target": state.target_node,
+      "cons": *state.constraints,
+    });
+    let target = env.slice.target_function_name();
+    let dir = self.options.assertion_target_slice_dir(target.as_str(), slice_id);
+    fs::create_dir_all(&dir).expect("Cannot create assertions folder");
+    self.dump_json_file(&json, dir.join(self.options.json_file_name(trace_id)), env);
   }
 
   pub fn finish_execution(
@@ -726,25 +1519,79 @@ where
     metadata: &mut MetaData,
     env: &mut Environment<'ctx>,
   ) {
+    if let FinishState::Unreachable = state.finish_state {
+      if self.options.report_unreachable() {
+        self.report_unreachable(&state, slice_id, metadata, env);
+      }
+    }
+    if let FinishState::Error(reason) = &state.finish_state {
+      warn!(target: EXECUTOR_LOG, "Slice {} execution error: {}", slice_id, reason);
+    }
+
     match state.target_node {
       Some(target_id) => match state.finish_state {
         FinishState::ProperlyReturned => {
           // Generate the trace for output
-          let raw_trace = TraceWithTarget::new(state.trace, target_id, state.statically_checked);
-          let trace = if !self.options.no_trace_reduction() {
-            raw_trace.reduce()
-          } else {
-            raw_trace
-          };
+          let raw_trace = TraceWithTarget::new(
+            state.trace.into_inner(),
+            target_id,
+            state.statically_checked,
+            state.constraints.into_inner(),
+          )
+          .with_pre_call_memory(state.pre_call_memory.map(Shared::into_inner))
+          .with_constraint_truncated(state.constraint_truncated);
 
           // Check block trace duplication
-          let block_trace = trace.block_trace();
+          let block_trace = raw_trace.block_trace();
           if !env.has_duplicate(&block_trace) {
             // Add block trace into environment
             env.add_block_trace(block_trace);
 
-            // Check path satisfaction
-            if state.constraints.sat() {
+            // Check path satisfaction. Paths z3 cannot decide are accounted on their own
+            let satisfiability = raw_trace.constraints.satisfiability(self.options.solver_rlimit());
+            if satisfiability == Satisfiability::Unknown {
+              if !self.options.keep_unknown() {
+                debug!(target: EXECUTOR_LOG, "Slice {} path satisfiability unknown", slice_id);
+                metadata.incr_unknown();
+                return;
+              }
+            }
+            if satisfiability != Satisfiability::Unsat {
+              // Proper traces are capped while we keep exploring for negative traces
+              if metadata.proper_trace_count >= self.max_trace_per_slice(env.slice.target_function_name().as_str()) {
+                metadata.incr_capped_proper();
+                return;
+              }
+              if satisfiability == Satisfiability::Unknown {
+                metadata.unknown_trace_count += 1;
+              }
+
+              // Blocks are taken before the reduction, which may drop all the nodes of a block
+              let block_trace_json = if self.options.block_trace_only() {
+                Some(raw_trace.to_block_trace_json())
+              } else {
+                None
+              };
+
+              // Reduce the trace around the target, unless only its blocks are dumped
+              let trace = if !self.options.no_trace_reduction() && block_trace_json.is_none() {
+                let num_nodes = raw_trace.trace.len();
+                let trace = raw_trace.reduce();
+                metadata.reduced_node_count += num_nodes - trace.trace.len();
+                trace
+              } else {
+                raw_trace
+              };
+
+              // Drop the nodes of kinds not asked for
+              let trace = match &self.keep_kinds {
+                Some(kinds) => trace.retain_kinds(kinds),
+                None => trace,
+              };
+
+              // Rename symbols so that equivalent traces dump to the same json
+              let trace = trace.canonicalize();
+
               // Need store
               let trace_id = metadata.proper_trace_count;
               let path = self.options.trace_target_slice_file_path(
@@ -759,15 +1606,64 @@ where
                 trace.print();
               }
 
-              // Dump the json
-              dump_json(&trace.to_json(), path).expect("Cannot dump json");
-
               // Increase the count in metadata
+              if trace.constraint_truncated {
+                metadata.constraint_truncated_trace_count += 1;
+              }
               metadata.incr_proper();
+
+              // Traces executed in memory are handed back instead of being dumped
+              if let Some(traces) = &mut env.collected_traces {
+                traces.push(trace);
+                return;
+              }
+
+              // Dump the dot graph next to the json
+              if self.options.dump_dot() {
+                fs::create_dir_all(path.parent().unwrap()).expect("Cannot create trace folder");
+                fs::write(path.with_extension("dot"), trace.to_dot()).expect("Cannot dump dot");
+              }
+
+              // Dump the json, either as a separate file or as a line of the slice's jsonl.
+              // Lines carry their trace id as they have no file name to take it from
+              let mut json = block_trace_json.unwrap_or_else(|| {
+                if self.options.reverse_traces() {
+                  trace.to_reversed_json()
+                } else {
+                  trace.to_json()
+                }
+              });
+              debug!(target: EXECUTOR_LOG, "Dumping slice {} trace {}", slice_id, trace_id);
+              match (&env.trace_writer, self.options.trace_format()) {
+                (Some(writer), TraceFormat::Jsonl) => {
+                  json["id"] = json!(trace_id);
+                  writer.write_line(&json).expect("Cannot write json line")
+                }
+                _ => {
+                  if self.options.trace_shard_size().is_some() {
+                    fs::create_dir_all(path.parent().unwrap()).expect("Cannot create trace shard folder");
+                  }
+                  self.dump_json_file(&json, path, env)
+                }
+              }
             } else {
+              debug!(
+                target: EXECUTOR_LOG,
+                "Slice {} path unsatisfiable with {} constraints",
+                slice_id,
+                raw_trace.constraints.len()
+              );
+
+              // Keep the unsatisfiable trace as a negative example
+              let trace_id = metadata.negative_path_unsat_trace_count;
+              if self.options.collect_negative() && trace_id < self.options.max_negative_trace_per_slice() {
+                self.dump_negative_trace(PATH_UNSAT, raw_trace.to_json(), slice_id, trace_id, env);
+                metadata.negative_path_unsat_trace_count += 1;
+              }
               metadata.incr_path_unsat()
             }
           } else {
+            trace!(target: EXECUTOR_LOG, "Slice {} duplicate block trace", slice_id);
             metadata.incr_duplicated()
           }
         }
@@ -777,11 +1673,32 @@ where
         FinishState::ExceedingMaxTraceLength => {
           metadata.incr_exceeding_length()
         },
+        FinishState::MemoryLimit => metadata.incr_memory_limit(),
         FinishState::Unreachable => {
           metadata.incr_unreachable()
         },
+        FinishState::Unwound => metadata.incr_unwound(),
+        FinishState::Error(_) => metadata.incr_error(),
       },
-      None => metadata.incr_no_target(),
+      None if matches!(state.finish_state, FinishState::Error(_)) => metadata.incr_error(),
+      None if matches!(state.finish_state, FinishState::MemoryLimit) => metadata.incr_memory_limit(),
+      None => {
+        // Keep the properly returned trace that never reached the target as a negative example
+        let trace_id = metadata.negative_no_target_trace_count;
+        if let FinishState::ProperlyReturned = state.finish_state {
+          if self.options.collect_negative() && trace_id < self.options.max_negative_trace_per_slice() {
+            let json = json!({
+              "instrs": trace_instrs_json(&state.trace),
+              "target": null,
+              "statically_checked": state.statically_checked,
+              "cons": *state.constraints,
+            });
+            self.dump_negative_trace(NO_TARGET, json, slice_id, trace_id, env);
+            metadata.negative_no_target_trace_count += 1;
+          }
+        }
+        metadata.incr_no_target()
+      }
     }
   }
 
@@ -792,13 +1709,136 @@ where
     }
   }
 
+  /// Count the traces dumped by a previous run of a finished slice
+  fn count_existing_traces(&self, target: &str, slice_id: usize) -> usize {
+    match self.options.trace_format() {
+      TraceFormat::Json => json_files_in_dir(&self.options.trace_target_slice_dir(target, slice_id))
+        .map(|files| files.len())
+        .unwrap_or(0),
+      TraceFormat::Jsonl => match self.options.existing_trace_target_slice_jsonl_path(target, slice_id) {
+        Some(path) => load_jsonl_t::<serde_json::Value>(&path).map_or(0, |lines| lines.len()),
+        None => 0,
+      },
+    }
+  }
+
   pub fn execute_slice(&self, slice: Slice<'ctx>, slice_id: usize) -> MetaData {
+    let begin = Instant::now();
     let mut metadata = MetaData::new();
+    let target = slice.target_function_name();
+    let done_path = self.options.trace_target_slice_done_path(target.as_str(), slice_id);
+
+    // Skip the slices already finished by a previous run
+    if self.options.resume() && done_path.exists() {
+      metadata.proper_trace_count = self.count_existing_traces(target.as_str(), slice_id);
+      metadata.resumed_slice_count = 1;
+      return metadata;
+    }
+
+    // Do not start new slices past the global timeout
+    if self.timed_out() {
+      metadata.truncated = true;
+      return metadata;
+    }
+
+    // The files of the slice are written and compressed on a separate thread
     let mut env = Environment::new(&slice, self.options.max_work(), self.options.seed());
+    env.trace_writer = Some(match self.options.trace_format() {
+      TraceFormat::Json => JsonWriter::new(),
+      TraceFormat::Jsonl => {
+        let path = self.options.trace_target_slice_jsonl_path(target.as_str(), slice_id);
+        JsonWriter::with_jsonl(path).expect("Cannot create trace jsonl")
+      }
+    });
+
+    self.explore_slice(&slice, slice_id, &mut metadata, &mut env);
+
+    // Wait for the files of this slice to be written and mark the slice as done,
+    // unless it was cut by the global timeout
+    if let Some(writer) = env.trace_writer.take() {
+      if let Err(err) = writer.finish() {
+        panic!("Cannot dump the traces of {} slice {}: {}", target, slice_id, err);
+      }
+    }
+    metadata.truncated = env.has_work() && self.timed_out();
+    if !metadata.truncated {
+      fs::write(done_path, "").expect("Cannot write slice done marker");
+    }
+
+    // Record the blocks covered by this slice
+    if self.options.coverage_report().is_some() {
+      metadata.block_coverage = BlockCoverage::from_slice(&slice, &env.visited_blocks);
+    }
+
+    // Record the time spent on this slice
+    metadata.elapsed_ms = begin.elapsed().as_secs_f64() * 1000.0;
+    if self.options.timing_report().is_some() {
+      metadata.slice_timings.push(SliceTiming {
+        target,
+        slice_id,
+        elapsed_ms: metadata.elapsed_ms,
+      });
+    }
+
+    // Persist the statistics of this slice next to its traces, without creating a
+    // folder for slices dumped in a jsonl file
+    let meta_path = match self.options.trace_format() {
+      TraceFormat::Json => {
+        let slice_dir = self.options.trace_target_slice_dir(target.as_str(), slice_id);
+        fs::create_dir_all(&slice_dir).expect("Cannot create trace slice folder");
+        self.options.trace_target_slice_meta_path(target.as_str(), slice_id)
+      }
+      TraceFormat::Jsonl => {
+        let target = target.as_str();
+        self.options.trace_target_slice_jsonl_meta_path(target, slice_id)
+      }
+    };
+    dump_json(&metadata.to_json(), meta_path).expect("Cannot dump slice meta");
+    metadata
+  }
+
+  /// Execute the slice, and under `--keep-going` record it as panicked instead of
+  /// bringing the whole run down when it panics. The traces, negative traces and
+  /// assertions it dumped so far are removed, since its exploration is incomplete
+  fn execute_slice_catching_panic(&self, target_name: &String, slice: Slice<'ctx>, slice_id: usize) -> MetaData {
+    if !self.options.keep_going() {
+      return self.execute_slice(slice, slice_id);
+    }
+    match panic::catch_unwind(AssertUnwindSafe(|| self.execute_slice(slice, slice_id))) {
+      Ok(metadata) => metadata,
+      Err(_) => {
+        warn!(target: EXECUTOR_LOG, "Slice {} of {} panicked, skipping it", slice_id, target_name);
+        let target = target_name.as_str();
+        let _ = fs::remove_dir_all(self.options.trace_target_slice_dir(target, slice_id));
+        let _ = fs::remove_file(self.options.trace_target_slice_jsonl_path(target, slice_id));
+        for kind in &[NO_TARGET, PATH_UNSAT] {
+          let _ = fs::remove_dir_all(self.options.negative_trace_target_slice_dir(kind, target, slice_id));
+        }
+        let _ = fs::remove_dir_all(self.options.assertion_target_slice_dir(target, slice_id));
+        let mut metadata = MetaData::new();
+        metadata.panicked_slice_count = 1;
+        metadata.panicked_slices.push((target_name.clone(), slice_id));
+        metadata
+      }
+    }
+  }
 
-    // Add a work to the environment list
-    if self.options.no_prefilter_block_trace() {
-      let first_work = Work::entry(&slice);
+  /// Execute the slice without touching the file system, returning the traces
+  /// that would otherwise be dumped, in the order of their trace ids
+  pub fn execute_slice_in_memory(&self, slice: Slice<'ctx>, slice_id: usize) -> (MetaData, Vec<TraceWithTarget<'ctx>>) {
+    let mut metadata = MetaData::new();
+    let mut env = Environment::new(&slice, self.options.max_work(), self.options.seed());
+    env.collected_traces = Some(vec![]);
+    self.explore_slice(&slice, slice_id, &mut metadata, &mut env);
+    metadata.truncated = env.has_work() && self.timed_out();
+    (metadata, env.collected_traces.unwrap_or_default())
+  }
+
+  fn explore_slice(&self, slice: &Slice<'ctx>, slice_id: usize, metadata: &mut MetaData, env: &mut Environment<'ctx>) {
+    // Add a work to the environment list. Merging states needs the arms of a
+    // branch to be explored together, which block traces would keep apart
+    if self.options.no_prefilter_block_trace() || self.options.merge_states() {
+      let first_work = Work::entry(slice, &self.seed);
       env.add_work(first_work);
     } else {
       let block_traces = slice.block_traces(
@@ -810,26 +1850,31 @@ where
         if self.options.print_block_trace() {
           println!("{:?}", block_trace);
         }
-        let work = Work::entry_with_block_trace(&slice, block_trace);
+        let work = Work::entry_with_block_trace(slice, block_trace, &self.seed);
         env.add_work(work);
       }
     }
 
     // Iterate till no more work to be done or should end execution
-    while env.has_work() && self.continue_execution(&metadata) {
+    let target = slice.target_function_name();
+    while env.has_work() && self.continue_execution(&target, metadata) {
+      metadata.update_work_list_size(env.num_works());
       let mut work = env.pop_work(!self.options.no_random_work());
 
       // Start the execution by iterating through instructions
-      self.execute_block_state(work.block, &mut work.state, &mut env);
+      self.execute_block_state(work.block, &mut work.state, env);
 
       // Finish the instruction and settle down the states
-      self.finish_execution(work.state, slice_id, &mut metadata, &mut env);
+      self.finish_execution(work.state, slice_id, metadata, env);
     }
-    metadata
+    metadata.merged_state_count += env.merged_state_count;
   }
 
   fn initialize_traces_function_slice_folder(&self, func_name: &String, slice_id: usize) -> Result<(), String> {
-    let path = self.options.trace_target_slice_dir(func_name.as_str(), slice_id);
+    let path = match self.options.trace_format() {
+      TraceFormat::Json => self.options.trace_target_slice_dir(func_name.as_str(), slice_id),
+      TraceFormat::Jsonl => self.options.trace_target_dir(func_name.as_str()),
+    };
     fs::create_dir_all(path).map_err(|_| "Cannot create trace function slice folder".to_string())
   }
 
@@ -839,35 +1884,69 @@ where
     slice_id_offset: usize,
     slices: Vec<Slice<'ctx>>,
   ) -> MetaData {
-    if self.options.use_serial() {
-      slices.into_iter().progress().enumerate().fold(
+    // Under `--slice-shard` the other slices are left to the other processes
+    let shard = self.options.slice_shard();
+    let slices = slices
+      .into_iter()
+      .enumerate()
+      .map(|(id, slice)| (slice_id_offset + id, slice))
+      .filter(|(slice_id, _)| shard.map_or(true, |shard| shard.contains(*slice_id)))
+      .collect::<Vec<_>>();
+    let metadata = if self.options.use_serial() {
+      slices.into_iter().progress().fold(
         MetaData::new(),
-        |meta: MetaData, (id, slice): (usize, Slice<'ctx>)| {
-          let slice_id = slice_id_offset + id;
+        |meta: MetaData, (slice_id, slice): (usize, Slice<'ctx>)| {
           self
             .initialize_traces_function_slice_folder(target_name, slice_id)
             .unwrap();
-          meta.combine(self.execute_slice(slice, slice_id))
+          meta.combine(self.execute_slice_catching_panic(target_name, slice, slice_id))
         },
       )
     } else {
       let num_slices = slices.len();
       slices
         .into_par_iter()
-        .enumerate()
         .fold(
           || MetaData::new(),
-          |meta: MetaData, (id, slice): (usize, Slice<'ctx>)| {
-            let slice_id = slice_id_offset + id;
+          |meta: MetaData, (slice_id, slice): (usize, Slice<'ctx>)| {
             self
               .initialize_traces_function_slice_folder(target_name, slice_id)
               .unwrap();
-            meta.combine(self.execute_slice(slice, slice_id))
+            meta.combine(self.execute_slice_catching_panic(target_name, slice, slice_id))
           },
         )
         .progress_count(num_slices as u64)
         .reduce(|| MetaData::new(), MetaData::combine)
+    };
+    self.dump_target_meta(target_name.as_str());
+    metadata
+  }
+
+  /// Persist the statistics aggregated over all the slices of the target found on
+  /// disk, so that the slices of previous batches and runs are accounted for. A
+  /// slice rerun in the other trace format only counts with its newer statistics
+  fn dump_target_meta(&self, target: &str) {
+    let mut slice_meta_paths = BTreeMap::new();
+    let target_dir = self.options.trace_target_dir(target);
+    let entries = fs::read_dir(&target_dir).into_iter().flatten();
+    for path in entries.flatten().map(|entry| entry.path()) {
+      let slice_meta = match path.file_name().and_then(|name| name.to_str()?.parse::<usize>().ok()) {
+        Some(slice_id) if path.is_dir() => Some((slice_id, path.join("meta.json"))),
+        _ => slice_meta_file_id(&path).map(|slice_id| (slice_id, path)),
+      };
+      if let Some((slice_id, meta_path)) = slice_meta.filter(|(_, meta_path)| meta_path.exists()) {
+        let meta_path = match slice_meta_paths.remove(&slice_id) {
+          Some(other) => newer_file(other, meta_path),
+          None => meta_path,
+        };
+        slice_meta_paths.insert(slice_id, meta_path);
+      }
     }
+    let metadata = slice_meta_paths
+      .values()
+      .filter_map(|meta_path| load_json(meta_path).ok())
+      .fold(MetaData::new(), |meta, json| meta.combine(MetaData::from_json(&json)));
+    dump_json(&metadata.to_json(), self.options.trace_target_meta_path(target)).expect("Cannot dump target meta");
   }
 
   pub fn execute_target_slices_map(&self, target_slices_map: HashMap<String, (usize, Vec<Slice<'ctx>>)>) -> MetaData {
@@ -892,3 +1971,51 @@ where
     }
   }
 }
+
+/// The join block of a diamond or triangle below the conditional branch, when the
+/// arms only move data around before jumping to it and it has no phi to pick
+/// values by the arm taken
+fn mergeable_join<'ctx>(instr: ConditionalBranchInstruction<'ctx>) -> Option<Block<'ctx>> {
+  let (then_blk, else_blk) = (instr.then_block(), instr.else_block());
+  if then_blk == else_blk {
+    return None;
+  }
+  let join = match (mergeable_arm_exit(then_blk), mergeable_arm_exit(else_blk)) {
+    (Some(then_exit), Some(else_exit)) if then_exit == else_exit => then_exit,
+    (Some(then_exit), _) if then_exit == else_blk => else_blk,
+    (_, Some(else_exit)) if else_exit == then_blk => then_blk,
+    _ => return None,
+  };
+  match join.first_instruction() {
+    Some(Instruction::Phi(_)) => None,
+    _ => Some(join),
+  }
+}
+
+/// The destination of the arm block if it holds no call nor branch but its
+/// final jump, which must not close a loop
+fn mergeable_arm_exit<'ctx>(block: Block<'ctx>) -> Option<Block<'ctx>> {
+  for instr in block.iter_instructions() {
+    match instr {
+      Instruction::Store(_) | Instruction::Load(_) | Instruction::GetElementPtr(_) => {}
+      Instruction::Binary(_) | Instruction::Unary(_) | Instruction::ICmp(_) | Instruction::FCmp(_) => {}
+      Instruction::Branch(BranchInstruction::Unconditional(br)) if !br.is_loop_jump().unwrap_or(true) => {
+        return Some(br.destination());
+      }
+      _ => return None,
+    }
+  }
+  None
+}
+
+/// Load a file of function name regexes, one per line, skipping `#` comments
+fn load_function_regexes(path: &String) -> Result<Vec<Regex>, AnalyzerError> {
+  let content =
+    fs::read_to_string(path).map_err(|_| AnalyzerError::Io(format!("Cannot read function regexes file {}", path)))?;
+  content
+    .lines()
+    .map(|line| line.trim())
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(|line| Regex::new(line).map_err(|_| AnalyzerError::Regex(format!("Cannot parse function regex {}", line))))
+    .collect()
+}
@@ -0,0 +1,335 @@
+use llir::{types::*, values::*};
+use std::rc::Rc;
+
+use crate::semantics::{rced::Value, *};
+use crate::utils::*;
+
+/// The bit width of an integer type; `None` for any other type or for integers
+/// wider than 64 bits, which we do not fold
+pub fn int_width<'ctx>(ty: Type<'ctx>) -> Option<u32> {
+  match ty {
+    Type::Int(i) if i.width() <= 64 => Some(i.width()),
+    _ => None,
+  }
+}
+
+/// The number of lanes of a vector type; `None` for any other type
+pub fn vector_num_lanes<'ctx>(ty: Type<'ctx>) -> Option<usize> {
+  match ty {
+    Type::Vector(v) => Some(v.num_elements()),
+    _ => None,
+  }
+}
+
+/// The value in a lane of a vector, unknown when the vector is not tracked lane
+/// by lane, e.g. when built by instructions we do not model
+pub fn vector_lane(value: &Rc<Value>, lane: usize) -> Rc<Value> {
+  match &**value {
+    Value::Vector(lanes) => lanes.get(lane).cloned().unwrap_or_else(|| Rc::new(Value::Unknown)),
+    _ => Rc::new(Value::Unknown),
+  }
+}
+
+/// The location of a lane of the vector stored at `loc`, i.e. `loc[0, lane]`, so
+/// that vector accesses never alias the scalar cell at `loc` itself
+pub fn vector_lane_location(loc: Rc<Value>, lane: usize) -> Rc<Value> {
+  Rc::new(fold_gep(
+    loc,
+    vec![Rc::new(Value::Int(0)), Rc::new(Value::Int(lane as i64))],
+  ))
+}
+
+/// The struct field selected by each of the indices following the first one of a
+/// GEP into `pointee`, which steps over the pointer, walking the type along them;
+/// `None` for the indices stepping over array or vector elements. The walk stops
+/// at a symbolic (`None`) struct index or at a type it cannot index further
+fn gep_steps<'ctx>(pointee: Type<'ctx>, indices: &[Option<i64>]) -> Vec<Option<StructField>> {
+  let mut ty = pointee;
+  let mut steps = vec![];
+  for index in indices.iter().skip(1) {
+    ty = match ty {
+      Type::Struct(st) => {
+        let index = match index {
+          Some(i) if *i >= 0 => *i as usize,
+          _ => break,
+        };
+        let struct_name = match &st {
+          StructType::NamedStruct(ns) => Some(ns.name()),
+          _ => None,
+        };
+        steps.push(Some(StructField {
+          struct_name,
+          index,
+          name: None,
+        }));
+        match st.field_types().get(index) {
+          Some(field_type) => *field_type,
+          None => break,
+        }
+      }
+      Type::Array(a) => {
+        steps.push(None);
+        a.element_type()
+      }
+      Type::Vector(v) => {
+        steps.push(None);
+        v.element_type()
+      }
+      _ => break,
+    };
+  }
+  steps
+}
+
+/// The innermost struct field accessed by a GEP into `pointee`, walking the type
+/// along the indices following the first one, which steps over the pointer.
+/// Array and vector indices may be symbolic (`None`), struct indices may not
+pub fn gep_struct_field<'ctx>(pointee: Type<'ctx>, indices: &[Option<i64>]) -> Option<StructField> {
+  gep_steps(pointee, indices).into_iter().flatten().last()
+}
+
+fn const_indices(indices: &[Rc<Value>]) -> Vec<Option<i64>> {
+  indices
+    .iter()
+    .map(|index| match &**index {
+      Value::Int(i) => Some(*i),
+      _ => None,
+    })
+    .collect()
+}
+
+/// The struct field accessed by a GEP on a location of type `loc_type`, if any
+pub fn gep_location_field<'ctx>(loc_type: Type<'ctx>, indices: &[Rc<Value>]) -> Option<StructField> {
+  match loc_type {
+    Type::Pointer(p) => gep_struct_field(p.element_type(), &const_indices(indices)),
+    _ => None,
+  }
+}
+
+/// Whether a location is the last field of a struct, as computed by a GEP; such
+/// a field may be a flexible array member declared with a size of 1
+pub fn is_trailing_member<'ctx>(loc: Operand<'ctx>) -> bool {
+  let const_index = |index: Constant<'ctx>| match index {
+    Constant::Int(i) => Some(i.sext_value()),
+    _ => None,
+  };
+  let (loc_type, indices) = match loc {
+    Operand::Instruction(Instruction::GetElementPtr(g)) => (
+      g.location().get_type(),
+      g.indices()
+        .iter()
+        .map(|index| match index {
+          Operand::Constant(c) => const_index(*c),
+          _ => None,
+        })
+        .collect::<Vec<_>>(),
+    ),
+    Operand::Constant(Constant::ConstExpr(ConstExpr::GetElementPtr(g))) => (
+      g.location().get_type(),
+      g.indices().into_iter().map(const_index).collect::<Vec<_>>(),
+    ),
+    _ => return false,
+  };
+  let mut ty = match loc_type {
+    Type::Pointer(p) => p.element_type(),
+    _ => return false,
+  };
+  let mut trailing = false;
+  for index in indices.iter().skip(1) {
+    ty = match (ty, index) {
+      (Type::Struct(st), Some(i)) if *i >= 0 => {
+        let field_types = st.field_types();
+        trailing = *i as usize + 1 == field_types.len();
+        match field_types.get(*i as usize) {
+          Some(field_type) => *field_type,
+          None => return false,
+        }
+      }
+      (Type::Array(a), _) => {
+        trailing = false;
+        a.element_type()
+      }
+      (Type::Vector(v), _) => {
+        trailing = false;
+        v.element_type()
+      }
+      _ => return false,
+    };
+  }
+  trailing
+}
+
+/// Whether the constant indices of a GEP on a location of type `loc_type` stay
+/// within the sizes of the arrays they index, walking the pointee type like
+/// `gep_struct_field`. A constant out of bounds index gives `Some(false)` even
+/// when other indices are symbolic; otherwise a symbolic array index, or no
+/// array index at all, gives `None`. Arrays of size 0, and arrays of size 1
+/// ending a struct, are flexible array members whose size is unknown, so their
+/// indices count as symbolic ones. `trailing` tells whether the location itself
+/// is the last field of a struct
+pub fn gep_in_bounds<'ctx>(loc_type: Type<'ctx>, indices: &[Rc<Value>], trailing: bool) -> Option<bool> {
+  let mut ty = match loc_type {
+    Type::Pointer(p) => p.element_type(),
+    _ => return None,
+  };
+  let (mut has_array_index, mut has_symbolic_index) = (false, false);
+  let mut trailing = trailing;
+  for index in indices.iter().skip(1) {
+    let index = match &**index {
+      Value::Int(i) => Some(*i),
+      _ => None,
+    };
+    ty = match ty {
+      Type::Struct(st) => {
+        let field_types = st.field_types();
+        match index.and_then(|i| field_types.get(i as usize).cloned()) {
+          Some(field_type) => {
+            trailing = index == Some(field_types.len() as i64 - 1);
+            field_type
+          }
+          None => break,
+        }
+      }
+      Type::Array(a) => {
+        let flexible = a.num_elements() == 0 || (a.num_elements() == 1 && trailing);
+        match index {
+          _ if flexible => has_symbolic_index = true,
+          Some(i) if i < 0 || i as usize >= a.num_elements() => return Some(false),
+          Some(_) => has_array_index = true,
+          None => has_symbolic_index = true,
+        }
+        trailing = false;
+        a.element_type()
+      }
+      _ => break,
+    };
+  }
+  if has_array_index && !has_symbolic_index {
+    Some(true)
+  } else {
+    None
+  }
+}
+
+/// The location of a GEP on a location of type `loc_type`. Every struct field the
+/// GEP selects gives a `Field` location, as in `p->y`, so that a `GEP` location
+/// only ever ends on an array or vector element, and the indices in between go
+/// through `fold_gep`. Fields of literal structs, which have no name, get an
+/// empty struct name
+pub fn gep_location<'ctx>(loc: Rc<Value>, loc_type: Type<'ctx>, indices: Vec<Rc<Value>>) -> Value {
+  let steps = match loc_type {
+    Type::Pointer(p) => gep_steps(p.element_type(), &const_indices(&indices)),
+    _ => vec![],
+  };
+  if steps.iter().all(Option::is_none) {
+    return fold_gep(loc, indices);
+  }
+  // The indices since the last struct field, stepping over elements only
+  let is_zero = |pending: &[Rc<Value>]| matches!(pending, [index] if **index == Value::Int(0));
+  let mut indices = indices.into_iter();
+  let (mut loc, mut pending) = (loc, indices.next().into_iter().collect::<Vec<_>>());
+  for (index, step) in indices.zip(steps.into_iter().chain(std::iter::repeat(None))) {
+    match step {
+      Some(StructField { struct_name, index, .. }) => {
+        let base = if is_zero(&pending) {
+          loc
+        } else {
+          Rc::new(fold_gep(loc, pending))
+        };
+        loc = Rc::new(Value::Field {
+          loc: base,
+          struct_name: struct_name.unwrap_or_default(),
+          index,
+        });
+        pending = vec![Rc::new(Value::Int(0))];
+      }
+      None => pending.push(index),
+    }
+  }
+  if is_zero(&pending) {
+    (*loc).clone()
+  } else {
+    fold_gep(loc, pending)
+  }
+}
+
+/// The location of a GEP, with a GEP on another GEP merged into a single one when
+/// the last index of the inner GEP and the first index of the outer GEP are both
+/// constant, as in `p = &a[1]; p[2]` becoming `a[3]`. Constant offsets thus end up
+/// at the same location however they are reached, while symbolic indices stay as
+/// they are. This is only sound since `gep_location` never ends a `GEP` location
+/// on a struct field, which the outer index would otherwise step past. Like
+/// elsewhere, casts between the two GEPs are not accounted for
+pub fn fold_gep(loc: Rc<Value>, indices: Vec<Rc<Value>>) -> Value {
+  if let Value::GEP {
+    loc: base,
+    indices: base_indices,
+  } = &*loc
+  {
+    let offset = match (base_indices.last().map(|i| &**i), indices.first().map(|i| &**i)) {
+      (Some(Value::Int(i)), Some(Value::Int(j))) => i.checked_add(*j),
+      _ => None,
+    };
+    if let Some(offset) = offset {
+      let mut folded = base_indices[..base_indices.len() - 1].to_vec();
+      folded.push(Rc::new(Value::Int(offset)));
+      folded.extend(indices.into_iter().skip(1));
+      return Value::GEP {
+        loc: base.clone(),
+        indices: folded,
+      };
+    }
+  }
+  Value::GEP { loc, indices }
+}
+
+/// The result of a binary operation, folded when both operands are integers
+/// of a known width
+pub fn binary_value(op: BinOp, op0: Rc<Value>, op1: Rc<Value>, width: Option<u32>) -> Rc<Value> {
+  let folded = match (&*op0, &*op1, width) {
+    (Value::Int(i0), Value::Int(i1), Some(width)) => fold_binary(op, *i0, *i1, width),
+    _ => None,
+  };
+  match folded {
+    Some(i) => Rc::new(Value::Int(i)),
+    None => Rc::new(Value::Bin {
+      op,
+      op0,
+      op1,
+      width: width.unwrap_or(64),
+    }),
+  }
+}
+
+/// Fold an integer cast of a constant from `from_width` bits to `to_width` bits.
+/// Returns `None` for non-integer casts
+pub fn fold_unary(op: UnaOp, op0: i64, from_width: u32, to_width: u32) -> Option<i64> {
+  match op {
+    UnaOp::Trunc => Some(sign_extend(op0, to_width)),
+    UnaOp::SExt => Some(sign_extend(sign_extend(op0, from_width), to_width)),
+    UnaOp::ZExt => Some(sign_extend(zero_extend(op0, from_width) as i64, to_width)),
+    _ => None,
+  }
+}
+
+/// Fold a float comparison of two constants. Ordered predicates are false when
+/// either operand is NaN, and unordered ones are true
+pub fn fold_fcmp(pred: FPredicate, op0: f64, op1: f64) -> bool {
+  let unordered = op0.is_nan() || op1.is_nan();
+  match pred {
+    FPredicate::OEQ => !unordered && op0 == op1,
+    FPredicate::OGE => !unordered && op0 >= op1,
+    FPredicate::OGT => !unordered && op0 > op1,
+    FPredicate::OLE => !unordered && op0 <= op1,
+    FPredicate::OLT => !unordered && op0 < op1,
+    FPredicate::ONE => !unordered && op0 != op1,
+    FPredicate::ORD => !unordered,
+    FPredicate::UEQ => unordered || op0 == op1,
+    FPredicate::UGE => unordered || op0 >= op1,
+    FPredicate::UGT => unordered || op0 > op1,
+    FPredicate::ULE => unordered || op0 <= op1,
+    FPredicate::ULT => unordered || op0 < op1,
+    FPredicate::UNE => unordered || op0 != op1,
+    FPredicate::UNO => unordered,
+  }
+}
@@ -3,13 +3,14 @@ use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use crate::semantics::rced::*;
+use crate::utils::*;
 
 pub type LocalMemory<'ctx> = HashMap<Instruction<'ctx>, Rc<Value>>;
 
 #[derive(Clone, Debug)]
 pub struct StackFrame<'ctx> {
   pub function: Function<'ctx>,
-  pub instr: Option<(usize, CallInstruction<'ctx>)>,
+  pub instr: Option<(usize, CallSite<'ctx>)>,
   pub memory: LocalMemory<'ctx>,
   pub arguments: Vec<Rc<Value>>,
 }
@@ -54,10 +55,23 @@ impl<'ctx> StackTrait<'ctx> for Stack<'ctx> {
 
 pub type Memory = HashMap<Rc<Value>, Rc<Value>>;
 
+/// A branch taken from one block to another. Switch cases also carry their case value, so that
+/// cases sharing a destination block (with each other or with the default) are explored separately;
+/// conditional branches and switch defaults use `None`.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct BranchDirection<'ctx> {
   pub from: Block<'ctx>,
   pub to: Block<'ctx>,
+  pub case: Option<i64>,
 }
 
 pub type VisitedBranch<'ctx> = HashSet<BranchDirection<'ctx>>;
+
+/// What is known about a heap pointer from the calls summarized along a path
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HeapStatus {
+  Allocated,
+  Freed,
+}
+
+pub type Heap = HashMap<Rc<Value>, HeapStatus>;
@@ -1,13 +1,43 @@
+use serde_json::json;
+
+use super::coverage::*;
+
+#[derive(Debug, Clone)]
+pub struct SliceTiming {
+  pub target: String,
+  pub slice_id: usize,
+  pub elapsed_ms: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct MetaData {
   pub proper_trace_count: usize,
   pub path_unsat_trace_count: usize,
+  pub unknown_trace_count: usize,
   pub branch_explored_trace_count: usize,
   pub duplicate_trace_count: usize,
   pub no_target_trace_count: usize,
   pub exceeding_length_trace_count: usize,
+  pub memory_limit_trace_count: usize,
   pub unreachable_trace_count: usize,
+  pub unwound_trace_count: usize,
+  pub error_trace_count: usize,
   pub explored_trace_count: usize,
+  pub capped_proper_trace_count: usize,
+  pub negative_no_target_trace_count: usize,
+  pub negative_path_unsat_trace_count: usize,
+  pub reduced_node_count: usize,
+  pub constraint_truncated_trace_count: usize,
+  pub merged_state_count: usize,
+  pub reported_unreachable_count: usize,
+  pub resumed_slice_count: usize,
+  pub panicked_slice_count: usize,
+  pub panicked_slices: Vec<(String, usize)>, // Target & slice id, under `--keep-going`
+  pub truncated: bool,
+  pub elapsed_ms: f64,
+  pub max_work_list_size: usize,
+  pub slice_timings: Vec<SliceTiming>,
+  pub block_coverage: BlockCoverage,
 }
 
 impl MetaData {
@@ -15,12 +45,31 @@ impl MetaData {
     MetaData {
       proper_trace_count: 0,
       path_unsat_trace_count: 0,
+      unknown_trace_count: 0,
       branch_explored_trace_count: 0,
       duplicate_trace_count: 0,
       no_target_trace_count: 0,
       exceeding_length_trace_count: 0,
+      memory_limit_trace_count: 0,
       unreachable_trace_count: 0,
+      unwound_trace_count: 0,
+      error_trace_count: 0,
       explored_trace_count: 0,
+      capped_proper_trace_count: 0,
+      negative_no_target_trace_count: 0,
+      negative_path_unsat_trace_count: 0,
+      reduced_node_count: 0,
+      constraint_truncated_trace_count: 0,
+      merged_state_count: 0,
+      reported_unreachable_count: 0,
+      resumed_slice_count: 0,
+      panicked_slice_count: 0,
+      panicked_slices: vec![],
+      truncated: false,
+      elapsed_ms: 0.0,
+      max_work_list_size: 0,
+      slice_timings: vec![],
+      block_coverage: BlockCoverage::new(),
     }
   }
 
@@ -28,12 +77,31 @@ impl MetaData {
     MetaData {
       proper_trace_count: self.proper_trace_count + other.proper_trace_count,
       path_unsat_trace_count: self.path_unsat_trace_count + other.path_unsat_trace_count,
+      unknown_trace_count: self.unknown_trace_count + other.unknown_trace_count,
       branch_explored_trace_count: self.branch_explored_trace_count + other.branch_explored_trace_count,
       duplicate_trace_count: self.duplicate_trace_count + other.duplicate_trace_count,
       no_target_trace_count: self.no_target_trace_count + other.no_target_trace_count,
       exceeding_length_trace_count: self.exceeding_length_trace_count + other.exceeding_length_trace_count,
+      memory_limit_trace_count: self.memory_limit_trace_count + other.memory_limit_trace_count,
       unreachable_trace_count: self.unreachable_trace_count + other.unreachable_trace_count,
+      unwound_trace_count: self.unwound_trace_count + other.unwound_trace_count,
+      error_trace_count: self.error_trace_count + other.error_trace_count,
       explored_trace_count: self.explored_trace_count + other.explored_trace_count,
+      capped_proper_trace_count: self.capped_proper_trace_count + other.capped_proper_trace_count,
+      negative_no_target_trace_count: self.negative_no_target_trace_count + other.negative_no_target_trace_count,
+      negative_path_unsat_trace_count: self.negative_path_unsat_trace_count + other.negative_path_unsat_trace_count,
+      reduced_node_count: self.reduced_node_count + other.reduced_node_count,
+      constraint_truncated_trace_count: self.constraint_truncated_trace_count + other.constraint_truncated_trace_count,
+      merged_state_count: self.merged_state_count + other.merged_state_count,
+      reported_unreachable_count: self.reported_unreachable_count + other.reported_unreachable_count,
+      resumed_slice_count: self.resumed_slice_count + other.resumed_slice_count,
+      panicked_slice_count: self.panicked_slice_count + other.panicked_slice_count,
+      panicked_slices: vec![self.panicked_slices, other.panicked_slices].concat(),
+      truncated: self.truncated || other.truncated,
+      elapsed_ms: self.elapsed_ms + other.elapsed_ms,
+      max_work_list_size: self.max_work_list_size.max(other.max_work_list_size),
+      slice_timings: vec![self.slice_timings, other.slice_timings].concat(),
+      block_coverage: self.block_coverage.combine(other.block_coverage),
     }
   }
 
@@ -42,11 +110,23 @@ impl MetaData {
     self.explored_trace_count += 1;
   }
 
+  pub fn incr_capped_proper(&mut self) {
+    self.capped_proper_trace_count += 1;
+    self.explored_trace_count += 1;
+  }
+
   pub fn incr_path_unsat(&mut self) {
     self.path_unsat_trace_count += 1;
     self.explored_trace_count += 1;
   }
 
+  /// A trace dropped as z3 could not decide its path. Kept ones are counted in
+  /// `unknown_trace_count` on top of being proper
+  pub fn incr_unknown(&mut self) {
+    self.unknown_trace_count += 1;
+    self.explored_trace_count += 1;
+  }
+
   pub fn incr_branch_explored(&mut self) {
     self.branch_explored_trace_count += 1;
     self.explored_trace_count += 1;
@@ -67,8 +147,133 @@ impl MetaData {
     self.explored_trace_count += 1;
   }
 
+  pub fn incr_memory_limit(&mut self) {
+    self.memory_limit_trace_count += 1;
+    self.explored_trace_count += 1;
+  }
+
   pub fn incr_unreachable(&mut self) {
     self.unreachable_trace_count += 1;
     self.explored_trace_count += 1;
   }
+
+  pub fn incr_unwound(&mut self) {
+    self.unwound_trace_count += 1;
+    self.explored_trace_count += 1;
+  }
+
+  pub fn incr_error(&mut self) {
+    self.error_trace_count += 1;
+    self.explored_trace_count += 1;
+  }
+
+  pub fn update_work_list_size(&mut self, size: usize) {
+    self.max_work_list_size = self.max_work_list_size.max(size);
+  }
+
+  /// The exploration statistics, dumped as `meta.json` next to the traces of
+  /// each slice and of each target, and as `<slice_id>.meta.json` next to the
+  /// jsonl file of a slice
+  pub fn to_json(&self) -> serde_json::Value {
+    json!({
+      "proper_trace_count": self.proper_trace_count,
+      "path_unsat_trace_count": self.path_unsat_trace_count,
+      "unknown_trace_count": self.unknown_trace_count,
+      "branch_explored_trace_count": self.branch_explored_trace_count,
+      "duplicate_trace_count": self.duplicate_trace_count,
+      "no_target_trace_count": self.no_target_trace_count,
+      "exceeding_length_trace_count": self.exceeding_length_trace_count,
+      "memory_limit_trace_count": self.memory_limit_trace_count,
+      "unreachable_trace_count": self.unreachable_trace_count,
+      "unwound_trace_count": self.unwound_trace_count,
+      "error_trace_count": self.error_trace_count,
+      "explored_trace_count": self.explored_trace_count,
+      "capped_proper_trace_count": self.capped_proper_trace_count,
+      "negative_no_target_trace_count": self.negative_no_target_trace_count,
+      "negative_path_unsat_trace_count": self.negative_path_unsat_trace_count,
+      "reduced_node_count": self.reduced_node_count,
+      "constraint_truncated_trace_count": self.constraint_truncated_trace_count,
+      "merged_state_count": self.merged_state_count,
+      "reported_unreachable_count": self.reported_unreachable_count,
+      "resumed_slice_count": self.resumed_slice_count,
+      "panicked_slice_count": self.panicked_slice_count,
+      "truncated": self.truncated,
+      "elapsed_ms": self.elapsed_ms,
+      "max_work_list_size": self.max_work_list_size,
+    })
+  }
+
+  /// The statistics dumped by `to_json`. Slice timings and coverage are not dumped
+  /// and are left empty
+  pub fn from_json(json: &serde_json::Value) -> Self {
+    let count = |name: &str| json[name].as_u64().unwrap_or(0) as usize;
+    MetaData {
+      proper_trace_count: count("proper_trace_count"),
+      path_unsat_trace_count: count("path_unsat_trace_count"),
+      unknown_trace_count: count("unknown_trace_count"),
+      branch_explored_trace_count: count("branch_explored_trace_count"),
+      duplicate_trace_count: count("duplicate_trace_count"),
+      no_target_trace_count: count("no_target_trace_count"),
+      exceeding_length_trace_count: count("exceeding_length_trace_count"),
+      memory_limit_trace_count: count("memory_limit_trace_count"),
+      unreachable_trace_count: count("unreachable_trace_count"),
+      unwound_trace_count: count("unwound_trace_count"),
+      error_trace_count: count("error_trace_count"),
+      explored_trace_count: count("explored_trace_count"),
+      capped_proper_trace_count: count("capped_proper_trace_count"),
+      negative_no_target_trace_count: count("negative_no_target_trace_count"),
+      negative_path_unsat_trace_count: count("negative_path_unsat_trace_count"),
+      reduced_node_count: count("reduced_node_count"),
+      constraint_truncated_trace_count: count("constraint_truncated_trace_count"),
+      merged_state_count: count("merged_state_count"),
+      reported_unreachable_count: count("reported_unreachable_count"),
+      resumed_slice_count: count("resumed_slice_count"),
+      panicked_slice_count: count("panicked_slice_count"),
+      truncated: json["truncated"].as_bool().unwrap_or(false),
+      elapsed_ms: json["elapsed_ms"].as_f64().unwrap_or(0.0),
+      max_work_list_size: count("max_work_list_size"),
+      ..MetaData::new()
+    }
+  }
+
+  /// The `n` slowest slices, slowest first
+  pub fn slowest_slices(&self, n: usize) -> Vec<&SliceTiming> {
+    let mut timings = self.slice_timings.iter().collect::<Vec<_>>();
+    timings.sort_by(|a, b| b.elapsed_ms.partial_cmp(&a.elapsed_ms).unwrap());
+    timings.into_iter().take(n).collect()
+  }
+}
+
+/// The outcome of a full run, dumped to `run-summary.json` for CI and dashboards
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+  pub metadata: MetaData,
+  pub num_targets: usize,
+  pub num_slices: usize,
+  pub wall_clock_ms: f64,
+  /// The command line arguments of the run, to reproduce it
+  pub args: Vec<String>,
+  /// The options parsed from the arguments, defaults included
+  pub options: serde_json::Value,
+}
+
+impl RunSummary {
+  /// The number of traces written to disk, proper and negative ones
+  pub fn num_dumped_traces(&self) -> usize {
+    self.metadata.proper_trace_count
+      + self.metadata.negative_no_target_trace_count
+      + self.metadata.negative_path_unsat_trace_count
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    json!({
+      "counts": self.metadata.to_json(),
+      "num_targets": self.num_targets,
+      "num_slices": self.num_slices,
+      "num_dumped_traces": self.num_dumped_traces(),
+      "wall_clock_ms": self.wall_clock_ms,
+      "args": self.args,
+      "options": self.options,
+    })
+  }
 }
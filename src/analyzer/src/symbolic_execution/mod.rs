@@ -1,21 +1,31 @@
 mod block_tracer;
+mod canonical;
 mod constraints;
+mod coverage;
 mod environment;
 mod execution;
+mod fold;
 mod memory;
 mod metadata;
 mod options;
+mod seed;
 mod state;
+mod summary;
 mod trace;
 mod work;
 
 pub use block_tracer::*;
+pub use canonical::*;
 pub use constraints::*;
+pub use coverage::*;
 pub use environment::*;
 pub use execution::*;
+pub use fold::*;
 pub use memory::*;
 pub use metadata::*;
 pub use options::*;
+pub use seed::*;
 pub use state::*;
+pub use summary::*;
 pub use trace::*;
 pub use work::*;
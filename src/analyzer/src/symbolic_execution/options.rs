@@ -7,12 +7,20 @@ pub trait SymbolicExecutionOptions: GeneralOptions + IOOptions + Send + Sync {
 
   fn no_random_work(&self) -> bool;
 
+  fn sample_branches(&self) -> bool;
+
   fn max_node_per_trace(&self) -> usize;
 
+  fn max_memory_cells(&self) -> Option<usize>;
+
   fn max_explored_trace_per_slice(&self) -> usize;
 
   fn max_trace_per_slice(&self) -> usize;
 
+  fn collect_negative(&self) -> bool;
+
+  fn max_negative_trace_per_slice(&self) -> usize;
+
   fn no_trace_reduction(&self) -> bool;
 
   fn no_prefilter_block_trace(&self) -> bool;
@@ -20,4 +28,48 @@ pub trait SymbolicExecutionOptions: GeneralOptions + IOOptions + Send + Sync {
   fn print_block_trace(&self) -> bool;
 
   fn print_trace(&self) -> bool;
+
+  fn timing_report(&self) -> Option<usize>;
+
+  fn dump_dot(&self) -> bool;
+
+  fn dump_memory(&self) -> bool;
+
+  fn block_trace_only(&self) -> bool;
+
+  fn reverse_traces(&self) -> bool;
+
+  fn trace_format(&self) -> TraceFormat;
+
+  fn slice_shard(&self) -> Option<SliceShard>;
+
+  fn trace_keep_kinds(&self) -> &Option<String>;
+
+  fn max_constraints(&self) -> Option<usize>;
+
+  fn seed_file(&self) -> &Option<String>;
+
+  fn merge_states(&self) -> bool;
+
+  fn report_unreachable(&self) -> bool;
+
+  fn keep_going(&self) -> bool;
+
+  fn opaque_functions(&self) -> &Option<String>;
+
+  fn exclude_intrinsics_list(&self) -> &Option<String>;
+
+  fn noreturn_functions(&self) -> &Option<String>;
+
+  fn resume(&self) -> bool;
+
+  fn global_timeout_sec(&self) -> Option<u64>;
+
+  fn coverage_report(&self) -> &Option<String>;
+
+  fn budget_file(&self) -> &Option<String>;
+
+  fn solver_rlimit(&self) -> Option<u32>;
+
+  fn keep_unknown(&self) -> bool;
 }
@@ -0,0 +1,29 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::semantics::rced::*;
+use crate::utils::*;
+
+/// Concrete values to start the execution of every slice with. Arguments of the
+/// slice entry are keyed by their index and globals by their name, e.g.
+/// `{ "arguments": { "0": { "Int": 3 } }, "globals": { "verbose": { "Int": 1 } } }`.
+/// Whatever is not seeded stays symbolic.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SeedValues {
+  #[serde(default)]
+  pub arguments: HashMap<usize, Rc<Value>>,
+  #[serde(default)]
+  pub globals: HashMap<String, Rc<Value>>,
+}
+
+impl SeedValues {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn load(path: &String) -> Result<Self, String> {
+    load_json_t(&PathBuf::from(path)).map_err(|err| format!("Cannot load seed file {}: {}", path, err))
+  }
+}
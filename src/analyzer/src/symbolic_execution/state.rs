@@ -1,35 +1,51 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use llir::values::*;
 
 use super::block_tracer::*;
 use super::constraints::*;
 use super::memory::*;
+use super::seed::*;
 use super::trace::*;
 use crate::semantics::rced::*;
 use crate::slicer::*;
+use crate::utils::*;
 
 #[derive(Clone, Debug)]
 pub enum FinishState {
   ProperlyReturned,
   BranchExplored,
   ExceedingMaxTraceLength,
+  MemoryLimit,
   Unreachable,
+  Unwound,
+  Error(String),
 }
 
+/// The state of a single execution path. Memory, heap, trace, visited branches
+/// and constraints are shared copy-on-write between forked states, so cloning a
+/// state on a branch does not deep-copy them.
 #[derive(Clone, Debug)]
 pub struct State<'ctx> {
   pub stack: Stack<'ctx>,
-  pub memory: Memory,
+  pub memory: Shared<Memory>,
+  pub heap: Shared<Heap>,
   pub block_trace_iter: BlockTraceIterator<'ctx>,
-  pub visited_branch: VisitedBranch<'ctx>,
-  pub trace: Trace<'ctx>,
+  pub visited_branch: Shared<VisitedBranch<'ctx>>,
+  pub trace: Shared<Trace<'ctx>>,
   pub target_node: Option<usize>,
+  pub pre_call_memory: Option<Shared<Memory>>,
   pub statically_checked: bool,
   pub prev_block: Option<Block<'ctx>>,
   pub finish_state: FinishState,
   pub pointer_value_id_map: HashMap<GenericValue<'ctx>, usize>,
-  pub constraints: Constraints,
+  pub constraints: Shared<Constraints>,
+  pub constraint_truncated: bool,
+
+  /// Stack slots whose address left the frame, e.g. passed to a callee not
+  /// stepped into or stored to memory, which may have been written through it
+  pub escaped_allocas: Shared<HashSet<Rc<Value>>>,
 
   // Identifiers
   alloca_id: usize,
@@ -41,16 +57,20 @@ impl<'ctx> State<'ctx> {
   pub fn new(slice: &Slice<'ctx>) -> Self {
     Self {
       stack: vec![StackFrame::entry(slice.entry)],
-      memory: Memory::new(),
+      memory: Shared::new(Memory::new()),
+      heap: Shared::new(Heap::new()),
       block_trace_iter: BlockTraceIterator::empty(),
-      visited_branch: VisitedBranch::new(),
-      trace: Vec::new(),
+      visited_branch: Shared::new(VisitedBranch::new()),
+      trace: Shared::new(Vec::new()),
       target_node: None,
+      pre_call_memory: None,
       statically_checked: false,
       prev_block: None,
       finish_state: FinishState::ProperlyReturned,
       pointer_value_id_map: HashMap::new(),
-      constraints: Vec::new(),
+      constraints: Shared::new(Vec::new()),
+      constraint_truncated: false,
+      escaped_allocas: Shared::new(HashSet::new()),
       alloca_id: 0,
       symbol_id: 0,
       pointer_value_id: 0,
@@ -60,22 +80,40 @@ impl<'ctx> State<'ctx> {
   pub fn from_block_trace(slice: &Slice<'ctx>, block_trace: BlockTrace<'ctx>) -> Self {
     Self {
       stack: vec![StackFrame::entry(slice.entry)],
-      memory: Memory::new(),
+      memory: Shared::new(Memory::new()),
+      heap: Shared::new(Heap::new()),
       block_trace_iter: BlockTraceIterator::from_block_trace(block_trace),
-      visited_branch: VisitedBranch::new(),
-      trace: Vec::new(),
+      visited_branch: Shared::new(VisitedBranch::new()),
+      trace: Shared::new(Vec::new()),
       target_node: None,
+      pre_call_memory: None,
       statically_checked: false,
       prev_block: None,
       finish_state: FinishState::ProperlyReturned,
       pointer_value_id_map: HashMap::new(),
-      constraints: Vec::new(),
+      constraints: Shared::new(Vec::new()),
+      constraint_truncated: false,
+      escaped_allocas: Shared::new(HashSet::new()),
       alloca_id: 0,
       symbol_id: 0,
       pointer_value_id: 0,
     }
   }
 
+  /// Replace the seeded entry arguments and globals by their concrete values
+  pub fn with_seed(mut self, seed: &SeedValues) -> Self {
+    let arguments = &mut self.stack[0].arguments;
+    for (index, value) in &seed.arguments {
+      if let Some(argument) = arguments.get_mut(*index) {
+        *argument = value.clone();
+      }
+    }
+    for (name, value) in &seed.globals {
+      self.memory.insert(Rc::new(Value::Glob(name.clone())), value.clone());
+    }
+    self
+  }
+
   pub fn new_alloca_id(&mut self) -> usize {
     let result = self.alloca_id;
     self.alloca_id += 1;
@@ -88,7 +126,154 @@ impl<'ctx> State<'ctx> {
     result
   }
 
-  pub fn add_constraint(&mut self, cond: Comparison, branch: bool) {
-    self.constraints.push(Constraint { cond, branch });
+  /// Continue numbering symbols after the ones created by `other`
+  pub fn sync_symbol_id(&mut self, other: &State<'ctx>) {
+    self.symbol_id = self.symbol_id.max(other.symbol_id);
+  }
+
+  /// Mark the stack slot `pointer` points into as escaped, looking through the
+  /// GEPs taken on its address
+  pub fn escape_alloca(&mut self, pointer: &Rc<Value>) {
+    match &**pointer {
+      Value::Alloc(_) => {
+        self.escaped_allocas.insert(pointer.clone());
+      }
+      Value::GEP { loc, .. } | Value::Field { loc, .. } => self.escape_alloca(loc),
+      _ => {}
+    }
+  }
+
+  /// The value held by a cell never stored to. A stack slot no callee could
+  /// have written is uninitialized, and cells that are the same in every trace
+  /// get a symbol named after them
+  pub fn initial_value(&mut self, location: &Rc<Value>) -> Rc<Value> {
+    match &**location {
+      Value::Alloc(_) if !self.escaped_allocas.contains(location) => Rc::new(Value::Undef(location.clone())),
+      _ if location.is_nameable_location() => Rc::new(Value::LocSym(location.clone())),
+      _ => Rc::new(Value::Sym(self.new_symbol_id())),
+    }
+  }
+
+  /// Merge the two arms of a branch on `cond`, both executed from this state,
+  /// back into this state. Memory cells the arms disagree on hold a
+  /// `Value::Select` guarded by the condition. Returns the cells stored to by
+  /// either arm along with their merged values, the then arm's first
+  pub fn merge_arms(
+    &mut self,
+    cond: &Rc<Value>,
+    then_state: &State<'ctx>,
+    else_state: &State<'ctx>,
+  ) -> Vec<(Rc<Value>, Rc<Value>)> {
+    self.sync_symbol_id(then_state);
+    self.sync_symbol_id(else_state);
+    self.alloca_id = self.alloca_id.max(then_state.alloca_id).max(else_state.alloca_id);
+
+    // A stack slot escaping in either arm may have been written through its address
+    let escaped = then_state.escaped_allocas.union(&else_state.escaped_allocas);
+    self.escaped_allocas.extend(escaped.cloned());
+
+    // Memory cells, where a cell missing from one arm still holds its initial value.
+    // When the other arm read that initial value before writing the cell, both arms
+    // share it, so that a cell only read by one arm stays as it is
+    let arms_begin = self.trace.len();
+    let mut locations = then_state.memory.keys().cloned().collect::<Vec<_>>();
+    for location in else_state.memory.keys() {
+      if !then_state.memory.contains_key(location) {
+        locations.push(location.clone());
+      }
+    }
+    for location in locations {
+      let (then_val, else_val) = match (then_state.memory.get(&location), else_state.memory.get(&location)) {
+        (Some(then_val), Some(else_val)) => (then_val.clone(), else_val.clone()),
+        (Some(then_val), None) => {
+          let else_val = match initially_read(then_state, arms_begin, &location) {
+            Some(value) => value,
+            None => self.initial_value(&location),
+          };
+          (then_val.clone(), else_val)
+        }
+        (None, Some(else_val)) => {
+          let then_val = match initially_read(else_state, arms_begin, &location) {
+            Some(value) => value,
+            None => self.initial_value(&location),
+          };
+          (then_val, else_val.clone())
+        }
+        (None, None) => continue,
+      };
+      self.memory.insert(location, select(cond, then_val, else_val));
+    }
+
+    // Values of the instructions of the arms are not used past the join, but
+    // allocas may have been bound to their stored values in either arm
+    let frame = &mut self.stack.top_mut().memory;
+    let then_frame = &then_state.stack.top().memory;
+    let else_frame = &else_state.stack.top().memory;
+    for (instr, then_val) in then_frame {
+      let value = match (&**then_val, else_frame.get(instr).map(|v| &**v)) {
+        (Value::AllocOf(then_val), Some(Value::AllocOf(else_val))) => {
+          Rc::new(Value::AllocOf(select(cond, then_val.clone(), else_val.clone())))
+        }
+        _ => then_val.clone(),
+      };
+      frame.insert(*instr, value);
+    }
+    for (instr, else_val) in else_frame {
+      if !then_frame.contains_key(instr) {
+        frame.insert(*instr, else_val.clone());
+      }
+    }
+
+    let mut stored: Vec<Rc<Value>> = vec![];
+    for arm in &[then_state, else_state] {
+      for node in &arm.trace[arms_begin..] {
+        if let Semantics::Store { loc, .. } = &node.semantics {
+          if !stored.contains(loc) {
+            stored.push(loc.clone());
+          }
+        }
+      }
+    }
+    stored
+      .into_iter()
+      .filter_map(|loc| {
+        let val = self.memory.get(&loc).cloned()?;
+        Some((loc, val))
+      })
+      .collect()
+  }
+
+  /// Add a path constraint, unless the state already holds `max_constraints`
+  /// of them; further branches are then left unconstrained
+  pub fn add_constraint(&mut self, cond: Comparison, branch: bool, max_constraints: Option<usize>) {
+    match max_constraints {
+      Some(max) if self.constraints.len() >= max => self.constraint_truncated = true,
+      _ => self.constraints.push(Constraint { cond, branch }),
+    }
+  }
+}
+
+/// The initial value of a cell as read by an arm, when the arm loaded the cell
+/// before storing to it
+fn initially_read<'ctx>(arm: &State<'ctx>, arm_begin: usize, location: &Rc<Value>) -> Option<Rc<Value>> {
+  for node in &arm.trace[arm_begin..] {
+    match &node.semantics {
+      Semantics::Load { loc } if loc == location => return node.result.clone(),
+      Semantics::Store { loc, .. } if loc == location => return None,
+      _ => {}
+    }
+  }
+  None
+}
+
+fn select(cond: &Rc<Value>, then_val: Rc<Value>, else_val: Rc<Value>) -> Rc<Value> {
+  if then_val == else_val {
+    then_val
+  } else {
+    Rc::new(Value::Select {
+      cond: cond.clone(),
+      then_val,
+      else_val,
+    })
   }
 }
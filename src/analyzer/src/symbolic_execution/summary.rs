@@ -0,0 +1,107 @@
+use llir::values::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::memory::*;
+use super::state::*;
+use super::trace::*;
+use crate::semantics::rced::*;
+
+/// A summary models a call to a function we do not step into. It is given the
+/// call instruction, the generic call result (`None` for void functions) and
+/// the call arguments, may update the state, and returns the result to store
+/// for the call
+pub type Summary =
+  for<'ctx> fn(Instruction<'ctx>, Option<Rc<Value>>, &[Rc<Value>], &mut State<'ctx>) -> Option<Rc<Value>>;
+
+/// Functions known to never return, whose calls end the trace
+pub const NORETURN_FUNCTIONS: &[&str] = &[
+  "exit",
+  "_exit",
+  "_Exit",
+  "quick_exit",
+  "abort",
+  "__assert_fail",
+  "__assert_rtn",
+  "__stack_chk_fail",
+  "panic",
+];
+
+/// Summaries keyed on callee name
+pub struct Summaries {
+  summaries: HashMap<String, Summary>,
+}
+
+impl Summaries {
+  pub fn new() -> Self {
+    Self {
+      summaries: HashMap::new(),
+    }
+  }
+
+  /// The built-in summaries of common libc functions
+  pub fn libc() -> Self {
+    let mut summaries = Self::new();
+    summaries.insert("malloc", alloc_summary);
+    summaries.insert("calloc", alloc_summary);
+    summaries.insert("free", free_summary);
+    summaries.insert("memcmp", compare_summary);
+    summaries.insert("strcmp", compare_summary);
+    summaries.insert("strncmp", compare_summary);
+    summaries
+  }
+
+  pub fn insert(&mut self, name: &str, summary: Summary) {
+    self.summaries.insert(name.to_string(), summary);
+  }
+
+  pub fn get(&self, name: &str) -> Option<Summary> {
+    self.summaries.get(name).cloned()
+  }
+}
+
+/// The call result is a fresh heap pointer, since its call id is unique
+pub fn alloc_summary<'ctx>(
+  _: Instruction<'ctx>,
+  result: Option<Rc<Value>>,
+  _: &[Rc<Value>],
+  state: &mut State<'ctx>,
+) -> Option<Rc<Value>> {
+  if let Some(result) = &result {
+    state.heap.insert(result.clone(), HeapStatus::Allocated);
+  }
+  result
+}
+
+/// Mark the pointer passed as the first argument as freed, and record the free
+/// in the trace right after the call
+pub fn free_summary<'ctx>(
+  instr: Instruction<'ctx>,
+  result: Option<Rc<Value>>,
+  args: &[Rc<Value>],
+  state: &mut State<'ctx>,
+) -> Option<Rc<Value>> {
+  if let Some(ptr) = args.first() {
+    state.heap.insert(ptr.clone(), HeapStatus::Freed);
+    state.trace.push(TraceNode {
+      instr,
+      semantics: Semantics::Free { loc: ptr.clone() },
+      result: None,
+    });
+  }
+  result
+}
+
+/// Comparing a value with itself gives 0, otherwise the call result stays a
+/// symbolic comparison value
+pub fn compare_summary<'ctx>(
+  _: Instruction<'ctx>,
+  result: Option<Rc<Value>>,
+  args: &[Rc<Value>],
+  _: &mut State<'ctx>,
+) -> Option<Rc<Value>> {
+  match (result, args) {
+    (Some(_), [op0, op1, ..]) if op0 == op1 => Some(Rc::new(Value::Int(0))),
+    (result, _) => result,
+  }
+}
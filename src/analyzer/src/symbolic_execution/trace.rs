@@ -1,9 +1,14 @@
 // use std::collections::HashSet;
 use llir::values::*;
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
-use crate::semantics::rced::*;
+use super::canonical::*;
+use super::constraints::*;
+use super::memory::*;
+use crate::semantics::{rced::*, SEMANTICS_KINDS};
+use crate::utils::{FunctionUtil, InstructionUtil};
 
 #[derive(Clone, Debug)]
 pub struct TraceNode<'ctx> {
@@ -14,34 +19,254 @@ pub struct TraceNode<'ctx> {
 
 pub type Trace<'ctx> = Vec<TraceNode<'ctx>>;
 
+/// Negative traces that returned properly without ever reaching the target
+pub const NO_TARGET: &str = "no_target";
+
+/// Negative traces that reached the target along an unsatisfiable path
+pub const PATH_UNSAT: &str = "path_unsat";
+
+/// Parse a comma separated list of semantics kinds, rejecting unknown ones
+pub fn parse_semantics_kinds(list: &str) -> Result<HashSet<String>, String> {
+  list
+    .split(',')
+    .map(|kind| kind.trim())
+    .filter(|kind| !kind.is_empty())
+    .map(|kind| {
+      if SEMANTICS_KINDS.contains(&kind) {
+        Ok(kind.to_string())
+      } else {
+        Err(format!(
+          "Unknown trace node kind `{}`, expected one of {}",
+          kind,
+          SEMANTICS_KINDS.join(", ")
+        ))
+      }
+    })
+    .collect()
+}
+
+pub fn trace_instrs_json<'ctx>(trace: &Trace<'ctx>) -> serde_json::Value {
+  json!(trace
+    .iter()
+    .map(|node| json!({
+      "loc": node.instr.trace_loc_string(),
+      "sem": node.semantics,
+      "res": node.result
+    }))
+    .collect::<Vec<_>>())
+}
+
+/// Render trace nodes, given as their location, semantics and result, the way
+/// `TraceWithTarget::to_text` does. Shared with traces read back from disk, which
+/// have no instructions left
+pub fn trace_nodes_text<'a, I>(nodes: I, target_index: usize) -> String
+where
+  I: Iterator<Item = (String, &'a Semantics, Option<&'a Rc<Value>>)>,
+{
+  let mut renaming = SymbolRenaming::new();
+  nodes
+    .enumerate()
+    .map(|(i, (loc, semantics, result))| {
+      let prefix = if i == target_index { "-> TARGET " } else { "" };
+      let semantics = renaming.rename_semantics(semantics).to_display_string();
+      match result {
+        Some(result) => format!(
+          "{}{} {} -> {}\n",
+          prefix,
+          loc,
+          semantics,
+          renaming.rename_value(result).to_display_string()
+        ),
+        None => format!("{}{} {}\n", prefix, loc, semantics),
+      }
+    })
+    .collect()
+}
+
 pub struct TraceWithTarget<'ctx> {
   pub trace: Trace<'ctx>,
   pub target_index: usize,
   pub statically_checked: bool,
+  pub constraints: Constraints,
+  pub pre_call_memory: Option<Memory>,
+  pub constraint_truncated: bool,
 }
 
 impl<'ctx> TraceWithTarget<'ctx> {
-  pub fn new(trace: Trace<'ctx>, target_index: usize, statically_checked: bool) -> Self {
-    Self { trace, target_index, statically_checked }
+  pub fn new(trace: Trace<'ctx>, target_index: usize, statically_checked: bool, constraints: Constraints) -> Self {
+    Self {
+      trace,
+      target_index,
+      statically_checked,
+      constraints,
+      pre_call_memory: None,
+      constraint_truncated: false,
+    }
+  }
+
+  /// Attach the memory right before the target call, dumped under `--dump-memory`
+  pub fn with_pre_call_memory(self, pre_call_memory: Option<Memory>) -> Self {
+    Self {
+      pre_call_memory,
+      ..self
+    }
+  }
+
+  /// Mark the path constraints as incomplete, as cut off by `--max-constraints`
+  pub fn with_constraint_truncated(self, constraint_truncated: bool) -> Self {
+    Self {
+      constraint_truncated,
+      ..self
+    }
   }
 
   pub fn target(&self) -> &TraceNode<'ctx> {
     &self.trace[self.target_index]
   }
 
+  /// Reduce the trace to the nodes related to the target: the target itself,
+  /// the nodes it transitively depends on, and the nodes transitively depending
+  /// on it. Branch and return nodes are always kept so that the path leading to
+  /// the target and the control flow around it stay intact, and so are free
+  /// nodes so that uses after free can still be told apart.
   pub fn reduce(self) -> Self {
-    self
+    let mut preds: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut succs: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (from, to) in self.def_use_edges() {
+      preds.entry(to).or_insert(vec![]).push(from);
+      succs.entry(from).or_insert(vec![]).push(to);
+    }
+
+    // Collect the nodes backward and forward reachable from the target
+    let mut related = HashSet::new();
+    related.insert(self.target_index);
+    for graph in vec![&preds, &succs] {
+      let mut fringe = vec![self.target_index];
+      while let Some(i) = fringe.pop() {
+        for j in graph.get(&i).into_iter().flatten() {
+          if related.insert(*j) {
+            fringe.push(*j);
+          }
+        }
+      }
+    }
+
+    self.retain_nodes(|i, node| {
+      related.contains(&i)
+        || match node.semantics {
+          Semantics::CondBr { .. }
+          | Semantics::UncondBr { .. }
+          | Semantics::Switch { .. }
+          | Semantics::Merge { .. }
+          | Semantics::Ret { .. }
+          | Semantics::Free { .. } => true,
+          _ => false,
+        }
+    })
+  }
+
+  /// Only keep the nodes of the given kinds (see `Semantics::kind`), along with
+  /// the target, the branch nodes needed by control flow features and the free
+  /// nodes the use-after-free features are keyed on
+  pub fn retain_kinds(self, kinds: &HashSet<String>) -> Self {
+    let target_index = self.target_index;
+    self.retain_nodes(|i, node| {
+      let kind = node.semantics.kind();
+      i == target_index || kind == "branch" || kind == "free" || kinds.contains(kind)
+    })
+  }
+
+  /// Filter the trace, always keeping the target, and recompute the target index
+  fn retain_nodes<F>(self, keep: F) -> Self
+  where
+    F: Fn(usize, &TraceNode<'ctx>) -> bool,
+  {
+    let Self {
+      trace,
+      target_index,
+      statically_checked,
+      constraints,
+      pre_call_memory,
+      constraint_truncated,
+    } = self;
+    let mut new_target_index = 0;
+    let trace = trace
+      .into_iter()
+      .enumerate()
+      .filter(|(i, node)| *i == target_index || keep(*i, node))
+      .enumerate()
+      .map(|(new_i, (i, node))| {
+        if i == target_index {
+          new_target_index = new_i;
+        }
+        node
+      })
+      .collect();
+    Self::new(trace, new_target_index, statically_checked, constraints)
+      .with_pre_call_memory(pre_call_memory)
+      .with_constraint_truncated(constraint_truncated)
   }
 
   pub fn to_json(&self) -> serde_json::Value {
-    json!({
-      "instrs": self.trace.iter().map(|node| json!({
-        "loc": node.instr.debug_loc_string(),
-        "sem": node.semantics,
-        "res": node.result
-      })).collect::<Vec<_>>(),
+    let mut json = json!({
+      "instrs": trace_instrs_json(&self.trace),
       "target": self.target_index,
       "statically_checked": self.statically_checked,
+      "cons": self.constraints,
+    });
+    if self.constraint_truncated {
+      json["constraint_truncated"] = json!(true);
+    }
+    if let Some(memory) = &self.pre_call_memory {
+      let mut cells = memory.iter().collect::<Vec<_>>();
+      cells.sort_by_cached_key(|(loc, _)| loc.to_display_string());
+      json["pre_call_memory"] = json!(cells
+        .into_iter()
+        .map(|(loc, val)| json!({ "loc": loc, "val": val }))
+        .collect::<Vec<_>>());
+    }
+    json
+  }
+
+  /// The json dumped under `--reverse-traces`: all the nodes from the end of the
+  /// trace back to the entry, with the target index counted in that order
+  pub fn to_reversed_json(&self) -> serde_json::Value {
+    let mut json = self.to_json();
+    let instrs = json["instrs"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .rev()
+      .cloned()
+      .collect::<Vec<_>>();
+    json["target"] = json!(instrs.len() - 1 - self.target_index);
+    json["instrs"] = json!(instrs);
+    json["reversed"] = json!(true);
+    json
+  }
+
+  /// The lighter json dumped under `--block-trace-only`: the visited blocks, each
+  /// as its function name and its index in the function, and the position of the
+  /// target's block among them
+  pub fn to_block_trace_json(&self) -> serde_json::Value {
+    let mut blocks: Vec<Block<'ctx>> = vec![];
+    let mut target = 0;
+    for (i, node) in self.trace.iter().enumerate() {
+      let curr_block = node.instr.parent_block();
+      if blocks.last() != Some(&curr_block) {
+        blocks.push(curr_block);
+      }
+      if i == self.target_index {
+        target = blocks.len() - 1;
+      }
+    }
+    json!({
+      "blocks": blocks.into_iter().map(|block| {
+        let func = block.parent_function();
+        let index = func.iter_blocks().position(|b| b == block).unwrap();
+        json!({ "func": func.simp_name(), "block": index })
+      }).collect::<Vec<_>>(),
+      "target": target,
     })
   }
 
@@ -56,15 +281,103 @@ impl<'ctx> TraceWithTarget<'ctx> {
     bt
   }
 
-  pub fn print(&self) {
+  /// Rename the symbols in the trace and its constraints to a trace-local normal
+  /// form. Nodes keep their positions so the target index stays valid.
+  pub fn canonicalize(self) -> Self {
+    let mut renaming = SymbolRenaming::new();
+    let trace = self
+      .trace
+      .iter()
+      .map(|node| TraceNode {
+        instr: node.instr,
+        semantics: renaming.rename_semantics(&node.semantics),
+        result: node.result.as_ref().map(|r| renaming.rename_value(r)),
+      })
+      .collect();
+    let constraints = self
+      .constraints
+      .iter()
+      .map(|Constraint { cond, branch }| Constraint {
+        cond: renaming.rename_comparison(cond),
+        branch: *branch,
+      })
+      .collect();
+
+    // Memory cells are renamed in a fixed order, as the map has none
+    let pre_call_memory = self.pre_call_memory.as_ref().map(|memory| {
+      let mut cells = memory.iter().collect::<Vec<_>>();
+      cells.sort_by_cached_key(|cell| format!("{:?}", cell));
+      cells
+        .into_iter()
+        .map(|(loc, val)| (renaming.rename_value(loc), renaming.rename_value(val)))
+        .collect()
+    });
+    Self::new(trace, self.target_index, self.statically_checked, constraints)
+      .with_pre_call_memory(pre_call_memory)
+      .with_constraint_truncated(self.constraint_truncated)
+  }
+
+  /// Def-use edges `(from, to)` between trace nodes, where node `to` uses the
+  /// value produced by node `from`. Values are identified by their `Rc`, so a
+  /// value shared by several nodes maps to the first node producing it.
+  pub fn def_use_edges(&self) -> Vec<(usize, usize)> {
+    let mut producers: HashMap<*const Value, usize> = HashMap::new();
+    let mut edges = vec![];
     for (i, node) in self.trace.iter().enumerate() {
-      if i == self.target_index {
-        print!("-> TARGET ");
+      let mut used = HashSet::new();
+      for op in node.semantics.operands() {
+        if let Some(from) = producers.get(&Rc::as_ptr(op)) {
+          if used.insert(*from) {
+            edges.push((*from, i));
+          }
+        }
       }
-      match &node.result {
-        Some(result) => println!("{} {:?} -> {:?}", node.instr.debug_loc_string(), node.semantics, result),
-        None => println!("{} {:?}", node.instr.debug_loc_string(), node.semantics),
+      if let Some(result) = &node.result {
+        producers.entry(Rc::as_ptr(result)).or_insert(i);
       }
     }
+    edges
+  }
+
+  /// Render the trace as a DOT graph, with def-use edges between trace nodes
+  pub fn to_dot(&self) -> String {
+    let nodes = self
+      .trace
+      .iter()
+      .enumerate()
+      .map(|(i, node)| {
+        let label = format!("{} {:?}", node.instr.trace_loc_string(), node.semantics).replace('"', "\\\"");
+        if i == self.target_index {
+          format!(
+            "  n{} [label=\"[TARGET] {}\", style=filled, fillcolor=yellow];",
+            i, label
+          )
+        } else {
+          format!("  n{} [label=\"{}\"];", i, label)
+        }
+      })
+      .collect::<Vec<_>>();
+    let edges = self
+      .def_use_edges()
+      .into_iter()
+      .map(|(from, to)| format!("  n{} -> n{};", from, to))
+      .collect::<Vec<_>>();
+    format!("digraph trace {{\n{}\n{}\n}}\n", nodes.join("\n"), edges.join("\n"))
+  }
+
+  /// Render the trace one node per line, with symbols renumbered in trace-local
+  /// order so that equivalent traces render identically and can be diffed
+  pub fn to_text(&self) -> String {
+    trace_nodes_text(
+      self
+        .trace
+        .iter()
+        .map(|node| (node.instr.trace_loc_string(), &node.semantics, node.result.as_ref())),
+      self.target_index,
+    )
+  }
+
+  pub fn print(&self) {
+    print!("{}", self.to_text());
   }
 }
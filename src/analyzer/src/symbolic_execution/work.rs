@@ -10,15 +10,15 @@ pub struct Work<'ctx> {
 }
 
 impl<'ctx> Work<'ctx> {
-  pub fn entry(slice: &Slice<'ctx>) -> Self {
+  pub fn entry(slice: &Slice<'ctx>, seed: &SeedValues) -> Self {
     let block = slice.entry.first_block().unwrap();
-    let state = State::new(slice);
+    let state = State::new(slice).with_seed(seed);
     Self { block, state }
   }
 
-  pub fn entry_with_block_trace(slice: &Slice<'ctx>, block_trace: BlockTrace<'ctx>) -> Self {
+  pub fn entry_with_block_trace(slice: &Slice<'ctx>, block_trace: BlockTrace<'ctx>, seed: &SeedValues) -> Self {
     let block = slice.entry.first_block().unwrap();
-    let state = State::from_block_trace(slice, block_trace);
+    let state = State::from_block_trace(slice, block_trace).with_seed(seed);
     Self { block, state }
   }
 
@@ -0,0 +1,140 @@
+//! Compare the traces of two analysis runs, e.g. before and after changing an
+//! option, by the textual form of each trace
+
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::semantics::rced::*;
+use crate::symbolic_execution::trace_nodes_text;
+use crate::utils::*;
+
+/// A trace, as its target, slice id and trace id
+pub type TraceKey = (String, usize, usize);
+
+/// A dumped trace read back with the values of the executor, so that it renders
+/// through `trace_nodes_text` like the trace it was dumped from
+#[derive(Deserialize)]
+struct TextTrace {
+  /// The trace id, only stored in jsonl lines
+  #[serde(default)]
+  id: Option<usize>,
+  target: usize,
+  instrs: Vec<TextTraceNode>,
+}
+
+#[derive(Deserialize)]
+struct TextTraceNode {
+  loc: String,
+  sem: Semantics,
+  res: Option<Rc<Value>>,
+}
+
+#[derive(Debug, Default)]
+pub struct TraceDiffReport {
+  pub added: Vec<TraceKey>,
+  pub removed: Vec<TraceKey>,
+  pub changed: Vec<TraceKey>,
+  pub num_unchanged: usize,
+}
+
+impl TraceDiffReport {
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    let keys_json = |keys: &Vec<TraceKey>| {
+      keys
+        .iter()
+        .map(|(target, slice_id, trace_id)| json!({ "target": target, "slice_id": slice_id, "trace_id": trace_id }))
+        .collect::<Vec<_>>()
+    };
+    json!({
+      "num_added": self.added.len(),
+      "num_removed": self.removed.len(),
+      "num_changed": self.changed.len(),
+      "num_unchanged": self.num_unchanged,
+      "added": keys_json(&self.added),
+      "removed": keys_json(&self.removed),
+      "changed": keys_json(&self.changed),
+    })
+  }
+}
+
+/// Diff the traces under the `old_dir` and `new_dir` trace roots, matching traces
+/// by target, slice id and trace id. Traces failing to load are compared by their
+/// error so that they show up as changed when the other side loads
+pub fn diff_traces(old_dir: &Path, new_dir: &Path, ignore_version: bool) -> Result<TraceDiffReport, String> {
+  let mut old_traces = load_trace_texts(old_dir, ignore_version)?;
+  let new_traces = load_trace_texts(new_dir, ignore_version)?;
+  let mut report = TraceDiffReport::default();
+  for (key, new_text) in new_traces {
+    match old_traces.remove(&key) {
+      Some(old_text) if old_text == new_text => report.num_unchanged += 1,
+      Some(_) => report.changed.push(key),
+      None => report.added.push(key),
+    }
+  }
+  report.removed = old_traces.into_iter().map(|(key, _)| key).collect();
+  Ok(report)
+}
+
+/// The textual form of every trace under a trace root, keyed and sorted by trace
+fn load_trace_texts(dir: &Path, ignore_version: bool) -> Result<BTreeMap<TraceKey, String>, String> {
+  let mut texts = BTreeMap::new();
+  let mut add = |key: TraceKey, trace: Result<TextTrace, String>| {
+    let text = match trace {
+      Ok(trace) => trace_text(&trace),
+      Err(err) => err,
+    };
+    texts.insert(key, text);
+  };
+  for target_dir in sub_paths(dir)?.into_iter().filter(|path| path.is_dir()) {
+    let target = unsanitize_file_name(&target_dir.file_name().unwrap().to_string_lossy())?;
+    for path in sub_paths(&target_dir)? {
+      if path.is_dir() {
+        // `<slice_id>` folders of `<trace_id>.json(.gz)` files
+        if let Ok(slice_id) = path.file_name().unwrap().to_string_lossy().parse::<usize>() {
+          for (trace_id, trace_path) in json_files_in_dir(&path)? {
+            add(
+              (target.clone(), slice_id, trace_id),
+              load_versioned_json_t(&trace_path, ignore_version),
+            );
+          }
+        }
+      } else if let Some(slice_id) = jsonl_file_id(&path) {
+        // `<slice_id>.jsonl(.gz)` files with a trace per line
+        for (line, trace) in load_versioned_jsonl_t(&path, ignore_version)?.into_iter().enumerate() {
+          let trace: Result<TextTrace, String> = trace;
+          let trace_id = trace.as_ref().ok().and_then(|trace| trace.id).unwrap_or(line);
+          add((target.clone(), slice_id, trace_id), trace);
+        }
+      }
+    }
+  }
+  Ok(texts)
+}
+
+fn sub_paths(dir: &Path) -> Result<Vec<PathBuf>, String> {
+  let entries = fs::read_dir(dir).map_err(|err| format!("Cannot read traces directory {:?}: {}", dir, err))?;
+  entries
+    .map(|entry| entry.map(|entry| entry.path()).map_err(|err| err.to_string()))
+    .collect()
+}
+
+/// The trace one node per line, as `TraceWithTarget::to_text` renders it, so that
+/// traces only differing in how their symbol, alloca and call ids were allocated
+/// render the same
+fn trace_text(trace: &TextTrace) -> String {
+  trace_nodes_text(
+    trace
+      .instrs
+      .iter()
+      .map(|node| (node.loc.clone(), &node.sem, node.res.as_ref())),
+    trace.target,
+  )
+}
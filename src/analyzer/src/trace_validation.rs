@@ -0,0 +1,202 @@
+//! Re-validate dumped traces against the current `Semantics` definitions,
+//! catching serialization drift and partially written trace files
+
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::feature_extraction::*;
+use crate::semantics::boxed::*;
+use crate::utils::*;
+
+/// The outcome of validating all the traces under a directory. Corrupt traces
+/// cannot be loaded at all, while invalid ones load but break an invariant
+#[derive(Debug, Default)]
+pub struct TraceValidationReport {
+  pub num_traces: usize,
+  pub num_corrupt: usize,
+  pub num_invalid: usize,
+
+  /// The trace, as its file path with the line number for jsonl files, and why it is rejected
+  pub problems: Vec<(String, String)>,
+}
+
+impl TraceValidationReport {
+  pub fn is_ok(&self) -> bool {
+    self.problems.is_empty()
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    json!({
+      "num_traces": self.num_traces,
+      "num_corrupt": self.num_corrupt,
+      "num_invalid": self.num_invalid,
+      "problems": self.problems.iter().map(|(trace, reason)| json!({
+        "trace": trace,
+        "reason": reason,
+      })).collect::<Vec<_>>(),
+    })
+  }
+
+  fn add(&mut self, name: String, trace: Result<Trace, String>) {
+    self.num_traces += 1;
+    match trace {
+      Ok(trace) => {
+        let problems = trace_problems(&trace);
+        if !problems.is_empty() {
+          self.num_invalid += 1;
+        }
+        self
+          .problems
+          .extend(problems.into_iter().map(|reason| (name.clone(), reason)));
+      }
+      Err(err) => {
+        self.num_corrupt += 1;
+        self.problems.push((name, err));
+      }
+    }
+  }
+}
+
+/// Validate every trace under `dir`, both the `<id>.json(.gz)` files and the
+/// traces of `<slice_id>.jsonl(.gz)` files
+pub fn validate_traces(dir: &Path, ignore_version: bool) -> Result<TraceValidationReport, String> {
+  let mut report = TraceValidationReport::default();
+  let mut paths = vec![];
+  collect_trace_file_paths(dir, &mut paths)?;
+  paths.sort();
+  for path in paths {
+    if jsonl_file_id(&path).is_some() {
+      match load_versioned_jsonl_t(&path, ignore_version) {
+        Ok(traces) => {
+          for (line, trace) in traces.into_iter().enumerate() {
+            report.add(format!("{}:{}", path.display(), line + 1), trace);
+          }
+        }
+        Err(err) => report.add(path.display().to_string(), Err(err)),
+      }
+    } else {
+      report.add(path.display().to_string(), load_versioned_json_t(&path, ignore_version));
+    }
+  }
+  Ok(report)
+}
+
+fn collect_trace_file_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), String> {
+  let entries = fs::read_dir(dir).map_err(|err| format!("Cannot read traces directory {:?}: {}", dir, err))?;
+  for entry in entries {
+    let path = entry.map_err(|err| err.to_string())?.path();
+    if path.is_dir() {
+      collect_trace_file_paths(&path, paths)?;
+    } else if json_file_id(&path).is_some() || jsonl_file_id(&path).is_some() {
+      paths.push(path);
+    }
+  }
+  Ok(())
+}
+
+/// The invariants a trace breaks, if any:
+/// - the target index points to a call node;
+/// - all the occurrences of a call result refer to the same call;
+/// - the constraints only use symbols showing up in the nodes.
+pub fn trace_problems(trace: &Trace) -> Vec<String> {
+  let mut problems = vec![];
+  match trace.instrs.get(trace.target) {
+    Some(instr) => match &instr.sem {
+      Semantics::Call { .. } => {}
+      _ => problems.push(format!("Target node {} is not a call", trace.target)),
+    },
+    None => problems.push(format!(
+      "Target index {} out of range of {} nodes",
+      trace.target,
+      trace.instrs.len()
+    )),
+  }
+
+  // Symbols defined by the nodes, i.e. used as operands or produced as results
+  let mut symbols = HashSet::new();
+  for instr in &trace.instrs {
+    for op in instr.sem.operands() {
+      collect_symbols(op, &mut symbols);
+    }
+    if let Some(res) = &instr.res {
+      collect_symbols(res, &mut symbols);
+    }
+  }
+
+  let mut calls: HashMap<usize, &Value> = HashMap::new();
+  for &symbol in &symbols {
+    if let Value::Call { id, .. } = symbol {
+      if let Some(other) = calls.insert(*id, symbol) {
+        if other != symbol {
+          problems.push(format!("Call result {} refers to different calls", id));
+        }
+      }
+    }
+  }
+
+  for (i, constraint) in trace.cons.iter().enumerate() {
+    let (op0, op1) = match &constraint.cond {
+      Comparison::ICmp { op0, op1, .. } | Comparison::FCmp { op0, op1, .. } => (op0, op1),
+    };
+    let mut used = HashSet::new();
+    collect_symbols(op0, &mut used);
+    collect_symbols(op1, &mut used);
+    let mut undefined = used
+      .into_iter()
+      .filter(|symbol| !symbols.contains(symbol))
+      .map(|symbol| symbol.to_display_string())
+      .collect::<Vec<_>>();
+    undefined.sort();
+    if !undefined.is_empty() {
+      problems.push(format!("Constraint {} uses undefined {}", i, undefined.join(", ")));
+    }
+  }
+  problems.sort();
+  problems.dedup();
+  problems
+}
+
+/// The symbols inside a value: fresh symbols, symbols of locations, uninitialized
+/// contents and call results
+fn collect_symbols<'a>(value: &'a Value, symbols: &mut HashSet<&'a Value>) {
+  match value {
+    Value::Sym(_) | Value::LocSym(_) | Value::Undef(_) => {
+      symbols.insert(value);
+    }
+    Value::Call { args, .. } => {
+      symbols.insert(value);
+      for arg in args {
+        collect_symbols(arg, symbols);
+      }
+    }
+    Value::AllocOf(v) => collect_symbols(v, symbols),
+    Value::GEP { loc, indices } => {
+      collect_symbols(loc, symbols);
+      for index in indices {
+        collect_symbols(index, symbols);
+      }
+    }
+    Value::Field { loc, .. } => collect_symbols(loc, symbols),
+    Value::Bin { op0, op1, .. } | Value::ICmp { op0, op1, .. } | Value::FCmp { op0, op1, .. } => {
+      collect_symbols(op0, symbols);
+      collect_symbols(op1, symbols);
+    }
+    Value::Select {
+      cond,
+      then_val,
+      else_val,
+    } => {
+      collect_symbols(cond, symbols);
+      collect_symbols(then_val, symbols);
+      collect_symbols(else_val, symbols);
+    }
+    Value::Vector(lanes) | Value::Aggregate(lanes) => {
+      for lane in lanes {
+        collect_symbols(lane, symbols);
+      }
+    }
+    _ => {}
+  }
+}
@@ -0,0 +1,92 @@
+//! Flag the traces leaving the return value of a target unchecked, when most
+//! of the other traces of that target do check it
+
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::utils::*;
+
+/// The name of the `ReturnValueCheckFeatureExtractor` features in feature files
+const RETURN_VALUE_CHECK: &str = "ret.check";
+
+#[derive(Debug, Default)]
+pub struct UncheckedReturnReport {
+  /// The number of traces and of checking traces of each target with return value check features
+  pub check_counts: BTreeMap<String, (usize, usize)>,
+
+  /// The unchecked traces, as their target and feature file path
+  pub unchecked: Vec<(String, String)>,
+}
+
+impl UncheckedReturnReport {
+  pub fn check_rate(&self, target: &str) -> Option<f32> {
+    self
+      .check_counts
+      .get(target)
+      .map(|(num_traces, num_checked)| *num_checked as f32 / *num_traces as f32)
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    json!({
+      "targets": self.check_counts.iter().map(|(target, (num_traces, num_checked))| json!({
+        "target": target,
+        "num_traces": num_traces,
+        "num_checked": num_checked,
+      })).collect::<Vec<_>>(),
+      "unchecked": self.unchecked.iter().map(|(target, trace)| json!({
+        "target": target,
+        "trace": trace,
+      })).collect::<Vec<_>>(),
+    })
+  }
+}
+
+/// Go through the feature files under `features_dir`, one folder per target, and
+/// flag the traces not checking the return value of targets checked by at least
+/// `min_check_rate` of their traces. Targets always checked have nothing to flag
+pub fn detect_unchecked_returns(features_dir: &Path, min_check_rate: f32) -> Result<UncheckedReturnReport, String> {
+  let mut report = UncheckedReturnReport::default();
+  let entries =
+    fs::read_dir(features_dir).map_err(|err| format!("Cannot read features directory {:?}: {}", features_dir, err))?;
+  let mut target_dirs = entries
+    .map(|entry| entry.map(|entry| entry.path()).map_err(|err| err.to_string()))
+    .collect::<Result<Vec<_>, _>>()?;
+  target_dirs.retain(|path| path.is_dir());
+  target_dirs.sort();
+
+  for target_dir in target_dirs {
+    let file_name = target_dir.file_name().unwrap().to_string_lossy().to_string();
+    let target = unsanitize_file_name(&file_name)?;
+    let mut paths = json_files_in_dir(&target_dir)?
+      .into_iter()
+      .map(|(_, path)| path)
+      .collect::<Vec<_>>();
+    paths.sort();
+
+    // Traces without the feature, e.g. of targets returning void, are left out
+    let mut checked = vec![];
+    for path in paths {
+      let features = load_json(&path).map_err(|err| format!("Cannot load features {:?}: {}", path, err))?;
+      if let Some(is_checked) = features[RETURN_VALUE_CHECK]["checked"].as_bool() {
+        checked.push((path.display().to_string(), is_checked));
+      }
+    }
+    if checked.is_empty() {
+      continue;
+    }
+
+    let num_checked = checked.iter().filter(|(_, is_checked)| *is_checked).count();
+    report.check_counts.insert(target.clone(), (checked.len(), num_checked));
+    if num_checked as f32 >= min_check_rate * checked.len() as f32 {
+      report.unchecked.extend(
+        checked
+          .into_iter()
+          .filter(|(_, is_checked)| !is_checked)
+          .map(|(trace, _)| (target.clone(), trace)),
+      );
+    }
+  }
+  Ok(report)
+}
@@ -0,0 +1,114 @@
+use llir::{types::*, values::*};
+
+/// A call site is either a call instruction, or an invoke instruction calling
+/// a function that may unwind. A returning invoke continues to its normal
+/// destination rather than to the next instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CallSite<'ctx> {
+  Call(CallInstruction<'ctx>),
+  Invoke(InvokeInstruction<'ctx>),
+}
+
+impl<'ctx> CallSite<'ctx> {
+  pub fn from_instruction(instr: Instruction<'ctx>) -> Option<Self> {
+    match instr {
+      Instruction::Call(call) => Some(Self::Call(call)),
+      Instruction::Invoke(invoke) => Some(Self::Invoke(invoke)),
+      _ => None,
+    }
+  }
+
+  pub fn as_instruction(&self) -> Instruction<'ctx> {
+    match self {
+      Self::Call(call) => call.as_instruction(),
+      Self::Invoke(invoke) => invoke.as_instruction(),
+    }
+  }
+
+  /// The function called, looking through a global alias to its aliasee
+  pub fn callee_function(&self) -> Option<Function<'ctx>> {
+    let direct_callee = match self {
+      Self::Call(call) => call.callee_function(),
+      Self::Invoke(invoke) => invoke.callee_function(),
+    };
+    direct_callee.or_else(|| self.callee_alias().and_then(aliased_function))
+  }
+
+  /// The global alias called, when the call goes through one
+  pub fn callee_alias(&self) -> Option<GlobalAlias<'ctx>> {
+    let callee = match self {
+      Self::Call(call) => call.callee(),
+      Self::Invoke(invoke) => invoke.callee(),
+    };
+    match callee {
+      Operand::Constant(Constant::Global(Global::Alias(alias))) => Some(alias),
+      _ => None,
+    }
+  }
+
+  pub fn callee_function_type(&self) -> FunctionType<'ctx> {
+    match self {
+      Self::Call(call) => call.callee_function_type(),
+      Self::Invoke(invoke) => invoke.callee_function_type(),
+    }
+  }
+
+  pub fn arguments(&self) -> Vec<Operand<'ctx>> {
+    match self {
+      Self::Call(call) => call.arguments(),
+      Self::Invoke(invoke) => invoke.arguments(),
+    }
+  }
+
+  pub fn num_arguments(&self) -> usize {
+    match self {
+      Self::Call(call) => call.num_arguments(),
+      Self::Invoke(invoke) => invoke.num_arguments(),
+    }
+  }
+
+  /// Intrinsics are never invoked
+  pub fn is_intrinsic_call(&self) -> bool {
+    match self {
+      Self::Call(call) => call.is_intrinsic_call(),
+      Self::Invoke(_) => false,
+    }
+  }
+
+  pub fn is_inline_asm_call(&self) -> bool {
+    match self {
+      Self::Call(call) => call.is_inline_asm_call(),
+      Self::Invoke(invoke) => invoke.is_inline_asm_call(),
+    }
+  }
+
+  pub fn parent_block(&self) -> Block<'ctx> {
+    self.as_instruction().parent_block()
+  }
+
+  pub fn debug_loc_string(&self) -> String {
+    self.as_instruction().debug_loc_string()
+  }
+
+  /// The position of the call among all the instructions of its function, which
+  /// tells apart the calls sharing a debug location
+  pub fn index_in_function(&self) -> usize {
+    let instr = self.as_instruction();
+    instr
+      .parent_block()
+      .parent_function()
+      .iter_blocks()
+      .flat_map(|block| block.iter_instructions())
+      .position(|i| i == instr)
+      .unwrap()
+  }
+}
+
+/// The function an alias ends up at, through any chain of aliases
+fn aliased_function<'ctx>(alias: GlobalAlias<'ctx>) -> Option<Function<'ctx>> {
+  match alias.aliasee() {
+    Constant::Function(func) => Some(func),
+    Constant::Global(Global::Alias(alias)) => aliased_function(alias),
+    _ => None,
+  }
+}
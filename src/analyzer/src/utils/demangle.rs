@@ -0,0 +1,9 @@
+/// The demangled form of a Rust or C++ symbol, e.g. `foo::bar(int)` for
+/// `_ZN3foo3barEi`; `None` for names that are not mangled, such as C functions
+pub fn demangle(name: &str) -> Option<String> {
+  if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+    // The alternate form leaves out the trailing hash
+    return Some(format!("{:#}", demangled));
+  }
+  cpp_demangle::Symbol::new(name).ok().map(|symbol| symbol.to_string())
+}
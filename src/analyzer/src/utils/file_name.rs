@@ -0,0 +1,67 @@
+/// Longest sanitized name we use as a directory name, leaving room for the
+/// hash suffix under the 255 bytes most filesystems allow
+const MAX_FILE_NAME_LEN: usize = 200;
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` gives the same hash across Rust
+/// releases, so that the hashed names stay the same between toolchains
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+  bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+    (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+  })
+}
+
+fn is_safe_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Turn a function name into a name usable as a single path component on any
+/// filesystem. Unsafe characters (e.g. `::`, `<`, `>` in C++ names) are
+/// percent-encoded so that `unsanitize_file_name` gives back the original
+/// name. Names too long once encoded are truncated and suffixed with a hash,
+/// and can only be mapped back through the target names file.
+pub fn sanitize_file_name(name: &str) -> String {
+  let mut result = String::new();
+  for c in name.chars() {
+    if is_safe_char(c) {
+      result.push(c);
+    } else {
+      let mut buf = [0; 4];
+      for byte in c.encode_utf8(&mut buf).bytes() {
+        result.push_str(&format!("%{:02X}", byte));
+      }
+    }
+  }
+  if result.starts_with('.') {
+    result.replace_range(..1, "%2E");
+  }
+  if result.len() > MAX_FILE_NAME_LEN {
+    let mut end = MAX_FILE_NAME_LEN;
+    while !result.is_char_boundary(end) {
+      end -= 1;
+    }
+    result.truncate(end);
+    result.push_str(&format!("-{:016x}", fnv1a_64(name.as_bytes())));
+  }
+  result
+}
+
+/// Decode a percent-encoded name produced by `sanitize_file_name`
+pub fn unsanitize_file_name(file_name: &str) -> Result<String, String> {
+  let bytes = file_name.as_bytes();
+  let mut result = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'%' {
+      let hex = file_name
+        .get(i + 1..i + 3)
+        .ok_or(format!("Truncated escape in {}", file_name))?;
+      let byte = u8::from_str_radix(hex, 16).map_err(|_| format!("Invalid escape in {}", file_name))?;
+      result.push(byte);
+      i += 3;
+    } else {
+      result.push(bytes[i]);
+      i += 1;
+    }
+  }
+  String::from_utf8(result).map_err(|_| format!("Invalid utf8 in {}", file_name))
+}
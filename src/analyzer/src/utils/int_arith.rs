@@ -0,0 +1,121 @@
+//! Width and signedness aware integer arithmetic, shared by the constant folding
+//! of the executor and the z3 translation of the path constraints, so that both
+//! agree on the result of every operation
+
+use crate::semantics::*;
+
+/// Interpret the lowest `width` bits of `value` as a signed integer. This is
+/// how integer constants are stored in `Value::Int`
+pub fn sign_extend(value: i64, width: u32) -> i64 {
+  if width >= 64 {
+    value
+  } else {
+    let shift = 64 - width;
+    (value << shift) >> shift
+  }
+}
+
+/// Interpret the lowest `width` bits of `value` as an unsigned integer
+pub fn zero_extend(value: i64, width: u32) -> u64 {
+  if width >= 64 {
+    value as u64
+  } else {
+    (value as u64) & ((1u64 << width) - 1)
+  }
+}
+
+/// Fold a binary operation on two integer constants of the given bit width,
+/// wrapping around on overflow. Returns `None` for opcodes we do not fold and
+/// for shift amounts that would yield a poison value
+pub fn fold_binary(op: BinOp, op0: i64, op1: i64, width: u32) -> Option<i64> {
+  let (op0, op1) = (sign_extend(op0, width), sign_extend(op1, width));
+  let shift_amount = || {
+    let amount = zero_extend(op1, width);
+    if amount < width as u64 {
+      Some(amount as u32)
+    } else {
+      None
+    }
+  };
+  let result = match op {
+    BinOp::Add => op0.wrapping_add(op1),
+    BinOp::Sub => op0.wrapping_sub(op1),
+    BinOp::Mul => op0.wrapping_mul(op1),
+    BinOp::And => op0 & op1,
+    BinOp::Or => op0 | op1,
+    BinOp::Xor => op0 ^ op1,
+    BinOp::Shl => op0.wrapping_shl(shift_amount()?),
+    BinOp::LShr => (zero_extend(op0, width) >> shift_amount()?) as i64,
+    BinOp::AShr => op0 >> shift_amount()?,
+    _ => return None,
+  };
+  Some(sign_extend(result, width))
+}
+
+/// Fold an integer comparison of two constants of the given bit width. The
+/// result is stored as `Value::Int(1)` or `Value::Int(0)`
+pub fn fold_icmp(pred: Predicate, op0: i64, op1: i64, width: u32) -> bool {
+  let (s0, s1) = (sign_extend(op0, width), sign_extend(op1, width));
+  let (u0, u1) = (zero_extend(op0, width), zero_extend(op1, width));
+  match pred {
+    Predicate::EQ => u0 == u1,
+    Predicate::NE => u0 != u1,
+    Predicate::SGE => s0 >= s1,
+    Predicate::SGT => s0 > s1,
+    Predicate::SLE => s0 <= s1,
+    Predicate::SLT => s0 < s1,
+    Predicate::UGE => u0 >= u1,
+    Predicate::UGT => u0 > u1,
+    Predicate::ULE => u0 <= u1,
+    Predicate::ULT => u0 < u1,
+  }
+}
+
+/// The low `width` bits of a 64-bit bitvector; `None` for widths we do not model
+pub fn truncate_z3_bv<'ctx>(bv: z3::ast::BV<'ctx>, width: u32) -> Option<z3::ast::BV<'ctx>> {
+  if width == 0 || width > 64 {
+    None
+  } else if width == 64 {
+    Some(bv)
+  } else {
+    Some(bv.extract(width - 1, 0))
+  }
+}
+
+/// The z3 counterpart of `fold_binary`, on two bitvectors of the operation width.
+/// Returns `None` for non-integer opcodes
+pub fn z3_binary<'ctx>(op: BinOp, op0: &z3::ast::BV<'ctx>, op1: &z3::ast::BV<'ctx>) -> Option<z3::ast::BV<'ctx>> {
+  match op {
+    BinOp::Add => Some(op0.bvadd(op1)),
+    BinOp::Sub => Some(op0.bvsub(op1)),
+    BinOp::Mul => Some(op0.bvmul(op1)),
+    BinOp::UDiv => Some(op0.bvudiv(op1)),
+    BinOp::SDiv => Some(op0.bvsdiv(op1)),
+    BinOp::URem => Some(op0.bvurem(op1)),
+    BinOp::SRem => Some(op0.bvsrem(op1)),
+    BinOp::And => Some(op0.bvand(op1)),
+    BinOp::Or => Some(op0.bvor(op1)),
+    BinOp::Xor => Some(op0.bvxor(op1)),
+    BinOp::Shl => Some(op0.bvshl(op1)),
+    BinOp::LShr => Some(op0.bvlshr(op1)),
+    BinOp::AShr => Some(op0.bvashr(op1)),
+    _ => None,
+  }
+}
+
+/// The z3 counterpart of `fold_icmp`, on two bitvectors of the comparison width
+pub fn z3_icmp<'ctx>(pred: Predicate, op0: &z3::ast::BV<'ctx>, op1: &z3::ast::BV<'ctx>) -> z3::ast::Bool<'ctx> {
+  use z3::ast::Ast;
+  match pred {
+    Predicate::EQ => op0._eq(op1),
+    Predicate::NE => op0._eq(op1).not(),
+    Predicate::SGE => op0.bvsge(op1),
+    Predicate::UGE => op0.bvuge(op1),
+    Predicate::SGT => op0.bvsgt(op1),
+    Predicate::UGT => op0.bvugt(op1),
+    Predicate::SLE => op0.bvsle(op1),
+    Predicate::ULE => op0.bvule(op1),
+    Predicate::SLT => op0.bvslt(op1),
+    Predicate::ULT => op0.bvult(op1),
+  }
+}
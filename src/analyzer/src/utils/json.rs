@@ -1,22 +1,354 @@
-use serde::de;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{de, Serialize};
 use serde_json::Value;
-use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Version of the layout of dumped json objects, to be bumped whenever `Semantics`
+/// or the trace & feature formats change in an incompatible way
+pub const JSON_VERSION: u64 = 1;
+
+/// A json object along with the top-level `"version"` field
+#[derive(Serialize)]
+struct Versioned<'a> {
+  version: u64,
+  #[serde(flatten)]
+  json: &'a Value,
+}
+
+fn to_versioned_string(json: &Value) -> serde_json::Result<String> {
+  if json.is_object() {
+    serde_json::to_string(&Versioned {
+      version: JSON_VERSION,
+      json,
+    })
+  } else {
+    serde_json::to_string(json)
+  }
+}
+
+/// Extension of dumped traces & features, and of their gzip compressed version
+pub const JSON_EXTENSION: &str = "json";
+pub const JSON_GZ_EXTENSION: &str = "json.gz";
+
+/// Extension of the jsonl files of slices, and of their gzip compressed version
+pub const JSONL_EXTENSION: &str = "jsonl";
+pub const JSONL_GZ_EXTENSION: &str = "jsonl.gz";
+
+/// Json and jsonl files are read & written gzip compressed when their path ends with `.gz`
+fn is_gzip(path: &Path) -> bool {
+  path.extension().map_or(false, |ext| ext == "gz")
+}
+
+/// The id of a `<id>.json` or `<id>.json.gz` file, such as a trace or feature file
+pub fn json_file_id(path: &Path) -> Option<usize> {
+  let name = path.file_name()?.to_str()?;
+  let stem = name
+    .strip_suffix(&format!(".{}", JSON_GZ_EXTENSION))
+    .or_else(|| name.strip_suffix(&format!(".{}", JSON_EXTENSION)))?;
+  stem.parse().ok()
+}
+
+/// The slice id of a `<slice_id>.jsonl` or `<slice_id>.jsonl.gz` file
+pub fn jsonl_file_id(path: &Path) -> Option<usize> {
+  let name = path.file_name()?.to_str()?;
+  let stem = name
+    .strip_suffix(&format!(".{}", JSONL_GZ_EXTENSION))
+    .or_else(|| name.strip_suffix(&format!(".{}", JSONL_EXTENSION)))?;
+  stem.parse().ok()
+}
+
+/// Extension of the statistics of a slice dumped next to its jsonl file
+pub const SLICE_META_EXTENSION: &str = "meta.json";
+
+/// The slice id of a `<slice_id>.meta.json` file
+pub fn slice_meta_file_id(path: &Path) -> Option<usize> {
+  let name = path.file_name()?.to_str()?;
+  name.strip_suffix(&format!(".{}", SLICE_META_EXTENSION))?.parse().ok()
+}
+
+/// Of two files holding the same data, e.g. `N.json` and `N.json.gz` after reruns
+/// with and without `--compress`, the one written last
+pub fn newer_file(path_1: PathBuf, path_2: PathBuf) -> PathBuf {
+  let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+  if modified(&path_2) > modified(&path_1) {
+    path_2
+  } else {
+    path_1
+  }
+}
+
+/// The `<id>.json(.gz)` files of a directory along with their ids, including the
+/// ones sharded into its subdirectories, sorted by id. An id with both a `.json`
+/// and a `.json.gz` file only gives the newer one
+pub fn json_files_in_dir(dir: &Path) -> Result<Vec<(usize, PathBuf)>, String> {
+  let mut files = BTreeMap::new();
+  add_json_files_in_dir(dir, &mut files)?;
+  Ok(files.into_iter().collect())
+}
+
+fn add_json_files_in_dir(dir: &Path, files: &mut BTreeMap<usize, PathBuf>) -> Result<(), String> {
+  let entries = fs::read_dir(dir).map_err(|err| format!("Cannot read folder {:?}: {}", dir, err))?;
+  for entry in entries {
+    let path = entry.map_err(|err| err.to_string())?.path();
+    if path.is_dir() {
+      add_json_files_in_dir(&path, files)?;
+    } else if let Some(id) = json_file_id(&path) {
+      let path = match files.remove(&id) {
+        Some(other) => newer_file(other, path),
+        None => path,
+      };
+      files.insert(id, path);
+    }
+  }
+  Ok(())
+}
+
+fn write_json_str(json_str: String, path: PathBuf) -> Result<(), String> {
+  let file = File::create(&path).map_err(|err| format!("Cannot create {:?}: {}", path, err))?;
+  let written = if is_gzip(&path) {
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder
+      .write_all(json_str.as_bytes())
+      .and_then(|_| encoder.finish().map(|_| ()))
+  } else {
+    let mut file = file;
+    file.write_all(json_str.as_bytes())
+  };
+  written.map_err(|err| format!("Cannot write to {:?}: {}", path, err))
+}
+
+/// Check that a json object was dumped with the current `JSON_VERSION`
+pub fn check_json_version(json: &Value) -> Result<(), String> {
+  match json.get("version").map(|version| version.as_u64()) {
+    Some(Some(JSON_VERSION)) => Ok(()),
+    Some(Some(version)) => Err(format!(
+      "Json version {} does not match the analyzer's version {}, rerun the analysis or pass --ignore-version",
+      version, JSON_VERSION
+    )),
+    _ => Err(format!(
+      "Json has no version while the analyzer's version is {}, rerun the analysis or pass --ignore-version",
+      JSON_VERSION
+    )),
+  }
+}
 
 pub fn dump_json(json: &Value, path: PathBuf) -> Result<(), String> {
   let json_str = serde_json::to_string(json).map_err(|_| "Cannot turn trace into json".to_string())?;
-  let mut file = File::create(path).map_err(|_| "Cannot create trace file".to_string())?;
-  file
-    .write_all(json_str.as_bytes())
-    .map_err(|_| "Cannot write to trace file".to_string())
+  write_json_str(json_str, path)
+}
+
+/// Dump a json object along with the top-level `"version"` field, for slices,
+/// traces and features. Maps keyed by names are dumped with `dump_json` instead
+pub fn dump_versioned_json(json: &Value, path: PathBuf) -> Result<(), String> {
+  let json_str = to_versioned_string(json).map_err(|_| "Cannot turn trace into json".to_string())?;
+  write_json_str(json_str, path)
 }
 
 pub fn load_json(path: &PathBuf) -> Result<serde_json::Value, String> {
   let file = File::open(PathBuf::from(path)).map_err(|_| "Cannot open file".to_string())?;
-  serde_json::from_reader(file).map_err(|x| format!("Cannot parse file: {:?}", x))
+  let json = if is_gzip(path) {
+    serde_json::from_reader(GzDecoder::new(file))
+  } else {
+    serde_json::from_reader(file)
+  };
+  json.map_err(|x| format!("Cannot parse file: {:?}", x))
 }
 
 pub fn load_json_t<T: de::DeserializeOwned>(path: &PathBuf) -> Result<T, String> {
   load_json(path).and_then(|json| serde_json::from_value(json).map_err(|x| format!("Cannot parse json into T: {:?}", x)))
 }
+
+/// Load a json dumped by `dump_versioned_json`, checking its version unless `ignore_version`
+pub fn load_versioned_json_t<T: de::DeserializeOwned>(path: &PathBuf, ignore_version: bool) -> Result<T, String> {
+  let json = load_json(path)?;
+  if !ignore_version {
+    check_json_version(&json).map_err(|err| format!("Cannot load {:?}: {}", path, err))?;
+  }
+  serde_json::from_value(json).map_err(|x| format!("Cannot parse json into T: {:?}", x))
+}
+
+/// The buffered jsonl file of a `JsonWriter`, gzip compressed when its path ends with `.gz`
+enum JsonlFile {
+  Plain(BufWriter<File>),
+  Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl JsonlFile {
+  fn create(path: &Path) -> Result<Self, String> {
+    let file = File::create(path).map_err(|err| format!("Cannot create {:?}: {}", path, err))?;
+    let file = BufWriter::new(file);
+    if is_gzip(path) {
+      Ok(Self::Gzip(GzEncoder::new(file, Compression::default())))
+    } else {
+      Ok(Self::Plain(file))
+    }
+  }
+
+  fn write_line(&mut self, json_str: &str) -> std::io::Result<()> {
+    let writer: &mut dyn Write = match self {
+      Self::Plain(file) => file,
+      Self::Gzip(encoder) => encoder,
+    };
+    writer.write_all(json_str.as_bytes())?;
+    writer.write_all(b"\n")
+  }
+
+  fn finish(self) -> std::io::Result<()> {
+    match self {
+      Self::Plain(mut file) => file.flush(),
+      Self::Gzip(encoder) => encoder.finish()?.flush(),
+    }
+  }
+}
+
+enum WriteJob {
+  File(String, PathBuf),
+  Line(String),
+}
+
+/// Writes json files, and the lines of a jsonl file, on a background thread.
+/// Json values are turned into strings by the caller, while the gzip compression
+/// of `.gz` files and the file system accesses happen on the writer thread, so
+/// that the executing threads do not block on them. The writer thread stops at
+/// the first I/O error, which `finish` returns once the writes before it are done
+pub struct JsonWriter {
+  sender: Option<Sender<WriteJob>>,
+  handle: Option<JoinHandle<Result<(), String>>>,
+}
+
+impl JsonWriter {
+  /// A writer of separate json files only
+  pub fn new() -> Self {
+    Self::spawn(None)
+  }
+
+  /// A writer of separate json files and of the lines of the jsonl file at `path`
+  pub fn with_jsonl(path: PathBuf) -> Result<Self, String> {
+    Ok(Self::spawn(Some((JsonlFile::create(&path)?, path))))
+  }
+
+  fn spawn(mut jsonl_file: Option<(JsonlFile, PathBuf)>) -> Self {
+    let (sender, receiver) = channel();
+    let handle = thread::spawn(move || {
+      for job in receiver {
+        match job {
+          WriteJob::File(json_str, path) => write_json_str(json_str, path)?,
+          WriteJob::Line(json_str) => {
+            let (file, path) = jsonl_file
+              .as_mut()
+              .ok_or_else(|| "No jsonl file to write lines to".to_string())?;
+            file
+              .write_line(&json_str)
+              .map_err(|err| format!("Cannot write to {:?}: {}", path, err))?
+          }
+        }
+      }
+      match jsonl_file {
+        Some((file, path)) => file
+          .finish()
+          .map_err(|err| format!("Cannot write to {:?}: {}", path, err)),
+        None => Ok(()),
+      }
+    });
+    Self {
+      sender: Some(sender),
+      handle: Some(handle),
+    }
+  }
+
+  /// Hand a job to the writer thread. Once the thread stopped on an error, later
+  /// jobs are dropped and that error is left for `finish` to return
+  fn send(&self, job: WriteJob) {
+    if let Some(sender) = &self.sender {
+      let _ = sender.send(job);
+    }
+  }
+
+  /// Dump a json object along with the top-level `"version"` field, like `dump_versioned_json`.
+  /// Only fails when the json cannot be turned into a string, I/O errors are returned by `finish`
+  pub fn dump_versioned_json(&self, json: &Value, path: PathBuf) -> Result<(), String> {
+    let json_str = to_versioned_string(json).map_err(|_| "Cannot turn trace into json".to_string())?;
+    self.send(WriteJob::File(json_str, path));
+    Ok(())
+  }
+
+  /// Append a json object along with the top-level `"version"` field to the jsonl file,
+  /// failing like `dump_versioned_json`
+  pub fn write_line(&self, json: &Value) -> Result<(), String> {
+    let json_str = to_versioned_string(json).map_err(|_| "Cannot write json line".to_string())?;
+    self.send(WriteJob::Line(json_str));
+    Ok(())
+  }
+
+  /// Wait for all the writes to complete, returning the first error
+  pub fn finish(mut self) -> Result<(), String> {
+    self.join()
+  }
+
+  fn join(&mut self) -> Result<(), String> {
+    self.sender.take();
+    match self.handle.take() {
+      Some(handle) => handle
+        .join()
+        .unwrap_or_else(|_| Err("Json writer thread panicked".to_string())),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Default for JsonWriter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Writes still pending, e.g. when a slice panics, are completed before the files
+/// of the slice get cleaned up
+impl Drop for JsonWriter {
+  fn drop(&mut self) {
+    let _ = self.join();
+  }
+}
+
+pub fn load_jsonl_t<T: de::DeserializeOwned>(path: &PathBuf) -> Result<Vec<Result<T, String>>, String> {
+  let file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+  let reader: Box<dyn Read> = if is_gzip(path) {
+    Box::new(GzDecoder::new(file))
+  } else {
+    Box::new(file)
+  };
+  Ok(
+    BufReader::new(reader)
+      .lines()
+      .map(|line| {
+        let line = line.map_err(|_| "Cannot read line".to_string())?;
+        serde_json::from_str(line.as_str()).map_err(|x| format!("Cannot parse json line into T: {:?}", x))
+      })
+      .collect(),
+  )
+}
+
+/// Load a jsonl file written by `JsonWriter`, checking the version of each line
+/// unless `ignore_version`
+pub fn load_versioned_jsonl_t<T: de::DeserializeOwned>(
+  path: &PathBuf,
+  ignore_version: bool,
+) -> Result<Vec<Result<T, String>>, String> {
+  Ok(
+    load_jsonl_t::<Value>(path)?
+      .into_iter()
+      .map(|json| {
+        let json = json?;
+        if !ignore_version {
+          check_json_version(&json)?;
+        }
+        serde_json::from_value(json).map_err(|x| format!("Cannot parse json line into T: {:?}", x))
+      })
+      .collect(),
+  )
+}
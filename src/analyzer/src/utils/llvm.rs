@@ -1,6 +1,8 @@
 use llir::{types::*, values::*, *};
 use std::collections::{HashMap, HashSet};
 
+use super::call_site::*;
+
 pub trait CallInstrUtil<'ctx> {
   fn is_dummy_intrinsic_call(&self) -> bool;
 }
@@ -26,6 +28,32 @@ impl<'ctx> CallInstrUtil<'ctx> for CallInstruction<'ctx> {
   }
 }
 
+impl<'ctx> CallInstrUtil<'ctx> for CallSite<'ctx> {
+  fn is_dummy_intrinsic_call(&self) -> bool {
+    match self {
+      CallSite::Call(call) => call.is_dummy_intrinsic_call(),
+      CallSite::Invoke(_) => false,
+    }
+  }
+}
+
+pub trait InstructionUtil<'ctx> {
+  /// The `file:line:col` source location of the instruction, from its debug
+  /// information, or `<function>` naming the enclosing function without it
+  fn trace_loc_string(&self) -> String;
+}
+
+impl<'ctx> InstructionUtil<'ctx> for Instruction<'ctx> {
+  fn trace_loc_string(&self) -> String {
+    let loc = self.debug_loc_string();
+    if loc.is_empty() {
+      format!("<{}>", self.parent_block().parent_function().simp_name())
+    } else {
+      loc
+    }
+  }
+}
+
 pub trait FunctionTypeUtil<'ctx> {
   fn used_types(&self) -> Vec<Type<'ctx>>;
 }
@@ -42,6 +70,9 @@ pub trait FunctionUtil<'ctx> {
   fn used_types(&self) -> Vec<Type<'ctx>>;
 
   fn used_struct_names(&self) -> HashSet<String>;
+
+  /// Whether the definition may be replaced by a strong one at link time
+  fn is_weak(&self) -> bool;
 }
 
 impl<'ctx> FunctionUtil<'ctx> for Function<'ctx> {
@@ -95,6 +126,13 @@ impl<'ctx> FunctionUtil<'ctx> for Function<'ctx> {
     }
     struct_names
   }
+
+  fn is_weak(&self) -> bool {
+    match self.linkage() {
+      Linkage::WeakAny | Linkage::WeakODR | Linkage::LinkOnceAny | Linkage::LinkOnceODR => true,
+      _ => false,
+    }
+  }
 }
 
 pub trait BlockTraceComparison {
@@ -131,3 +169,21 @@ impl<'ctx> FunctionTypesTrait<'ctx> for Module<'ctx> {
     result
   }
 }
+
+impl<'ctx> FunctionTypesTrait<'ctx> for [Module<'ctx>] {
+  /// Function types over all the modules, taking the type of a definition over
+  /// the one of a declaration when a name appears in several modules
+  fn function_types(&self) -> HashMap<String, FunctionType<'ctx>> {
+    let mut result = HashMap::new();
+    let mut defined = HashSet::new();
+    for func in self.iter().flat_map(|module| module.iter_functions()) {
+      let name = func.simp_name();
+      if !func.is_declaration_only() && defined.insert(name.clone()) {
+        result.insert(name, func.get_function_type());
+      } else {
+        result.entry(name).or_insert_with(|| func.get_function_type());
+      }
+    }
+    result
+  }
+}
@@ -1,12 +1,19 @@
 use chrono::{DateTime, Local};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 
 use crate::options::*;
 use crate::symbolic_execution::*;
 
+/// `log` targets of the analysis phases, so that e.g. `RUST_LOG=executor=debug`
+/// only shows the diagnostics of symbolic execution
+pub const SLICER_LOG: &str = "slicer";
+pub const EXECUTOR_LOG: &str = "executor";
+pub const FEATURES_LOG: &str = "features";
+
 pub struct LoggingContext {
-  pub log_file: File,
+  pub log_file: Option<File>,
 }
 
 impl LoggingContext {
@@ -18,16 +25,24 @@ impl LoggingContext {
     // Create the log file
     let log_path = output_path.join("log.txt");
     let log_file = File::create(log_path).map_err(|_| String::from("Cannot create log file"))?;
-    Ok(Self { log_file })
+    Ok(Self {
+      log_file: Some(log_file),
+    })
+  }
+
+  /// A logging context only printing to stdout, leaving the output directory untouched
+  pub fn stdout_only() -> Self {
+    Self { log_file: None }
   }
 
   pub fn log(&mut self, s: &str) -> Result<(), String> {
     let now: DateTime<Local> = Local::now();
     let log_str = format!("[{}] {}\n", now, s);
-    self
-      .log_file
-      .write_all(log_str.as_bytes())
-      .map_err(|_| String::from("Cannot write to byte code file"))?;
+    if let Some(log_file) = &mut self.log_file {
+      log_file
+        .write_all(log_str.as_bytes())
+        .map_err(|_| String::from("Cannot write to byte code file"))?;
+    }
     print!("{}", log_str);
     Ok(())
   }
@@ -52,9 +67,23 @@ impl LoggingContext {
     self.log(format!("{} slices generated, dumping slices to json...", num_slices).as_str())
   }
 
+  pub fn log_dropped_slices(&mut self, num_dropped: usize) -> Result<(), String> {
+    self.log(format!("Dropped {} slices over the maximum number of functions", num_dropped).as_str())
+  }
+
+  pub fn log_slice_counts(&mut self, target_num_slices_map: &HashMap<String, usize>) -> Result<(), String> {
+    let mut targets = target_num_slices_map.iter().collect::<Vec<_>>();
+    targets.sort();
+    for (target, num_slices) in targets {
+      self.log(format!("  {}: {} slices", target, num_slices).as_str())?;
+    }
+    let total: usize = target_num_slices_map.values().sum();
+    self.log(format!("{} slices in total", total).as_str())
+  }
+
   pub fn log_dividing_batches(&mut self, use_batch: bool) -> Result<(), String> {
     if use_batch {
-      self.log("Slices dumped, dividing slices into batches")
+      self.log("Dividing call edges into batches")
     } else {
       Ok(())
     }
@@ -95,10 +124,45 @@ impl LoggingContext {
     }
   }
 
+  pub fn log_timing_report(&mut self, metadata: &MetaData, n: usize) -> Result<(), String> {
+    self.log(format!("Slowest {} slices:", n).as_str())?;
+    for timing in metadata.slowest_slices(n) {
+      self.log(
+        format!(
+          "  {} slice {}: {:.2}ms",
+          timing.target, timing.slice_id, timing.elapsed_ms
+        )
+        .as_str(),
+      )?;
+    }
+    Ok(())
+  }
+
   pub fn log_extracting_features(&mut self) -> Result<(), String> {
     self.log("Extracting features...")
   }
 
+  pub fn log_skipped_block_only_features(&mut self) -> Result<(), String> {
+    self.log("Skipping feature extraction, which needs full traces rather than --block-trace-only ones")
+  }
+
+  pub fn log_skipped_traces(&mut self, num_skipped: usize) -> Result<(), String> {
+    if num_skipped > 0 {
+      self.log(format!("Skipped {} unparseable traces", num_skipped).as_str())
+    } else {
+      Ok(())
+    }
+  }
+
+  pub fn log_dropped_traces(&mut self, num_dropped: &HashMap<String, usize>) -> Result<(), String> {
+    let mut targets = num_dropped.iter().collect::<Vec<_>>();
+    targets.sort();
+    for (target, num_dropped) in targets {
+      self.log(format!("Dropped {} traces of {} over the per-target cap", num_dropped, target).as_str())?;
+    }
+    Ok(())
+  }
+
   pub fn log_finished_extracting_features(&mut self) -> Result<(), String> {
     self.log("Feature extractor finished")
   }
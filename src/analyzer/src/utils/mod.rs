@@ -1,10 +1,22 @@
 mod batching;
+mod call_site;
 mod cartesian;
+mod demangle;
+mod file_name;
+mod int_arith;
 mod json;
 mod llvm;
 mod logging;
+mod shared;
+mod source;
 pub use batching::*;
+pub use call_site::*;
 pub use cartesian::*;
+pub use demangle::*;
+pub use file_name::*;
+pub use int_arith::*;
 pub use json::*;
 pub use llvm::*;
 pub use logging::*;
+pub use shared::*;
+pub use source::*;
@@ -0,0 +1,53 @@
+//! Copy-on-write container used by symbolic execution states
+//!
+//! Cloning a `Shared<T>` only bumps a reference count. The underlying data is
+//! cloned lazily the first time one of the owners mutates it, so forked states
+//! share structure until they actually diverge.
+
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+#[derive(Debug, Default)]
+pub struct Shared<T: Clone>(Rc<T>);
+
+impl<T: Clone> Shared<T> {
+  pub fn new(data: T) -> Self {
+    Self(Rc::new(data))
+  }
+
+  /// Whether two shared containers are still pointing to the same data
+  pub fn ptr_eq(&self, other: &Self) -> bool {
+    Rc::ptr_eq(&self.0, &other.0)
+  }
+
+  /// Take the data out, cloning only when it is still shared with others
+  pub fn into_inner(self) -> T {
+    Rc::try_unwrap(self.0).unwrap_or_else(|rc| (*rc).clone())
+  }
+}
+
+impl<T: Clone> Clone for Shared<T> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<T: Clone> Deref for Shared<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.0
+  }
+}
+
+impl<T: Clone> DerefMut for Shared<T> {
+  fn deref_mut(&mut self) -> &mut T {
+    Rc::make_mut(&mut self.0)
+  }
+}
+
+impl<T: Clone> From<T> for Shared<T> {
+  fn from(data: T) -> Self {
+    Self::new(data)
+  }
+}
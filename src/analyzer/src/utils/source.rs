@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+/// The lines of the source files named by debug locations, each read at most
+/// once and shared by all the slices. A file that cannot be read is remembered
+/// as missing
+#[derive(Default)]
+pub struct SourceFiles {
+  files: Mutex<HashMap<String, Option<Vec<String>>>>,
+}
+
+impl SourceFiles {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// The member named at the `file:line:col` debug location, i.e. the identifier
+  /// starting at `col` when it follows a `.` or `->`. llir exposes the debug
+  /// locations but not the DI types, so this is how the field name of a struct
+  /// GEP is recovered
+  pub fn member_name_at(&self, loc: &str) -> Option<String> {
+    let mut parts = loc.rsplitn(3, ':');
+    let col = parts.next()?.parse::<usize>().ok()?;
+    let line = parts.next()?.parse::<usize>().ok()?;
+    let file = parts.next()?;
+    let mut files = self.files.lock().unwrap();
+    let lines = files.entry(file.to_string()).or_insert_with(|| {
+      fs::read_to_string(file)
+        .ok()
+        .map(|text| text.lines().map(String::from).collect())
+    });
+    member_name_in(lines.as_ref()?.get(line.checked_sub(1)?)?, col)
+  }
+}
+
+fn member_name_in(line: &str, col: usize) -> Option<String> {
+  let start = col.checked_sub(1)?;
+  let before = line.get(..start)?.trim_end();
+  if !before.ends_with('.') && !before.ends_with("->") {
+    return None;
+  }
+  let name = line
+    .get(start..)?
+    .chars()
+    .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+    .collect::<String>();
+  if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+    None
+  } else {
+    Some(name)
+  }
+}
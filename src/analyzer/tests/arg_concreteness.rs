@@ -0,0 +1,22 @@
+mod common;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+
+use common::*;
+
+#[test]
+fn each_argument_is_classified() -> Result<(), String> {
+  let path = "tests/c_files/arg_concreteness/sink.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("each_argument_is_classified");
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    execute(module, call_graph, slice, &options);
+    let trace: Trace = serde_json::from_value(load_trace_json(&options, "sink", 0, 0)).unwrap();
+    let features = ArgumentConcretenessFeatureExtractor::new().extract(0, &slice_json, &trace);
+    assert_eq!(features["arg0"], "concrete");
+    assert_eq!(features["arg1"], "location");
+    assert_eq!(features["arg2"], "symbolic");
+    assert_eq!(features["arg3"], "call-result");
+  })
+}
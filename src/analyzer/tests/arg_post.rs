@@ -0,0 +1,47 @@
+mod common;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+
+use common::*;
+
+fn out_param_features(caller: &str, callee: &str) -> Result<serde_json::Value, String> {
+  let path = "tests/c_files/arg_post/out_param.bc";
+  let mut features = serde_json::Value::Null;
+  with_slice(path, caller, caller, callee, |module, call_graph, slice| {
+    let options = TestOptions::new(&format!("out_param_features_{}", caller));
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    execute(module, call_graph, slice, &options);
+    let trace: Trace = serde_json::from_value(load_trace_json(&options, callee, 0, 0)).unwrap();
+    features = ArgumentPostconditionFeatureExtractor::new(0).extract(0, &slice_json, &trace);
+  })?;
+  Ok(features)
+}
+
+#[test]
+fn out_param_written_with_a_constant() -> Result<(), String> {
+  let features = out_param_features("constant_out", "get")?;
+  assert_eq!(features["out_written"], true);
+  assert_eq!(features["out_written_const"], true);
+  assert_eq!(features["out_written_sym"], false);
+  Ok(())
+}
+
+#[test]
+fn out_param_written_with_a_symbolic_value() -> Result<(), String> {
+  let features = out_param_features("symbolic_out", "get")?;
+  assert_eq!(features["out_written"], true);
+  assert_eq!(features["out_written_const"], false);
+  assert_eq!(features["out_written_sym"], true);
+  Ok(())
+}
+
+#[test]
+fn out_param_written_through_a_struct_field() -> Result<(), String> {
+  let features = out_param_features("struct_out", "get_pair")?;
+  assert_eq!(features["derefed"], true);
+  assert_eq!(features["out_written"], true);
+  assert_eq!(features["out_written_const"], true);
+  assert_eq!(features["out_written_sym"], false);
+  Ok(())
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::*;
+
+fn target_args(target: &str, name: &str) -> Result<serde_json::Value, String> {
+  target_args_in("tests/ll_files/atomic/atomic.bc", target, name)
+}
+
+fn target_args_in(path: &str, target: &str, name: &str) -> Result<serde_json::Value, String> {
+  let mut args = serde_json::Value::Null;
+  with_slice(path, "main", "main", target, |module, call_graph, slice| {
+    let options = TestOptions::new(name);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, target, 0, 0);
+    let index = trace["target"].as_u64().unwrap() as usize;
+    args = trace["instrs"][index]["sem"]["Call"]["args"].clone();
+  })?;
+  Ok(args)
+}
+
+#[test]
+fn atomic_add_stores_the_sum_and_results_in_the_old_value() -> Result<(), String> {
+  let args = target_args("sink", "atomic_add_stores_the_sum_and_results_in_the_old_value")?;
+  let sum = &args[1]["Bin"];
+  assert_eq!(sum["op"], "Add");
+  assert_eq!(sum["op0"], args[0]);
+  assert_eq!(sum["op1"]["Int"], 1);
+  Ok(())
+}
+
+#[test]
+fn atomic_add_on_a_stack_slot_behaves_like_a_store() -> Result<(), String> {
+  let path = "tests/ll_files/atomic/alloca.bc";
+  let args = target_args_in(path, "sink", "atomic_add_on_a_stack_slot_behaves_like_a_store")?;
+  assert_eq!(args[0]["Int"], 1);
+  assert_eq!(args[1]["Int"], 3);
+  Ok(())
+}
+
+#[test]
+fn cmpxchg_results_in_the_old_value_and_success_flag() -> Result<(), String> {
+  let args = target_args("pair", "cmpxchg_results_in_the_old_value_and_success_flag")?;
+  let fields = args[0]["Aggregate"].as_array().unwrap();
+  assert_eq!(fields.len(), 2);
+  assert_eq!(fields[0]["Bin"]["op"], "Add");
+  assert_eq!(fields[1]["ICmp"]["pred"], "EQ");
+  assert_eq!(fields[1]["ICmp"]["op0"], fields[0]);
+
+  // Extracting the flag gives back the comparison
+  assert_eq!(args[1], fields[1]);
+  Ok(())
+}
@@ -1,6 +1,16 @@
-use std::collections::HashMap;
+mod common;
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use analyzer::call_graph::*;
+use analyzer::options::*;
+use analyzer::slicer::*;
+use analyzer::symbolic_execution::*;
 use analyzer::utils::*;
+use llir::Module;
+
+use common::*;
 
 #[test]
 fn test_only_batch() {
@@ -59,3 +69,69 @@ fn test_lots_of_batches() {
   }
   assert_eq!(iter_count, num_batches, "{} batches should be generated", num_batches);
 }
+
+/// Slice and execute the call edges batch by batch as the analyzer does,
+/// returning the number of slices of each target
+fn execute_batches<'ctx>(
+  module: &Module<'ctx>,
+  call_graph: &CallGraph<'ctx>,
+  options: &TestOptions,
+  use_batch: bool,
+) -> TargetNumSlicesMap {
+  let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, options).unwrap();
+  let mut progress = TargetSlicingProgressMap::new();
+  for (_, target_edges_batch) in target_edges_map.batches(use_batch, 1) {
+    let (target_slices_map, _) =
+      OffsetTargetSlicesMap::from_target_edges_batch(&target_edges_batch, call_graph, options, &mut progress).unwrap();
+    target_slices_map.dump(options);
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, options).unwrap();
+    ctx.execute_target_slices_map(target_slices_map);
+  }
+  progress.target_num_slices_map()
+}
+
+/// All the slice and trace files under `dir`, keyed by their path relative to `root`.
+/// Completion markers and statistics, which hold timings, are left out
+fn collect_json_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, serde_json::Value>) {
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.is_dir() {
+      collect_json_files(root, &path, files);
+    } else if json_file_id(&path).is_some() {
+      let key = path.strip_prefix(root).unwrap().to_string_lossy().to_string();
+      files.insert(key, load_json(&path).unwrap());
+    }
+  }
+}
+
+#[test]
+fn batched_execution_produces_the_same_traces() -> Result<(), String> {
+  let path = "tests/c_files/batch/batch.bc";
+  with_call_graph(path, |module, call_graph| {
+    let mut results = vec![];
+    for &use_batch in &[false, true] {
+      let mut options = TestOptions::new(&format!("batched_execution_produces_the_same_traces_{}", use_batch));
+      options.slice_depth = 0;
+      let target_num_slices_map = execute_batches(module, call_graph, &options, use_batch);
+      assert_eq!(target_num_slices_map["malloc"], 2);
+
+      // The statistics of the target cover the slices of every batch
+      let proper_count = |dir: PathBuf| load_json(&dir.join("meta.json")).unwrap()["proper_trace_count"].as_u64();
+      assert_eq!(
+        proper_count(options.trace_target_dir("malloc")),
+        Some(
+          proper_count(options.trace_target_slice_dir("malloc", 0)).unwrap()
+            + proper_count(options.trace_target_slice_dir("malloc", 1)).unwrap()
+        )
+      );
+      let mut files = BTreeMap::new();
+      collect_json_files(&options.output_path(), &options.slice_dir(), &mut files);
+      collect_json_files(&options.output_path(), &options.trace_dir(), &mut files);
+      results.push((target_num_slices_map, files));
+    }
+
+    // Each malloc call is in its own batch, yet gets the same slice id as when unbatched
+    assert_eq!(results[0], results[1]);
+    assert!(results[0].1.contains_key("traces/malloc/1/0.json"));
+  })
+}
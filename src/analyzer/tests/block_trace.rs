@@ -12,6 +12,10 @@ impl CallGraphOptions for TempOptions {
   fn remove_llvm_funcs(&self) -> bool {
     false
   }
+
+  fn resolve_indirect(&self) -> bool {
+    false
+  }
 }
 
 fn process_slice<F>(path: &Path, entry: &str, caller: &str, target: &str, f: F) -> Result<(), String>
@@ -37,7 +41,7 @@ where
           if !call.is_intrinsic_call() {
             match call.callee_function() {
               Some(f) if f.simp_name() == target => {
-                call_instr = Some(call);
+                call_instr = Some(CallSite::Call(call));
                 target_func = Some(f);
               }
               _ => {}
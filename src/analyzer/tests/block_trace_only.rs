@@ -0,0 +1,90 @@
+mod common;
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use analyzer::feature_extraction::*;
+use analyzer::options::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn block_only_traces_keep_the_executed_blocks() -> Result<(), String> {
+  let path = "tests/c_files/trace/block_trace_2.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("block_only_traces_keep_the_executed_blocks");
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).unwrap();
+    let (_, traces) = ctx.execute_slice_in_memory(slice.clone(), 0);
+    let mut expected = traces
+      .iter()
+      .map(|trace| {
+        trace
+          .block_trace()
+          .into_iter()
+          .map(|block| {
+            let func = block.parent_function();
+            TraceBlock {
+              func: func.simp_name(),
+              block: func.iter_blocks().position(|b| b == block).unwrap(),
+            }
+          })
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+
+    options.block_trace_only = true;
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    assert!(trace.get("instrs").is_none());
+
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    let mut loaded = vec![];
+    for (_, trace) in ctx.load_block_traces(&String::from("malloc"), 0) {
+      let trace = trace.unwrap();
+      let target_block = &trace.blocks[trace.target];
+      assert_eq!(target_block.func, "f");
+      loaded.push(trace.blocks);
+    }
+    let key = |blocks: &Vec<TraceBlock>| format!("{:?}", blocks);
+    expected.sort_by_key(key);
+    loaded.sort_by_key(key);
+    assert_eq!(loaded, expected);
+  })
+}
+
+#[test]
+fn block_only_traces_are_not_reduced() -> Result<(), String> {
+  let path = "tests/c_files/reduce/reduce_1.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("block_only_traces_are_not_reduced");
+    options.no_trace_reduction = false;
+    options.block_trace_only = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+    assert_eq!(metadata.reduced_node_count, 0);
+  })
+}
+
+#[test]
+fn block_only_traces_skip_feature_extraction() -> Result<(), String> {
+  let options = TestOptions::new("block_only_traces_skip_feature_extraction");
+  let output = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg("tests/c_files/basic/example_1.bc")
+    .arg(&options.output)
+    .arg("--target=malloc")
+    .arg("--block-trace-only")
+    .output()
+    .map_err(|err| err.to_string())?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+  // The traces only hold their blocks, and no features are extracted from them
+  let trace = load_json(&options.trace_target_slice_file_path("malloc", 0, 0))?;
+  assert!(trace.get("instrs").is_none());
+  assert_eq!(trace["blocks"][trace["target"].as_u64().unwrap() as usize]["func"], "f");
+  assert!(!options.feature_target_dir("malloc").exists());
+  Ok(())
+}
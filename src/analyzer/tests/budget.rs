@@ -0,0 +1,24 @@
+mod common;
+
+use analyzer::options::*;
+
+use common::*;
+
+#[test]
+fn targets_with_a_budget_stop_at_it() -> Result<(), String> {
+  let path = "tests/c_files/budget/budget.bc";
+  let mut options = TestOptions::new("targets_with_a_budget_stop_at_it");
+  std::fs::create_dir_all(options.output_path()).unwrap();
+  let budget_file = options.output_path().join("budget.json");
+  std::fs::write(&budget_file, r#"{ "malloc": 2 }"#).unwrap();
+  options.budget_file = Some(budget_file.to_str().unwrap().to_string());
+
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+  })?;
+  with_slice(path, "main", "main", "free", |module, call_graph, slice| {
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 4);
+  })
+}
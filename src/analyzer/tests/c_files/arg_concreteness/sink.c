@@ -0,0 +1,9 @@
+int get();
+
+void sink(int c, int *loc, int sym, int res);
+
+int main(int x) {
+  int local = 0;
+  sink(5, &local, x, get());
+  return local;
+}
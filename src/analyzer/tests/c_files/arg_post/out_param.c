@@ -0,0 +1,26 @@
+int get(int *out);
+int source(void);
+
+void constant_out(void) {
+  int out;
+  get(&out);
+  out = 0;
+}
+
+void symbolic_out(void) {
+  int out;
+  get(&out);
+  out = source();
+}
+
+struct pair {
+  int first;
+  int second;
+};
+
+int get_pair(struct pair *out);
+
+void struct_out(struct pair *out) {
+  get_pair(out);
+  out->second = 0;
+}
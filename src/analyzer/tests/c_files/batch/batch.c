@@ -0,0 +1,18 @@
+void *malloc(int);
+void free(void *);
+
+void *alloc_a() {
+  return malloc(1);
+}
+
+void *alloc_b() {
+  return malloc(2);
+}
+
+int main() {
+  void *a = alloc_a();
+  void *b = alloc_b();
+  free(a);
+  free(b);
+  return 0;
+}
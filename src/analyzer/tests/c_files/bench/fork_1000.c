@@ -0,0 +1,23 @@
+void *malloc(unsigned long);
+
+// Ten independent branches give 1024 paths, so exploring the slice forks the
+// state 1023 times
+#define BRANCH(i) \
+  if (c[i])       \
+    n += i;
+
+int main(int *c) {
+  int n = 0;
+  BRANCH(0)
+  BRANCH(1)
+  BRANCH(2)
+  BRANCH(3)
+  BRANCH(4)
+  BRANCH(5)
+  BRANCH(6)
+  BRANCH(7)
+  BRANCH(8)
+  BRANCH(9)
+  malloc(n);
+  return 0;
+}
@@ -0,0 +1,15 @@
+void *malloc(int);
+void free(void *);
+
+int main(int a, int b) {
+  int n = 0;
+  if (a) {
+    n += 1;
+  }
+  if (b) {
+    n += 2;
+  }
+  void *p = malloc(n);
+  free(p);
+  return 0;
+}
@@ -0,0 +1,13 @@
+#include <stdlib.h>
+
+void *real_alloc(int size) {
+  return malloc(size);
+}
+
+void *my_alloc(int size) __attribute__((alias("real_alloc")));
+
+int main() {
+  void *p = my_alloc(4);
+  free(p);
+  return 0;
+}
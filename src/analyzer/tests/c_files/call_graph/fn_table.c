@@ -0,0 +1,27 @@
+void *malloc(int);
+
+typedef void *(*handler_t)(int);
+
+void *alloc_handler(int n) {
+  return malloc(n);
+}
+
+void *null_handler(int n) {
+  return 0;
+}
+
+// Same type as the handlers but never has its address taken
+void *unused_handler(int n) {
+  return malloc(n);
+}
+
+int other(char c) {
+  return c;
+}
+
+handler_t handlers[] = {alloc_handler, null_handler};
+
+int main(int argc) {
+  void *p = handlers[argc](argc);
+  return other('a');
+}
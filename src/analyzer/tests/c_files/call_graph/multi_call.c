@@ -0,0 +1,11 @@
+void *malloc(int);
+
+int fact(int n) {
+  return n <= 1 ? 1 : n * fact(n - 1);
+}
+
+int main() {
+  void *p = malloc(1);
+  void *q = malloc(2);
+  return fact(3);
+}
@@ -0,0 +1,10 @@
+#define O_WRONLY 01
+#define O_CREAT 0100
+
+int open(const char *path, int flags, ...);
+
+int main() {
+  int flags = O_WRONLY;
+  flags = flags | O_CREAT;
+  return open("out.txt", flags, 0644);
+}
@@ -0,0 +1,13 @@
+int open(const char *path, int flags, ...);
+
+int main(int argc, char **argv) {
+  int flags;
+  if (argc > 2) {
+    flags = 1;
+  } else if (argc > 1) {
+    flags = 2;
+  } else {
+    flags = 6;
+  }
+  return open(argv[0], flags, 0);
+}
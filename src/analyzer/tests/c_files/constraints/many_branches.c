@@ -0,0 +1,16 @@
+void *malloc(unsigned long);
+
+int main(int a, int b, int c) {
+  int size = 8;
+  if (a > 1) {
+    size += 1;
+  }
+  if (b > 2) {
+    size += 2;
+  }
+  if (c > 3) {
+    size += 3;
+  }
+  void *p = malloc(size);
+  return p == 0;
+}
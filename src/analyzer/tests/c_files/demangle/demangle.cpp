@@ -0,0 +1,8 @@
+namespace foo {
+void *bar(int n);
+}
+
+int main() {
+  void *p = foo::bar(1);
+  return p != 0;
+}
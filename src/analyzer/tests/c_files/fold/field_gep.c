@@ -0,0 +1,19 @@
+int use(long, int);
+
+struct inner {
+  int a;
+  long b;
+};
+
+struct outer {
+  int x;
+  struct inner in;
+};
+
+struct outer g;
+
+int main() {
+  g.in.b = 3;
+  int *q = &g.in.a;
+  return use(g.in.b, q[1]);
+}
@@ -0,0 +1,8 @@
+void *malloc(int);
+
+int main(int argc) {
+  int a = 3;
+  int b = 4;
+  void *ptr = malloc((a + b) * 2);
+  return 0;
+}
@@ -0,0 +1,7 @@
+void *malloc(int);
+
+int main(int argc) {
+  int a = 3;
+  void *ptr = malloc(argc + a);
+  return 0;
+}
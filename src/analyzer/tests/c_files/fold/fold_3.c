@@ -0,0 +1,14 @@
+void *malloc(int);
+
+int g(int);
+
+int main(int argc) {
+  int a = 3;
+  if (a > 5) {
+    a = g(a);
+  } else {
+    a = a + 1;
+  }
+  void *ptr = malloc(a);
+  return 0;
+}
@@ -0,0 +1,10 @@
+int use(int, int, int);
+
+int main() {
+  int a[4];
+  a[0] = 1;
+  a[1] = 2;
+  a[2] = 3;
+  int *p = &a[1];
+  return use(a[0], a[1], p[1]);
+}
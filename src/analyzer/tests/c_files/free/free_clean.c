@@ -0,0 +1,9 @@
+#include <stdlib.h>
+
+int main() {
+  int *p = (int *) malloc(sizeof(int));
+  *p = 10;
+  int x = *p;
+  free(p);
+  return x;
+}
@@ -0,0 +1,8 @@
+#include <stdlib.h>
+
+int main() {
+  int *p = (int *) malloc(sizeof(int));
+  *p = 10;
+  free(p);
+  return *p;
+}
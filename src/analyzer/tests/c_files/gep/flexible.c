@@ -0,0 +1,25 @@
+void *malloc(unsigned long);
+void sink(int a, int b, int c);
+
+struct packet {
+  int len;
+  int data[];
+};
+
+struct header {
+  int len;
+  int data[1];
+};
+
+struct pair {
+  int data[1];
+  int len;
+};
+
+int main() {
+  struct packet *p = malloc(sizeof(struct packet) + 4 * sizeof(int));
+  struct header *h = malloc(sizeof(struct header) + 4 * sizeof(int));
+  struct pair *q = malloc(sizeof(struct pair));
+  sink(p->data[3], h->data[3], q->data[3]);
+  return 0;
+}
@@ -0,0 +1,8 @@
+void sink(int *p);
+
+int main(int i) {
+  int a[4];
+  sink(&a[5]);
+  sink(&a[i]);
+  return a[2];
+}
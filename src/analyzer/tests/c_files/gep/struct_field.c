@@ -0,0 +1,12 @@
+void *malloc(unsigned long);
+
+struct point {
+  int x;
+  int y;
+};
+
+int main() {
+  struct point *p = malloc(sizeof(struct point));
+  p->y = 3;
+  return p->x;
+}
@@ -0,0 +1,13 @@
+void *malloc(unsigned long);
+
+struct triple {
+  int a;
+  int b;
+  int c;
+};
+
+int main() {
+  struct triple *t = malloc(sizeof(struct triple));
+  t->c = 3;
+  return 0;
+}
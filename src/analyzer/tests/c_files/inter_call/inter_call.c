@@ -0,0 +1,15 @@
+void lock(void);
+void prepare(void);
+void log_msg(void);
+
+void first(void) {
+  lock();
+}
+
+int main() {
+  lock();
+  prepare();
+  log_msg();
+  first();
+  return 0;
+}
@@ -0,0 +1,14 @@
+extern "C" void *acquire(int n);
+extern "C" void release();
+
+struct Guard {
+  ~Guard() {
+    release();
+  }
+};
+
+int main() {
+  Guard guard;
+  void *p = acquire(10);
+  return p ? 0 : 1;
+}
@@ -0,0 +1,13 @@
+extern "C" void *acquire(int n);
+
+int main() {
+  try {
+    void *p = acquire(10);
+    if (p) {
+      return 0;
+    }
+  } catch (...) {
+    return 1;
+  }
+  return 2;
+}
@@ -0,0 +1,5 @@
+#include <stdlib.h>
+
+char *helper(int n) {
+  return (char *) malloc(n);
+}
@@ -0,0 +1,9 @@
+char *helper(int n);
+
+int main() {
+  char *p = helper(10);
+  if (p) {
+    p[0] = 0;
+  }
+  return 0;
+}
@@ -0,0 +1,13 @@
+void *malloc(unsigned long);
+
+int main(int x) {
+  void *p = malloc(8);
+  int r = p == 0;
+  if (x > 3) {
+    r += 1;
+  }
+  if (x < 2) {
+    r += 2;
+  }
+  return r;
+}
@@ -0,0 +1,8 @@
+void sink(int v);
+
+void chase(int **pp, int *cell) {
+  *cell = 42;
+  *pp = cell;
+  int *p = *pp;
+  sink(*p);
+}
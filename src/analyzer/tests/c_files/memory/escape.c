@@ -0,0 +1,16 @@
+struct holder {
+  int *slot;
+};
+
+void init(void);
+void init_high(char *);
+int use(int, long);
+
+int main(struct holder *h) {
+  int x;
+  long z;
+  h->slot = &x;
+  init_high((char *)&z + 4);
+  init();
+  return use(x, z);
+}
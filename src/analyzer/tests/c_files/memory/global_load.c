@@ -0,0 +1,13 @@
+void *malloc(unsigned long);
+
+int size;
+int *extra;
+
+int main(int x) {
+  int n = 0;
+  if (x > 0) {
+    n = *extra;
+  }
+  void *p = malloc(size + n);
+  return p == 0;
+}
@@ -0,0 +1,23 @@
+void sink(long *buf);
+
+int main() {
+  long buf[16];
+  buf[0] = 0;
+  buf[1] = 1;
+  buf[2] = 2;
+  buf[3] = 3;
+  buf[4] = 4;
+  buf[5] = 5;
+  buf[6] = 6;
+  buf[7] = 7;
+  buf[8] = 8;
+  buf[9] = 9;
+  buf[10] = 10;
+  buf[11] = 11;
+  buf[12] = 12;
+  buf[13] = 13;
+  buf[14] = 14;
+  buf[15] = 15;
+  sink(buf);
+  return 0;
+}
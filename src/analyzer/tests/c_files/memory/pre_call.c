@@ -0,0 +1,7 @@
+void *malloc(unsigned long);
+
+int main() {
+  int size = 42;
+  void *p = malloc(size);
+  return p == 0;
+}
@@ -0,0 +1,11 @@
+typedef long v2l __attribute__((vector_size(16)));
+
+void sink(long, long);
+
+int main(v2l *p, long x) {
+  v2l v = *p;
+  v[0] = x;
+  v2l s = __builtin_shufflevector(v, v, 1, 0);
+  sink(s[0], s[1]);
+  return 0;
+}
@@ -0,0 +1,11 @@
+int size;
+
+void init(int *);
+int use(int, int, int, int);
+
+int main(int *p) {
+  int x;
+  int y;
+  init(&y);
+  return use(x, y, size, *p);
+}
@@ -0,0 +1,9 @@
+typedef long v2l __attribute__((vector_size(16)));
+
+void sink(v2l);
+
+int main(v2l *p) {
+  v2l v = *p;
+  sink(v);
+  return 0;
+}
@@ -0,0 +1,12 @@
+void *malloc(unsigned long);
+
+int main(int argc) {
+  int size;
+  if (argc > 3) {
+    size = 8;
+  } else {
+    size = 16;
+  }
+  void *p = malloc(size);
+  return p == 0;
+}
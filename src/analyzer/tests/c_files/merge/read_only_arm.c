@@ -0,0 +1,15 @@
+void *malloc(unsigned long);
+void init(int *p);
+
+int main(int argc) {
+  int n;
+  int size;
+  init(&n);
+  if (argc > 3) {
+    size = n;
+  } else {
+    size = 16;
+  }
+  void *p = malloc(n);
+  return p == 0 && size;
+}
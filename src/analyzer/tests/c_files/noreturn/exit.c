@@ -0,0 +1,14 @@
+void *malloc(unsigned long);
+
+void free(void *);
+
+void exit(int);
+
+int main() {
+  void *p = malloc(8);
+  if (!p) {
+    exit(1);
+  }
+  free(p);
+  return 0;
+}
@@ -0,0 +1,12 @@
+void *malloc(unsigned long);
+
+void free(void *);
+
+void __asan_report_load4(unsigned long);
+
+int main() {
+  int *ptr = malloc(4);
+  __asan_report_load4((unsigned long)ptr);
+  free(ptr);
+  return 0;
+}
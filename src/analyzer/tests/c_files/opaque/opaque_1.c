@@ -0,0 +1,13 @@
+void *malloc(int);
+
+void free(void *);
+
+void *my_alloc(int size) {
+  return malloc(size);
+}
+
+int main() {
+  void *ptr = my_alloc(10);
+  free(ptr);
+  return 0;
+}
@@ -0,0 +1,7 @@
+void *malloc(int);
+
+int main(int argc) {
+  int size = argc > 1 ? 8 : 16;
+  void *ptr = malloc(size);
+  return 0;
+}
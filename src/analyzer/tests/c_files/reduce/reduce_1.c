@@ -0,0 +1,10 @@
+void *malloc(unsigned long size);
+
+int global;
+
+int main() {
+  int unrelated = global * 3;
+  int size = global + 4;
+  void *p = malloc(size);
+  return unrelated;
+}
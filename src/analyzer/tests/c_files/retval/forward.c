@@ -0,0 +1,9 @@
+int get(void);
+
+int forward(void) {
+  return get();
+}
+
+int main() {
+  return forward();
+}
@@ -0,0 +1,9 @@
+int get(void);
+
+int main() {
+  int r = get();
+  if (r >= 0 && r <= 10) {
+    return r;
+  }
+  return 0;
+}
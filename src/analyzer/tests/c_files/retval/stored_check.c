@@ -0,0 +1,9 @@
+int get(void);
+
+int main() {
+  int r = get();
+  if (r < 0) {
+    return -1;
+  }
+  return 0;
+}
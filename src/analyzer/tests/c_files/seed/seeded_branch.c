@@ -0,0 +1,10 @@
+void *malloc(unsigned long);
+
+int main(int argc) {
+  int size = 1;
+  if (argc > 3) {
+    size = 16;
+  }
+  void *p = malloc(size);
+  return p == 0;
+}
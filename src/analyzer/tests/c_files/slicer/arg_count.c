@@ -0,0 +1,15 @@
+int log_msg(int level, const char *fmt, ...);
+
+void f() {
+  log_msg(1, "f");
+}
+
+void g(int i) {
+  log_msg(2, "g %d", i);
+}
+
+int main() {
+  f();
+  g(3);
+  return 0;
+}
@@ -0,0 +1,9 @@
+void *malloc(int);
+
+#define ALLOC_TWO(a, b) a = malloc(1); b = malloc(2)
+
+int main() {
+  void *a, *b;
+  ALLOC_TWO(a, b);
+  return 0;
+}
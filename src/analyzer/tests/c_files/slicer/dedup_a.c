@@ -0,0 +1,10 @@
+void *malloc(int);
+
+// The same static helper is compiled in both modules
+static void *alloc() {
+  return malloc(1);
+}
+
+void *alloc_a() {
+  return alloc();
+}
@@ -0,0 +1,16 @@
+void *malloc(int);
+
+void *alloc_a() {
+  return malloc(1);
+}
+
+#line 1 "other_file.c"
+void *alloc_b() {
+  return malloc(2);
+}
+
+int main() {
+  alloc_a();
+  alloc_b();
+  return 0;
+}
@@ -0,0 +1,14 @@
+int log_msg(int);
+
+int helper(int i) {
+  return log_msg(i);
+}
+
+// The target is defined here and only reaches `helper` through itself
+int log_msg(int i) {
+  return i > 0 ? helper(i - 1) : 0;
+}
+
+int entry(int i) {
+  return log_msg(i);
+}
@@ -0,0 +1,14 @@
+int foo(int);
+int bar(int);
+
+int f(int i) {
+  return foo(i) + bar(i);
+}
+
+int g(int i) {
+  return bar(foo(i));
+}
+
+int main() {
+  return f(1) + g(2);
+}
@@ -0,0 +1,14 @@
+void *malloc(int);
+
+void helper_a() {}
+void helper_b() {}
+void helper_c() {}
+void helper_d() {}
+
+void *alloc() {
+  helper_a();
+  helper_b();
+  helper_c();
+  helper_d();
+  return malloc(1);
+}
@@ -0,0 +1,11 @@
+void *malloc(int);
+
+void *foo_wrapper(int size) {
+  return malloc(size);
+}
+
+int main() {
+  void *p = malloc(1);
+  void *q = foo_wrapper(2);
+  return 0;
+}
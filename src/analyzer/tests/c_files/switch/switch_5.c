@@ -0,0 +1,15 @@
+void *malloc(int);
+
+int g(int);
+
+int main(int argc) {
+  void *ptr;
+  int n = 0;
+  switch (argc) {
+    case 1:
+    case 2: n = g(n); break;
+    default: n = 3;
+  }
+  ptr = malloc(n);
+  return 0;
+}
@@ -0,0 +1,9 @@
+void *malloc(unsigned long size);
+
+int counter;
+
+int main() {
+  counter = counter + 1;
+  void *p = malloc(8);
+  return 0;
+}
@@ -0,0 +1,9 @@
+void *malloc(unsigned long);
+
+int main(int x, int y) {
+  void *p = malloc(8);
+  if (x * y == 1234567) {
+    return 1;
+  }
+  return p == 0;
+}
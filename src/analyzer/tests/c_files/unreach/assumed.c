@@ -0,0 +1,9 @@
+void *malloc(unsigned long);
+
+int main(int argc) {
+  void *p = malloc(8);
+  if (argc > 3) {
+    __builtin_unreachable();
+  }
+  return p == 0;
+}
@@ -0,0 +1,6 @@
+#include <stdio.h>
+
+int main(int a, int b) {
+  printf("%d %d", a, b);
+  return 0;
+}
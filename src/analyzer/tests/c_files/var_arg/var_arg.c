@@ -0,0 +1,8 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+int main() {
+  int *p = (int *) malloc(sizeof(int));
+  printf("%p %d\n", p, 10);
+  return 0;
+}
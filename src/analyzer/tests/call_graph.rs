@@ -0,0 +1,115 @@
+mod common;
+
+use llir::*;
+use std::path::Path;
+
+use analyzer::call_graph::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn dumped_call_graph_counts_call_sites() -> Result<(), String> {
+  let path = "tests/c_files/call_graph/multi_call.bc";
+  with_call_graph(path, |_, call_graph| {
+    let options = TestOptions::new("dumped_call_graph_counts_call_sites");
+    std::fs::create_dir_all(&options.output).unwrap();
+    let path = options.output.join("call_graph.json");
+    call_graph.dump(path.clone()).unwrap();
+
+    let json = load_json(&path).unwrap();
+    let nodes = json["nodes"].as_array().unwrap();
+    for name in &["fact", "main", "malloc"] {
+      assert!(nodes.contains(&serde_json::json!(name)));
+    }
+
+    // Debug intrinsics are kept in the graph, so only look at the edges we know of
+    let count = |caller: &str, callee: &str| {
+      json["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|edge| edge["caller"] == caller && edge["callee"] == callee)
+        .map(|edge| edge["count"].as_u64().unwrap())
+    };
+    assert_eq!(count("main", "malloc"), Some(2));
+    assert_eq!(count("main", "fact"), Some(1));
+    assert_eq!(count("fact", "fact"), Some(1));
+    assert_eq!(count("fact", "malloc"), None);
+  })
+}
+
+#[test]
+fn indirect_calls_reach_handlers_of_the_same_type() -> Result<(), String> {
+  let ctx = Context::create();
+  let module = ctx.load_module(Path::new("tests/c_files/call_graph/fn_table.bc"))?;
+  let main = module.get_function("main").unwrap();
+  let malloc = module.get_function("malloc").unwrap();
+  let callees = |call_graph: &CallGraph| {
+    let mut callees = call_graph
+      .edge_counts()
+      .into_iter()
+      .filter(|(caller, _, _)| caller == "main")
+      .map(|(_, callee, _)| callee)
+      .collect::<Vec<_>>();
+    callees.retain(|callee| callee != "dbg");
+    callees
+  };
+
+  // Without resolution the handlers are not reachable from main
+  let mut options = TestOptions::new("indirect_calls_reach_handlers_of_the_same_type");
+  let call_graph = CallGraph::from_module(&module, &options);
+  assert_eq!(callees(&call_graph), vec!["other"]);
+  assert!(call_graph.paths(main, malloc, 2).is_empty());
+
+  // Only the address-taken handlers are candidates, not `unused_handler`
+  options.resolve_indirect = true;
+  let call_graph = CallGraph::from_module(&module, &options);
+  assert_eq!(callees(&call_graph), vec!["alloc_handler", "null_handler", "other"]);
+  assert_eq!(call_graph.paths(main, malloc, 2).len(), 1);
+  Ok(())
+}
+
+#[test]
+fn calls_through_an_alias_reach_the_aliasee() -> Result<(), String> {
+  let path = "tests/c_files/call_graph/alias.bc";
+  with_call_graph(path, |_, call_graph| {
+    let options = TestOptions::new("calls_through_an_alias_reach_the_aliasee");
+    let slices = target_slices(call_graph, &options, "real_alloc");
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].caller.simp_name(), "main");
+
+    let edge_id = call_graph
+      .graph
+      .edge_indices()
+      .find(|edge_id| call_graph.graph.call_edge(*edge_id).unwrap().callee.simp_name() == "real_alloc")
+      .unwrap();
+    let edge = call_graph.graph.call_edge(edge_id).unwrap();
+    assert_eq!(edge.alias, Some(String::from("my_alloc")));
+    assert_eq!(edge.caller.simp_name(), "main");
+  })
+}
+
+#[test]
+fn listed_functions_show_arity_and_definitions() -> Result<(), String> {
+  let path = "tests/c_files/call_graph/multi_call.bc";
+  with_call_graph(path, |_, call_graph| {
+    let listings = call_graph.list_functions(None);
+    let fact = listings.iter().find(|listing| listing.name == "fact").unwrap();
+    assert_eq!(fact.num_arguments, 1);
+    assert!(fact.is_definition);
+    assert!(fact.has_return_type);
+    assert_eq!(fact.num_call_sites, 2);
+    let malloc = listings.iter().find(|listing| listing.name == "malloc").unwrap();
+    assert!(!malloc.is_definition);
+    assert_eq!(malloc.num_call_sites, 2);
+
+    let filter = regex::Regex::new("^ma").unwrap();
+    let names = call_graph
+      .list_functions(Some(&filter))
+      .into_iter()
+      .map(|listing| listing.name)
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["main", "malloc"]);
+  })
+}
@@ -0,0 +1,47 @@
+use std::rc::Rc;
+
+use analyzer::semantics::rced::*;
+use analyzer::symbolic_execution::*;
+
+fn rename_all(semantics: &[Semantics]) -> serde_json::Value {
+  let mut renaming = SymbolRenaming::new();
+  let renamed = semantics
+    .iter()
+    .map(|s| renaming.rename_semantics(s))
+    .collect::<Vec<_>>();
+  serde_json::to_value(renamed).unwrap()
+}
+
+fn trace_with_symbols(ptr: usize, len: usize, alloca: usize) -> Vec<Semantics> {
+  let ptr = Rc::new(Value::Sym(ptr));
+  let len = Rc::new(Value::Sym(len));
+  let loc = Rc::new(Value::GEP {
+    loc: Rc::new(Value::Alloc(alloca)),
+    indices: vec![Rc::new(Value::Int(0)), Rc::new(Value::Int(1))],
+  });
+  vec![
+    Semantics::Load { loc: loc.clone() },
+    Semantics::Store { loc, val: ptr.clone() },
+    Semantics::Call {
+      func: Rc::new(Value::Func("memcpy".to_string())),
+      args: vec![ptr, len],
+      num_params: 3,
+      is_var_arg: false,
+      mangled: None,
+    },
+  ]
+}
+
+#[test]
+fn symbol_allocation_order_does_not_matter() {
+  let first = rename_all(&trace_with_symbols(3, 7, 2));
+  let second = rename_all(&trace_with_symbols(8, 1, 5));
+  assert_eq!(first, second);
+}
+
+#[test]
+fn distinct_symbols_stay_distinct() {
+  let first = rename_all(&trace_with_symbols(3, 7, 2));
+  let second = rename_all(&trace_with_symbols(3, 3, 2));
+  assert_ne!(first, second);
+}
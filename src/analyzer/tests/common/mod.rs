@@ -0,0 +1,517 @@
+#![allow(dead_code)]
+
+use llir::{values::*, *};
+use std::path::{Path, PathBuf};
+
+use analyzer::call_graph::*;
+use analyzer::feature_extraction::*;
+use analyzer::options::*;
+use analyzer::slicer::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+
+pub struct TestOptions {
+  pub output: PathBuf,
+  pub seed: u64,
+  pub demangle: bool,
+  pub slice_depth: usize,
+  pub max_work: usize,
+  pub sample_branches: bool,
+  pub max_node_per_trace: usize,
+  pub max_memory_cells: Option<usize>,
+  pub max_explored_trace_per_slice: usize,
+  pub max_trace_per_slice: usize,
+  pub collect_negative: bool,
+  pub max_negative_trace_per_slice: usize,
+  pub timing_report: Option<usize>,
+  pub dump_dot: bool,
+  pub dump_memory: bool,
+  pub block_trace_only: bool,
+  pub reverse_traces: bool,
+  pub no_trace_reduction: bool,
+  pub entry_filter: Option<String>,
+  pub target: Option<String>,
+  pub target_arg_count: Option<usize>,
+  pub entry_file_filter: Option<String>,
+  pub exclude_call_site: Option<String>,
+  pub dedup_slices: bool,
+  pub trace_format: TraceFormat,
+  pub slice_shard: Option<SliceShard>,
+  pub compress: bool,
+  pub trace_shard_size: Option<usize>,
+  pub trace_keep_kinds: Option<String>,
+  pub max_constraints: Option<usize>,
+  pub seed_file: Option<String>,
+  pub merge_states: bool,
+  pub report_unreachable: bool,
+  pub keep_going: bool,
+  pub strict_traces: bool,
+  pub opaque_functions: Option<String>,
+  pub exclude_intrinsics_list: Option<String>,
+  pub noreturn_functions: Option<String>,
+  pub resume: bool,
+  pub global_timeout_sec: Option<u64>,
+  pub coverage_report: Option<String>,
+  pub budget_file: Option<String>,
+  pub solver_rlimit: Option<u32>,
+  pub keep_unknown: bool,
+  pub resolve_indirect: bool,
+  pub max_slice_functions: Option<usize>,
+  pub force_entry: Option<String>,
+  pub max_traces_per_target: Option<usize>,
+  pub incremental: bool,
+  pub ignore_version: bool,
+  pub feature_stats: bool,
+  pub inter_call_window: usize,
+  pub enabled_features: Option<String>,
+  pub disabled_features: Option<String>,
+}
+
+impl TestOptions {
+  pub fn new(name: &str) -> Self {
+    let output = std::env::temp_dir().join("analyzer_tests").join(name);
+    let _ = std::fs::remove_dir_all(&output);
+    Self {
+      output,
+      seed: 12345,
+      demangle: false,
+      slice_depth: 1,
+      max_work: 50,
+      sample_branches: false,
+      max_node_per_trace: 5000,
+      max_memory_cells: None,
+      max_explored_trace_per_slice: 1000,
+      max_trace_per_slice: 50,
+      collect_negative: false,
+      max_negative_trace_per_slice: 10,
+      timing_report: None,
+      dump_dot: false,
+      dump_memory: false,
+      block_trace_only: false,
+      reverse_traces: false,
+      no_trace_reduction: true,
+      entry_filter: None,
+      target: None,
+      target_arg_count: None,
+      entry_file_filter: None,
+      exclude_call_site: None,
+      dedup_slices: false,
+      trace_format: TraceFormat::Json,
+      slice_shard: None,
+      compress: false,
+      trace_shard_size: None,
+      trace_keep_kinds: None,
+      max_constraints: None,
+      seed_file: None,
+      merge_states: false,
+      report_unreachable: false,
+      keep_going: false,
+      strict_traces: false,
+      opaque_functions: None,
+      exclude_intrinsics_list: None,
+      noreturn_functions: None,
+      resume: false,
+      global_timeout_sec: None,
+      coverage_report: None,
+      budget_file: None,
+      solver_rlimit: None,
+      keep_unknown: false,
+      resolve_indirect: false,
+      max_slice_functions: None,
+      force_entry: None,
+      max_traces_per_target: None,
+      incremental: false,
+      ignore_version: false,
+      feature_stats: false,
+      inter_call_window: 10,
+      enabled_features: None,
+      disabled_features: None,
+    }
+  }
+}
+
+impl GeneralOptions for TestOptions {
+  fn use_serial(&self) -> bool {
+    true
+  }
+
+  fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  fn demangle(&self) -> bool {
+    self.demangle
+  }
+}
+
+impl IOOptions for TestOptions {
+  fn input_path(&self) -> PathBuf {
+    PathBuf::new()
+  }
+
+  fn output_path(&self) -> PathBuf {
+    self.output.clone()
+  }
+
+  fn default_package(&self) -> Option<&str> {
+    None
+  }
+
+  fn compress(&self) -> bool {
+    self.compress
+  }
+
+  fn trace_shard_size(&self) -> Option<usize> {
+    self.trace_shard_size
+  }
+}
+
+impl CallGraphOptions for TestOptions {
+  fn remove_llvm_funcs(&self) -> bool {
+    false
+  }
+
+  fn resolve_indirect(&self) -> bool {
+    self.resolve_indirect
+  }
+}
+
+impl SlicerOptions for TestOptions {
+  fn no_reduce_slice(&self) -> bool {
+    true
+  }
+
+  fn slice_depth(&self) -> usize {
+    self.slice_depth
+  }
+
+  fn entry_filter(&self) -> &Option<String> {
+    &self.entry_filter
+  }
+
+  fn target(&self) -> &Option<String> {
+    &self.target
+  }
+
+  fn target_inclusion_filter(&self) -> &Option<String> {
+    &None
+  }
+
+  fn target_exclusion_filter(&self) -> &Option<String> {
+    &None
+  }
+
+  fn use_regex_filter(&self) -> bool {
+    true
+  }
+
+  fn max_avg_num_blocks(&self) -> usize {
+    1000
+  }
+
+  fn target_arg_count(&self) -> Option<usize> {
+    self.target_arg_count
+  }
+
+  fn entry_file_filter(&self) -> &Option<String> {
+    &self.entry_file_filter
+  }
+
+  fn exclude_call_site(&self) -> &Option<String> {
+    &self.exclude_call_site
+  }
+
+  fn dedup_slices(&self) -> bool {
+    self.dedup_slices
+  }
+
+  fn max_slice_functions(&self) -> Option<usize> {
+    self.max_slice_functions
+  }
+
+  fn force_entry(&self) -> &Option<String> {
+    &self.force_entry
+  }
+}
+
+impl SymbolicExecutionOptions for TestOptions {
+  fn slice_depth(&self) -> usize {
+    self.slice_depth
+  }
+
+  fn max_work(&self) -> usize {
+    self.max_work
+  }
+
+  fn no_random_work(&self) -> bool {
+    true
+  }
+
+  fn sample_branches(&self) -> bool {
+    self.sample_branches
+  }
+
+  fn max_node_per_trace(&self) -> usize {
+    self.max_node_per_trace
+  }
+
+  fn max_memory_cells(&self) -> Option<usize> {
+    self.max_memory_cells
+  }
+
+  fn max_explored_trace_per_slice(&self) -> usize {
+    self.max_explored_trace_per_slice
+  }
+
+  fn max_trace_per_slice(&self) -> usize {
+    self.max_trace_per_slice
+  }
+
+  fn collect_negative(&self) -> bool {
+    self.collect_negative
+  }
+
+  fn max_negative_trace_per_slice(&self) -> usize {
+    self.max_negative_trace_per_slice
+  }
+
+  fn no_trace_reduction(&self) -> bool {
+    self.no_trace_reduction
+  }
+
+  fn no_prefilter_block_trace(&self) -> bool {
+    true
+  }
+
+  fn print_block_trace(&self) -> bool {
+    false
+  }
+
+  fn print_trace(&self) -> bool {
+    false
+  }
+
+  fn timing_report(&self) -> Option<usize> {
+    self.timing_report
+  }
+
+  fn dump_dot(&self) -> bool {
+    self.dump_dot
+  }
+
+  fn dump_memory(&self) -> bool {
+    self.dump_memory
+  }
+
+  fn block_trace_only(&self) -> bool {
+    self.block_trace_only
+  }
+
+  fn reverse_traces(&self) -> bool {
+    self.reverse_traces
+  }
+
+  fn trace_format(&self) -> TraceFormat {
+    self.trace_format
+  }
+
+  fn slice_shard(&self) -> Option<SliceShard> {
+    self.slice_shard
+  }
+
+  fn trace_keep_kinds(&self) -> &Option<String> {
+    &self.trace_keep_kinds
+  }
+
+  fn max_constraints(&self) -> Option<usize> {
+    self.max_constraints
+  }
+
+  fn seed_file(&self) -> &Option<String> {
+    &self.seed_file
+  }
+
+  fn merge_states(&self) -> bool {
+    self.merge_states
+  }
+
+  fn report_unreachable(&self) -> bool {
+    self.report_unreachable
+  }
+
+  fn keep_going(&self) -> bool {
+    self.keep_going
+  }
+
+  fn opaque_functions(&self) -> &Option<String> {
+    &self.opaque_functions
+  }
+
+  fn exclude_intrinsics_list(&self) -> &Option<String> {
+    &self.exclude_intrinsics_list
+  }
+
+  fn noreturn_functions(&self) -> &Option<String> {
+    &self.noreturn_functions
+  }
+
+  fn resume(&self) -> bool {
+    self.resume
+  }
+
+  fn global_timeout_sec(&self) -> Option<u64> {
+    self.global_timeout_sec
+  }
+
+  fn coverage_report(&self) -> &Option<String> {
+    &self.coverage_report
+  }
+
+  fn budget_file(&self) -> &Option<String> {
+    &self.budget_file
+  }
+
+  fn solver_rlimit(&self) -> Option<u32> {
+    self.solver_rlimit
+  }
+
+  fn keep_unknown(&self) -> bool {
+    self.keep_unknown
+  }
+}
+
+impl FeatureExtractorOptions for TestOptions {
+  fn causality_dictionary_size(&self) -> usize {
+    5
+  }
+
+  fn inter_call_window(&self) -> usize {
+    self.inter_call_window
+  }
+
+  fn strict_traces(&self) -> bool {
+    self.strict_traces
+  }
+
+  fn max_traces_per_target(&self) -> Option<usize> {
+    self.max_traces_per_target
+  }
+
+  fn enabled_features(&self) -> &Option<String> {
+    &self.enabled_features
+  }
+
+  fn disabled_features(&self) -> &Option<String> {
+    &self.disabled_features
+  }
+
+  fn incremental(&self) -> bool {
+    self.incremental
+  }
+
+  fn ignore_version(&self) -> bool {
+    self.ignore_version
+  }
+
+  fn feature_stats(&self) -> bool {
+    self.feature_stats
+  }
+}
+
+/// Find the first call to `target` inside `caller` and build a slice starting from `entry`
+pub fn build_slice<'ctx>(module: &Module<'ctx>, entry: &str, caller: &str, target: &str) -> Slice<'ctx> {
+  let entry_func = module.get_function(entry).unwrap();
+  let caller_func = module.get_function(caller).unwrap();
+  let (instr, callee) = caller_func
+    .iter_instructions()
+    .find_map(|instr| match instr {
+      Instruction::Call(call) if !call.is_intrinsic_call() => match call.callee_function() {
+        Some(f) if f.simp_name() == target => Some((CallSite::Call(call), f)),
+        _ => None,
+      },
+      _ => None,
+    })
+    .unwrap();
+  Slice {
+    entry: entry_func,
+    caller: caller_func,
+    callee,
+    instr,
+    functions: vec![entry_func, caller_func].into_iter().collect(),
+  }
+}
+
+/// Load the byte code at `path` and run `f` with its call graph and a slice
+pub fn with_slice<F>(path: &str, entry: &str, caller: &str, target: &str, f: F) -> Result<(), String>
+where
+  F: FnOnce(&Module, &CallGraph, Slice),
+{
+  let ctx = Context::create();
+  let module = ctx.load_module(Path::new(path))?;
+  let call_graph = CallGraph::from_module(&module, &TestOptions::new("call_graph"));
+  let slice = build_slice(&module, entry, caller, target);
+  f(&module, &call_graph, slice);
+  Ok(())
+}
+
+/// Load the byte code at `path` and run `f` with its call graph
+pub fn with_call_graph<F>(path: &str, f: F) -> Result<(), String>
+where
+  F: FnOnce(&Module, &CallGraph),
+{
+  let ctx = Context::create();
+  let module = ctx.load_module(Path::new(path))?;
+  let call_graph = CallGraph::from_module(&module, &TestOptions::new("call_graph"));
+  f(&module, &call_graph);
+  Ok(())
+}
+
+/// Load the byte code at each of `paths` and run `f` with the call graph linking them
+pub fn with_linked_call_graph<F>(paths: &[&str], f: F) -> Result<(), String>
+where
+  F: FnOnce(&[Module], &CallGraph),
+{
+  let ctx = Context::create();
+  let modules = paths
+    .iter()
+    .map(|path| ctx.load_module(Path::new(path)))
+    .collect::<Result<Vec<_>, _>>()?;
+  let call_graph = CallGraph::from_modules(&modules, &TestOptions::new("call_graph"));
+  f(&modules, &call_graph);
+  Ok(())
+}
+
+/// Run the slicer over the call graph and return the slices of `target`
+pub fn target_slices<'ctx>(call_graph: &CallGraph<'ctx>, options: &TestOptions, target: &str) -> Vec<Slice<'ctx>> {
+  let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, options).unwrap();
+  let mut target_slices_map = TargetSlicesMap::from_target_edges_map(&target_edges_map, call_graph, options).unwrap();
+  target_slices_map.remove(target).unwrap_or_default()
+}
+
+/// Execute a single slice as slice #0 of its target, creating the trace folder first
+pub fn execute<'ctx>(
+  module: &Module<'ctx>,
+  call_graph: &CallGraph<'ctx>,
+  slice: Slice<'ctx>,
+  options: &TestOptions,
+) -> MetaData {
+  execute_linked(std::slice::from_ref(module), call_graph, slice, options)
+}
+
+/// Execute a single slice spanning several modules as slice #0 of its target
+pub fn execute_linked<'ctx>(
+  modules: &[Module<'ctx>],
+  call_graph: &CallGraph<'ctx>,
+  slice: Slice<'ctx>,
+  options: &TestOptions,
+) -> MetaData {
+  let dir = options.trace_target_slice_dir(slice.target_function_name().as_str(), 0);
+  std::fs::create_dir_all(dir).unwrap();
+  let ctx = SymbolicExecutionContext::new(modules, call_graph, options).unwrap();
+  ctx.execute_slice(slice, 0)
+}
+
+/// Load the dumped json of a trace
+pub fn load_trace_json(options: &TestOptions, target: &str, slice_id: usize, trace_id: usize) -> serde_json::Value {
+  load_json(&options.trace_target_slice_file_path(target, slice_id, trace_id)).unwrap()
+}
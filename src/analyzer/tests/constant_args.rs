@@ -0,0 +1,23 @@
+mod common;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+
+use common::*;
+
+#[test]
+fn folded_open_flags_are_reported() -> Result<(), String> {
+  let path = "tests/c_files/constant_args/open.bc";
+  with_slice(path, "main", "main", "open", |module, call_graph, slice| {
+    let options = TestOptions::new("folded_open_flags_are_reported");
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    execute(module, call_graph, slice, &options);
+    let trace: Trace = serde_json::from_value(load_trace_json(&options, "open", 0, 0)).unwrap();
+    let features = ConstantArgumentFeatureExtractor::new().extract(0, &slice_json, &trace);
+
+    // O_CREAT | O_WRONLY, folded from the or on the loaded flags
+    assert_eq!(features["arg1"], 0o101);
+    assert_eq!(features["arg2"], 0o644);
+    assert!(features["arg0"].is_null());
+  })
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use std::rc::Rc;
+
+use analyzer::semantics::{rced::*, *};
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+fn icmp(pred: Predicate, op0: Value, op1: Value, width: u32, branch: bool) -> Constraint {
+  let cond = Value::ICmp {
+    pred,
+    op0: Rc::new(op0),
+    op1: Rc::new(op1),
+    width,
+  };
+  Constraint {
+    cond: cond.as_comparison().unwrap(),
+    branch,
+  }
+}
+
+fn fcmp(pred: FPredicate, op0: Value, op1: Value, branch: bool) -> Constraint {
+  let cond = Value::FCmp {
+    pred,
+    op0: Rc::new(op0),
+    op1: Rc::new(op1),
+  };
+  Constraint {
+    cond: cond.as_comparison().unwrap(),
+    branch,
+  }
+}
+
+#[test]
+fn unsat_float_conjunction_is_rejected() {
+  let constraints: Constraints = vec![
+    fcmp(FPredicate::OGT, Value::Sym(0), Value::Float(Float(1.5)), true),
+    fcmp(FPredicate::OLT, Value::Sym(0), Value::Float(Float(1.0)), true),
+  ];
+  assert!(!constraints.sat());
+}
+
+#[test]
+fn sat_float_conjunction_is_kept() {
+  let constraints: Constraints = vec![
+    fcmp(FPredicate::OGT, Value::Sym(0), Value::Float(Float(0.25)), true),
+    fcmp(FPredicate::OLT, Value::Sym(0), Value::Float(Float(0.5)), true),
+  ];
+  assert!(constraints.sat());
+}
+
+#[test]
+fn nan_sensitive_float_constraints_are_possibly_sat() {
+  // Both comparisons are false when the symbol is NaN
+  let constraints: Constraints = vec![
+    fcmp(FPredicate::OGT, Value::Sym(0), Value::Float(Float(1.5)), false),
+    fcmp(FPredicate::OLE, Value::Sym(0), Value::Float(Float(1.5)), false),
+  ];
+  assert!(constraints.sat());
+
+  // Both comparisons are true when the symbol is NaN
+  let constraints: Constraints = vec![
+    fcmp(FPredicate::UGT, Value::Sym(0), Value::Float(Float(1.5)), true),
+    fcmp(FPredicate::ULT, Value::Sym(0), Value::Float(Float(1.0)), true),
+  ];
+  assert!(constraints.sat());
+}
+
+#[test]
+fn signedness_decides_int_comparison() {
+  // -1 is negative when signed, but the largest value when unsigned
+  let signed: Constraints = vec![
+    icmp(Predicate::EQ, Value::Sym(0), Value::Int(-1), 32, true),
+    icmp(Predicate::SGT, Value::Sym(0), Value::Int(5), 32, true),
+  ];
+  assert!(!signed.sat());
+
+  let unsigned: Constraints = vec![
+    icmp(Predicate::EQ, Value::Sym(0), Value::Int(-1), 32, true),
+    icmp(Predicate::UGT, Value::Sym(0), Value::Int(5), 32, true),
+  ];
+  assert!(unsigned.sat());
+}
+
+#[test]
+fn narrow_add_wraps_around() {
+  // x + 1 < x only holds when the addition overflows, which is impossible over
+  // unbounded integers but happens for x = 127 with 8-bit integers
+  let sum = Value::Bin {
+    op: BinOp::Add,
+    op0: Rc::new(Value::Sym(0)),
+    op1: Rc::new(Value::Int(1)),
+    width: 8,
+  };
+  let constraints: Constraints = vec![icmp(Predicate::SLT, sum.clone(), Value::Sym(0), 8, true)];
+  assert!(constraints.sat());
+
+  // With x = 127 the sum wraps to -128
+  let constraints: Constraints = vec![
+    icmp(Predicate::EQ, Value::Sym(0), Value::Int(127), 8, true),
+    icmp(Predicate::SGT, sum, Value::Int(0), 8, true),
+  ];
+  assert!(!constraints.sat());
+}
+
+#[test]
+fn swapped_comparisons_canonicalize_equally() {
+  let x = || Value::Arg(0);
+  let five = || Value::Int(5);
+  let lt = icmp(Predicate::SLT, x(), five(), 32, true).cond;
+  let gt = icmp(Predicate::SGT, five(), x(), 32, true).cond;
+  assert_eq!(lt, gt);
+  assert_ne!(lt, icmp(Predicate::SGT, x(), five(), 32, true).cond);
+
+  let x = || Value::Sym(1);
+  let y = || Value::Sym(2);
+  assert_eq!(
+    icmp(Predicate::ULE, x(), y(), 64, true).cond,
+    icmp(Predicate::UGE, y(), x(), 64, true).cond
+  );
+  assert_eq!(
+    fcmp(FPredicate::OLT, x(), Value::Float(Float(1.5)), true).cond,
+    fcmp(FPredicate::OGT, Value::Float(Float(1.5)), x(), true).cond
+  );
+}
+
+#[test]
+fn duplicate_and_concretely_true_constraints_are_simplified_away() {
+  let constraints: Constraints = vec![
+    icmp(Predicate::SGT, Value::Sym(0), Value::Int(5), 32, true),
+    icmp(Predicate::EQ, Value::Int(1), Value::Int(1), 1, true),
+    icmp(Predicate::SGT, Value::Sym(0), Value::Int(5), 32, true),
+    icmp(Predicate::SLT, Value::Sym(0), Value::Int(10), 32, true),
+  ];
+  let simplified = constraints.simplify().unwrap();
+  assert_eq!(simplified, vec![constraints[0].clone(), constraints[3].clone()]);
+  assert_eq!(simplified.sat(), constraints.sat());
+  assert!(constraints.sat());
+}
+
+#[test]
+fn concretely_false_constraint_is_unsat_without_solving() {
+  let constraints: Constraints = vec![
+    icmp(Predicate::SGT, Value::Sym(0), Value::Int(5), 32, true),
+    fcmp(
+      FPredicate::OLT,
+      Value::Float(Float(2.0)),
+      Value::Float(Float(1.0)),
+      true,
+    ),
+  ];
+  assert!(constraints.simplify().is_none());
+  assert!(!constraints.sat());
+}
+
+#[test]
+fn constraints_past_the_cap_are_dropped() -> Result<(), String> {
+  let path = "tests/c_files/constraints/many_branches.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("constraints_past_the_cap_are_dropped");
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert_eq!(metadata.constraint_truncated_trace_count, 0);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    assert_eq!(trace["cons"].as_array().unwrap().len(), 3);
+    assert!(trace["constraint_truncated"].is_null());
+
+    options.max_constraints = Some(1);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.constraint_truncated_trace_count, metadata.proper_trace_count);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    assert_eq!(trace["cons"].as_array().unwrap().len(), 1);
+    assert_eq!(trace["constraint_truncated"], true);
+  })
+}
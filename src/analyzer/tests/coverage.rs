@@ -0,0 +1,22 @@
+mod common;
+
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+#[test]
+fn unreached_block_is_uncovered() -> Result<(), String> {
+  let path = "tests/c_files/fold/fold_3.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("unreached_block_is_uncovered");
+    options.coverage_report = Some(String::from("coverage.json"));
+    let metadata = execute(module, call_graph, slice, &options);
+
+    // The branch calling g is never taken as `a > 5` folds to false
+    let report = metadata.block_coverage.report(call_graph);
+    let main = &report["functions"]["main"];
+    assert_eq!(main["status"], "partial");
+    assert_eq!(main["uncovered_blocks"].as_array().unwrap().len(), 1);
+    assert_eq!(report["partially_covered"], 1);
+  })
+}
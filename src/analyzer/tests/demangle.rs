@@ -0,0 +1,24 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn demangled_callee_appears_in_the_call_node() -> Result<(), String> {
+  let path = "tests/c_files/demangle/demangle.bc";
+  with_call_graph(path, |module, call_graph| {
+    let mut options = TestOptions::new("demangled_callee_appears_in_the_call_node");
+    options.demangle = true;
+    options.target = Some(String::from("foo::bar(int)"));
+
+    // Slices stay keyed by the raw symbol
+    let mut slices = target_slices(call_graph, &options, "_ZN3foo3barEi");
+    assert_eq!(slices.len(), 1);
+
+    let metadata = execute(module, call_graph, slices.remove(0), &options);
+    assert!(metadata.proper_trace_count > 0);
+    let trace = load_trace_json(&options, "_ZN3foo3barEi", 0, 0);
+    let call = &trace["instrs"][trace["target"].as_u64().unwrap() as usize]["sem"]["Call"];
+    assert_eq!(call["func"]["Func"], "foo::bar(int)");
+    assert_eq!(call["mangled"], "_ZN3foo3barEi");
+  })
+}
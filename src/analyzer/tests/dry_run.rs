@@ -0,0 +1,25 @@
+mod common;
+
+use std::process::Command;
+
+use analyzer::options::*;
+
+use common::*;
+
+#[test]
+fn dry_run_only_prints_slice_counts() -> Result<(), String> {
+  let options = TestOptions::new("dry_run_only_prints_slice_counts");
+  let output = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg("tests/c_files/basic/example_1.bc")
+    .arg(&options.output)
+    .arg("--dry-run")
+    .output()
+    .map_err(|err| err.to_string())?;
+  assert!(output.status.success());
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  assert!(stdout.contains("malloc: "));
+  assert!(stdout.contains("slices in total"));
+  assert!(!options.trace_dir().exists());
+  assert!(!options.output_path().exists());
+  Ok(())
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::process::Command;
+
+use analyzer::error::*;
+use analyzer::options::*;
+use analyzer::slicer::*;
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+#[test]
+fn invalid_slice_depth_is_an_option_error() {
+  assert_eq!(parse_slice_depth("3"), Ok(3));
+  assert!(matches!(parse_slice_depth("-1"), Err(AnalyzerError::OptionParse(_))));
+  assert!(matches!(parse_slice_depth("deep"), Err(AnalyzerError::OptionParse(_))));
+}
+
+#[test]
+fn invalid_slice_depth_is_reported_by_the_analyzer() -> Result<(), String> {
+  let options = TestOptions::new("invalid_slice_depth_is_reported_by_the_analyzer");
+  let output = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg("tests/c_files/basic/example_1.bc")
+    .arg(&options.output)
+    .arg("--slice-depth=-1")
+    .output()
+    .map_err(|err| err.to_string())?;
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("Invalid slice depth -1, expected a non-negative integer"));
+  Ok(())
+}
+
+#[test]
+fn bad_function_lists_are_told_apart() -> Result<(), String> {
+  let path = "tests/c_files/opaque/opaque_1.bc";
+  with_call_graph(path, |module, call_graph| {
+    let mut options = TestOptions::new("bad_function_lists_are_told_apart");
+    let opaque_path = options.output_path().join("opaque.txt");
+    options.opaque_functions = Some(opaque_path.to_str().unwrap().to_string());
+    let err = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).err();
+    assert!(matches!(err, Some(AnalyzerError::Io(_))));
+
+    std::fs::create_dir_all(options.output_path()).unwrap();
+    std::fs::write(&opaque_path, "my_alloc(\n").unwrap();
+    let err = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).err();
+    assert_eq!(
+      err.map(|err| err.to_string()),
+      Some(String::from("Cannot parse function regex my_alloc("))
+    );
+  })
+}
+
+#[test]
+fn bad_call_site_exclusion_is_a_regex_error() -> Result<(), String> {
+  let path = "tests/c_files/slicer/wrapper.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("bad_call_site_exclusion_is_a_regex_error");
+    options.exclude_call_site = Some(String::from("_wrapper("));
+    let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, &options).unwrap();
+    let err = TargetSlicesMap::from_target_edges_map(&target_edges_map, call_graph, &options).err();
+    assert!(matches!(err, Some(AnalyzerError::Regex(_))));
+  })
+}
+
+#[test]
+fn bad_entry_file_filter_is_a_regex_error() -> Result<(), String> {
+  let path = "tests/c_files/slicer/entry_file.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("bad_entry_file_filter_is_a_regex_error");
+    options.entry_file_filter = Some(String::from("[entry"));
+    let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, &options).unwrap();
+    let err = TargetSlicesMap::from_target_edges_map(&target_edges_map, call_graph, &options).err();
+    assert!(matches!(err, Some(AnalyzerError::Regex(_))));
+  })
+}
+
+#[test]
+fn bad_entry_filter_is_a_regex_error() -> Result<(), String> {
+  let path = "tests/c_files/slicer/entry_file.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("bad_entry_filter_is_a_regex_error");
+    options.entry_filter = Some(String::from("*.c"));
+    let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, &options).unwrap();
+    let err = TargetSlicesMap::from_target_edges_map(&target_edges_map, call_graph, &options).err();
+    assert!(matches!(err, Some(AnalyzerError::Regex(_))));
+  })
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use serde_json::json;
+use std::process::{Command, Output};
+
+use analyzer::options::*;
+use analyzer::utils::*;
+
+use common::*;
+
+const PACKAGE: &str = "pkg";
+
+const BC_PATH: &str = "tests/c_files/basic/example_1.bc";
+
+/// Analyze `example_1.bc` as the package `pkg` with the extra analyzer arguments
+fn analyze(options: &TestOptions, analyzer_args: &[&str]) -> Result<(), String> {
+  let output = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg(BC_PATH)
+    .arg(&options.output)
+    .arg("--target=malloc")
+    .arg(format!("--subfolder={}", PACKAGE))
+    .arg("--no-feature")
+    .args(analyzer_args)
+    .output()
+    .map_err(|err| err.to_string())?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+  Ok(())
+}
+
+/// Extract the features of `malloc` in the package `pkg` with the `feature-extract` binary
+fn extract(options: &TestOptions) -> Result<Output, String> {
+  let num_slices = json_files_in_dir(&options.slice_target_package_dir("malloc", PACKAGE))?.len();
+  let input = json!({
+    "packages": [{ "name": PACKAGE, "dir": BC_PATH }],
+    "functions": [{ "name": "malloc", "occurrences": [[PACKAGE, num_slices]] }],
+  });
+  let input_path = options.output_path().join("input.json");
+  dump_json(&input, input_path.clone())?;
+  Command::new(env!("CARGO_BIN_EXE_feature-extract"))
+    .arg(input_path)
+    .arg(&options.output)
+    .output()
+    .map_err(|err| err.to_string())
+}
+
+fn analyze_and_extract(options: &TestOptions, analyzer_args: &[&str]) -> Result<Output, String> {
+  analyze(options, analyzer_args)?;
+  extract(options)
+}
+
+/// The features extracted for a slice, in the order of their trace ids
+fn extracted_features(options: &TestOptions, slice_id: usize) -> Result<Vec<serde_json::Value>, String> {
+  let dir = options.feature_target_package_slice_dir("malloc", PACKAGE, slice_id);
+  json_files_in_dir(&dir)?
+    .into_iter()
+    .map(|(_, path)| load_json(&path))
+    .collect()
+}
+
+#[test]
+fn jsonl_traces_are_extracted() -> Result<(), String> {
+  let json = TestOptions::new("feature_extract_jsonl_traces_are_extracted_json");
+  let output = analyze_and_extract(&json, &[])?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+  let options = TestOptions::new("feature_extract_jsonl_traces_are_extracted");
+  let output = analyze_and_extract(&options, &["--trace-format=jsonl"])?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+  assert!(!options.trace_target_package_slice_dir("malloc", PACKAGE, 0).exists());
+
+  // Each line gets the features its trace would get from its own json file
+  let jsonl_path = options
+    .existing_trace_target_package_slice_jsonl_path("malloc", PACKAGE, 0)
+    .unwrap();
+  let num_traces = load_jsonl_t::<serde_json::Value>(&jsonl_path)?.len();
+  let json_features = extracted_features(&json, 0)?;
+  assert_eq!(json_features.len(), num_traces);
+  assert_eq!(extracted_features(&options, 0)?, json_features);
+  Ok(())
+}
+
+#[test]
+fn reversed_traces_give_the_forward_features() -> Result<(), String> {
+  let forward = TestOptions::new("feature_extract_reversed_traces_forward");
+  let output = analyze_and_extract(&forward, &[])?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+  let reversed = TestOptions::new("feature_extract_reversed_traces_reversed");
+  let output = analyze_and_extract(&reversed, &["--reverse-traces"])?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+  let trace_path = reversed.trace_target_package_slice_file_path("malloc", PACKAGE, 0, 0);
+  assert_eq!(load_json(&trace_path)?["reversed"], json!(true));
+
+  let forward_features = extracted_features(&forward, 0)?;
+  assert!(!forward_features.is_empty());
+  assert_eq!(extracted_features(&reversed, 0)?, forward_features);
+  Ok(())
+}
+
+#[test]
+fn truncated_traces_are_skipped_and_counted() -> Result<(), String> {
+  let options = TestOptions::new("feature_extract_truncated_traces_are_skipped_and_counted");
+  analyze(&options, &[])?;
+
+  // Truncate trace 1 as if the run was killed mid-write
+  let trace_dir = options.trace_target_package_slice_dir("malloc", PACKAGE, 0);
+  let num_traces = json_files_in_dir(&trace_dir)?.len();
+  assert!(num_traces > 1);
+  let trace_path = options.trace_target_package_slice_file_path("malloc", PACKAGE, 0, 1);
+  let trace = std::fs::read_to_string(&trace_path).map_err(|err| err.to_string())?;
+  std::fs::write(&trace_path, &trace[..trace.len() / 2]).map_err(|err| err.to_string())?;
+
+  let output = extract(&options)?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert_eq!(stderr.matches("Skipping malloc slice 0 trace 1").count(), 1);
+  assert!(stderr.contains("Skipped 1 unparseable traces"));
+  assert_eq!(extracted_features(&options, 0)?.len(), num_traces - 1);
+  assert!(!options
+    .feature_target_package_slice_file_path("malloc", PACKAGE, 0, 1)
+    .exists());
+  Ok(())
+}
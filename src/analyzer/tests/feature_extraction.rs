@@ -0,0 +1,430 @@
+mod common;
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use analyzer::feature_extraction::*;
+use analyzer::options::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn truncated_traces_are_skipped() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("truncated_traces_are_skipped");
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 1);
+
+    // Replace trace 1 by the first half of trace 0, as if the run was killed mid-write
+    let trace = std::fs::read_to_string(options.trace_target_slice_file_path("malloc", 0, 0)).unwrap();
+    std::fs::write(
+      options.trace_target_slice_file_path("malloc", 0, 1),
+      &trace[..trace.len() / 2],
+    )
+    .unwrap();
+
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    assert_eq!(ctx.extract_features(&mut logging_ctx), 1);
+    let feature_files = json_files_in_dir(&options.feature_target_slice_dir("malloc", 0)).unwrap();
+    assert_eq!(feature_files.len(), metadata.proper_trace_count - 1);
+    assert!(options.feature_target_slice_file_path("malloc", 0, 0).exists());
+    assert!(!options.feature_target_slice_file_path("malloc", 0, 1).exists());
+  })
+}
+
+#[test]
+fn traces_are_capped_per_target() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("traces_are_capped_per_target");
+    options.max_traces_per_target = Some(100);
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+
+    // Duplicate the first trace into 1000 traces
+    let trace_path = options.trace_target_slice_file_path("malloc", 0, 0);
+    for trace_id in 1..1000 {
+      std::fs::copy(&trace_path, options.trace_target_slice_file_path("malloc", 0, trace_id)).unwrap();
+    }
+
+    let extract = || {
+      let _ = std::fs::remove_dir_all(options.feature_dir());
+      let mut logging_ctx = LoggingContext::new(&options).unwrap();
+      let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+      let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+      ctx.extract_features(&mut logging_ctx);
+      let mut files = std::fs::read_dir(options.feature_target_slice_dir("malloc", 0))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+      files.sort();
+      files
+    };
+
+    // Exactly 100 feature files, and the same ones under the same seed
+    let files = extract();
+    assert_eq!(files.len(), 100);
+    assert_eq!(extract(), files);
+  })
+}
+
+#[test]
+fn feature_ids_match_trace_ids() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("feature_ids_match_trace_ids");
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+
+    // Keep only traces with sparse ids
+    let trace = std::fs::read_to_string(options.trace_target_slice_file_path("malloc", 0, 0)).unwrap();
+    std::fs::remove_dir_all(options.trace_target_slice_dir("malloc", 0)).unwrap();
+    std::fs::create_dir_all(options.trace_target_slice_dir("malloc", 0)).unwrap();
+    for trace_id in &[3, 7, 42] {
+      std::fs::write(options.trace_target_slice_file_path("malloc", 0, *trace_id), &trace).unwrap();
+    }
+
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    ctx.extract_features(&mut logging_ctx);
+    for trace_id in 0..50 {
+      let exists = options.feature_target_slice_file_path("malloc", 0, trace_id).exists();
+      assert_eq!(exists, [3, 7, 42].contains(&trace_id));
+    }
+  })
+}
+
+#[test]
+fn only_enabled_features_are_extracted() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("only_enabled_features_are_extracted");
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+
+    // Unknown names are rejected, listing the valid ones
+    options.enabled_features = Some(String::from("ret,unknown"));
+    let err = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map.clone(), &options)
+      .err()
+      .unwrap();
+    assert!(err.contains("unknown") && err.contains("ret.check"));
+
+    options.enabled_features = Some(String::from("ret"));
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    ctx.extract_features(&mut logging_ctx);
+    let features = load_json(&options.feature_target_slice_file_path("malloc", 0, 0)).unwrap();
+    let keys = features
+      .as_object()
+      .unwrap()
+      .keys()
+      .filter(|key| *key != "version")
+      .collect::<Vec<_>>();
+    assert_eq!(keys, vec!["ret"]);
+  })
+}
+
+#[test]
+fn registry_names_are_the_dumped_feature_keys() {
+  let registry = FeatureExtractorRegistry::builtin();
+  for name in registry.names() {
+    let mut options = TestOptions::new("registry_names_are_the_dumped_feature_keys");
+    options.enabled_features = Some(String::from(name));
+    let extractors = registry.build(&options).unwrap();
+    let names = extractors.iter().map(|extractor| extractor.name()).collect::<Vec<_>>();
+    assert_eq!(names, vec![name]);
+  }
+}
+
+#[test]
+fn incremental_extraction_only_rewrites_changed_traces() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("incremental_extraction_only_rewrites_changed_traces");
+    options.incremental = true;
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+
+    // Keep exactly three copies of the first trace
+    let trace = std::fs::read_to_string(options.trace_target_slice_file_path("malloc", 0, 0)).unwrap();
+    std::fs::remove_dir_all(options.trace_target_slice_dir("malloc", 0)).unwrap();
+    std::fs::create_dir_all(options.trace_target_slice_dir("malloc", 0)).unwrap();
+    for trace_id in 0..3 {
+      std::fs::write(options.trace_target_slice_file_path("malloc", 0, trace_id), &trace).unwrap();
+    }
+
+    let extract = |options: &TestOptions| {
+      let mut logging_ctx = LoggingContext::new(options).unwrap();
+      let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+      let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, options).unwrap();
+      ctx.extract_features(&mut logging_ctx);
+    };
+    let modified = |path: &std::path::Path| {
+      std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap()
+    };
+    let (feature_0, feature_1) = (
+      options.feature_target_slice_file_path("malloc", 0, 0),
+      options.feature_target_slice_file_path("malloc", 0, 1),
+    );
+    let wait = || std::thread::sleep(std::time::Duration::from_secs(1));
+    extract(&options);
+    let (time_0, time_1) = (modified(&feature_0), modified(&feature_1));
+
+    // Nothing changed, so nothing is extracted again, leaving enough time for mtimes to differ
+    wait();
+    extract(&options);
+    assert_eq!(modified(&feature_0), time_0);
+    assert_eq!(modified(&feature_1), time_1);
+
+    // Modifying trace 1 only extracts its features again, and deleting trace 2 drops its own
+    wait();
+    std::fs::write(options.trace_target_slice_file_path("malloc", 0, 1), &trace).unwrap();
+    std::fs::remove_file(options.trace_target_slice_file_path("malloc", 0, 2)).unwrap();
+    extract(&options);
+    assert_eq!(modified(&feature_0), time_0);
+    assert!(modified(&feature_1) > time_1);
+    assert!(!options.feature_target_slice_file_path("malloc", 0, 2).exists());
+    let time_1 = modified(&feature_1);
+
+    // So does changing the configuration of the extractors
+    options.inter_call_window += 1;
+    wait();
+    extract(&options);
+    assert!(modified(&feature_0) > time_0);
+    assert!(modified(&feature_1) > time_1);
+  })
+}
+
+#[test]
+fn traces_with_unknown_version_are_rejected() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("traces_with_unknown_version_are_rejected");
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+
+    // Pretend the trace was dumped by a future analyzer
+    let trace_path = options.trace_target_slice_file_path("malloc", 0, 0);
+    let mut trace = load_json(&trace_path).unwrap();
+    assert_eq!(trace["version"], JSON_VERSION);
+    trace["version"] = serde_json::json!(JSON_VERSION + 1);
+    dump_json(&trace, trace_path.clone()).unwrap();
+
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx =
+      FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map.clone(), &options).unwrap();
+    let err = ctx.load_trace(&trace_path).err().unwrap();
+    assert!(err.contains(&format!("version {}", JSON_VERSION + 1)));
+    assert!(err.contains("--ignore-version"));
+
+    options.ignore_version = true;
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    assert!(ctx.load_trace(&trace_path).is_ok());
+  })
+}
+
+#[test]
+fn features_are_extracted_from_a_trace_corpus() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("features_are_extracted_from_a_trace_corpus");
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+    TargetTypes::from_modules(std::slice::from_ref(module))
+      .dump(options.target_types_path())
+      .unwrap();
+
+    // From here on only the files on disk are used
+    let target_types = TargetTypes::load(&options.target_types_path(), false).unwrap();
+    assert!(target_types["malloc"].has_return_type);
+    let target_num_slices_map = discover_target_num_slices_map(&options).unwrap();
+    assert_eq!(
+      target_num_slices_map,
+      vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>()
+    );
+    assert!(
+      FeatureExtractionContext::from_target_types(TargetTypes::new(), target_num_slices_map.clone(), &options).is_err()
+    );
+
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    let ctx = FeatureExtractionContext::from_target_types(target_types, target_num_slices_map, &options).unwrap();
+    assert_eq!(ctx.extract_features(&mut logging_ctx), 0);
+    let features = load_json(&options.feature_target_slice_file_path("malloc", 0, 0)).unwrap();
+    assert!(features.get("ret").is_some());
+  })
+}
+
+#[test]
+fn feature_stats_are_accumulated_per_target() -> Result<(), String> {
+  let path = "tests/c_files/constant_args/open_flags.bc";
+  with_slice(path, "main", "main", "open", |module, call_graph, slice| {
+    let mut options = TestOptions::new("feature_stats_are_accumulated_per_target");
+    options.enabled_features = Some(String::from("constant_args"));
+    options.feature_stats = true;
+    std::fs::create_dir_all(options.slice_target_dir("open")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("open", 0)).unwrap();
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 3);
+
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    let target_num_slices_map = vec![(String::from("open"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    ctx.extract_features(&mut logging_ctx);
+
+    // The flags are 1, 2 and 6 across the three traces
+    let stats = load_json(&options.feature_target_stats_path("open")).unwrap();
+    let flags = &stats["stats"]["constant_args.arg1"];
+    assert_eq!(flags["count"], 3);
+    assert_eq!(flags["mean"], 3.0);
+    assert_eq!(flags["min"], 1.0);
+    assert_eq!(flags["max"], 6.0);
+    assert!(stats["stats"].get("constant_args.arg0").is_none());
+  })
+}
+
+#[test]
+fn compressed_traces_are_loaded_alongside_plain_ones() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("compressed_traces_are_loaded_alongside_plain_ones");
+    options.compress = true;
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    let metadata = execute(module, call_graph, slice, &options);
+    let trace_path = options.trace_target_slice_file_path("malloc", 0, 0);
+    assert!(trace_path.to_str().unwrap().ends_with(".json.gz"));
+    assert_eq!(std::fs::read(&trace_path).unwrap()[..2], [0x1f, 0x8b]);
+
+    // Add an uncompressed copy of the first trace under the next trace id
+    let num_traces = metadata.proper_trace_count;
+    let plain_path = options
+      .trace_target_slice_dir("malloc", 0)
+      .join(format!("{}.json", num_traces));
+    dump_json(&load_json(&trace_path).unwrap(), plain_path).unwrap();
+
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    let mut traces = ctx.load_traces(&String::from("malloc"), 0);
+    traces.sort_by_key(|(trace_id, _)| *trace_id);
+    assert_eq!(
+      traces.iter().map(|(trace_id, _)| *trace_id).collect::<Vec<_>>(),
+      (0..=num_traces).collect::<Vec<_>>()
+    );
+    assert!(traces.iter().all(|(_, trace)| trace.is_ok()));
+
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    assert_eq!(ctx.extract_features(&mut logging_ctx), 0);
+    assert!(options.feature_target_slice_file_path("malloc", 0, num_traces).exists());
+  })
+}
+
+#[test]
+fn plain_and_compressed_copies_of_a_trace_are_loaded_once() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("plain_and_compressed_copies_of_a_trace_are_loaded_once");
+    options.compress = true;
+    let metadata = execute(module, call_graph, slice, &options);
+
+    // Rerun without `--compress`, leaving both `0.json.gz` and a newer `0.json`
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    let trace_path = options.trace_target_slice_file_path("malloc", 0, 0);
+    let plain_path = options.trace_target_slice_dir("malloc", 0).join("0.json");
+    dump_json(&load_json(&trace_path).unwrap(), plain_path.clone()).unwrap();
+
+    let files = json_files_in_dir(&options.trace_target_slice_dir("malloc", 0)).unwrap();
+    assert_eq!(files.len(), metadata.proper_trace_count);
+    assert_eq!(files[0], (0, plain_path));
+  })
+}
+
+#[test]
+fn sharded_traces_are_discovered_with_their_ids() {
+  let mut options = TestOptions::new("sharded_traces_are_discovered_with_their_ids");
+  options.trace_shard_size = Some(100);
+  for trace_id in 0..1000 {
+    let path = options.trace_target_slice_file_path("malloc", 0, trace_id);
+    assert_eq!(
+      path.parent().unwrap(),
+      options
+        .trace_target_slice_dir("malloc", 0)
+        .join((trace_id / 100).to_string())
+    );
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    dump_versioned_json(&serde_json::json!({ "id": trace_id }), path).unwrap();
+  }
+  assert_eq!(
+    std::fs::read_dir(options.trace_target_slice_dir("malloc", 0))
+      .unwrap()
+      .count(),
+    10
+  );
+
+  let ctx = FeatureExtractionContext::from_target_types(TargetTypes::new(), HashMap::new(), &options).unwrap();
+  let mut paths = ctx.load_trace_file_paths(&String::from("malloc"), 0);
+  paths.sort();
+  assert_eq!(paths.len(), 1000);
+  for (trace_id, path) in paths {
+    assert_eq!(path, options.trace_target_slice_file_path("malloc", 0, trace_id));
+    let json = load_json(&path).unwrap();
+    assert_eq!(json["id"], serde_json::json!(trace_id));
+  }
+}
+
+#[test]
+fn zero_trace_shard_size_is_rejected_by_both_binaries() -> Result<(), String> {
+  let options = TestOptions::new("zero_trace_shard_size_is_rejected_by_both_binaries");
+  let analyzer = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg("tests/c_files/basic/example_1.bc")
+    .arg(&options.output)
+    .arg("--trace-shard-size=0")
+    .output()
+    .map_err(|err| err.to_string())?;
+  let feature_extract = Command::new(env!("CARGO_BIN_EXE_feature-extract"))
+    .arg(options.output_path().join("input.json"))
+    .arg(&options.output)
+    .arg("--trace-shard-size=0")
+    .output()
+    .map_err(|err| err.to_string())?;
+  for output in vec![analyzer, feature_extract] {
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--trace-shard-size must be positive"));
+  }
+  assert!(!options.output_path().exists());
+  Ok(())
+}
+
+#[test]
+fn cached_target_types_keep_the_extractor_selection() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_call_graph(path, |module, _| {
+    let options = TestOptions::new("cached_target_types_keep_the_extractor_selection");
+    std::fs::create_dir_all(options.output_path()).unwrap();
+    let target_types = TargetTypes::from_modules(std::slice::from_ref(module));
+    target_types.dump(options.target_types_path()).unwrap();
+    let cached = TargetTypes::load(&options.target_types_path(), false).unwrap();
+    assert_eq!(cached, target_types);
+    for (target, target_type) in &target_types {
+      assert_eq!(
+        FeatureExtractors::extractors_for_target(target, &cached[target], &options).names(),
+        FeatureExtractors::extractors_for_target(target, target_type, &options).names()
+      );
+    }
+  })
+}
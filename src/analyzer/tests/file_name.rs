@@ -0,0 +1,39 @@
+mod common;
+
+use std::collections::HashMap;
+
+use analyzer::options::*;
+use analyzer::slicer::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn cpp_symbol_gives_valid_path() -> Result<(), String> {
+  let symbol = "std::vector<int, std::allocator<int> >::operator[](unsigned long)";
+  let options = TestOptions::new("cpp_symbol_gives_valid_path");
+  let dir = options.trace_target_slice_dir(symbol, 0);
+  let name = dir.parent().unwrap().file_name().unwrap().to_str().unwrap();
+  assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || "%_-.".contains(c)));
+  assert_eq!(unsanitize_file_name(name)?, symbol);
+
+  // The directory can be created, and the target names file maps it back
+  std::fs::create_dir_all(&dir).unwrap();
+  let target_slices_map: TargetSlicesMap = vec![(symbol.to_string(), vec![])].into_iter().collect();
+  target_slices_map.dump(&options);
+  assert!(options.slice_target_dir(symbol).exists());
+  let target_names: HashMap<String, String> = load_json_t(&options.target_names_path())?;
+  assert_eq!(target_names[name], symbol);
+  Ok(())
+}
+
+#[test]
+fn long_names_are_hashed() {
+  let long = "a".repeat(300);
+  let name = sanitize_file_name(&long);
+  assert!(name.len() < 255);
+  assert_ne!(name, sanitize_file_name(&"a".repeat(301)));
+
+  // The suffix is a fixed hash, so output paths do not change with the toolchain
+  assert!(name.ends_with("-ee7717eba7a17e21"));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use analyzer::semantics::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn fold_binary_wraps_around_bit_width() {
+  assert_eq!(fold_binary(BinOp::Add, 3, 4, 32), Some(7));
+  assert_eq!(fold_binary(BinOp::Add, i32::MAX as i64, 1, 32), Some(i32::MIN as i64));
+  assert_eq!(fold_binary(BinOp::Mul, 100, 3, 8), Some(44));
+  assert_eq!(fold_binary(BinOp::Sub, 0, 1, 64), Some(-1));
+  assert_eq!(fold_binary(BinOp::Xor, 0b1100, 0b1010, 32), Some(0b0110));
+  assert_eq!(fold_binary(BinOp::Shl, 1, 31, 32), Some(i32::MIN as i64));
+  assert_eq!(fold_binary(BinOp::LShr, -1, 28, 32), Some(0xf));
+  assert_eq!(fold_binary(BinOp::AShr, -16, 2, 32), Some(-4));
+  assert_eq!(fold_binary(BinOp::Shl, 1, 32, 32), None);
+  assert_eq!(fold_binary(BinOp::SDiv, 8, 2, 32), None);
+}
+
+#[test]
+fn fold_unary_casts_integers() {
+  assert_eq!(fold_unary(UnaOp::Trunc, 300, 32, 8), Some(44));
+  assert_eq!(fold_unary(UnaOp::SExt, -1, 8, 32), Some(-1));
+  assert_eq!(fold_unary(UnaOp::ZExt, -1, 8, 32), Some(255));
+  assert_eq!(fold_unary(UnaOp::BitCast, 1, 32, 32), None);
+}
+
+#[test]
+fn constant_operands_are_folded() -> Result<(), String> {
+  let path = "tests/c_files/fold/fold_1.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("constant_operands_are_folded");
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    assert_eq!(trace["instrs"][target]["sem"]["Call"]["args"][0]["Int"], 14);
+  })
+}
+
+#[test]
+fn symbolic_operands_are_preserved() -> Result<(), String> {
+  let path = "tests/c_files/fold/fold_2.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("symbolic_operands_are_preserved");
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let arg = &trace["instrs"][target]["sem"]["Call"]["args"][0]["Bin"];
+    assert_eq!(arg["op"], "Add");
+    assert_eq!(arg["op1"]["Int"], 3);
+  })
+}
+
+#[test]
+fn fold_icmp_respects_signedness() {
+  assert!(fold_icmp(Predicate::SLT, -1, 0, 32));
+  assert!(!fold_icmp(Predicate::ULT, -1, 0, 32));
+  assert!(fold_icmp(Predicate::EQ, -1, 0xff, 8));
+  assert!(fold_icmp(Predicate::UGT, 0x80, 0x7f, 8));
+}
+
+#[test]
+fn constant_condition_only_explores_live_branch() -> Result<(), String> {
+  let path = "tests/c_files/fold/fold_3.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("constant_condition_only_explores_live_branch");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.explored_trace_count, 1);
+    assert_eq!(metadata.proper_trace_count, 1);
+
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let instrs = trace["instrs"].as_array().unwrap();
+    assert!(instrs.iter().all(|instr| instr["sem"]["Call"]["func"]["Func"] != "g"));
+    let target = trace["target"].as_u64().unwrap() as usize;
+    assert_eq!(instrs[target]["sem"]["Call"]["args"][0]["Int"], 4);
+  })
+}
+
+#[test]
+fn constant_array_indices_get_distinct_locations() -> Result<(), String> {
+  let path = "tests/c_files/fold/fold_gep.bc";
+  with_slice(path, "main", "main", "use", |module, call_graph, slice| {
+    let options = TestOptions::new("constant_array_indices_get_distinct_locations");
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "use", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let args = &trace["instrs"][target]["sem"]["Call"]["args"];
+
+    // `a[0]` and `a[1]` do not collide, and `p[1]` is found at `a[2]`
+    assert_eq!(args[0]["Int"], 1);
+    assert_eq!(args[1]["Int"], 2);
+    assert_eq!(args[2]["Int"], 3);
+  })
+}
+
+#[test]
+fn constant_offsets_do_not_step_over_struct_fields() -> Result<(), String> {
+  let path = "tests/c_files/fold/field_gep.bc";
+  with_slice(path, "main", "main", "use", |module, call_graph, slice| {
+    let options = TestOptions::new("constant_offsets_do_not_step_over_struct_fields");
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "use", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let args = &trace["instrs"][target]["sem"]["Call"]["args"];
+
+    // `q[1]` lies in the padding after `g.in.a`, not at `g.in.b`
+    assert_eq!(args[0]["Int"], 3);
+    assert_ne!(args[1]["Int"], 3);
+  })
+}
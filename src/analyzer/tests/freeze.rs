@@ -0,0 +1,31 @@
+mod common;
+
+use common::*;
+
+fn target_args(target: &str, name: &str) -> Result<serde_json::Value, String> {
+  let path = "tests/ll_files/freeze/freeze.bc";
+  let mut args = serde_json::Value::Null;
+  with_slice(path, "main", "main", target, |module, call_graph, slice| {
+    let options = TestOptions::new(name);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, target, 0, 0);
+    let index = trace["target"].as_u64().unwrap() as usize;
+    args = trace["instrs"][index]["sem"]["Call"]["args"].clone();
+  })?;
+  Ok(args)
+}
+
+#[test]
+fn frozen_value_flows_to_its_uses() -> Result<(), String> {
+  let args = target_args("use", "frozen_value_flows_to_its_uses")?;
+  assert_eq!(args[0]["Call"]["func"]["Func"], "get");
+  Ok(())
+}
+
+#[test]
+fn frozen_undef_is_a_fresh_symbol() -> Result<(), String> {
+  let args = target_args("sink", "frozen_undef_is_a_fresh_symbol")?;
+  assert!(args[0]["Sym"].is_number());
+  Ok(())
+}
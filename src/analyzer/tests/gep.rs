@@ -0,0 +1,104 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn struct_gep_records_the_accessed_field() -> Result<(), String> {
+  let path = "tests/c_files/gep/struct_field.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("struct_gep_records_the_accessed_field");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let fields = trace["instrs"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .filter_map(|instr| instr["sem"]["GEP"].as_object())
+      .map(|gep| {
+        let field = &gep["field"];
+        (
+          field["struct_name"].as_str().unwrap().to_string(),
+          field["index"].as_u64().unwrap(),
+          field["name"].as_str().map(String::from),
+        )
+      })
+      .collect::<Vec<_>>();
+    // The field names come from the debug locations, as the fixture is built with `-g`
+    assert_eq!(
+      fields,
+      vec![
+        (String::from("struct.point"), 1, Some(String::from("y"))),
+        (String::from("struct.point"), 0, Some(String::from("x")))
+      ]
+    );
+  })
+}
+
+#[test]
+fn named_struct_gep_gives_a_field_location() -> Result<(), String> {
+  let path = "tests/c_files/gep/third_field.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("named_struct_gep_gives_a_field_location");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let instrs = trace["instrs"].as_array().unwrap();
+    let gep = instrs.iter().find(|instr| instr["sem"]["GEP"].is_object()).unwrap();
+    let field = &gep["res"]["Field"];
+    assert_eq!(field["struct_name"], "struct.triple");
+    assert_eq!(field["index"], 2);
+
+    // The store into `t->c` goes to the same field location
+    let store = instrs
+      .iter()
+      .find(|instr| instr["sem"]["Store"]["val"]["Int"] == 3)
+      .unwrap();
+    assert_eq!(store["sem"]["Store"]["loc"], gep["res"]);
+  })
+}
+
+#[test]
+fn constant_gep_index_is_checked_against_the_array_size() -> Result<(), String> {
+  let path = "tests/c_files/gep/out_of_bounds.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("constant_gep_index_is_checked_against_the_array_size");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    // `a[5]`, `a[i]` and `a[2]` on the `[4 x i32]`
+    let trace = load_trace_json(&options, "sink", 0, 0);
+    let in_bounds = trace["instrs"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .filter_map(|instr| instr["sem"]["GEP"].as_object())
+      .map(|gep| gep.get("in_bounds").and_then(|b| b.as_bool()))
+      .collect::<Vec<_>>();
+    assert_eq!(in_bounds, vec![Some(false), None, Some(true)]);
+  })
+}
+
+#[test]
+fn flexible_array_members_have_no_bound() -> Result<(), String> {
+  let path = "tests/c_files/gep/flexible.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("flexible_array_members_have_no_bound");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    // The field and element GEPs of `p->data[3]`, `h->data[3]` and `q->data[3]`:
+    // only the `[1 x i32]` which does not end its struct has a known size
+    let trace = load_trace_json(&options, "sink", 0, 0);
+    let in_bounds = trace["instrs"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .filter_map(|instr| instr["sem"]["GEP"].as_object())
+      .map(|gep| gep.get("in_bounds").and_then(|b| b.as_bool()))
+      .collect::<Vec<_>>();
+    assert_eq!(in_bounds, vec![None, None, None, None, None, Some(false)]);
+  })
+}
@@ -0,0 +1,82 @@
+use proptest::prelude::*;
+use z3::ast::{Ast, BV};
+
+use analyzer::semantics::*;
+use analyzer::utils::*;
+
+const WIDTHS: [u32; 5] = [1, 8, 16, 32, 64];
+
+const BIN_OPS: [BinOp; 13] = [
+  BinOp::Add,
+  BinOp::Sub,
+  BinOp::Mul,
+  BinOp::UDiv,
+  BinOp::SDiv,
+  BinOp::URem,
+  BinOp::SRem,
+  BinOp::And,
+  BinOp::Or,
+  BinOp::Xor,
+  BinOp::Shl,
+  BinOp::LShr,
+  BinOp::AShr,
+];
+
+const PREDICATES: [Predicate; 10] = [
+  Predicate::EQ,
+  Predicate::NE,
+  Predicate::SGE,
+  Predicate::SGT,
+  Predicate::SLE,
+  Predicate::SLT,
+  Predicate::UGE,
+  Predicate::UGT,
+  Predicate::ULE,
+  Predicate::ULT,
+];
+
+/// Operands around the interesting boundaries of every width, along with
+/// arbitrary ones
+fn operand() -> impl Strategy<Value = i64> {
+  prop_oneof![
+    any::<i64>(),
+    -2i64..66,
+    prop::sample::select(WIDTHS.to_vec()).prop_map(|width| sign_extend(1 << (width - 1), width)),
+    prop::sample::select(WIDTHS.to_vec()).prop_map(|width| sign_extend((1i64 << (width - 1)).wrapping_sub(1), width)),
+  ]
+}
+
+/// The operand as z3 sees it: a 64-bit bitvector truncated to the width
+fn z3_operand<'ctx>(z3_ctx: &'ctx z3::Context, value: i64, width: u32) -> BV<'ctx> {
+  truncate_z3_bv(BV::from_i64(z3_ctx, value, 64), width).unwrap()
+}
+
+proptest! {
+  #[test]
+  fn folded_binary_agrees_with_z3(
+    op in prop::sample::select(BIN_OPS.to_vec()),
+    width in prop::sample::select(WIDTHS.to_vec()),
+    op0 in operand(),
+    op1 in operand(),
+  ) {
+    if let Some(folded) = fold_binary(op, op0, op1, width) {
+      let z3_ctx = z3::Context::new(&z3::Config::default());
+      let (z3_op0, z3_op1) = (z3_operand(&z3_ctx, op0, width), z3_operand(&z3_ctx, op1, width));
+      let result = z3_binary(op, &z3_op0, &z3_op1).unwrap().sign_ext(64 - width).simplify();
+      prop_assert_eq!(result.as_u64(), Some(folded as u64));
+    }
+  }
+
+  #[test]
+  fn folded_icmp_agrees_with_z3(
+    pred in prop::sample::select(PREDICATES.to_vec()),
+    width in prop::sample::select(WIDTHS.to_vec()),
+    op0 in operand(),
+    op1 in operand(),
+  ) {
+    let z3_ctx = z3::Context::new(&z3::Config::default());
+    let (z3_op0, z3_op1) = (z3_operand(&z3_ctx, op0, width), z3_operand(&z3_ctx, op1, width));
+    let result = z3_icmp(pred, &z3_op0, &z3_op1).simplify();
+    prop_assert_eq!(result.as_bool(), Some(fold_icmp(pred, op0, op1, width)));
+  }
+}
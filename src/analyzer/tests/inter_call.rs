@@ -0,0 +1,39 @@
+mod common;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+
+use common::*;
+
+fn inter_call_features(caller: &str, window: usize, name: &str) -> Result<serde_json::Value, String> {
+  let path = "tests/c_files/inter_call/inter_call.bc";
+  let mut features = serde_json::Value::Null;
+  with_slice(path, "main", caller, "lock", |module, call_graph, slice| {
+    let options = TestOptions::new(name);
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    execute(module, call_graph, slice, &options);
+    let trace: Trace = serde_json::from_value(load_trace_json(&options, "lock", 0, 0)).unwrap();
+    features = InterCallSequenceFeatureExtractor::new(window).extract(0, &slice_json, &trace);
+  })?;
+  Ok(features)
+}
+
+#[test]
+fn calls_since_the_previous_target_call_are_recorded() -> Result<(), String> {
+  let features = inter_call_features("first", 10, "calls_since_the_previous_target_call_are_recorded")?;
+  assert_eq!(features["calls"], serde_json::json!(["prepare", "log_msg", "first"]));
+  assert_eq!(features["since_entry"], false);
+
+  // Only the calls closest to the target fit in the window
+  let features = inter_call_features("first", 2, "calls_since_the_previous_target_call_are_windowed")?;
+  assert_eq!(features["calls"], serde_json::json!(["log_msg", "first"]));
+  Ok(())
+}
+
+#[test]
+fn first_target_call_records_calls_since_entry() -> Result<(), String> {
+  let features = inter_call_features("main", 10, "first_target_call_records_calls_since_entry")?;
+  assert_eq!(features["calls"], serde_json::json!([]));
+  assert_eq!(features["since_entry"], true);
+  Ok(())
+}
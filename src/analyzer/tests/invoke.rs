@@ -0,0 +1,45 @@
+mod common;
+
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn invoked_target_is_a_call_on_the_normal_path() -> Result<(), String> {
+  let path = "tests/c_files/invoke/invoke.bc";
+  with_call_graph(path, |module, call_graph| {
+    let options = TestOptions::new("invoked_target_is_a_call_on_the_normal_path");
+    let mut slices = target_slices(call_graph, &options, "acquire");
+    assert_eq!(slices.len(), 1);
+    let slice = slices.remove(0);
+    assert!(matches!(slice.instr, CallSite::Invoke(_)));
+
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    // Only the normal path gets a result for the invoked call
+    let normal_paths = (0..metadata.proper_trace_count)
+      .map(|trace_id| load_trace_json(&options, "acquire", 0, trace_id))
+      .filter(|trace| {
+        let target = &trace["instrs"][trace["target"].as_u64().unwrap() as usize];
+        assert_eq!(target["sem"]["Call"]["func"]["Func"], "acquire");
+        !target["res"].is_null()
+      })
+      .count();
+    assert_eq!(normal_paths, 1);
+  })
+}
+
+#[test]
+fn unwind_path_without_handler_is_unwound() -> Result<(), String> {
+  let path = "tests/c_files/invoke/cleanup.bc";
+  with_call_graph(path, |module, call_graph| {
+    let options = TestOptions::new("unwind_path_without_handler_is_unwound");
+    let slice = target_slices(call_graph, &options, "acquire").remove(0);
+
+    // The cleanup of the guard resumes unwinding rather than returning
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.unwound_trace_count, 1);
+    assert!(metadata.proper_trace_count > 0);
+  })
+}
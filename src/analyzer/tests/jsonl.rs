@@ -0,0 +1,112 @@
+mod common;
+
+use serde_json::json;
+use std::collections::HashMap;
+
+use analyzer::feature_extraction::*;
+use analyzer::options::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn jsonl_traces_are_loaded_back() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_call_graph(path, |module, _| {
+    let mut options = TestOptions::new("jsonl_traces_are_loaded_back");
+    options.trace_format = TraceFormat::Jsonl;
+    std::fs::create_dir_all(options.trace_target_dir("malloc")).unwrap();
+
+    // Write 100 traces into the jsonl file of slice 0
+    let writer = JsonWriter::with_jsonl(options.trace_target_slice_jsonl_path("malloc", 0)).unwrap();
+    for i in 0..100 {
+      let trace = json!({
+        "instrs": [{
+          "loc": format!("test.c:{}:1", i),
+          "sem": { "Call": { "func": { "Func": "malloc" }, "args": [{ "Int": i }] } },
+          "res": { "Call": { "id": 0, "func": { "Func": "malloc" }, "args": [{ "Int": i }] } },
+        }],
+        "target": 0,
+      });
+      writer.write_line(&trace).unwrap();
+    }
+    writer.finish().unwrap();
+
+    // All of them are read back in order through the feature extraction loader
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), HashMap::new(), &options).unwrap();
+    let traces = ctx.load_traces(&String::from("malloc"), 0);
+    assert_eq!(traces.len(), 100);
+    for (i, (trace_id, trace)) in traces.into_iter().enumerate() {
+      assert_eq!(trace_id, i);
+      let trace = trace.unwrap();
+      assert_eq!(trace.instrs[trace.target].loc, format!("test.c:{}:1", i));
+    }
+  })
+}
+
+#[test]
+fn compressed_jsonl_traces_are_loaded_back() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("compressed_jsonl_traces_are_loaded_back");
+    options.trace_format = TraceFormat::Jsonl;
+    options.compress = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    let jsonl_path = options.trace_target_slice_jsonl_path("malloc", 0);
+    assert!(jsonl_path.to_str().unwrap().ends_with(".jsonl.gz"));
+    assert_eq!(std::fs::read(&jsonl_path).unwrap()[..2], [0x1f, 0x8b]);
+
+    let target_num_slices_map = discover_target_num_slices_map(&options).unwrap();
+    assert_eq!(target_num_slices_map["malloc"], 1);
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    let traces = ctx.load_traces(&String::from("malloc"), 0);
+    assert_eq!(traces.len(), metadata.proper_trace_count);
+    assert!(traces.iter().all(|(_, trace)| trace.is_ok()));
+  })
+}
+
+#[test]
+fn writer_errors_are_returned_when_finishing() {
+  let options = TestOptions::new("writer_errors_are_returned_when_finishing");
+  let missing_dir = options.output_path().join("missing");
+  let writer = JsonWriter::new();
+
+  // The writes after the failed one are dropped without failing the caller
+  for trace_id in 0..3 {
+    let path = missing_dir.join(options.json_file_name(trace_id));
+    assert!(writer.dump_versioned_json(&json!({ "id": trace_id }), path).is_ok());
+  }
+  let err = writer.finish().unwrap_err();
+  assert!(err.contains(&format!("{:?}", missing_dir.join("0.json"))));
+}
+
+#[test]
+fn jsonl_slices_get_no_folder() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("jsonl_slices_get_no_folder");
+    options.trace_format = TraceFormat::Jsonl;
+    let target = String::from("malloc");
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).unwrap();
+    let metadata = ctx.execute_target_slices(&target, 0, vec![slice.clone(), slice]);
+    assert!(metadata.proper_trace_count > 0);
+
+    // The statistics of each slice sit next to its jsonl file, and add up to the target's
+    let mut slice_proper_count = 0;
+    for slice_id in 0..2 {
+      assert!(!options.trace_target_slice_dir("malloc", slice_id).exists());
+      assert!(options.trace_target_slice_jsonl_path("malloc", slice_id).exists());
+      let meta = load_json(&options.trace_target_slice_jsonl_meta_path("malloc", slice_id)).unwrap();
+      slice_proper_count += meta["proper_trace_count"].as_u64().unwrap();
+    }
+    let target_meta = load_json(&options.trace_target_meta_path("malloc")).unwrap();
+    assert_eq!(slice_proper_count, metadata.proper_trace_count as u64);
+    assert_eq!(target_meta["proper_trace_count"].as_u64(), Some(slice_proper_count));
+
+    // Slice ids are still discovered from the jsonl files alone
+    assert_eq!(discover_target_num_slices_map(&options).unwrap()["malloc"], 2);
+  })
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use analyzer::options::*;
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+#[test]
+fn panicking_slice_does_not_stop_its_siblings() -> Result<(), String> {
+  let path = "tests/c_files/retval/forward.bc";
+  with_slice(path, "main", "forward", "get", |module, call_graph, slice| {
+    let mut options = TestOptions::new("panicking_slice_does_not_stop_its_siblings");
+    options.keep_going = true;
+
+    // Starting from a function without body panics right away
+    let mut bad_slice = slice.clone();
+    bad_slice.entry = slice.callee;
+
+    // Stand for the negative traces & assertions a slice dumps before panicking
+    let negative_dir = options.negative_trace_target_slice_dir(PATH_UNSAT, "get", 0);
+    let assertion_dir = options.assertion_target_slice_dir("get", 0);
+    std::fs::create_dir_all(&negative_dir).unwrap();
+    std::fs::create_dir_all(&assertion_dir).unwrap();
+
+    let target = String::from("get");
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).unwrap();
+    let metadata = ctx.execute_target_slices(&target, 0, vec![bad_slice, slice.clone(), slice]);
+    assert_eq!(metadata.panicked_slice_count, 1);
+    assert_eq!(metadata.panicked_slices, vec![(target.clone(), 0)]);
+    assert_eq!(metadata.proper_trace_count, 2);
+    assert!(!options.trace_target_slice_dir("get", 0).exists());
+    assert!(!negative_dir.exists());
+    assert!(!assertion_dir.exists());
+    assert!(options.trace_target_slice_file_path("get", 2, 0).exists());
+  })
+}
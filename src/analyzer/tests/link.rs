@@ -0,0 +1,31 @@
+mod common;
+
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn slice_spans_linked_modules() -> Result<(), String> {
+  let paths = ["tests/c_files/link/caller.bc", "tests/c_files/link/callee.bc"];
+  with_linked_call_graph(&paths, |modules, call_graph| {
+    let options = TestOptions::new("slice_spans_linked_modules");
+
+    // The declaration of helper in the caller module resolves to its definition
+    let declared = modules[0].get_function("helper").unwrap();
+    let defined = modules[1].get_function("helper").unwrap();
+    assert!(declared.is_declaration_only());
+    assert_eq!(call_graph.resolve(declared), defined);
+
+    // Starting from main, the slice calls malloc through the other module
+    let slice = target_slices(call_graph, &options, "malloc")
+      .into_iter()
+      .find(|slice| slice.entry.simp_name() == "main")
+      .unwrap();
+    assert_eq!(slice.caller, defined);
+    assert!(slice.functions.contains(&modules[0].get_function("main").unwrap()));
+    assert!(slice.functions.contains(&defined));
+
+    let metadata = execute_linked(modules, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+  })
+}
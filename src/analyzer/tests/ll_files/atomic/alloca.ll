@@ -0,0 +1,11 @@
+declare void @sink(i32, i32)
+
+define i32 @main() {
+entry:
+  %slot = alloca i32
+  store i32 1, i32* %slot
+  %old = atomicrmw add i32* %slot, i32 2 seq_cst
+  %new = load i32, i32* %slot
+  call void @sink(i32 %old, i32 %new)
+  ret i32 0
+}
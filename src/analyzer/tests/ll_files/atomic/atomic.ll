@@ -0,0 +1,14 @@
+declare void @sink(i32, i32)
+
+declare void @pair({ i32, i1 }, i1)
+
+define i32 @main(i32* %count) {
+entry:
+  %old = atomicrmw add i32* %count, i32 1 seq_cst
+  %new = load i32, i32* %count
+  call void @sink(i32 %old, i32 %new)
+  %res = cmpxchg i32* %count, i32 %old, i32 0 seq_cst seq_cst
+  %success = extractvalue { i32, i1 } %res, 1
+  call void @pair({ i32, i1 } %res, i1 %success)
+  ret i32 0
+}
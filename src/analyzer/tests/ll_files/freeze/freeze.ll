@@ -0,0 +1,15 @@
+declare i32 @get()
+
+declare void @use(i32)
+
+declare void @sink(i32)
+
+define i32 @main() {
+entry:
+  %x = call i32 @get()
+  %fx = freeze i32 %x
+  call void @use(i32 %fx)
+  %fu = freeze i32 undef
+  call void @sink(i32 %fu)
+  ret i32 0
+}
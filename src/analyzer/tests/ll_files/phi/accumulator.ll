@@ -0,0 +1,22 @@
+declare i32 @get()
+
+declare i32 @step()
+
+declare void @sink(i32)
+
+define i32 @main() {
+entry:
+  %init = call i32 @get()
+  br label %loop
+
+loop:
+  %sum = phi i32 [ %init, %entry ], [ %next, %loop ]
+  %s = call i32 @step()
+  %next = add i32 %sum, %s
+  %done = icmp sgt i32 %next, 100
+  br i1 %done, label %exit, label %loop
+
+exit:
+  call void @sink(i32 %next)
+  ret i32 0
+}
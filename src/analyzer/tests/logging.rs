@@ -0,0 +1,48 @@
+mod common;
+
+use log::{Level, Log, Metadata, Record};
+use std::sync::Mutex;
+
+use analyzer::utils::*;
+
+use common::*;
+
+/// Keeps all the records around so that the test can look at them
+struct CapturedLogger {
+  records: Mutex<Vec<(Level, String, String)>>,
+}
+
+impl Log for CapturedLogger {
+  fn enabled(&self, _: &Metadata) -> bool {
+    true
+  }
+
+  fn log(&self, record: &Record) {
+    let entry = (record.level(), record.target().to_string(), record.args().to_string());
+    self.records.lock().unwrap().push(entry);
+  }
+
+  fn flush(&self) {}
+}
+
+static LOGGER: CapturedLogger = CapturedLogger {
+  records: Mutex::new(Vec::new()),
+};
+
+#[test]
+fn path_unsat_is_logged_with_constraint_count() -> Result<(), String> {
+  log::set_logger(&LOGGER).unwrap();
+  log::set_max_level(log::LevelFilter::Trace);
+
+  let path = "tests/c_files/logging/unsat.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("path_unsat_is_logged_with_constraint_count");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.path_unsat_trace_count > 0);
+
+    let records = LOGGER.records.lock().unwrap();
+    assert!(records.iter().any(|(level, target, message)| *level == Level::Debug
+      && target == EXECUTOR_LOG
+      && message.ends_with("path unsatisfiable with 2 constraints")));
+  })
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn pre_call_memory_holds_stored_values() -> Result<(), String> {
+  let path = "tests/c_files/memory/pre_call.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("pre_call_memory_holds_stored_values");
+    execute(module, call_graph, slice.clone(), &options);
+    assert!(load_trace_json(&options, "malloc", 0, 0)["pre_call_memory"].is_null());
+
+    options.dump_memory = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let memory = trace["pre_call_memory"].as_array().unwrap();
+    assert!(memory.iter().any(|cell| cell["val"]["Int"] == 42));
+
+    // The pointer returned by the target is only stored after the call
+    assert!(memory.iter().all(|cell| cell["val"]["Call"].is_null()));
+  })
+}
+
+#[test]
+fn same_global_loads_the_same_symbol_across_traces() -> Result<(), String> {
+  let path = "tests/c_files/memory/global_load.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("same_global_loads_the_same_symbol_across_traces");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+
+    // Only one of the traces loads `extra` before `size`
+    let size_symbol = |trace_id: usize| {
+      let trace = load_trace_json(&options, "malloc", 0, trace_id);
+      let instrs = trace["instrs"].as_array().unwrap().clone();
+      instrs
+        .into_iter()
+        .find(|instr| instr["sem"]["Load"]["loc"]["Glob"] == "size")
+        .map(|instr| instr["res"].clone())
+        .unwrap()
+    };
+    assert_eq!(size_symbol(0), size_symbol(1));
+    assert_eq!(size_symbol(0)["LocSym"]["Glob"], "size");
+  })
+}
+
+#[test]
+fn unwritten_alloca_loads_undef() -> Result<(), String> {
+  let path = "tests/c_files/memory/uninit.bc";
+  with_slice(path, "main", "main", "use", |module, call_graph, slice| {
+    let options = TestOptions::new("unwritten_alloca_loads_undef");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, "use", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let args = &trace["instrs"][target]["sem"]["Call"]["args"];
+
+    // `x` is never written, while `y` may have been initialized by `init`
+    assert!(args[0]["Undef"]["Alloc"].is_number());
+    assert!(args[1]["Sym"].is_number());
+
+    // Globals and memory reached from arguments still hold symbols
+    assert_eq!(args[2]["LocSym"]["Glob"], "size");
+    assert_eq!(args[3]["LocSym"]["Arg"], 0);
+  })
+}
+
+#[test]
+fn escaped_alloca_does_not_load_undef() -> Result<(), String> {
+  let path = "tests/c_files/memory/escape.bc";
+  with_slice(path, "main", "main", "use", |module, call_graph, slice| {
+    let options = TestOptions::new("escaped_alloca_does_not_load_undef");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, "use", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let args = &trace["instrs"][target]["sem"]["Call"]["args"];
+
+    // `x` escapes through a store and `z` through a GEP on its address
+    assert!(args[0]["Sym"].is_number());
+    assert!(args[1]["Sym"].is_number());
+  })
+}
+
+#[test]
+fn vector_lanes_round_trip_through_memory() -> Result<(), String> {
+  let path = "tests/c_files/memory/vector.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("vector_lanes_round_trip_through_memory");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, "sink", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+
+    // Each lane of `*p` goes through its own cell of `v` and reaches the call
+    let lanes = &trace["instrs"][target]["sem"]["Call"]["args"][0]["Vector"];
+    assert_eq!(lanes.as_array().unwrap().len(), 2);
+    for lane in 0..2 {
+      let loc = &lanes[lane]["LocSym"]["GEP"];
+      assert_eq!(loc["loc"]["Arg"], 0);
+      assert_eq!(loc["indices"], serde_json::json!([{ "Int": 0 }, { "Int": lane }]));
+    }
+  })
+}
+
+#[test]
+fn vector_lanes_are_tracked_through_shuffles() -> Result<(), String> {
+  let path = "tests/c_files/memory/shuffle.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("vector_lanes_are_tracked_through_shuffles");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, "sink", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+
+    // `x` is inserted in lane 0, then the shuffle swaps the two lanes
+    let args = &trace["instrs"][target]["sem"]["Call"]["args"];
+    let loc = &args[0]["LocSym"]["GEP"];
+    assert_eq!(loc["loc"]["Arg"], 0);
+    assert_eq!(loc["indices"], serde_json::json!([{ "Int": 0 }, { "Int": 1 }]));
+    assert_eq!(args[1]["Arg"], 1);
+  })
+}
+
+#[test]
+fn memory_limit_ends_the_trace() -> Result<(), String> {
+  let path = "tests/c_files/memory/many_cells.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let mut options = TestOptions::new("memory_limit_ends_the_trace");
+    options.max_memory_cells = Some(64);
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    assert_eq!(metadata.memory_limit_trace_count, 0);
+
+    // The 16 stores to distinct elements outgrow the limit before the call
+    options.max_memory_cells = Some(8);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 0);
+    assert_eq!(metadata.memory_limit_trace_count, 1);
+  })
+}
+
+#[test]
+fn loads_chase_a_stored_pointer() -> Result<(), String> {
+  let path = "tests/c_files/memory/double_pointer.bc";
+  with_slice(path, "chase", "chase", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("loads_chase_a_stored_pointer");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    let trace = load_trace_json(&options, "sink", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    assert_eq!(trace["instrs"][target]["sem"]["Call"]["args"][0]["Int"], 42);
+  })
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn diamond_arms_are_merged_into_one_state() -> Result<(), String> {
+  let path = "tests/c_files/merge/diamond.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("diamond_arms_are_merged_into_one_state");
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+    assert_eq!(metadata.merged_state_count, 0);
+
+    options.merge_states = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.explored_trace_count, 1);
+    assert_eq!(metadata.proper_trace_count, 1);
+    assert_eq!(metadata.merged_state_count, 1);
+
+    // The size reaching malloc is selected by the branch condition
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let instrs = trace["instrs"].as_array().unwrap();
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let select = &instrs[target]["sem"]["Call"]["args"][0]["Select"];
+    assert_eq!(select["then_val"]["Int"], 8);
+    assert_eq!(select["else_val"]["Int"], 16);
+    assert!(trace["cons"].as_array().unwrap().is_empty());
+
+    // The arms leave a single merge node of the branch, with the guarded store to
+    // `size` in place of the stores of each arm
+    let merges = instrs
+      .iter()
+      .filter(|instr| instr["sem"]["Merge"].is_object())
+      .collect::<Vec<_>>();
+    assert_eq!(merges.len(), 1);
+    let merge = &merges[0]["sem"]["Merge"];
+    assert_eq!(&merge["cond"], &select["cond"]);
+    let cells = merge["cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 1);
+    assert_eq!(&cells[0][1]["Select"], select);
+    assert!(!instrs.iter().any(|instr| instr["sem"]["Store"]["val"]["Int"] == 8));
+    assert!(!instrs.iter().any(|instr| instr["sem"]["CondBr"].is_object()));
+  })
+}
+
+#[test]
+fn arms_stopping_early_are_forked() -> Result<(), String> {
+  let path = "tests/c_files/merge/diamond.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("arms_stopping_early_are_forked");
+    options.merge_states = true;
+
+    // The return value and `argc` fill the memory up to the branch, and the store
+    // to `size` of each arm outgrows it
+    options.max_memory_cells = Some(2);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.merged_state_count, 0);
+    assert_eq!(metadata.memory_limit_trace_count, 2);
+    assert_eq!(metadata.proper_trace_count, 0);
+  })
+}
+
+#[test]
+fn cell_read_by_one_arm_is_left_unchanged() -> Result<(), String> {
+  let path = "tests/c_files/merge/read_only_arm.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("cell_read_by_one_arm_is_left_unchanged");
+    options.merge_states = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.merged_state_count, 1);
+
+    // `n` escaped to `init`, so its content is a symbol read by the then arm only
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let instrs = trace["instrs"].as_array().unwrap();
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let arg = &instrs[target]["sem"]["Call"]["args"][0];
+    assert!(arg["Sym"].is_number());
+
+    // Only `size` is stored to by the arms, guarded between that symbol and 16
+    let merge = instrs[..target]
+      .iter()
+      .find_map(|instr| instr["sem"]["Merge"].as_object())
+      .unwrap();
+    let cells = merge["cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 1);
+    assert_eq!(&cells[0][1]["Select"]["then_val"], arg);
+    assert_eq!(cells[0][1]["Select"]["else_val"]["Int"], 16);
+  })
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::process::Command;
+
+use analyzer::options::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn execute_slice_records_timing() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("execute_slice_records_timing");
+    options.timing_report = Some(1);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.elapsed_ms > 0.0);
+    assert!(metadata.max_work_list_size > 0);
+    assert_eq!(metadata.slowest_slices(1).len(), 1);
+    assert_eq!(metadata.slowest_slices(1)[0].target, "malloc");
+  })
+}
+
+#[test]
+fn resumed_run_skips_finished_slice() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("resumed_run_skips_finished_slice");
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert!(metadata.proper_trace_count > 0);
+    assert_eq!(metadata.resumed_slice_count, 0);
+
+    // Rerunning with resume only recounts the dumped traces
+    options.resume = true;
+    let resumed = execute(module, call_graph, slice, &options);
+    assert_eq!(resumed.resumed_slice_count, 1);
+    assert_eq!(resumed.explored_trace_count, 0);
+    assert_eq!(resumed.proper_trace_count, metadata.proper_trace_count);
+  })
+}
+
+#[test]
+fn global_timeout_truncates_the_run() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("global_timeout_truncates_the_run");
+    options.global_timeout_sec = Some(0);
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.truncated);
+    assert_eq!(metadata.explored_trace_count, 0);
+  })
+}
+
+#[test]
+fn execute_slice_dumps_its_meta() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("execute_slice_dumps_its_meta");
+    let metadata = execute(module, call_graph, slice, &options);
+    let meta_path = options.trace_target_slice_dir("malloc", 0).join("meta.json");
+    let meta = load_json(&meta_path).unwrap();
+    assert_eq!(meta["proper_trace_count"], metadata.proper_trace_count);
+    assert_eq!(meta["duplicate_trace_count"], metadata.duplicate_trace_count);
+    assert_eq!(meta["explored_trace_count"], metadata.explored_trace_count);
+  })
+}
+
+#[test]
+fn run_summary_totals_the_targets() -> Result<(), String> {
+  let options = TestOptions::new("run_summary_totals_the_targets");
+  let output = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg("tests/c_files/batch/batch.bc")
+    .arg(&options.output)
+    .arg("--slice-depth=0")
+    .arg("--no-feature")
+    .output()
+    .map_err(|err| err.to_string())?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+  // The summary written by the run adds up the statistics of every target
+  let json = load_json(&options.run_summary_path())?;
+  assert!(json["num_targets"].as_u64().unwrap() > 1);
+  let mut target_proper_count = 0;
+  for entry in std::fs::read_dir(options.trace_dir()).map_err(|err| err.to_string())? {
+    let meta_path = entry.map_err(|err| err.to_string())?.path().join("meta.json");
+    if meta_path.exists() {
+      target_proper_count += load_json(&meta_path)?["proper_trace_count"].as_u64().unwrap();
+    }
+  }
+  assert!(target_proper_count > 0);
+  assert_eq!(json["counts"]["proper_trace_count"].as_u64(), Some(target_proper_count));
+
+  // Along with the arguments and the options parsed from them
+  assert!(json["args"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .any(|arg| arg == "--slice-depth=0"));
+  assert_eq!(json["options"]["slice_depth"], 0);
+  assert_eq!(json["options"]["trace_format"], "json");
+  assert_eq!(json["options"]["no_feature"], true);
+  Ok(())
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn trace_ends_at_exit() -> Result<(), String> {
+  let path = "tests/c_files/noreturn/exit.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("trace_ends_at_exit");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+
+    // The path calling exit stops there instead of going on to free
+    let last_calls = (0..metadata.proper_trace_count)
+      .map(|trace_id| {
+        let trace = load_trace_json(&options, "malloc", 0, trace_id);
+        let instrs = trace["instrs"].as_array().unwrap().clone();
+        let calls = instrs
+          .iter()
+          .filter_map(|instr| instr["sem"]["Call"]["func"]["Func"].as_str())
+          .map(String::from)
+          .collect::<Vec<_>>();
+        if calls.contains(&String::from("exit")) {
+          assert_eq!(instrs.last().unwrap()["sem"]["Call"]["func"]["Func"], "exit");
+        }
+        calls
+      })
+      .collect::<Vec<_>>();
+    assert!(last_calls.contains(&vec![String::from("malloc"), String::from("exit")]));
+    assert!(last_calls.contains(&vec![String::from("malloc"), String::from("free")]));
+  })
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use analyzer::options::*;
+
+use common::*;
+
+fn called_functions(trace: &serde_json::Value) -> Vec<String> {
+  trace["instrs"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .filter_map(|instr| instr["sem"]["Call"]["func"]["Func"].as_str())
+    .map(String::from)
+    .collect()
+}
+
+#[test]
+fn opaque_allocator_is_not_stepped_into() -> Result<(), String> {
+  let path = "tests/c_files/opaque/opaque_1.bc";
+  with_slice(path, "main", "main", "free", |module, call_graph, mut slice| {
+    slice.functions.insert(module.get_function("my_alloc").unwrap());
+
+    // By default the allocator is stepped into
+    let options = TestOptions::new("opaque_allocator_is_stepped_into");
+    execute(module, call_graph, slice.clone(), &options);
+    let trace = load_trace_json(&options, "free", 0, 0);
+    assert_eq!(called_functions(&trace), vec!["my_alloc", "malloc", "free"]);
+
+    // Matched by the regex, it is summarized by its call result
+    let mut options = TestOptions::new("opaque_allocator_is_not_stepped_into");
+    let opaque_path = options.output_path().join("opaque.txt");
+    std::fs::create_dir_all(options.output_path()).unwrap();
+    std::fs::write(&opaque_path, "# Allocators\n^my_alloc$\n").unwrap();
+    options.opaque_functions = Some(opaque_path.to_str().unwrap().to_string());
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "free", 0, 0);
+    assert_eq!(called_functions(&trace), vec!["my_alloc", "free"]);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let arg = &trace["instrs"][target]["sem"]["Call"]["args"][0];
+    assert_eq!(arg["Call"]["func"]["Func"], "my_alloc");
+  })
+}
+
+#[test]
+fn excluded_instrumentation_calls_leave_no_node() -> Result<(), String> {
+  let path = "tests/c_files/opaque/asan.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    // By default only the llvm intrinsics are skipped
+    let options = TestOptions::new("instrumentation_calls_are_kept");
+    execute(module, call_graph, slice.clone(), &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    assert_eq!(called_functions(&trace), vec!["malloc", "__asan_report_load4", "free"]);
+
+    let mut options = TestOptions::new("excluded_instrumentation_calls_leave_no_node");
+    let list_path = options.output_path().join("exclude.txt");
+    std::fs::create_dir_all(options.output_path()).unwrap();
+    std::fs::write(&list_path, "llvm\\.\n^__asan_\n").unwrap();
+    options.exclude_intrinsics_list = Some(list_path.to_str().unwrap().to_string());
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    assert_eq!(called_functions(&trace), vec!["malloc", "free"]);
+  })
+}
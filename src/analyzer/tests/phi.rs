@@ -0,0 +1,91 @@
+mod common;
+
+use llir::values::*;
+
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+#[test]
+fn phi_records_the_taken_edge() -> Result<(), String> {
+  let path = "tests/c_files/phi/diamond.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("phi_records_the_taken_edge");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+
+    // Blocks are entry, cond.true, cond.false and cond.end
+    for trace_id in 0..2 {
+      let trace = load_trace_json(&options, "malloc", 0, trace_id);
+      let instrs = trace["instrs"].as_array().unwrap();
+      let br = instrs.iter().find_map(|instr| instr["sem"].get("CondBr")).unwrap();
+      let phi = instrs.iter().find_map(|instr| instr["sem"].get("Phi")).unwrap();
+      if br["br"] == "Then" {
+        assert_eq!(phi["incoming_block"], 1);
+        assert_eq!(phi["value"]["Int"], 8);
+      } else {
+        assert_eq!(phi["incoming_block"], 2);
+        assert_eq!(phi["value"]["Int"], 16);
+      }
+    }
+  })
+}
+
+#[test]
+fn phi_without_matching_predecessor_is_an_error() -> Result<(), String> {
+  let path = "tests/c_files/phi/diamond.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("phi_without_matching_predecessor_is_an_error");
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &options).unwrap();
+    let mut env = Environment::new(&slice, options.max_work, options.seed);
+    let phi = slice
+      .entry
+      .iter_instructions()
+      .find_map(|instr| match instr {
+        Instruction::Phi(phi) => Some(phi),
+        _ => None,
+      })
+      .unwrap();
+
+    // The entry block is not a predecessor of the block holding the phi
+    let mut state = State::new(&slice);
+    state.prev_block = slice.entry.first_block();
+    assert!(ctx.transfer_phi_instr(phi, &mut state, &mut env).is_none());
+    assert!(matches!(state.finish_state, FinishState::Error(_)));
+
+    let mut metadata = MetaData::new();
+    ctx.finish_execution(state, 0, &mut metadata, &mut env);
+    assert_eq!(metadata.error_trace_count, 1);
+  })
+}
+
+#[test]
+fn loop_carried_phi_is_cut_by_a_fresh_symbol() -> Result<(), String> {
+  let path = "tests/ll_files/phi/accumulator.bc";
+  with_slice(path, "main", "main", "sink", |module, call_graph, slice| {
+    let options = TestOptions::new("loop_carried_phi_is_cut_by_a_fresh_symbol");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+
+    // The trace going around the loop once more resolves the phi to a symbol
+    // instead of `add(call, call)`, so that the sum stays one addition deep
+    let looped = (0..2)
+      .map(|trace_id| load_trace_json(&options, "sink", 0, trace_id))
+      .find(|trace| {
+        let instrs = trace["instrs"].as_array().unwrap();
+        instrs.iter().filter(|instr| instr["sem"].get("Phi").is_some()).count() == 2
+      })
+      .unwrap();
+    let instrs = looped["instrs"].as_array().unwrap();
+    let phi = instrs
+      .iter()
+      .filter_map(|instr| instr["sem"].get("Phi"))
+      .last()
+      .unwrap();
+    assert!(phi["value"]["Sym"].is_number());
+    let target = looped["target"].as_u64().unwrap() as usize;
+    let sum = &instrs[target]["sem"]["Call"]["args"][0]["Bin"];
+    assert_eq!(sum["op"], "Add");
+    assert_eq!(sum["op0"], phi["value"]);
+  })
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use serde_json::json;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+
+use common::*;
+
+#[test]
+fn result_range_from_path_constraints() -> Result<(), String> {
+  let path = "tests/c_files/retval/range.bc";
+  with_slice(path, "main", "main", "get", |module, call_graph, slice| {
+    let options = TestOptions::new("result_range_from_path_constraints");
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    let metadata = execute(module, call_graph, slice, &options);
+    let ranges = (0..metadata.proper_trace_count)
+      .map(|trace_id| {
+        let trace: Trace = serde_json::from_value(load_trace_json(&options, "get", 0, trace_id)).unwrap();
+        ReturnValueRangeFeatureExtractor::new().extract(0, &slice_json, &trace)
+      })
+      .collect::<Vec<_>>();
+
+    // Only the path through both checks bounds the result
+    assert!(ranges.contains(&json!({ "min": 0, "max": 10, "bounded": true })));
+    assert!(ranges.contains(&json!({ "min": null, "max": -1, "bounded": false })));
+  })
+}
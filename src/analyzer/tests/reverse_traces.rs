@@ -0,0 +1,72 @@
+mod common;
+
+use std::collections::HashMap;
+
+use analyzer::feature_extraction::*;
+use analyzer::options::*;
+use analyzer::utils::*;
+
+use common::*;
+
+fn dump_trace(name: &str, reverse_traces: bool) -> Result<serde_json::Value, String> {
+  let path = "tests/c_files/constant_args/open.bc";
+  let mut trace = serde_json::Value::Null;
+  with_slice(path, "main", "main", "open", |module, call_graph, slice| {
+    let mut options = TestOptions::new(name);
+    options.reverse_traces = reverse_traces;
+    execute(module, call_graph, slice, &options);
+    trace = load_trace_json(&options, "open", 0, 0);
+  })?;
+  Ok(trace)
+}
+
+#[test]
+fn reversed_trace_keeps_all_the_nodes() -> Result<(), String> {
+  let forward = dump_trace("reversed_trace_keeps_all_the_nodes_forward", false)?;
+  let reversed = dump_trace("reversed_trace_keeps_all_the_nodes", true)?;
+  let (target, len) = (
+    forward["target"].as_u64().unwrap() as usize,
+    forward["instrs"].as_array().unwrap().len(),
+  );
+  assert_eq!(reversed["reversed"], true);
+  assert_eq!(reversed["target"], len - 1 - target);
+  assert_eq!(reversed["instrs"][len - 1 - target], forward["instrs"][target]);
+  assert_eq!(reversed["instrs"][0], forward["instrs"][len - 1]);
+
+  // Loading turns the trace forward again
+  let loaded: Trace = serde_json::from_value(reversed).unwrap();
+  let loaded = loaded.into_forward();
+  assert_eq!(loaded.target, target);
+  assert_eq!(loaded.instrs.len(), len);
+  assert!(!loaded.reversed);
+  Ok(())
+}
+
+/// The features extracted from the traces of `malloc` in example_1
+fn extract_features(name: &str, reverse_traces: bool) -> Result<serde_json::Value, String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  let mut features = serde_json::Value::Null;
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new(name);
+    options.reverse_traces = reverse_traces;
+    std::fs::create_dir_all(options.slice_target_dir("malloc")).unwrap();
+    dump_versioned_json(&slice.to_json(), options.slice_target_file_path("malloc", 0)).unwrap();
+    execute(module, call_graph, slice, &options);
+
+    let mut logging_ctx = LoggingContext::new(&options).unwrap();
+    let target_num_slices_map = vec![(String::from("malloc"), 1)].into_iter().collect::<HashMap<_, _>>();
+    let ctx = FeatureExtractionContext::new(std::slice::from_ref(module), target_num_slices_map, &options).unwrap();
+    ctx.extract_features(&mut logging_ctx);
+    features = load_json(&options.feature_target_slice_file_path("malloc", 0, 0)).unwrap();
+  })?;
+  Ok(features)
+}
+
+#[test]
+fn features_of_reversed_traces_match_the_forward_ones() -> Result<(), String> {
+  let forward = extract_features("features_of_reversed_traces_match_the_forward_ones_forward", false)?;
+  let reversed = extract_features("features_of_reversed_traces_match_the_forward_ones", true)?;
+  assert!(forward.get("ret").is_some());
+  assert_eq!(forward, reversed);
+  Ok(())
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::*;
+
+/// The constraints of the traces explored with the given seed, in exploration order
+fn explored_constraints(seed: u64, name: &str) -> Result<Vec<serde_json::Value>, String> {
+  let path = "tests/c_files/constraints/many_branches.bc";
+  let mut explored = vec![];
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new(name);
+    options.seed = seed;
+    options.sample_branches = true;
+    options.max_explored_trace_per_slice = 3;
+    let metadata = execute(module, call_graph, slice, &options);
+    explored = (0..metadata.proper_trace_count)
+      .map(|trace_id| load_trace_json(&options, "malloc", 0, trace_id)["cons"].clone())
+      .collect();
+  })?;
+  Ok(explored)
+}
+
+#[test]
+fn sampled_branches_are_reproducible_per_seed() -> Result<(), String> {
+  let first = explored_constraints(1, "sampled_branches_first_run")?;
+  let second = explored_constraints(1, "sampled_branches_second_run")?;
+  assert_eq!(first.len(), 3);
+  assert_eq!(first, second);
+
+  // Other seeds pick other branches first
+  let others = (2..10)
+    .map(|seed| explored_constraints(seed, &format!("sampled_branches_seed_{}", seed)))
+    .collect::<Result<Vec<_>, _>>()?;
+  assert!(others.iter().any(|other| other != &first));
+  Ok(())
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use analyzer::options::*;
+
+use common::*;
+
+#[test]
+fn seeded_argument_selects_a_single_branch() -> Result<(), String> {
+  let path = "tests/c_files/seed/seeded_branch.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("seeded_argument_selects_a_single_branch");
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+
+    let seed_path = options.output_path().join("seed.json");
+    std::fs::create_dir_all(options.output_path()).unwrap();
+    std::fs::write(&seed_path, r#"{ "arguments": { "0": { "Int": 5 } } }"#).unwrap();
+    options.seed_file = Some(seed_path.to_str().unwrap().to_string());
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+
+    // The then branch is taken without constraining anything
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    assert_eq!(trace["instrs"][target]["sem"]["Call"]["args"][0]["Int"], 16);
+    assert!(trace["cons"].as_array().unwrap().is_empty());
+  })
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use std::process::Command;
+
+use analyzer::options::*;
+use analyzer::utils::*;
+
+use common::*;
+
+fn run_analyzer(options: &TestOptions, mode: &str) -> Result<(), String> {
+  let output = Command::new(env!("CARGO_BIN_EXE_analyzer"))
+    .arg("tests/c_files/basic/example_1.bc")
+    .arg(&options.output)
+    .arg("--target=malloc")
+    .arg("--no-feature")
+    .arg(mode)
+    .output()
+    .map_err(|err| err.to_string())?;
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+  Ok(())
+}
+
+#[test]
+fn slice_only_dumps_slices_without_executing_them() -> Result<(), String> {
+  let options = TestOptions::new("slice_only_dumps_slices_without_executing_them");
+  run_analyzer(&options, "--slice-only")?;
+  let slices = json_files_in_dir(&options.slice_target_dir("malloc"))?;
+  assert!(!slices.is_empty());
+  for (_, path) in slices {
+    let slice = load_json(&path)?;
+    assert_eq!(slice["callee"], "malloc");
+    assert_eq!(slice["caller"], "f");
+  }
+  assert!(!options.trace_target_dir("malloc").exists());
+  Ok(())
+}
+
+#[test]
+fn execute_from_slices_runs_the_dumped_slices() -> Result<(), String> {
+  let options = TestOptions::new("execute_from_slices_runs_the_dumped_slices");
+  run_analyzer(&options, "--slice-only")?;
+  let num_slices = json_files_in_dir(&options.slice_target_dir("malloc"))?.len();
+  run_analyzer(&options, "--execute-from-slices")?;
+
+  // Every dumped slice gets executed, with its traces under the same slice id
+  for slice_id in 0..num_slices {
+    assert!(options.trace_target_slice_dir("malloc", slice_id).exists());
+  }
+  let run_summary = load_json(&options.run_summary_path())?;
+  assert_eq!(run_summary["num_slices"], num_slices);
+  Ok(())
+}
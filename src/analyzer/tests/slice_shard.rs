@@ -0,0 +1,69 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use analyzer::call_graph::*;
+use analyzer::options::*;
+use analyzer::slicer::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+use llir::Module;
+
+use common::*;
+
+fn execute_all<'ctx>(module: &Module<'ctx>, call_graph: &CallGraph<'ctx>, options: &TestOptions) {
+  let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, options).unwrap();
+  let target_slices_map = TargetSlicesMap::from_target_edges_map(&target_edges_map, call_graph, options).unwrap();
+  let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, options).unwrap();
+  ctx.execute_target_slices_map(
+    target_slices_map
+      .into_iter()
+      .map(|(target, slices)| (target, (0, slices)))
+      .collect(),
+  );
+}
+
+/// All the trace files under `dir`, keyed by their path relative to `root`
+fn collect_traces(root: &Path, dir: &Path, files: &mut BTreeMap<String, serde_json::Value>) {
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.is_dir() {
+      collect_traces(root, &path, files);
+    } else if json_file_id(&path).is_some() {
+      let key = path.strip_prefix(root).unwrap().to_string_lossy().to_string();
+      files.insert(key, load_json(&path).unwrap());
+    }
+  }
+}
+
+#[test]
+fn shards_together_produce_the_unsharded_traces() -> Result<(), String> {
+  let path = "tests/c_files/batch/batch.bc";
+  with_call_graph(path, |module, call_graph| {
+    let mut unsharded = TestOptions::new("shards_together_produce_the_unsharded_traces");
+    unsharded.slice_depth = 0;
+    execute_all(module, call_graph, &unsharded);
+
+    // Both shards write into the same output folder
+    let mut sharded = TestOptions::new("shards_together_produce_the_unsharded_traces_sharded");
+    sharded.slice_depth = 0;
+    for index in 0..2 {
+      sharded.slice_shard = Some(SliceShard { index, count: 2 });
+      execute_all(module, call_graph, &sharded);
+    }
+
+    let (mut expected, mut actual) = (BTreeMap::new(), BTreeMap::new());
+    collect_traces(&unsharded.trace_dir(), &unsharded.trace_dir(), &mut expected);
+    collect_traces(&sharded.trace_dir(), &sharded.trace_dir(), &mut actual);
+    assert!(expected.contains_key("malloc/1/0.json"));
+    assert_eq!(expected, actual);
+  })
+}
+
+#[test]
+fn slice_shard_is_parsed_from_i_over_n() {
+  assert_eq!("1/3".parse(), Ok(SliceShard { index: 1, count: 3 }));
+  assert!("3/3".parse::<SliceShard>().is_err());
+  assert!("1".parse::<SliceShard>().is_err());
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use analyzer::slicer::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+use llir::values::*;
+
+use common::*;
+
+#[test]
+fn target_arg_count_filters_call_sites() -> Result<(), String> {
+  let path = "tests/c_files/slicer/arg_count.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("target_arg_count_filters_call_sites");
+    assert_eq!(target_slices(call_graph, &options, "log_msg").len(), 2);
+
+    options.target_arg_count = Some(3);
+    let slices = target_slices(call_graph, &options, "log_msg");
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].caller.simp_name(), "g");
+    assert_eq!(slices[0].instr.num_arguments(), 3);
+  })
+}
+
+#[test]
+fn entry_file_filter_drops_entries_from_other_files() -> Result<(), String> {
+  let path = "tests/c_files/slicer/entry_file.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("entry_file_filter_drops_entries_from_other_files");
+    options.slice_depth = 0;
+    assert_eq!(target_slices(call_graph, &options, "malloc").len(), 2);
+
+    options.entry_file_filter = Some(String::from("entry_file\\.c$"));
+    let slices = target_slices(call_graph, &options, "malloc");
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].entry.simp_name(), "alloc_a");
+  })
+}
+
+#[test]
+fn excluded_call_sites_are_not_sliced() -> Result<(), String> {
+  let path = "tests/c_files/slicer/wrapper.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("excluded_call_sites_are_not_sliced");
+    options.slice_depth = 0;
+    assert_eq!(target_slices(call_graph, &options, "malloc").len(), 2);
+
+    options.exclude_call_site = Some(String::from("_wrapper$"));
+    let slices = target_slices(call_graph, &options, "malloc");
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].caller.simp_name(), "main");
+  })
+}
+
+#[test]
+fn dedup_slices_keeps_one_slice_per_call_site() -> Result<(), String> {
+  let paths = ["tests/c_files/slicer/dedup_a.bc", "tests/c_files/slicer/dedup_b.bc"];
+  with_linked_call_graph(&paths, |_, call_graph| {
+    let mut options = TestOptions::new("dedup_slices_keeps_one_slice_per_call_site");
+    options.slice_depth = 0;
+
+    // The static helper of each module gives the same slice
+    let slices = target_slices(call_graph, &options, "malloc");
+    assert_eq!(slices.len(), 2);
+    assert_eq!(slices[0].dedup_key(), slices[1].dedup_key());
+
+    options.dedup_slices = true;
+    assert_eq!(target_slices(call_graph, &options, "malloc").len(), 1);
+  })
+}
+
+#[test]
+fn dedup_slices_keeps_calls_sharing_a_location() -> Result<(), String> {
+  let path = "tests/c_files/slicer/dedup.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("dedup_slices_keeps_calls_sharing_a_location");
+    options.slice_depth = 0;
+    options.dedup_slices = true;
+
+    // Both calls expanded from the macro share the same debug location
+    let slices = target_slices(call_graph, &options, "malloc");
+    assert_eq!(slices.len(), 2);
+    assert_eq!(slices[0].instr.debug_loc_string(), slices[1].instr.debug_loc_string());
+  })
+}
+
+#[test]
+fn max_slice_functions_drops_wide_slices() -> Result<(), String> {
+  let path = "tests/c_files/slicer/wide.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("max_slice_functions_drops_wide_slices");
+    options.slice_depth = 1;
+    let slices = target_slices(call_graph, &options, "malloc");
+    assert_eq!(slices.len(), 1);
+    assert!(slices[0].size() > 3);
+
+    options.max_slice_functions = Some(3);
+    let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, &options).unwrap();
+    let (mut target_slices_map, num_dropped) =
+      TargetSlicesMap::from_target_edges_map_with_dropped(&target_edges_map, call_graph, &options).unwrap();
+    assert!(target_slices_map.remove("malloc").unwrap_or_default().is_empty());
+    assert_eq!(num_dropped, 1);
+  })
+}
+
+#[test]
+fn single_target_only_slices_its_calls() -> Result<(), String> {
+  let path = "tests/c_files/slicer/single_target.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("single_target_only_slices_its_calls");
+    options.slice_depth = 0;
+    options.target = Some(String::from("foo"));
+    let target_edges_map = TargetEdgesMap::from_call_graph(call_graph, &options).unwrap();
+    assert_eq!(target_edges_map.keys().collect::<Vec<_>>(), vec!["foo"]);
+    let slices = target_slices(call_graph, &options, "foo");
+    assert_eq!(slices.len(), 2);
+    assert!(slices.iter().all(|slice| slice.callee.simp_name() == "foo"));
+
+    // The slice depth still applies
+    options.slice_depth = 1;
+    let slices = target_slices(call_graph, &options, "foo");
+    assert_eq!(slices.len(), 2);
+    assert!(slices.iter().all(|slice| slice.entry.simp_name() == "main"));
+  })
+}
+
+#[test]
+fn forced_entry_overrides_the_slice_entry() -> Result<(), String> {
+  let path = "tests/c_files/slicer/single_target.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("forced_entry_overrides_the_slice_entry");
+    options.slice_depth = 0;
+    options.force_entry = Some(String::from("main"));
+    let slices = target_slices(call_graph, &options, "foo");
+    assert_eq!(slices.len(), 2);
+    for slice in &slices {
+      assert_eq!(slice.entry.simp_name(), "main");
+      assert!(slice.contains(slice.caller));
+      assert_eq!(State::new(slice).stack[0].function.simp_name(), "main");
+    }
+
+    // `g` does not call `f`, so the call to `foo` in `f` is dropped
+    options.force_entry = Some(String::from("g"));
+    let slices = target_slices(call_graph, &options, "foo");
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].caller.simp_name(), "g");
+  })
+}
+
+#[test]
+fn forced_entry_does_not_go_through_the_target() -> Result<(), String> {
+  let path = "tests/c_files/slicer/force_entry.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("forced_entry_does_not_go_through_the_target");
+    options.slice_depth = 0;
+    options.force_entry = Some(String::from("entry"));
+
+    // `entry` only reaches `helper` through `log_msg` itself
+    let slices = target_slices(call_graph, &options, "log_msg");
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].caller.simp_name(), "entry");
+    assert!(slices.iter().all(|slice| !slice.contains(slice.callee)));
+
+    // The forced entry still has to be defined in the matched files
+    options.entry_file_filter = Some(String::from("other\\.c$"));
+    assert!(target_slices(call_graph, &options, "log_msg").is_empty());
+  })
+}
+
+#[test]
+fn slice_json_keeps_calls_sharing_a_location_apart() -> Result<(), String> {
+  let path = "tests/c_files/slicer/dedup.bc";
+  with_call_graph(path, |_, call_graph| {
+    let mut options = TestOptions::new("slice_json_keeps_calls_sharing_a_location_apart");
+    options.slice_depth = 0;
+    for slice in target_slices(call_graph, &options, "malloc") {
+      let loaded = Slice::from_json(&slice.to_json(), call_graph).unwrap();
+      assert_eq!(loaded.instr, slice.instr);
+    }
+  })
+}
+
+#[test]
+fn slice_json_rejects_ambiguous_call_sites() -> Result<(), String> {
+  let paths = ["tests/c_files/slicer/dedup_a.bc", "tests/c_files/slicer/dedup_b.bc"];
+  with_linked_call_graph(&paths, |_, call_graph| {
+    let mut options = TestOptions::new("slice_json_rejects_ambiguous_call_sites");
+    options.slice_depth = 0;
+
+    // Both static helpers are named alloc and call malloc at the same index
+    let slices = target_slices(call_graph, &options, "malloc");
+    assert!(Slice::from_json(&slices[0].to_json(), call_graph).is_err());
+  })
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use analyzer::semantics::rced::*;
+use analyzer::symbolic_execution::*;
+use std::rc::Rc;
+
+use common::*;
+
+#[test]
+fn forked_state_does_not_affect_parent() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |_, _, slice| {
+    let mut parent = State::new(&slice);
+    parent
+      .memory
+      .insert(Rc::new(Value::Glob("a".to_string())), Rc::new(Value::Int(1)));
+
+    // Forking only shares the data
+    let mut forked = parent.clone();
+    assert!(forked.memory.ptr_eq(&parent.memory));
+    assert!(forked.constraints.ptr_eq(&parent.constraints));
+
+    // Mutating the fork copies the memory and leaves the parent untouched
+    forked
+      .memory
+      .insert(Rc::new(Value::Glob("a".to_string())), Rc::new(Value::Int(2)));
+    forked
+      .memory
+      .insert(Rc::new(Value::Glob("b".to_string())), Rc::new(Value::Int(3)));
+    let block = slice.entry.first_block().unwrap();
+    forked.visited_branch.insert(BranchDirection {
+      from: block,
+      to: block,
+      case: None,
+    });
+    assert!(!forked.memory.ptr_eq(&parent.memory));
+    assert_eq!(parent.memory.len(), 1);
+    assert_eq!(*parent.memory[&Value::Glob("a".to_string())], Value::Int(1));
+    assert_eq!(*forked.memory[&Value::Glob("a".to_string())], Value::Int(2));
+    assert!(parent.visited_branch.is_empty());
+    assert_eq!(forked.visited_branch.len(), 1);
+  })
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use analyzer::semantics::rced::*;
+use analyzer::symbolic_execution::*;
+use llir::values::*;
+use std::rc::Rc;
+
+use common::*;
+
+fn call(id: usize, func: &str, args: &[Rc<Value>]) -> Rc<Value> {
+  Rc::new(Value::Call {
+    id,
+    func: Rc::new(Value::Func(func.to_string())),
+    args: args.to_vec(),
+  })
+}
+
+#[test]
+fn malloc_summary_allocates_distinct_pointers() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |_, _, slice| {
+    let malloc = Summaries::libc().get("malloc").unwrap();
+    let mut state = State::new(&slice);
+    let instr = slice.instr.as_instruction();
+    let size = [Rc::new(Value::Int(8))];
+    let p0 = malloc(instr, Some(call(0, "malloc", &size)), &size, &mut state).unwrap();
+    let p1 = malloc(instr, Some(call(1, "malloc", &size)), &size, &mut state).unwrap();
+    assert_ne!(p0, p1);
+    assert_eq!(state.heap.get(&p0), Some(&HeapStatus::Allocated));
+    assert_eq!(state.heap.get(&p1), Some(&HeapStatus::Allocated));
+  })
+}
+
+#[test]
+fn free_summary_marks_pointer_freed() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |_, _, slice| {
+    let summaries = Summaries::libc();
+    let (malloc, free) = (summaries.get("malloc").unwrap(), summaries.get("free").unwrap());
+    let mut state = State::new(&slice);
+    let instr = slice.instr.as_instruction();
+    let size = [Rc::new(Value::Int(8))];
+    let ptr = malloc(instr, Some(call(0, "malloc", &size)), &size, &mut state).unwrap();
+
+    // Freeing in a forked state leaves the parent untouched
+    let mut forked = state.clone();
+    assert_eq!(free(instr, None, &[ptr.clone()], &mut forked), None);
+    assert_eq!(forked.heap.get(&ptr), Some(&HeapStatus::Freed));
+    assert_eq!(state.heap.get(&ptr), Some(&HeapStatus::Allocated));
+
+    // The free is recorded in the trace
+    match &forked.trace.last().unwrap().semantics {
+      Semantics::Free { loc } => assert_eq!(loc, &ptr),
+      _ => panic!("Expected a free node"),
+    }
+  })
+}
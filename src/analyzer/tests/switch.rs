@@ -0,0 +1,17 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn switch_cases_sharing_block_are_all_forked() -> Result<(), String> {
+  let path = "tests/c_files/switch/switch_5.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("switch_cases_sharing_block_are_all_forked");
+    let metadata = execute(module, call_graph, slice, &options);
+
+    // Case 1, case 2 and default each reach the target once; the two cases
+    // share a block trace so one of them is reported as duplicate
+    assert_eq!(metadata.proper_trace_count, 2);
+    assert_eq!(metadata.duplicate_trace_count, 1);
+  })
+}
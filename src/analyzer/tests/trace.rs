@@ -0,0 +1,234 @@
+mod common;
+
+use std::rc::Rc;
+
+use analyzer::options::*;
+use analyzer::semantics::rced::*;
+use analyzer::symbolic_execution::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn dump_dot_marks_target() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("dump_dot_marks_target");
+    options.dump_dot = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    let dot_path = options.trace_target_slice_file_path("malloc", 0, 0).with_extension("dot");
+    let dot = std::fs::read_to_string(dot_path).unwrap();
+    assert!(dot.starts_with("digraph trace {"));
+    let targets = dot.lines().filter(|line| line.contains("[TARGET]")).collect::<Vec<_>>();
+    assert_eq!(targets.len(), 1);
+    assert!(targets[0].contains("Call"));
+    assert!(targets[0].contains("fillcolor=yellow"));
+  })
+}
+
+#[test]
+fn reduce_prunes_unrelated_nodes() -> Result<(), String> {
+  let path = "tests/c_files/reduce/reduce_1.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("reduce_prunes_unrelated_nodes");
+    options.no_trace_reduction = false;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.reduced_node_count > 0);
+
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let instrs = trace["instrs"].as_array().unwrap();
+    let bin_ops = instrs
+      .iter()
+      .filter_map(|instr| instr["sem"]["Bin"]["op"].as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(bin_ops, vec!["Add"]);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    assert!(instrs[target]["sem"]["Call"].is_object());
+  })
+}
+
+#[test]
+fn collect_negative_dumps_no_target_traces() -> Result<(), String> {
+  let path = "tests/c_files/trace/block_trace_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("collect_negative_dumps_no_target_traces");
+    execute(module, call_graph, slice.clone(), &options);
+    assert!(!options.negative_trace_dir().exists());
+
+    options.collect_negative = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert!(metadata.proper_trace_count > 0);
+    assert!(metadata.negative_no_target_trace_count > 0);
+
+    let dir = options.negative_trace_target_slice_dir(NO_TARGET, "malloc", 0);
+    let trace = load_json(&dir.join("0.json")).unwrap();
+    assert!(trace["target"].is_null());
+    assert!(!trace["instrs"].as_array().unwrap().is_empty());
+  })
+}
+
+#[test]
+fn equivalent_traces_render_identical_text() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |_, _, slice| {
+    let instr = slice.instr.as_instruction();
+    let trace_with_symbols = |ptr: usize, call_id: usize| {
+      let ptr = Rc::new(Value::Sym(ptr));
+      let malloc = Rc::new(Value::Func("malloc".to_string()));
+      let result = Rc::new(Value::Call {
+        id: call_id,
+        func: malloc.clone(),
+        args: vec![ptr.clone()],
+      });
+      let nodes = vec![
+        TraceNode {
+          instr,
+          semantics: Semantics::Load { loc: ptr.clone() },
+          result: None,
+        },
+        TraceNode {
+          instr,
+          semantics: Semantics::Call {
+            func: malloc,
+            args: vec![ptr, Rc::new(Value::Int(7))],
+            num_params: 2,
+            is_var_arg: false,
+            mangled: None,
+          },
+          result: Some(result),
+        },
+      ];
+      TraceWithTarget::new(nodes, 1, false, vec![])
+    };
+
+    let text = trace_with_symbols(3, 5).to_text();
+    assert_eq!(text, trace_with_symbols(8, 2).to_text());
+    let lines = text.lines().collect::<Vec<_>>();
+    assert!(lines[0].ends_with("load sym0"));
+    assert!(lines[1].starts_with("-> TARGET "));
+    assert!(lines[1].ends_with("call malloc(sym0, 7) -> call0:malloc(sym0)"));
+  })
+}
+
+#[test]
+fn trace_keep_kinds_drops_other_nodes() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new("trace_keep_kinds_drops_other_nodes");
+    execute(module, call_graph, slice.clone(), &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    let has_kind = |trace: &serde_json::Value, kind: &str| {
+      let instrs = trace["instrs"].as_array().unwrap();
+      instrs.iter().any(|instr| !instr["sem"][kind].is_null())
+    };
+    assert!(has_kind(&trace, "Load") || has_kind(&trace, "Store"));
+
+    options.trace_keep_kinds = Some(String::from("call,compare,return"));
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "malloc", 0, 0);
+    assert!(!has_kind(&trace, "Load"));
+    assert!(!has_kind(&trace, "Store"));
+    assert!(!has_kind(&trace, "GEP"));
+    let target = trace["target"].as_u64().unwrap() as usize;
+    assert_eq!(trace["instrs"][target]["sem"]["Call"]["func"]["Func"], "malloc");
+  })
+}
+
+#[test]
+fn unknown_trace_kinds_are_rejected() {
+  assert_eq!(parse_semantics_kinds("call, return").unwrap().len(), 2);
+  let err = parse_semantics_kinds("call,loads").unwrap_err();
+  assert!(err.contains("loads") && err.contains("load"));
+}
+
+#[test]
+fn in_memory_traces_match_dumped_traces() -> Result<(), String> {
+  let path = "tests/c_files/basic/example_1.bc";
+  with_slice(path, "main", "f", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("in_memory_traces_match_dumped_traces");
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert!(metadata.proper_trace_count > 0);
+
+    let mut in_memory_options = TestOptions::new("in_memory_traces_match_dumped_traces_in_memory");
+    in_memory_options.collect_negative = true;
+    let ctx = SymbolicExecutionContext::new(std::slice::from_ref(module), call_graph, &in_memory_options).unwrap();
+    let (in_memory_metadata, traces) = ctx.execute_slice_in_memory(slice, 0);
+    assert_eq!(in_memory_metadata.proper_trace_count, metadata.proper_trace_count);
+    assert_eq!(traces.len(), metadata.proper_trace_count);
+    for (trace_id, trace) in traces.iter().enumerate() {
+      let mut dumped = load_trace_json(&options, "malloc", 0, trace_id);
+      dumped.as_object_mut().unwrap().remove("version");
+      assert_eq!(trace.to_json(), dumped);
+    }
+
+    // Nothing is written, not even negative traces
+    assert!(!in_memory_options.output_path().exists());
+  })
+}
+
+#[test]
+fn entry_return_records_its_source() -> Result<(), String> {
+  let path = "tests/c_files/retval/forward.bc";
+  with_slice(path, "main", "forward", "get", |module, call_graph, slice| {
+    let options = TestOptions::new("entry_return_records_its_source");
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "get", 0, 0);
+    let rets = trace["instrs"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .filter_map(|instr| instr["sem"].get("Ret"))
+      .collect::<Vec<_>>();
+
+    // Only the return of main, forwarding the result of get, is annotated
+    assert_eq!(rets.len(), 2);
+    assert!(rets[0].get("source").is_none());
+    assert_eq!(rets[1]["source"], "call-result");
+  })
+}
+
+#[test]
+fn stored_call_result_reaches_its_check() -> Result<(), String> {
+  let path = "tests/c_files/retval/stored_check.bc";
+  with_slice(path, "main", "main", "get", |module, call_graph, slice| {
+    let options = TestOptions::new("stored_call_result_reaches_its_check");
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 2);
+
+    for trace_id in 0..2 {
+      let trace = load_trace_json(&options, "get", 0, trace_id);
+      let instrs = trace["instrs"].as_array().unwrap();
+      let target = trace["target"].as_u64().unwrap() as usize;
+      let retval = &instrs[target]["res"];
+      assert!(retval["Call"].is_object());
+
+      // The call result is stored to `r`, loaded back and compared against 0
+      let load = instrs[target..]
+        .iter()
+        .find(|instr| instr["sem"]["Load"].is_object())
+        .unwrap();
+      assert_eq!(&load["res"], retval);
+      let icmp = instrs[target..]
+        .iter()
+        .find(|instr| instr["sem"]["ICmp"].is_object())
+        .unwrap();
+      assert_eq!(&icmp["sem"]["ICmp"]["op0"], retval);
+      assert_eq!(&trace["cons"][0]["cond"]["ICmp"]["op0"], retval);
+    }
+  })
+}
+
+#[test]
+fn trace_nodes_carry_their_source_location() -> Result<(), String> {
+  let path = "tests/c_files/retval/stored_check.bc";
+  with_slice(path, "main", "main", "get", |module, call_graph, slice| {
+    let options = TestOptions::new("trace_nodes_carry_their_source_location");
+    execute(module, call_graph, slice, &options);
+    let trace = load_trace_json(&options, "get", 0, 0);
+    let target = trace["target"].as_u64().unwrap() as usize;
+    let loc = trace["instrs"][target]["loc"].as_str().unwrap();
+    assert!(loc.contains("stored_check.c:4:"), "{}", loc);
+  })
+}
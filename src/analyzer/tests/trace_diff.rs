@@ -0,0 +1,70 @@
+mod common;
+
+use serde_json::json;
+
+use analyzer::options::*;
+use analyzer::trace_diff::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn trace_reduction_changes_a_single_trace() -> Result<(), String> {
+  let path = "tests/c_files/trace_diff/counter.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let full = TestOptions::new("trace_reduction_changes_a_single_trace_full");
+    execute(module, call_graph, slice.clone(), &full);
+    let report = diff_traces(&full.trace_dir(), &full.trace_dir(), false).unwrap();
+    assert!(report.is_empty());
+    assert_eq!(report.num_unchanged, 1);
+
+    // The reduction drops the nodes updating the counter, unrelated to the target
+    let mut reduced = TestOptions::new("trace_reduction_changes_a_single_trace_reduced");
+    reduced.no_trace_reduction = false;
+    execute(module, call_graph, slice, &reduced);
+    let report = diff_traces(&full.trace_dir(), &reduced.trace_dir(), false).unwrap();
+    assert!(report.added.is_empty());
+    assert!(report.removed.is_empty());
+    assert_eq!(report.changed, vec![(String::from("malloc"), 0, 0)]);
+    assert_eq!(report.num_unchanged, 0);
+  })
+}
+
+/// Shift the symbol, alloca and call ids of a dumped trace
+fn shift_ids(json: &mut serde_json::Value) {
+  match json {
+    serde_json::Value::Object(map) => {
+      // Call values carry their id along with their arguments
+      let is_call = map.contains_key("id") && map.contains_key("args");
+      for (key, value) in map.iter_mut() {
+        match (key.as_str(), value.as_u64()) {
+          ("Sym", Some(id)) | ("ConstSym", Some(id)) | ("Alloc", Some(id)) => *value = json!(id + 100),
+          ("id", Some(id)) if is_call => *value = json!(id + 100),
+          _ => shift_ids(value),
+        }
+      }
+    }
+    serde_json::Value::Array(values) => values.iter_mut().for_each(shift_ids),
+    _ => {}
+  }
+}
+
+#[test]
+fn traces_only_differing_in_their_ids_are_unchanged() -> Result<(), String> {
+  let path = "tests/c_files/trace_diff/counter.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("traces_only_differing_in_their_ids_are_unchanged");
+    execute(module, call_graph, slice, &options);
+    let shifted = TestOptions::new("traces_only_differing_in_their_ids_are_unchanged_shifted");
+    let trace = load_json(&options.trace_target_slice_file_path("malloc", 0, 0)).unwrap();
+    let mut shifted_trace = trace.clone();
+    shift_ids(&mut shifted_trace);
+    assert_ne!(shifted_trace, trace);
+    std::fs::create_dir_all(shifted.trace_target_slice_dir("malloc", 0)).unwrap();
+    dump_json(&shifted_trace, shifted.trace_target_slice_file_path("malloc", 0, 0)).unwrap();
+
+    let report = diff_traces(&options.trace_dir(), &shifted.trace_dir(), false).unwrap();
+    assert!(report.is_empty());
+    assert_eq!(report.num_unchanged, 1);
+  })
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use analyzer::options::*;
+use analyzer::trace_validation::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn out_of_range_target_is_flagged() -> Result<(), String> {
+  let path = "tests/c_files/retval/forward.bc";
+  with_slice(path, "main", "forward", "get", |module, call_graph, slice| {
+    let options = TestOptions::new("out_of_range_target_is_flagged");
+    execute(module, call_graph, slice, &options);
+    let report = validate_traces(&options.trace_dir(), false).unwrap();
+    assert_eq!(report.num_traces, 1);
+    assert!(report.is_ok(), "{:?}", report.problems);
+
+    // A copy of the trace pointing past its nodes, and a partially written one
+    let mut trace = load_trace_json(&options, "get", 0, 0);
+    trace["target"] = serde_json::json!(1000);
+    dump_json(&trace, options.trace_target_slice_file_path("get", 0, 1)).unwrap();
+    std::fs::write(
+      options.trace_target_slice_file_path("get", 0, 2),
+      "{\"target\": 0, \"ins",
+    )
+    .unwrap();
+
+    let report = validate_traces(&options.trace_dir(), false).unwrap();
+    assert_eq!(report.num_traces, 3);
+    assert_eq!(report.num_invalid, 1);
+    assert_eq!(report.num_corrupt, 1);
+    let (name, reason) = report
+      .problems
+      .iter()
+      .find(|(_, reason)| reason.contains("out of range"))
+      .unwrap();
+    assert!(name.ends_with("1.json"));
+    assert!(reason.starts_with("Target index 1000"));
+  })
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use serde_json::json;
+
+use analyzer::options::*;
+use analyzer::unchecked_return::*;
+use analyzer::utils::*;
+
+use common::*;
+
+fn dump_check_features(options: &TestOptions, target: &str, checked: &[bool]) {
+  std::fs::create_dir_all(options.feature_target_slice_dir(target, 0)).unwrap();
+  for (trace_id, checked) in checked.iter().enumerate() {
+    let features = json!({ "ret.check": { "checked": checked } });
+    let path = options.feature_target_slice_file_path(target, 0, trace_id);
+    dump_versioned_json(&features, path).unwrap();
+  }
+}
+
+#[test]
+fn minority_unchecked_trace_is_flagged() {
+  let options = TestOptions::new("minority_unchecked_trace_is_flagged");
+  let mut malloc_checked = vec![true; 10];
+  malloc_checked[6] = false;
+  dump_check_features(&options, "malloc", &malloc_checked);
+  dump_check_features(&options, "printf", &[true, false, true, false]);
+
+  let report = detect_unchecked_returns(&options.feature_dir(), 0.8).unwrap();
+  assert_eq!(report.check_rate("malloc"), Some(0.9));
+  assert_eq!(report.check_rate("printf"), Some(0.5));
+  let unchecked_path = options.feature_target_slice_file_path("malloc", 0, 6);
+  assert_eq!(
+    report.unchecked,
+    vec![(String::from("malloc"), unchecked_path.display().to_string())]
+  );
+}
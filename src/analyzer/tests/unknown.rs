@@ -0,0 +1,77 @@
+mod common;
+
+use std::rc::Rc;
+
+use analyzer::semantics::{rced::*, *};
+use analyzer::symbolic_execution::*;
+
+use common::*;
+
+/// Both branches of `x * y == 1234567`, which z3 cannot decide within a resource limit of 1
+fn run_mul<F>(name: &str, keep_unknown: bool, configure: F) -> Result<MetaData, String>
+where
+  F: FnOnce(&mut TestOptions),
+{
+  let path = "tests/c_files/unknown/mul.bc";
+  let mut result = MetaData::new();
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let mut options = TestOptions::new(name);
+    options.solver_rlimit = Some(1);
+    options.keep_unknown = keep_unknown;
+    configure(&mut options);
+    result = execute(module, call_graph, slice, &options);
+  })?;
+  Ok(result)
+}
+
+#[test]
+fn exhausted_solver_reports_unknown() {
+  let product = Value::Bin {
+    op: BinOp::Mul,
+    op0: Rc::new(Value::Sym(0)),
+    op1: Rc::new(Value::Sym(1)),
+    width: 32,
+  };
+  let cond = Value::ICmp {
+    pred: Predicate::EQ,
+    op0: Rc::new(product),
+    op1: Rc::new(Value::Int(1234567)),
+    width: 32,
+  };
+  let constraints: Constraints = vec![Constraint {
+    cond: cond.as_comparison().unwrap(),
+    branch: true,
+  }];
+  assert_eq!(constraints.satisfiability(Some(1)), Satisfiability::Unknown);
+  assert_eq!(constraints.satisfiability(None), Satisfiability::Sat);
+}
+
+#[test]
+fn unknown_paths_are_dropped_by_default() -> Result<(), String> {
+  let metadata = run_mul("unknown_paths_are_dropped_by_default", false, |_| {})?;
+  assert_eq!(metadata.unknown_trace_count, 2);
+  assert_eq!(metadata.proper_trace_count, 0);
+  assert_eq!(metadata.path_unsat_trace_count, 0);
+  Ok(())
+}
+
+#[test]
+fn unknown_paths_are_kept_with_keep_unknown() -> Result<(), String> {
+  let metadata = run_mul("unknown_paths_are_kept_with_keep_unknown", true, |_| {})?;
+  assert_eq!(metadata.unknown_trace_count, 2);
+  assert_eq!(metadata.proper_trace_count, 2);
+  Ok(())
+}
+
+#[test]
+fn capped_unknown_paths_are_not_counted_as_unknown() -> Result<(), String> {
+  // Keep exploring past the cap, looking for negative traces
+  let metadata = run_mul("capped_unknown_paths_are_not_counted_as_unknown", true, |options| {
+    options.max_trace_per_slice = 1;
+    options.collect_negative = true;
+  })?;
+  assert_eq!(metadata.unknown_trace_count, 1);
+  assert_eq!(metadata.proper_trace_count, 1);
+  assert_eq!(metadata.capped_proper_trace_count, 1);
+  Ok(())
+}
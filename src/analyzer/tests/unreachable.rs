@@ -0,0 +1,32 @@
+mod common;
+
+use analyzer::options::*;
+use analyzer::utils::*;
+
+use common::*;
+
+#[test]
+fn satisfiable_unreachable_path_is_reported() -> Result<(), String> {
+  let path = "tests/c_files/unreach/assumed.bc";
+  with_slice(path, "main", "main", "malloc", |module, call_graph, slice| {
+    let options = TestOptions::new("unreachable_path_is_dropped");
+    let metadata = execute(module, call_graph, slice.clone(), &options);
+    assert_eq!(metadata.unreachable_trace_count, 1);
+    assert_eq!(metadata.reported_unreachable_count, 0);
+
+    let mut options = TestOptions::new("satisfiable_unreachable_path_is_reported");
+    options.report_unreachable = true;
+    let metadata = execute(module, call_graph, slice, &options);
+    assert_eq!(metadata.proper_trace_count, 1);
+    assert_eq!(metadata.unreachable_trace_count, 1);
+    assert_eq!(metadata.reported_unreachable_count, 1);
+
+    // The report carries the path constraint leading to the unreachable
+    let dir = options.assertion_target_slice_dir("malloc", 0);
+    let report = load_json(&dir.join("0.json")).unwrap();
+    assert!(report["target"].is_u64());
+    let cons = report["cons"].as_array().unwrap();
+    assert_eq!(cons.len(), 1);
+    assert_eq!(cons[0]["branch"], true);
+  })
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+
+use common::*;
+
+fn use_after_free_features(path: &str, name: &str, keep_kinds: Option<&str>) -> Result<serde_json::Value, String> {
+  let mut features = serde_json::Value::Null;
+  with_slice(path, "main", "main", "free", |module, call_graph, slice| {
+    let mut options = TestOptions::new(name);
+    options.trace_keep_kinds = keep_kinds.map(String::from);
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    execute(module, call_graph, slice, &options);
+    let trace: Trace = serde_json::from_value(load_trace_json(&options, "free", 0, 0)).unwrap();
+    features = UseAfterFreeFeatureExtractor::new().extract(0, &slice_json, &trace);
+  })?;
+  Ok(features)
+}
+
+#[test]
+fn clean_free_is_not_use_after_free() -> Result<(), String> {
+  let path = "tests/c_files/free/free_clean.bc";
+  let features = use_after_free_features(path, "clean_free_is_not_use_after_free", None)?;
+  assert_eq!(features["use_after_free"], false);
+  assert_eq!(features["double_free"], false);
+  Ok(())
+}
+
+#[test]
+fn load_after_free_is_use_after_free() -> Result<(), String> {
+  let path = "tests/c_files/free/use_after_free.bc";
+  let features = use_after_free_features(path, "load_after_free_is_use_after_free", None)?;
+  assert_eq!(features["use_after_free"], true);
+  assert_eq!(features["double_free"], false);
+  Ok(())
+}
+
+#[test]
+fn free_nodes_are_kept_whatever_the_kept_kinds() -> Result<(), String> {
+  let path = "tests/c_files/free/use_after_free.bc";
+  let name = "free_nodes_are_kept_whatever_the_kept_kinds";
+  let features = use_after_free_features(path, name, Some("load,store"))?;
+  assert_eq!(features["use_after_free"], true);
+  assert_eq!(features["double_free"], false);
+  Ok(())
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use analyzer::feature_extraction::{FeatureExtractor, Slice, Trace};
+use analyzer::feature_extractors::*;
+use analyzer::semantics::boxed::*;
+
+use common::*;
+
+fn target_call_and_features(target: &str, name: &str) -> Result<(serde_json::Value, serde_json::Value), String> {
+  let path = "tests/c_files/var_arg/var_arg.bc";
+  let mut result = (serde_json::Value::Null, serde_json::Value::Null);
+  with_slice(path, "main", "main", target, |module, call_graph, slice| {
+    let options = TestOptions::new(name);
+    let slice_json: Slice = serde_json::from_value(slice.to_json()).unwrap();
+    execute(module, call_graph, slice, &options);
+    let trace_json = load_trace_json(&options, target, 0, 0);
+    let index = trace_json["target"].as_u64().unwrap() as usize;
+    let call = trace_json["instrs"][index]["sem"]["Call"].clone();
+    let trace: Trace = serde_json::from_value(trace_json).unwrap();
+    result = (call, VarArgFeatureExtractor::new().extract(0, &slice_json, &trace));
+  })?;
+  Ok(result)
+}
+
+#[test]
+fn fixed_arity_call() -> Result<(), String> {
+  let (call, features) = target_call_and_features("malloc", "fixed_arity_call")?;
+  assert_eq!(call["num_params"], 1);
+  assert_eq!(call["is_var_arg"], false);
+  assert_eq!(features["has_var_args"], false);
+  Ok(())
+}
+
+#[test]
+fn variadic_call_passes_extra_args() -> Result<(), String> {
+  let (call, features) = target_call_and_features("printf", "variadic_call_passes_extra_args")?;
+  assert_eq!(call["num_params"], 1);
+  assert_eq!(call["is_var_arg"], true);
+  assert_eq!(features["has_var_args"], true);
+  assert_eq!(features["num_var_args"], 2);
+  Ok(())
+}
+
+#[test]
+fn variadic_tail_is_recorded_in_the_call_node() -> Result<(), String> {
+  let path = "tests/c_files/var_arg/format.bc";
+  with_slice(path, "main", "main", "printf", |module, call_graph, slice| {
+    let options = TestOptions::new("variadic_tail_is_recorded_in_the_call_node");
+    execute(module, call_graph, slice, &options);
+    let trace_json = load_trace_json(&options, "printf", 0, 0);
+    let trace: Trace = serde_json::from_value(trace_json).unwrap();
+
+    // The format string is the only fixed argument, `a` and `b` form the variadic tail
+    let sem = &trace.target_instr().sem;
+    assert_eq!(sem.call_args().len(), 3);
+    assert!(matches!(
+      sem,
+      Semantics::Call {
+        num_params: 1,
+        is_var_arg: true,
+        ..
+      }
+    ));
+    let var_args = sem.call_var_args().into_iter().cloned().collect::<Vec<_>>();
+    assert_eq!(var_args, vec![Value::Arg(0), Value::Arg(1)]);
+  })
+}